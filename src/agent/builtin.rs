@@ -0,0 +1,346 @@
+//! Built-in agent backends
+//!
+//! This module provides backends for the agent CLIs Ralph supports out of the
+//! box (Claude Code, Codex, Amp) plus a generic backend for arbitrary commands
+//! defined in configuration.
+
+#![allow(dead_code)]
+
+use super::stream::StreamFormat;
+use super::traits::{AgentBackend, AgentCapabilities, AgentInvocation, AgentRequest};
+
+/// Placeholder replaced with the prompt in command backend arguments
+pub const PROMPT_PLACEHOLDER: &str = "{prompt}";
+
+/// Placeholder replaced with the project root in command backend arguments
+pub const PROJECT_ROOT_PLACEHOLDER: &str = "{project_root}";
+
+/// Backend for the Claude Code CLI.
+#[derive(Debug, Clone, Default)]
+pub struct ClaudeBackend;
+
+impl AgentBackend for ClaudeBackend {
+    fn name(&self) -> &str {
+        "claude"
+    }
+
+    fn program(&self) -> &str {
+        "claude"
+    }
+
+    fn build_invocation(&self, request: &AgentRequest<'_>) -> AgentInvocation {
+        // Use --print for non-interactive mode, --dangerously-skip-permissions
+        // to allow file changes, and stream-json (which requires --verbose)
+        // so tool calls can be followed as they happen
+        let mut args = vec![
+            "--print".to_string(),
+            "--dangerously-skip-permissions".to_string(),
+            "--output-format".to_string(),
+            "stream-json".to_string(),
+            "--verbose".to_string(),
+        ];
+        if let Some(model) = request.model {
+            args.push("--model".to_string());
+            args.push(model.to_string());
+        }
+        args.push(request.prompt.to_string());
+
+        AgentInvocation::new(self.program(), args)
+    }
+
+    fn stream_format(&self) -> StreamFormat {
+        StreamFormat::ClaudeStreamJson
+    }
+
+    fn capabilities(&self) -> AgentCapabilities {
+        AgentCapabilities {
+            structured_output: true,
+            model_selection: true,
+            non_interactive: true,
+        }
+    }
+}
+
+/// Backend for the OpenAI Codex CLI.
+///
+/// The model and local provider can be selected with the `CODEX_MODEL`,
+/// `CODEX_OSS_MODEL`, `CODEX_OSS` and `CODEX_OSS_PROVIDER` environment variables.
+/// A model requested for the story takes precedence over the variables.
+#[derive(Debug, Clone, Default)]
+pub struct CodexBackend;
+
+impl AgentBackend for CodexBackend {
+    fn name(&self) -> &str {
+        "codex"
+    }
+
+    fn program(&self) -> &str {
+        "codex"
+    }
+
+    fn build_invocation(&self, request: &AgentRequest<'_>) -> AgentInvocation {
+        let mut args = vec![
+            "-C".to_string(),
+            request.project_root.display().to_string(),
+            "exec".to_string(),
+            "--full-auto".to_string(),
+            "--json".to_string(),
+            request.prompt.to_string(),
+        ];
+
+        if let Some(model) = request.model {
+            args.push("--model".to_string());
+            args.push(model.to_string());
+        } else if let Ok(model) = std::env::var("CODEX_OSS_MODEL") {
+            args.push("--model".to_string());
+            args.push(model);
+        } else if let Ok(model) = std::env::var("CODEX_MODEL") {
+            args.push("--model".to_string());
+            args.push(model);
+        }
+
+        if std::env::var("CODEX_OSS")
+            .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+            .unwrap_or(false)
+        {
+            let provider =
+                std::env::var("CODEX_OSS_PROVIDER").unwrap_or_else(|_| "ollama".to_string());
+            args.push("--oss".to_string());
+            args.push("--local-provider".to_string());
+            args.push(provider);
+        }
+
+        AgentInvocation::new(self.program(), args)
+    }
+
+    fn stream_format(&self) -> StreamFormat {
+        StreamFormat::CodexJson
+    }
+
+    fn capabilities(&self) -> AgentCapabilities {
+        AgentCapabilities {
+            structured_output: true,
+            model_selection: true,
+            non_interactive: true,
+        }
+    }
+}
+
+/// Backend for the Amp CLI.
+#[derive(Debug, Clone, Default)]
+pub struct AmpBackend;
+
+impl AgentBackend for AmpBackend {
+    fn name(&self) -> &str {
+        "amp"
+    }
+
+    fn program(&self) -> &str {
+        "amp"
+    }
+
+    fn build_invocation(&self, request: &AgentRequest<'_>) -> AgentInvocation {
+        AgentInvocation::new(
+            self.program(),
+            vec!["--prompt".to_string(), request.prompt.to_string()],
+        )
+    }
+
+    fn capabilities(&self) -> AgentCapabilities {
+        AgentCapabilities {
+            non_interactive: true,
+            ..Default::default()
+        }
+    }
+}
+
+/// Backend for an arbitrary agent command.
+///
+/// Arguments may contain `{prompt}` and `{project_root}` placeholders. If no
+/// argument references `{prompt}`, the prompt is appended as the last argument.
+#[derive(Debug, Clone)]
+pub struct CommandBackend {
+    /// Registry name of the backend
+    name: String,
+    /// Program to execute
+    command: String,
+    /// Argument templates
+    args: Vec<String>,
+    /// Extra environment variables
+    env: Vec<(String, String)>,
+}
+
+impl CommandBackend {
+    /// Create a command backend that passes the prompt as its only argument.
+    pub fn new(name: impl Into<String>, command: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            command: command.into(),
+            args: Vec::new(),
+            env: Vec::new(),
+        }
+    }
+
+    /// Set the argument templates.
+    pub fn with_args(mut self, args: Vec<String>) -> Self {
+        self.args = args;
+        self
+    }
+
+    /// Add an environment variable for the agent process.
+    pub fn with_env(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.env.push((key.into(), value.into()));
+        self
+    }
+}
+
+impl AgentBackend for CommandBackend {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn program(&self) -> &str {
+        &self.command
+    }
+
+    fn build_invocation(&self, request: &AgentRequest<'_>) -> AgentInvocation {
+        let root = request.project_root.display().to_string();
+        let mut args: Vec<String> = self
+            .args
+            .iter()
+            .map(|arg| {
+                arg.replace(PROJECT_ROOT_PLACEHOLDER, &root)
+                    .replace(PROMPT_PLACEHOLDER, request.prompt)
+            })
+            .collect();
+
+        if !self.args.iter().any(|arg| arg.contains(PROMPT_PLACEHOLDER)) {
+            args.push(request.prompt.to_string());
+        }
+
+        AgentInvocation {
+            program: self.command.clone(),
+            args,
+            env: self.env.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    fn request(prompt: &str) -> AgentRequest<'_> {
+        AgentRequest::new(prompt, Path::new("/tmp/project"))
+    }
+
+    #[test]
+    fn test_claude_invocation() {
+        let invocation = ClaudeBackend.build_invocation(&request("test prompt"));
+
+        assert_eq!(invocation.program, "claude");
+        assert_eq!(
+            invocation.args,
+            vec![
+                "--print",
+                "--dangerously-skip-permissions",
+                "--output-format",
+                "stream-json",
+                "--verbose",
+                "test prompt"
+            ]
+        );
+        assert!(invocation.env.is_empty());
+    }
+
+    #[test]
+    fn test_claude_parses_stream_json() {
+        let events = ClaudeBackend.parse_stream_line(
+            r#"{"type":"assistant","message":{"content":[{"type":"tool_use","name":"Write","input":{"file_path":"a.txt"}}]}}"#,
+        );
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].summary(), "edit: a.txt");
+
+        let output = ClaudeBackend.parse_output(r#"{"type":"result","result":"Finished"}"#);
+        assert_eq!(output.final_message, Some("Finished".to_string()));
+    }
+
+    #[test]
+    fn test_codex_invocation_default() {
+        let invocation = CodexBackend.build_invocation(&request("test prompt"));
+
+        assert_eq!(invocation.program, "codex");
+        assert_eq!(invocation.args[0], "-C");
+        assert_eq!(invocation.args[1], "/tmp/project");
+        assert_eq!(invocation.args[2], "exec");
+        assert!(invocation.args.contains(&"--full-auto".to_string()));
+        assert!(invocation.args.contains(&"--json".to_string()));
+        assert!(invocation.args.contains(&"test prompt".to_string()));
+    }
+
+    #[test]
+    fn test_story_model_is_passed_to_agents_with_model_selection() {
+        let request = request("test prompt").with_model(Some("opus"));
+
+        let claude = ClaudeBackend.build_invocation(&request);
+        assert_eq!(
+            claude.args[claude.args.len() - 3..],
+            ["--model", "opus", "test prompt"]
+        );
+
+        let codex = CodexBackend.build_invocation(&request);
+        let position = codex.args.iter().position(|arg| arg == "--model").unwrap();
+        assert_eq!(codex.args[position + 1], "opus");
+    }
+
+    #[test]
+    fn test_amp_invocation() {
+        let invocation = AmpBackend.build_invocation(&request("test prompt"));
+
+        assert_eq!(invocation.program, "amp");
+        assert_eq!(invocation.args, vec!["--prompt", "test prompt"]);
+    }
+
+    #[test]
+    fn test_command_backend_appends_prompt() {
+        let backend = CommandBackend::new("custom", "my-agent");
+        let invocation = backend.build_invocation(&request("do it"));
+
+        assert_eq!(backend.name(), "custom");
+        assert_eq!(invocation.program, "my-agent");
+        assert_eq!(invocation.args, vec!["do it"]);
+    }
+
+    #[test]
+    fn test_command_backend_placeholders() {
+        let backend = CommandBackend::new("custom", "my-agent")
+            .with_args(vec![
+                "--cwd".to_string(),
+                "{project_root}".to_string(),
+                "--message={prompt}".to_string(),
+            ])
+            .with_env("AGENT_MODE", "batch");
+        let invocation = backend.build_invocation(&request("do it"));
+
+        assert_eq!(
+            invocation.args,
+            vec!["--cwd", "/tmp/project", "--message=do it"]
+        );
+        assert_eq!(
+            invocation.env,
+            vec![("AGENT_MODE".to_string(), "batch".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_builtin_capabilities() {
+        assert!(ClaudeBackend.capabilities().structured_output);
+        assert!(CodexBackend.capabilities().model_selection);
+        assert!(!AmpBackend.capabilities().structured_output);
+        assert_eq!(
+            CommandBackend::new("x", "y").capabilities(),
+            AgentCapabilities::default()
+        );
+    }
+}
@@ -0,0 +1,28 @@
+//! Agent backend module
+//!
+//! This module contains the pluggable agent backends (Claude Code, Codex, Amp,
+//! config-defined commands, and a scripted backend for tests) and the
+//! registry used to resolve them.
+
+#![allow(unused_imports)]
+
+pub mod builtin;
+pub mod registry;
+pub mod report;
+pub mod scripted;
+pub mod stream;
+pub mod traits;
+
+pub use builtin::{AmpBackend, ClaudeBackend, CodexBackend, CommandBackend};
+pub use registry::{
+    AgentConfigError, AgentDefinition, AgentRegistry, AgentsConfig, AGENTS_CONFIG_FILE,
+};
+pub use report::{AgentReport, ReportStatus, COMPLETION_SENTINEL};
+pub use scripted::{
+    FixtureFormat, ScriptFixture, ScriptStep, ScriptedBackend, StoryScript, RATE_LIMIT_MESSAGE,
+    SCRIPTED_AGENT_PREFIX,
+};
+pub use stream::{AgentEvent, StreamFormat, TokenUsage};
+pub use traits::{
+    is_program_in_path, AgentBackend, AgentCapabilities, AgentInvocation, AgentOutput, AgentRequest,
+};
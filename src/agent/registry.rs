@@ -0,0 +1,460 @@
+//! Agent registry for resolving agent backends
+//!
+//! This module provides a registry that maps agent names to backends, detects
+//! which agent CLI is installed, and loads additional command backends from
+//! `ralph-agents.toml`.
+
+#![allow(dead_code)]
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::path::Path;
+use std::sync::Arc;
+
+use serde::Deserialize;
+use thiserror::Error;
+
+use super::builtin::{AmpBackend, ClaudeBackend, CodexBackend, CommandBackend};
+use super::scripted::ScriptedBackend;
+use super::traits::AgentBackend;
+
+/// File name of the per-project agent configuration
+pub const AGENTS_CONFIG_FILE: &str = "ralph-agents.toml";
+
+/// Errors that can occur when loading agent configuration
+#[derive(Error, Debug)]
+pub enum AgentConfigError {
+    #[error("Failed to read agent config file: {0}")]
+    ReadError(#[from] std::io::Error),
+    #[error("Failed to parse agent config: {0}")]
+    ParseError(#[from] toml::de::Error),
+    #[error("Invalid agent definition '{0}': {1}")]
+    InvalidAgent(String, String),
+}
+
+/// Definition of a command backend in `ralph-agents.toml`
+#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
+pub struct AgentDefinition {
+    /// Program to execute
+    pub command: String,
+    /// Argument templates (`{prompt}` and `{project_root}` are substituted)
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Extra environment variables for the agent process
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+}
+
+/// Agent configuration loaded from `ralph-agents.toml`
+///
+/// ```toml
+/// detection_order = ["my-agent", "claude"]
+/// fallback = ["codex", "my-agent"]
+///
+/// [agents.my-agent]
+/// command = "my-agent"
+/// args = ["--cwd", "{project_root}", "{prompt}"]
+/// env = { MY_AGENT_MODE = "batch" }
+/// ```
+#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
+pub struct AgentsConfig {
+    /// Order in which agents are tried when none is specified
+    #[serde(default)]
+    pub detection_order: Option<Vec<String>>,
+    /// Agents to switch to, in order, when the active agent hits a usage limit
+    #[serde(default)]
+    pub fallback: Option<Vec<String>>,
+    /// Command backends by name
+    #[serde(default)]
+    pub agents: BTreeMap<String, AgentDefinition>,
+}
+
+impl AgentsConfig {
+    /// Parse agent configuration from a TOML string
+    pub fn parse(content: &str) -> Result<Self, AgentConfigError> {
+        let config: AgentsConfig = toml::from_str(content)?;
+        for (name, definition) in &config.agents {
+            if definition.command.trim().is_empty() {
+                return Err(AgentConfigError::InvalidAgent(
+                    name.clone(),
+                    "command must not be empty".to_string(),
+                ));
+            }
+        }
+        Ok(config)
+    }
+
+    /// Load agent configuration from a file
+    pub fn load(path: &Path) -> Result<Self, AgentConfigError> {
+        let content = std::fs::read_to_string(path)?;
+        Self::parse(&content)
+    }
+}
+
+/// Registry of agent backends
+///
+/// The registry resolves the configured agent command to a backend and
+/// detects an installed agent when none is configured.
+#[derive(Clone)]
+pub struct AgentRegistry {
+    /// Registered backends by name
+    backends: HashMap<String, Arc<dyn AgentBackend>>,
+    /// Names tried in order by [`AgentRegistry::detect`]
+    detection_order: Vec<String>,
+    /// Names switched to, in order, when the active agent hits a usage limit
+    fallback_order: Vec<String>,
+}
+
+impl fmt::Debug for AgentRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut names = self.names();
+        names.sort();
+        f.debug_struct("AgentRegistry")
+            .field("backends", &names)
+            .field("detection_order", &self.detection_order)
+            .field("fallback_order", &self.fallback_order)
+            .finish()
+    }
+}
+
+impl Default for AgentRegistry {
+    fn default() -> Self {
+        Self::with_builtins()
+    }
+}
+
+impl AgentRegistry {
+    /// Create a new empty agent registry
+    pub fn new() -> Self {
+        Self {
+            backends: HashMap::new(),
+            detection_order: Vec::new(),
+            fallback_order: Vec::new(),
+        }
+    }
+
+    /// Create a registry with the built-in backends
+    ///
+    /// Detection prefers Claude Code, then Codex, then Amp.
+    pub fn with_builtins() -> Self {
+        let mut registry = Self::new();
+        registry.register(Arc::new(ClaudeBackend));
+        registry.register(Arc::new(CodexBackend));
+        registry.register(Arc::new(AmpBackend));
+        registry
+    }
+
+    /// Create a registry with the built-in backends plus those from `ralph-agents.toml`
+    /// in the given project root, if the file exists
+    pub fn load_for_project(project_root: &Path) -> Result<Self, AgentConfigError> {
+        let mut registry = Self::with_builtins();
+        let path = project_root.join(AGENTS_CONFIG_FILE);
+        if path.exists() {
+            registry.apply_config(&AgentsConfig::load(&path)?);
+        }
+        Ok(registry)
+    }
+
+    /// Register the command backends, detection order and fallback order from a config
+    pub fn apply_config(&mut self, config: &AgentsConfig) {
+        for (name, definition) in &config.agents {
+            let mut backend = CommandBackend::new(name.clone(), definition.command.clone())
+                .with_args(definition.args.clone());
+            for (key, value) in &definition.env {
+                backend = backend.with_env(key.clone(), value.clone());
+            }
+            self.register(Arc::new(backend));
+        }
+
+        if let Some(order) = &config.detection_order {
+            self.set_detection_order(order.clone());
+        }
+        if let Some(order) = &config.fallback {
+            self.set_fallback_order(order.clone());
+        }
+    }
+
+    /// Register a backend
+    ///
+    /// If a backend with the same name already exists, it will be replaced.
+    /// New backends are appended to the detection order.
+    ///
+    /// # Returns
+    /// The name of the registered backend
+    pub fn register(&mut self, backend: Arc<dyn AgentBackend>) -> String {
+        let name = backend.name().to_string();
+        if !self.detection_order.contains(&name) {
+            self.detection_order.push(name.clone());
+        }
+        self.backends.insert(name.clone(), backend);
+        name
+    }
+
+    /// Get a backend by name
+    pub fn get(&self, name: &str) -> Option<Arc<dyn AgentBackend>> {
+        self.backends.get(name).cloned()
+    }
+
+    /// Check if a backend is registered
+    pub fn has(&self, name: &str) -> bool {
+        self.backends.contains_key(name)
+    }
+
+    /// Get the names of all registered backends
+    pub fn names(&self) -> Vec<&str> {
+        self.backends.keys().map(|s| s.as_str()).collect()
+    }
+
+    /// Get the number of registered backends
+    pub fn len(&self) -> usize {
+        self.backends.len()
+    }
+
+    /// Check if the registry is empty
+    pub fn is_empty(&self) -> bool {
+        self.backends.is_empty()
+    }
+
+    /// Set the order in which backends are tried by [`AgentRegistry::detect`]
+    pub fn set_detection_order(&mut self, order: Vec<String>) {
+        self.detection_order = order;
+    }
+
+    /// Get the detection order
+    pub fn detection_order(&self) -> &[String] {
+        &self.detection_order
+    }
+
+    /// Set the agents switched to, in order, when the active agent hits a usage limit
+    pub fn set_fallback_order(&mut self, order: Vec<String>) {
+        self.fallback_order = order;
+    }
+
+    /// Get the fallback order
+    pub fn fallback_order(&self) -> &[String] {
+        &self.fallback_order
+    }
+
+    /// Next agent to fall back to after `tried` agents
+    ///
+    /// # Returns
+    /// The first agent in fallback order that has not been tried and whose
+    /// program is available
+    pub fn next_fallback(&self, tried: &[String]) -> Option<String> {
+        self.fallback_order
+            .iter()
+            .find(|name| !tried.contains(name) && self.resolve(name).is_available())
+            .cloned()
+    }
+
+    /// Detect the first available agent in detection order
+    ///
+    /// # Returns
+    /// The name of the first registered backend whose program is available
+    pub fn detect(&self) -> Option<String> {
+        self.detection_order
+            .iter()
+            .find(|name| self.get(name).is_some_and(|b| b.is_available()))
+            .cloned()
+    }
+
+    /// Resolve an agent command to a backend
+    ///
+    /// The command is matched against registered names first, then by the
+    /// file name of a path (e.g., `/usr/local/bin/claude`). Unknown commands
+    /// are run directly with the prompt as the last argument, and
+    /// `scripted:<fixture>` selects the [`ScriptedBackend`].
+    pub fn resolve(&self, agent_command: &str) -> Arc<dyn AgentBackend> {
+        if let Some(backend) = ScriptedBackend::from_agent_command(agent_command) {
+            return Arc::new(backend);
+        }
+
+        if let Some(backend) = self.get(agent_command) {
+            return backend;
+        }
+
+        let file_name = Path::new(agent_command)
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or(agent_command);
+        if let Some(backend) = self.get(file_name) {
+            return backend;
+        }
+
+        Arc::new(CommandBackend::new(agent_command, agent_command))
+    }
+
+    /// Resolve the configured agent command, or detect one if none is configured
+    ///
+    /// # Returns
+    /// The agent command name, or None if no agent is configured or available
+    pub fn resolve_or_detect(&self, agent_command: Option<&str>) -> Option<String> {
+        agent_command
+            .map(|command| command.to_string())
+            .or_else(|| self.detect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::AgentRequest;
+    use std::io::Write;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_with_builtins() {
+        let registry = AgentRegistry::with_builtins();
+        assert_eq!(registry.len(), 3);
+        assert!(registry.has("claude"));
+        assert!(registry.has("codex"));
+        assert!(registry.has("amp"));
+        assert_eq!(registry.detection_order(), ["claude", "codex", "amp"]);
+    }
+
+    #[test]
+    fn test_new_registry_is_empty() {
+        let registry = AgentRegistry::new();
+        assert!(registry.is_empty());
+        assert!(registry.detect().is_none());
+    }
+
+    #[test]
+    fn test_resolve_by_name() {
+        let registry = AgentRegistry::with_builtins();
+        assert_eq!(registry.resolve("codex").name(), "codex");
+    }
+
+    #[test]
+    fn test_resolve_by_path() {
+        let registry = AgentRegistry::with_builtins();
+        assert_eq!(registry.resolve("/usr/local/bin/claude").name(), "claude");
+    }
+
+    #[test]
+    fn test_resolve_unknown_command() {
+        let registry = AgentRegistry::with_builtins();
+        let backend = registry.resolve("./scripts/agent.sh");
+        let invocation = backend.build_invocation(&AgentRequest::new("prompt", Path::new("/tmp")));
+
+        assert_eq!(invocation.program, "./scripts/agent.sh");
+        assert_eq!(invocation.args, vec!["prompt"]);
+    }
+
+    #[test]
+    fn test_resolve_scripted_command() {
+        let registry = AgentRegistry::with_builtins();
+        let backend = registry.resolve("scripted:/tmp/fixture.toml");
+
+        assert_eq!(backend.name(), "scripted");
+        assert_eq!(backend.program(), "sh");
+    }
+
+    #[test]
+    fn test_resolve_or_detect_prefers_explicit() {
+        let registry = AgentRegistry::new();
+        assert_eq!(
+            registry.resolve_or_detect(Some("my-agent")),
+            Some("my-agent".to_string())
+        );
+        assert!(registry.resolve_or_detect(None).is_none());
+    }
+
+    #[test]
+    fn test_detect_skips_unavailable() {
+        let mut registry = AgentRegistry::new();
+        registry.register(Arc::new(CommandBackend::new(
+            "missing",
+            "ralph-definitely-not-installed-agent",
+        )));
+        assert!(registry.detect().is_none());
+    }
+
+    #[test]
+    fn test_next_fallback_skips_tried_and_unavailable() {
+        let mut registry = AgentRegistry::new();
+        registry.register(Arc::new(CommandBackend::new(
+            "missing",
+            "ralph-definitely-not-installed-agent",
+        )));
+        registry.set_fallback_order(vec![
+            "first".to_string(),
+            "missing".to_string(),
+            "scripted:/tmp/fallback.toml".to_string(),
+        ]);
+
+        let tried = vec!["first".to_string()];
+        assert_eq!(
+            registry.next_fallback(&tried),
+            Some("scripted:/tmp/fallback.toml".to_string())
+        );
+        let tried = vec!["scripted:/tmp/fallback.toml".to_string()];
+        assert!(AgentRegistry::new().next_fallback(&tried).is_none());
+    }
+
+    #[test]
+    fn test_parse_config() {
+        let config = AgentsConfig::parse(
+            r#"
+detection_order = ["local", "claude"]
+fallback = ["codex", "local"]
+
+[agents.local]
+command = "local-agent"
+args = ["--dir", "{project_root}", "{prompt}"]
+env = { LOCAL_AGENT_MODE = "batch" }
+"#,
+        )
+        .unwrap();
+
+        let mut registry = AgentRegistry::with_builtins();
+        registry.apply_config(&config);
+
+        assert_eq!(registry.len(), 4);
+        assert_eq!(registry.detection_order(), ["local", "claude"]);
+        assert_eq!(registry.fallback_order(), ["codex", "local"]);
+
+        let invocation = registry
+            .resolve("local")
+            .build_invocation(&AgentRequest::new("go", Path::new("/work")));
+        assert_eq!(invocation.program, "local-agent");
+        assert_eq!(invocation.args, vec!["--dir", "/work", "go"]);
+        assert_eq!(
+            invocation.env,
+            vec![("LOCAL_AGENT_MODE".to_string(), "batch".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_parse_config_rejects_empty_command() {
+        let result = AgentsConfig::parse("[agents.broken]\ncommand = \"\"\n");
+        assert!(matches!(result, Err(AgentConfigError::InvalidAgent(_, _))));
+    }
+
+    #[test]
+    fn test_config_overrides_builtin() {
+        let config =
+            AgentsConfig::parse("[agents.claude]\ncommand = \"claude-wrapper\"\n").unwrap();
+        let mut registry = AgentRegistry::with_builtins();
+        registry.apply_config(&config);
+
+        assert_eq!(registry.len(), 3);
+        assert_eq!(registry.resolve("claude").program(), "claude-wrapper");
+    }
+
+    #[test]
+    fn test_load_for_project() {
+        let dir = TempDir::new().unwrap();
+        assert_eq!(
+            AgentRegistry::load_for_project(dir.path()).unwrap().len(),
+            3
+        );
+
+        let mut file = std::fs::File::create(dir.path().join(AGENTS_CONFIG_FILE)).unwrap();
+        writeln!(file, "[agents.custom]\ncommand = \"custom-agent\"").unwrap();
+
+        let registry = AgentRegistry::load_for_project(dir.path()).unwrap();
+        assert!(registry.has("custom"));
+        assert_eq!(registry.detection_order().last().unwrap(), "custom");
+    }
+}
@@ -0,0 +1,235 @@
+//! Agent self-report protocol
+//!
+//! Agents end their final message with a status block telling Ralph how the
+//! iteration went:
+//!
+//! ```text
+//! <ralph-status>
+//! status: partial
+//! remaining:
+//! - Show an error for invalid emails
+//! learnings:
+//! - Form validation lives in src/forms/validate.ts
+//! </ralph-status>
+//! ```
+//!
+//! `status` is `complete`, `blocked` (with a `reason:` line) or `partial`
+//! (with a `remaining:` list of acceptance criteria still to do). A block may
+//! also carry only `learnings:`. The `<promise>COMPLETE</promise>` sentinel
+//! used by `ralph.sh` is accepted as `status: complete`.
+
+/// Tag that opens a status block
+pub const REPORT_OPEN_TAG: &str = "<ralph-status>";
+
+/// Tag that closes a status block
+pub const REPORT_CLOSE_TAG: &str = "</ralph-status>";
+
+/// Completion sentinel understood by `ralph.sh` and the loop prompt
+pub const COMPLETION_SENTINEL: &str = "<promise>COMPLETE</promise>";
+
+/// Outcome an agent reported for its iteration
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReportStatus {
+    /// The agent believes every acceptance criterion is met
+    Complete,
+    /// The agent cannot proceed without help
+    Blocked {
+        /// Why the agent is blocked
+        reason: String,
+    },
+    /// The agent made progress but did not finish
+    Partial {
+        /// Acceptance criteria the agent says are still outstanding
+        remaining: Vec<String>,
+    },
+}
+
+/// A status block parsed from an agent's final message
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AgentReport {
+    /// Reported outcome (None when the block only carries learnings)
+    pub status: Option<ReportStatus>,
+    /// Learnings the agent wants recorded in progress.txt
+    pub learnings: Vec<String>,
+}
+
+/// List field a bullet belongs to while parsing
+#[derive(Clone, Copy)]
+enum ListField {
+    Remaining,
+    Learnings,
+    Other,
+}
+
+impl AgentReport {
+    /// Parse the last status block in `message`.
+    ///
+    /// Returns None when the message has neither a status block nor the
+    /// completion sentinel.
+    pub fn parse(message: &str) -> Option<Self> {
+        let Some(body) = last_block(message) else {
+            return message.contains(COMPLETION_SENTINEL).then(|| Self {
+                status: Some(ReportStatus::Complete),
+                learnings: Vec::new(),
+            });
+        };
+
+        let mut status: Option<String> = None;
+        let mut reason = String::new();
+        let mut remaining = Vec::new();
+        let mut learnings = Vec::new();
+        let mut field = ListField::Other;
+
+        for line in body.lines() {
+            let line = line.trim();
+            if let Some(item) = line.strip_prefix("- ").or_else(|| line.strip_prefix("* ")) {
+                let item = item.trim().to_string();
+                match field {
+                    ListField::Remaining if !item.is_empty() => remaining.push(item),
+                    ListField::Learnings if !item.is_empty() => learnings.push(item),
+                    _ => {}
+                }
+                continue;
+            }
+
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let value = value.trim();
+            field = ListField::Other;
+            match key.trim().to_lowercase().as_str() {
+                "status" => status = Some(value.to_lowercase()),
+                "reason" => reason = value.to_string(),
+                "remaining" => {
+                    field = ListField::Remaining;
+                    if !value.is_empty() {
+                        remaining.push(value.to_string());
+                    }
+                }
+                "learnings" => {
+                    field = ListField::Learnings;
+                    if !value.is_empty() {
+                        learnings.push(value.to_string());
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let status = match status.as_deref() {
+            Some("complete") | Some("completed") | Some("done") => Some(ReportStatus::Complete),
+            Some("blocked") => Some(ReportStatus::Blocked {
+                reason: if reason.is_empty() {
+                    "no reason given".to_string()
+                } else {
+                    reason
+                },
+            }),
+            Some("partial") | Some("incomplete") => Some(ReportStatus::Partial { remaining }),
+            _ if message.contains(COMPLETION_SENTINEL) => Some(ReportStatus::Complete),
+            _ => None,
+        };
+
+        Some(Self { status, learnings })
+    }
+
+    /// Whether the agent reported the story complete
+    pub fn is_complete(&self) -> bool {
+        self.status == Some(ReportStatus::Complete)
+    }
+}
+
+/// Body of the last `<ralph-status>` block in `message`
+fn last_block(message: &str) -> Option<&str> {
+    let start = message.rfind(REPORT_OPEN_TAG)? + REPORT_OPEN_TAG.len();
+    let rest = &message[start..];
+    let end = rest.find(REPORT_CLOSE_TAG).unwrap_or(rest.len());
+    Some(&rest[..end])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_complete_with_learnings() {
+        let message = "Implemented the form.\n\n<ralph-status>\nstatus: complete\nlearnings:\n- Forms use the Form helper\n- Seed a user in tests\n</ralph-status>\n";
+        let report = AgentReport::parse(message).unwrap();
+
+        assert!(report.is_complete());
+        assert_eq!(
+            report.learnings,
+            vec!["Forms use the Form helper", "Seed a user in tests"]
+        );
+    }
+
+    #[test]
+    fn test_parse_blocked() {
+        let message = "<ralph-status>\nstatus: Blocked\nreason: The API key for the payments sandbox is missing\n</ralph-status>";
+        let report = AgentReport::parse(message).unwrap();
+
+        assert_eq!(
+            report.status,
+            Some(ReportStatus::Blocked {
+                reason: "The API key for the payments sandbox is missing".to_string()
+            })
+        );
+        assert!(!report.is_complete());
+
+        let report = AgentReport::parse("<ralph-status>status: blocked</ralph-status>").unwrap();
+        assert!(
+            matches!(report.status, Some(ReportStatus::Blocked { ref reason }) if reason == "no reason given")
+        );
+    }
+
+    #[test]
+    fn test_parse_partial() {
+        let message = "<ralph-status>\nstatus: partial\nremaining:\n- Show an error for invalid emails\n* Disable submit while saving\nlearnings:\n- Validation is in validate.ts\n</ralph-status>";
+        let report = AgentReport::parse(message).unwrap();
+
+        assert_eq!(
+            report.status,
+            Some(ReportStatus::Partial {
+                remaining: vec![
+                    "Show an error for invalid emails".to_string(),
+                    "Disable submit while saving".to_string(),
+                ]
+            })
+        );
+        assert_eq!(report.learnings, vec!["Validation is in validate.ts"]);
+    }
+
+    #[test]
+    fn test_parse_uses_last_block() {
+        let message = "Template:\n<ralph-status>\nstatus: blocked\n</ralph-status>\nActual:\n<ralph-status>\nstatus: complete\n</ralph-status>";
+        assert!(AgentReport::parse(message).unwrap().is_complete());
+    }
+
+    #[test]
+    fn test_parse_learnings_only_block() {
+        let report = AgentReport::parse(
+            "<ralph-status>\nlearnings: Cache keys are per user\n</ralph-status>",
+        )
+        .unwrap();
+        assert_eq!(report.status, None);
+        assert_eq!(report.learnings, vec!["Cache keys are per user"]);
+    }
+
+    #[test]
+    fn test_parse_completion_sentinel() {
+        let report = AgentReport::parse("All done.\n<promise>COMPLETE</promise>").unwrap();
+        assert!(report.is_complete());
+        assert!(report.learnings.is_empty());
+    }
+
+    #[test]
+    fn test_parse_without_report() {
+        assert_eq!(AgentReport::parse("Implemented the feature."), None);
+    }
+
+    #[test]
+    fn test_parse_unterminated_block() {
+        let report = AgentReport::parse("<ralph-status>\nstatus: complete").unwrap();
+        assert!(report.is_complete());
+    }
+}
@@ -0,0 +1,555 @@
+//! Scripted agent backend
+//!
+//! A deterministic stand-in for a real agent CLI, driven by a TOML fixture.
+//! Each iteration can write files, apply a patch, print output, emit
+//! rate-limit text, stall, and exit with a chosen code. This lets the
+//! executor, runner, timeout and checkpoint paths be exercised without a
+//! model. Select it with `agent_command = "scripted:<fixture path>"`.
+//!
+//! ```toml
+//! # Output format the lines are parsed as: "text", "claude" or "codex"
+//! stream_format = "text"
+//!
+//! # Optional file every prompt is appended to, for asserting on prompt content
+//! prompt_log = "/tmp/prompts.log"
+//!
+//! # Steps used by every story without its own script
+//! [[iterations]]
+//! exit_code = 1
+//! stderr = ["error: build failed"]
+//!
+//! [[iterations]]
+//! output = ["Implemented the feature"]
+//! write_files = { "src/feature.txt" = "done\n" }
+//!
+//! # Steps for a specific story
+//! [[stories.US-002.iterations]]
+//! rate_limit = true
+//!
+//! # Fail the first two attempts with a network error, then succeed
+//! [[stories.US-003.iterations]]
+//! transient_failures = 2
+//! output = ["Recovered"]
+//! ```
+//!
+//! Iteration `n` runs step `n`; iterations past the end repeat the last step.
+//! Retries of an iteration after a transient failure rerun the same step.
+
+#![allow(dead_code)]
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use super::registry::AgentConfigError;
+use super::stream::StreamFormat;
+use super::traits::{
+    is_program_in_path, AgentBackend, AgentCapabilities, AgentInvocation, AgentRequest,
+};
+
+/// Prefix of agent commands that select the scripted backend
+pub const SCRIPTED_AGENT_PREFIX: &str = "scripted:";
+
+/// Text printed to stderr by steps with `rate_limit = true`
+pub const RATE_LIMIT_MESSAGE: &str = "Error: rate limit exceeded (429 Too Many Requests)";
+
+/// Text printed to stderr by the failing attempts of steps with `transient_failures`
+pub const TRANSIENT_ERROR_MESSAGE: &str = "Error: connection reset by peer";
+
+/// Output format a fixture's lines are parsed as
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum FixtureFormat {
+    /// Plain text output
+    #[default]
+    Text,
+    /// Claude Code stream-json lines
+    Claude,
+    /// Codex `exec --json` lines
+    Codex,
+}
+
+impl From<FixtureFormat> for StreamFormat {
+    fn from(format: FixtureFormat) -> Self {
+        match format {
+            FixtureFormat::Text => StreamFormat::Text,
+            FixtureFormat::Claude => StreamFormat::ClaudeStreamJson,
+            FixtureFormat::Codex => StreamFormat::CodexJson,
+        }
+    }
+}
+
+/// What the scripted agent does in one iteration
+#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
+pub struct ScriptStep {
+    /// Lines printed to stdout
+    #[serde(default)]
+    pub output: Vec<String>,
+    /// Lines printed to stderr
+    #[serde(default)]
+    pub stderr: Vec<String>,
+    /// Files written relative to the project root (path -> content)
+    #[serde(default)]
+    pub write_files: BTreeMap<String, String>,
+    /// Unified diff applied with `git apply`
+    #[serde(default)]
+    pub patch: Option<String>,
+    /// Print a rate-limit error to stderr (exits 1 unless `exit_code` is set)
+    #[serde(default)]
+    pub rate_limit: bool,
+    /// Seconds to sleep without output before exiting
+    #[serde(default)]
+    pub stall_seconds: Option<u64>,
+    /// Process exit code (defaults to 0)
+    #[serde(default)]
+    pub exit_code: Option<i32>,
+    /// Attempts of this iteration that fail with a transient network error
+    /// before the step runs
+    #[serde(default)]
+    pub transient_failures: u32,
+}
+
+impl ScriptStep {
+    /// The exit code this step finishes with
+    pub fn effective_exit_code(&self) -> i32 {
+        self.exit_code
+            .unwrap_or(if self.rate_limit { 1 } else { 0 })
+    }
+
+    /// Render the script for an attempt (1-indexed) of this step's iteration
+    pub fn script_for_attempt(&self, attempt: u32) -> String {
+        if attempt <= self.transient_failures {
+            format!(
+                "printf '%s\\n' {} >&2\nexit 1",
+                sh_quote(TRANSIENT_ERROR_MESSAGE)
+            )
+        } else {
+            self.to_shell_script()
+        }
+    }
+
+    /// Render this step as a POSIX shell script
+    pub fn to_shell_script(&self) -> String {
+        let mut lines = vec!["set -e".to_string()];
+
+        for (path, content) in &self.write_files {
+            let path = sh_quote(path);
+            lines.push(format!("mkdir -p \"$(dirname {})\"", path));
+            lines.push(format!("printf '%s' {} > {}", sh_quote(content), path));
+        }
+
+        if let Some(patch) = &self.patch {
+            lines.push(format!(
+                "printf '%s\\n' {} | git apply - || {{ echo 'scripted agent: patch did not apply' >&2; exit 1; }}",
+                sh_quote(patch.trim_end_matches('\n'))
+            ));
+        }
+
+        for line in &self.output {
+            lines.push(format!("printf '%s\\n' {}", sh_quote(line)));
+        }
+        for line in &self.stderr {
+            lines.push(format!("printf '%s\\n' {} >&2", sh_quote(line)));
+        }
+        if self.rate_limit {
+            lines.push(format!(
+                "printf '%s\\n' {} >&2",
+                sh_quote(RATE_LIMIT_MESSAGE)
+            ));
+        }
+
+        if let Some(seconds) = self.stall_seconds {
+            lines.push(format!("sleep {}", seconds));
+        }
+
+        lines.push(format!("exit {}", self.effective_exit_code()));
+        lines.join("\n")
+    }
+}
+
+/// Steps for a single story
+#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
+pub struct StoryScript {
+    /// Steps by iteration
+    #[serde(default)]
+    pub iterations: Vec<ScriptStep>,
+}
+
+/// A scripted agent fixture
+#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
+pub struct ScriptFixture {
+    /// Format the step output is parsed as
+    #[serde(default)]
+    pub stream_format: FixtureFormat,
+    /// Steps for stories without their own script
+    #[serde(default)]
+    pub iterations: Vec<ScriptStep>,
+    /// Per-story steps by story ID
+    #[serde(default)]
+    pub stories: BTreeMap<String, StoryScript>,
+    /// File each prompt is appended to (relative paths are under the project root)
+    #[serde(default)]
+    pub prompt_log: Option<String>,
+}
+
+impl ScriptFixture {
+    /// Parse a fixture from a TOML string
+    pub fn parse(content: &str) -> Result<Self, AgentConfigError> {
+        Ok(toml::from_str(content)?)
+    }
+
+    /// Load a fixture from a file
+    pub fn load(path: &Path) -> Result<Self, AgentConfigError> {
+        let content = std::fs::read_to_string(path)?;
+        Self::parse(&content)
+    }
+
+    /// The step to run for a story iteration
+    ///
+    /// Iterations are 1-indexed; iterations past the end of the script repeat
+    /// the last step. Returns None if no steps apply.
+    pub fn step_for(&self, story_id: &str, iteration: u32) -> Option<&ScriptStep> {
+        let steps = self
+            .stories
+            .get(story_id)
+            .map(|story| &story.iterations)
+            .filter(|steps| !steps.is_empty())
+            .unwrap_or(&self.iterations);
+
+        let index = (iteration.max(1) as usize - 1).min(steps.len().checked_sub(1)?);
+        steps.get(index)
+    }
+}
+
+/// Agent backend that replays a fixture instead of calling a model
+#[derive(Debug, Clone)]
+pub struct ScriptedBackend {
+    /// Path the fixture was loaded from
+    fixture_path: PathBuf,
+    /// The loaded fixture, or the error that prevented loading it
+    fixture: Result<ScriptFixture, String>,
+}
+
+impl ScriptedBackend {
+    /// Load a scripted backend from a fixture file
+    ///
+    /// Load errors are reported by the agent process itself (exit code 2), so
+    /// they surface like any other agent failure.
+    pub fn from_file(path: impl Into<PathBuf>) -> Self {
+        let fixture_path = path.into();
+        let fixture = ScriptFixture::load(&fixture_path).map_err(|e| e.to_string());
+        Self {
+            fixture_path,
+            fixture,
+        }
+    }
+
+    /// Create a scripted backend from an in-memory fixture
+    pub fn from_fixture(fixture: ScriptFixture) -> Self {
+        Self {
+            fixture_path: PathBuf::new(),
+            fixture: Ok(fixture),
+        }
+    }
+
+    /// Parse a `scripted:<path>` agent command
+    ///
+    /// # Returns
+    /// The backend, or None if the command does not use the scripted prefix
+    pub fn from_agent_command(agent_command: &str) -> Option<Self> {
+        agent_command
+            .strip_prefix(SCRIPTED_AGENT_PREFIX)
+            .map(|path| Self::from_file(path.trim()))
+    }
+
+    /// Path the fixture was loaded from
+    pub fn fixture_path(&self) -> &Path {
+        &self.fixture_path
+    }
+
+    /// The loaded fixture, if it parsed
+    pub fn fixture(&self) -> Option<&ScriptFixture> {
+        self.fixture.as_ref().ok()
+    }
+}
+
+impl AgentBackend for ScriptedBackend {
+    fn name(&self) -> &str {
+        "scripted"
+    }
+
+    fn program(&self) -> &str {
+        "sh"
+    }
+
+    fn build_invocation(&self, request: &AgentRequest<'_>) -> AgentInvocation {
+        let script = match &self.fixture {
+            Ok(fixture) => {
+                let step = fixture
+                    .step_for(request.story_id, request.iteration)
+                    .cloned()
+                    .unwrap_or_default()
+                    .script_for_attempt(request.attempt);
+                match &fixture.prompt_log {
+                    Some(log) => format!(
+                        "printf '%s\\n%s\\n' {} '=== end of prompt ===' >> {}\n{}",
+                        sh_quote(request.prompt),
+                        sh_quote(log),
+                        step
+                    ),
+                    None => step,
+                }
+            }
+            Err(e) => format!(
+                "printf '%s\\n' {} >&2\nexit 2",
+                sh_quote(&format!(
+                    "scripted agent: failed to load {}: {}",
+                    self.fixture_path.display(),
+                    e
+                ))
+            ),
+        };
+
+        AgentInvocation::new(self.program(), vec!["-c".to_string(), script])
+    }
+
+    fn stream_format(&self) -> StreamFormat {
+        self.fixture()
+            .map(|fixture| fixture.stream_format.into())
+            .unwrap_or_default()
+    }
+
+    fn capabilities(&self) -> AgentCapabilities {
+        AgentCapabilities {
+            structured_output: self.stream_format() != StreamFormat::Text,
+            model_selection: false,
+            non_interactive: true,
+        }
+    }
+
+    fn is_available(&self) -> bool {
+        is_program_in_path(self.program())
+    }
+}
+
+/// Quote a string for use as a single POSIX shell word
+fn sh_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "'\\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::AgentEvent;
+
+    const FIXTURE: &str = r#"
+stream_format = "claude"
+
+[[iterations]]
+exit_code = 1
+stderr = ["error: build failed"]
+
+[[iterations]]
+output = ["done"]
+
+[[stories.US-002.iterations]]
+rate_limit = true
+"#;
+
+    fn request(story_id: &str, iteration: u32) -> AgentRequest<'_> {
+        AgentRequest::new("prompt", Path::new("/tmp/project")).with_story(story_id, iteration)
+    }
+
+    #[test]
+    fn test_parse_fixture() {
+        let fixture = ScriptFixture::parse(FIXTURE).unwrap();
+        assert_eq!(fixture.stream_format, FixtureFormat::Claude);
+        assert_eq!(fixture.iterations.len(), 2);
+        assert_eq!(fixture.iterations[0].exit_code, Some(1));
+        assert!(fixture.stories["US-002"].iterations[0].rate_limit);
+    }
+
+    #[test]
+    fn test_parse_fixture_rejects_unknown_format() {
+        assert!(ScriptFixture::parse("stream_format = \"xml\"").is_err());
+    }
+
+    #[test]
+    fn test_step_for_repeats_last_step() {
+        let fixture = ScriptFixture::parse(FIXTURE).unwrap();
+        assert_eq!(fixture.step_for("US-001", 1).unwrap().exit_code, Some(1));
+        assert_eq!(fixture.step_for("US-001", 2).unwrap().output, vec!["done"]);
+        assert_eq!(fixture.step_for("US-001", 9).unwrap().output, vec!["done"]);
+        assert_eq!(fixture.step_for("US-001", 0).unwrap().exit_code, Some(1));
+    }
+
+    #[test]
+    fn test_step_for_prefers_story_script() {
+        let fixture = ScriptFixture::parse(FIXTURE).unwrap();
+        assert!(fixture.step_for("US-002", 1).unwrap().rate_limit);
+        assert!(fixture.step_for("US-002", 5).unwrap().rate_limit);
+    }
+
+    #[test]
+    fn test_step_for_empty_fixture() {
+        let fixture = ScriptFixture::default();
+        assert!(fixture.step_for("US-001", 1).is_none());
+    }
+
+    #[test]
+    fn test_effective_exit_code() {
+        assert_eq!(ScriptStep::default().effective_exit_code(), 0);
+        let rate_limited = ScriptStep {
+            rate_limit: true,
+            ..Default::default()
+        };
+        assert_eq!(rate_limited.effective_exit_code(), 1);
+        let explicit = ScriptStep {
+            rate_limit: true,
+            exit_code: Some(3),
+            ..Default::default()
+        };
+        assert_eq!(explicit.effective_exit_code(), 3);
+    }
+
+    #[test]
+    fn test_shell_script_quotes_content() {
+        let step = ScriptStep {
+            output: vec!["it's done".to_string()],
+            write_files: BTreeMap::from([("dir/a b.txt".to_string(), "x'y".to_string())]),
+            stall_seconds: Some(2),
+            exit_code: Some(4),
+            ..Default::default()
+        };
+        let script = step.to_shell_script();
+        assert!(script.contains("printf '%s\\n' 'it'\\''s done'"));
+        assert!(script.contains("printf '%s' 'x'\\''y' > 'dir/a b.txt'"));
+        assert!(script.contains("sleep 2"));
+        assert!(script.ends_with("exit 4"));
+    }
+
+    #[test]
+    fn test_shell_script_rate_limit() {
+        let step = ScriptStep {
+            rate_limit: true,
+            ..Default::default()
+        };
+        let script = step.to_shell_script();
+        assert!(script.contains(RATE_LIMIT_MESSAGE));
+        assert!(script.ends_with("exit 1"));
+    }
+
+    #[test]
+    fn test_script_for_attempt_fails_transiently_first() {
+        let step = ScriptStep {
+            output: vec!["done".to_string()],
+            transient_failures: 2,
+            ..Default::default()
+        };
+        for attempt in 1..=2 {
+            let script = step.script_for_attempt(attempt);
+            assert!(script.contains(TRANSIENT_ERROR_MESSAGE));
+            assert!(script.ends_with("exit 1"));
+        }
+        assert_eq!(step.script_for_attempt(3), step.to_shell_script());
+    }
+
+    #[test]
+    fn test_from_agent_command() {
+        assert!(ScriptedBackend::from_agent_command("claude").is_none());
+        let backend = ScriptedBackend::from_agent_command("scripted:/nonexistent.toml").unwrap();
+        assert_eq!(backend.fixture_path(), Path::new("/nonexistent.toml"));
+        assert!(backend.fixture().is_none());
+    }
+
+    #[test]
+    fn test_missing_fixture_invocation_fails() {
+        let backend = ScriptedBackend::from_file("/nonexistent/fixture.toml");
+        let invocation = backend.build_invocation(&request("US-001", 1));
+        assert_eq!(invocation.program, "sh");
+        assert_eq!(invocation.args[0], "-c");
+        assert!(invocation.args[1].contains("failed to load /nonexistent/fixture.toml"));
+        assert!(invocation.args[1].ends_with("exit 2"));
+    }
+
+    #[test]
+    fn test_backend_uses_fixture_stream_format() {
+        let backend = ScriptedBackend::from_fixture(ScriptFixture::parse(FIXTURE).unwrap());
+        assert_eq!(backend.stream_format(), StreamFormat::ClaudeStreamJson);
+        assert!(backend.capabilities().structured_output);
+
+        let line = r#"{"type":"assistant","message":{"content":[{"type":"text","text":"hi"}]}}"#;
+        assert_eq!(
+            backend.parse_stream_line(line),
+            vec![AgentEvent::AssistantText {
+                text: "hi".to_string()
+            }]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_invocation_runs_step() {
+        let dir = tempfile::tempdir().unwrap();
+        let fixture = ScriptFixture::parse(
+            r#"
+[[iterations]]
+output = ["hello"]
+stderr = ["warn"]
+write_files = { "nested/out.txt" = "content\n" }
+exit_code = 3
+"#,
+        )
+        .unwrap();
+        let backend = ScriptedBackend::from_fixture(fixture);
+        let invocation = backend.build_invocation(&request("US-001", 1));
+
+        let output = std::process::Command::new(&invocation.program)
+            .args(&invocation.args)
+            .current_dir(dir.path())
+            .output()
+            .unwrap();
+
+        assert_eq!(output.status.code(), Some(3));
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "hello\n");
+        assert_eq!(String::from_utf8_lossy(&output.stderr), "warn\n");
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("nested/out.txt")).unwrap(),
+            "content\n"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_invocation_logs_prompt() {
+        let dir = tempfile::tempdir().unwrap();
+        let fixture = ScriptFixture::parse(
+            r#"
+prompt_log = "prompts.log"
+
+[[iterations]]
+output = ["done"]
+"#,
+        )
+        .unwrap();
+        let backend = ScriptedBackend::from_fixture(fixture);
+        let request = AgentRequest::new("Implement 'US-001'\nnow", dir.path());
+
+        for _ in 0..2 {
+            let invocation = backend.build_invocation(&request);
+            let status = std::process::Command::new(&invocation.program)
+                .args(&invocation.args)
+                .current_dir(dir.path())
+                .status()
+                .unwrap();
+            assert!(status.success());
+        }
+
+        let log = std::fs::read_to_string(dir.path().join("prompts.log")).unwrap();
+        assert_eq!(
+            log,
+            "Implement 'US-001'\nnow\n=== end of prompt ===\n".repeat(2)
+        );
+    }
+}
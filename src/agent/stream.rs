@@ -0,0 +1,560 @@
+//! Structured agent output parsing
+//!
+//! This module turns the line-delimited JSON emitted by agent CLIs (Claude
+//! Code's `--output-format stream-json` and Codex's `exec --json`) into typed
+//! [`AgentEvent`]s describing what the agent did during an iteration.
+
+#![allow(dead_code)]
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Token usage reported by an agent.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenUsage {
+    /// Input (prompt) tokens
+    pub input_tokens: u64,
+    /// Output (completion) tokens
+    pub output_tokens: u64,
+    /// Input tokens served from the prompt cache
+    pub cache_read_input_tokens: u64,
+    /// Input tokens written to the prompt cache
+    pub cache_creation_input_tokens: u64,
+}
+
+impl TokenUsage {
+    /// Total tokens across all categories.
+    pub fn total(&self) -> u64 {
+        self.input_tokens
+            + self.output_tokens
+            + self.cache_read_input_tokens
+            + self.cache_creation_input_tokens
+    }
+
+    /// Add another usage record to this one.
+    pub fn add(&mut self, other: &TokenUsage) {
+        self.input_tokens += other.input_tokens;
+        self.output_tokens += other.output_tokens;
+        self.cache_read_input_tokens += other.cache_read_input_tokens;
+        self.cache_creation_input_tokens += other.cache_creation_input_tokens;
+    }
+
+    /// Parse a usage object (Claude and Codex field names).
+    fn from_json(value: &Value) -> Self {
+        let field = |name: &str| value.get(name).and_then(Value::as_u64).unwrap_or(0);
+        Self {
+            input_tokens: field("input_tokens"),
+            output_tokens: field("output_tokens"),
+            cache_read_input_tokens: field("cache_read_input_tokens")
+                + field("cached_input_tokens"),
+            cache_creation_input_tokens: field("cache_creation_input_tokens"),
+        }
+    }
+}
+
+/// A typed event parsed from agent output.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AgentEvent {
+    /// Text written by the assistant
+    AssistantText { text: String },
+    /// A tool invocation that is not a file edit or shell command
+    ToolCall { name: String, input: Value },
+    /// A file created or modified by the agent
+    FileEdit { path: String, tool: String },
+    /// A shell command run by the agent
+    ShellCommand {
+        command: String,
+        exit_code: Option<i32>,
+    },
+    /// Final result of the agent run
+    Result {
+        success: bool,
+        text: Option<String>,
+        duration_ms: Option<u64>,
+        num_turns: Option<u32>,
+    },
+    /// Token usage (and cost, when the agent reports it)
+    Usage {
+        usage: TokenUsage,
+        cost_usd: Option<f64>,
+    },
+}
+
+impl AgentEvent {
+    /// Short human-readable description for activity displays.
+    pub fn summary(&self) -> String {
+        match self {
+            AgentEvent::AssistantText { text } => {
+                let first_line = text.lines().next().unwrap_or_default();
+                truncate(first_line, 80)
+            }
+            AgentEvent::ToolCall { name, .. } => format!("tool: {}", name),
+            AgentEvent::FileEdit { path, .. } => format!("edit: {}", path),
+            AgentEvent::ShellCommand { command, .. } => format!("$ {}", truncate(command, 76)),
+            AgentEvent::Result { success, .. } => {
+                if *success {
+                    "agent finished".to_string()
+                } else {
+                    "agent reported an error".to_string()
+                }
+            }
+            AgentEvent::Usage { usage, .. } => format!("{} tokens", usage.total()),
+        }
+    }
+}
+
+/// Line formats understood by the stream parser.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StreamFormat {
+    /// Unstructured text; no events are produced
+    #[default]
+    Text,
+    /// Claude Code `--output-format stream-json`
+    ClaudeStreamJson,
+    /// Codex `exec --json`
+    CodexJson,
+}
+
+impl StreamFormat {
+    /// Parse one line of agent output into zero or more events.
+    ///
+    /// Lines that are not valid JSON (or not a recognized message) yield no events.
+    pub fn parse_line(&self, line: &str) -> Vec<AgentEvent> {
+        let trimmed = line.trim();
+        if !trimmed.starts_with('{') {
+            return Vec::new();
+        }
+
+        let value: Value = match serde_json::from_str(trimmed) {
+            Ok(v) => v,
+            Err(_) => return Vec::new(),
+        };
+
+        match self {
+            StreamFormat::Text => Vec::new(),
+            StreamFormat::ClaudeStreamJson => parse_claude_message(&value),
+            StreamFormat::CodexJson => parse_codex_message(&value),
+        }
+    }
+
+    /// Find the agent's final message in collected stdout.
+    pub fn final_message(&self, stdout: &str) -> Option<String> {
+        let mut last = None;
+        for line in stdout.lines() {
+            for event in self.parse_line(line) {
+                match event {
+                    AgentEvent::Result {
+                        text: Some(text), ..
+                    } if *self == StreamFormat::ClaudeStreamJson => last = Some(text),
+                    AgentEvent::AssistantText { text } if *self == StreamFormat::CodexJson => {
+                        last = Some(text)
+                    }
+                    _ => {}
+                }
+            }
+        }
+        last
+    }
+}
+
+/// Map a tool invocation to the most specific event type.
+fn tool_event(name: &str, input: &Value) -> AgentEvent {
+    let str_field = |key: &str| input.get(key).and_then(Value::as_str).map(str::to_string);
+
+    match name {
+        "Edit" | "MultiEdit" | "Write" | "NotebookEdit" => {
+            if let Some(path) = str_field("file_path").or_else(|| str_field("notebook_path")) {
+                return AgentEvent::FileEdit {
+                    path,
+                    tool: name.to_string(),
+                };
+            }
+        }
+        "Bash" => {
+            if let Some(command) = str_field("command") {
+                return AgentEvent::ShellCommand {
+                    command,
+                    exit_code: None,
+                };
+            }
+        }
+        _ => {}
+    }
+
+    AgentEvent::ToolCall {
+        name: name.to_string(),
+        input: input.clone(),
+    }
+}
+
+/// Parse a Claude Code stream-json message.
+fn parse_claude_message(value: &Value) -> Vec<AgentEvent> {
+    let mut events = Vec::new();
+
+    match value.get("type").and_then(Value::as_str) {
+        Some("assistant") => {
+            let content = value
+                .get("message")
+                .and_then(|m| m.get("content"))
+                .and_then(Value::as_array);
+            for block in content.into_iter().flatten() {
+                match block.get("type").and_then(Value::as_str) {
+                    Some("text") => {
+                        if let Some(text) = block.get("text").and_then(Value::as_str) {
+                            if !text.trim().is_empty() {
+                                events.push(AgentEvent::AssistantText {
+                                    text: text.to_string(),
+                                });
+                            }
+                        }
+                    }
+                    Some("tool_use") => {
+                        let name = block.get("name").and_then(Value::as_str).unwrap_or("");
+                        let input = block.get("input").cloned().unwrap_or(Value::Null);
+                        events.push(tool_event(name, &input));
+                    }
+                    _ => {}
+                }
+            }
+        }
+        Some("result") => {
+            let is_error = value
+                .get("is_error")
+                .and_then(Value::as_bool)
+                .unwrap_or(false);
+            events.push(AgentEvent::Result {
+                success: !is_error,
+                text: value
+                    .get("result")
+                    .and_then(Value::as_str)
+                    .map(str::to_string),
+                duration_ms: value.get("duration_ms").and_then(Value::as_u64),
+                num_turns: value
+                    .get("num_turns")
+                    .and_then(Value::as_u64)
+                    .map(|n| n as u32),
+            });
+            if let Some(usage) = value.get("usage") {
+                events.push(AgentEvent::Usage {
+                    usage: TokenUsage::from_json(usage),
+                    cost_usd: value.get("total_cost_usd").and_then(Value::as_f64),
+                });
+            }
+        }
+        _ => {}
+    }
+
+    events
+}
+
+/// Parse a Codex `exec --json` message.
+fn parse_codex_message(value: &Value) -> Vec<AgentEvent> {
+    let mut events = Vec::new();
+
+    match value.get("type").and_then(Value::as_str) {
+        Some("item.completed") => {
+            let Some(item) = value.get("item") else {
+                return events;
+            };
+            let str_field = |key: &str| item.get(key).and_then(Value::as_str).map(str::to_string);
+
+            match item.get("type").and_then(Value::as_str) {
+                Some("agent_message") => {
+                    if let Some(text) = str_field("text") {
+                        events.push(AgentEvent::AssistantText { text });
+                    }
+                }
+                Some("command_execution") => {
+                    if let Some(command) = str_field("command") {
+                        events.push(AgentEvent::ShellCommand {
+                            command,
+                            exit_code: item
+                                .get("exit_code")
+                                .and_then(Value::as_i64)
+                                .map(|c| c as i32),
+                        });
+                    }
+                }
+                Some("file_change") => {
+                    let changes = item.get("changes").and_then(Value::as_array);
+                    for change in changes.into_iter().flatten() {
+                        if let Some(path) = change.get("path").and_then(Value::as_str) {
+                            events.push(AgentEvent::FileEdit {
+                                path: path.to_string(),
+                                tool: change
+                                    .get("kind")
+                                    .and_then(Value::as_str)
+                                    .unwrap_or("patch")
+                                    .to_string(),
+                            });
+                        }
+                    }
+                }
+                Some("mcp_tool_call") => {
+                    let name = match (str_field("server"), str_field("tool")) {
+                        (Some(server), Some(tool)) => format!("{}.{}", server, tool),
+                        (_, Some(tool)) => tool,
+                        _ => "mcp".to_string(),
+                    };
+                    events.push(AgentEvent::ToolCall {
+                        name,
+                        input: item.get("arguments").cloned().unwrap_or(Value::Null),
+                    });
+                }
+                _ => {}
+            }
+        }
+        Some("turn.completed") => {
+            events.push(AgentEvent::Result {
+                success: true,
+                text: None,
+                duration_ms: None,
+                num_turns: None,
+            });
+            if let Some(usage) = value.get("usage") {
+                events.push(AgentEvent::Usage {
+                    usage: TokenUsage::from_json(usage),
+                    cost_usd: None,
+                });
+            }
+        }
+        Some("turn.failed") => {
+            events.push(AgentEvent::Result {
+                success: false,
+                text: value
+                    .get("error")
+                    .and_then(|e| e.get("message"))
+                    .and_then(Value::as_str)
+                    .map(str::to_string),
+                duration_ms: None,
+                num_turns: None,
+            });
+        }
+        _ => {}
+    }
+
+    events
+}
+
+/// Truncate a string to a maximum number of characters.
+fn truncate(s: &str, max_chars: usize) -> String {
+    if s.chars().count() <= max_chars {
+        s.to_string()
+    } else {
+        let truncated: String = s.chars().take(max_chars.saturating_sub(3)).collect();
+        format!("{}...", truncated)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_format_produces_no_events() {
+        assert!(StreamFormat::Text
+            .parse_line(r#"{"type":"assistant"}"#)
+            .is_empty());
+        assert!(StreamFormat::ClaudeStreamJson
+            .parse_line("plain text output")
+            .is_empty());
+        assert!(StreamFormat::ClaudeStreamJson
+            .parse_line("{not json")
+            .is_empty());
+    }
+
+    #[test]
+    fn test_claude_assistant_message() {
+        let line = r#"{"type":"assistant","message":{"content":[
+            {"type":"text","text":"Adding the parser."},
+            {"type":"tool_use","id":"t1","name":"Edit","input":{"file_path":"src/lib.rs","old_string":"a","new_string":"b"}},
+            {"type":"tool_use","id":"t2","name":"Bash","input":{"command":"cargo test"}},
+            {"type":"tool_use","id":"t3","name":"Grep","input":{"pattern":"fn main"}}
+        ]}}"#
+            .replace('\n', "");
+
+        let events = StreamFormat::ClaudeStreamJson.parse_line(&line);
+        assert_eq!(events.len(), 4);
+        assert_eq!(
+            events[0],
+            AgentEvent::AssistantText {
+                text: "Adding the parser.".to_string()
+            }
+        );
+        assert_eq!(
+            events[1],
+            AgentEvent::FileEdit {
+                path: "src/lib.rs".to_string(),
+                tool: "Edit".to_string()
+            }
+        );
+        assert_eq!(
+            events[2],
+            AgentEvent::ShellCommand {
+                command: "cargo test".to_string(),
+                exit_code: None
+            }
+        );
+        assert!(matches!(&events[3], AgentEvent::ToolCall { name, .. } if name == "Grep"));
+    }
+
+    #[test]
+    fn test_claude_result_with_usage() {
+        let line = r#"{"type":"result","subtype":"success","is_error":false,"duration_ms":5400,"num_turns":6,"result":"Done.","total_cost_usd":0.0421,"usage":{"input_tokens":1200,"output_tokens":350,"cache_read_input_tokens":9000,"cache_creation_input_tokens":400}}"#;
+
+        let events = StreamFormat::ClaudeStreamJson.parse_line(line);
+        assert_eq!(
+            events[0],
+            AgentEvent::Result {
+                success: true,
+                text: Some("Done.".to_string()),
+                duration_ms: Some(5400),
+                num_turns: Some(6),
+            }
+        );
+        match &events[1] {
+            AgentEvent::Usage { usage, cost_usd } => {
+                assert_eq!(usage.input_tokens, 1200);
+                assert_eq!(usage.output_tokens, 350);
+                assert_eq!(usage.cache_read_input_tokens, 9000);
+                assert_eq!(usage.cache_creation_input_tokens, 400);
+                assert_eq!(usage.total(), 10950);
+                assert_eq!(*cost_usd, Some(0.0421));
+            }
+            other => panic!("expected usage event, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_claude_final_message() {
+        let stdout = concat!(
+            r#"{"type":"system","subtype":"init"}"#,
+            "\n",
+            r#"{"type":"assistant","message":{"content":[{"type":"text","text":"Working"}]}}"#,
+            "\n",
+            r#"{"type":"result","is_error":false,"result":"All done"}"#,
+            "\n"
+        );
+        assert_eq!(
+            StreamFormat::ClaudeStreamJson.final_message(stdout),
+            Some("All done".to_string())
+        );
+    }
+
+    #[test]
+    fn test_codex_items() {
+        let format = StreamFormat::CodexJson;
+
+        let events = format.parse_line(
+            r#"{"type":"item.completed","item":{"id":"i0","type":"agent_message","text":"Plan ready"}}"#,
+        );
+        assert_eq!(
+            events,
+            vec![AgentEvent::AssistantText {
+                text: "Plan ready".to_string()
+            }]
+        );
+
+        let events = format.parse_line(
+            r#"{"type":"item.completed","item":{"id":"i1","type":"command_execution","command":"npm test","exit_code":1,"status":"failed"}}"#,
+        );
+        assert_eq!(
+            events,
+            vec![AgentEvent::ShellCommand {
+                command: "npm test".to_string(),
+                exit_code: Some(1)
+            }]
+        );
+
+        let events = format.parse_line(
+            r#"{"type":"item.completed","item":{"id":"i2","type":"file_change","changes":[{"path":"a.rs","kind":"update"},{"path":"b.rs","kind":"add"}]}}"#,
+        );
+        assert_eq!(events.len(), 2);
+        assert_eq!(
+            events[1],
+            AgentEvent::FileEdit {
+                path: "b.rs".to_string(),
+                tool: "add".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_codex_turn_events() {
+        let format = StreamFormat::CodexJson;
+
+        let events = format.parse_line(
+            r#"{"type":"turn.completed","usage":{"input_tokens":500,"cached_input_tokens":100,"output_tokens":50}}"#,
+        );
+        assert!(matches!(
+            events[0],
+            AgentEvent::Result { success: true, .. }
+        ));
+        match &events[1] {
+            AgentEvent::Usage { usage, cost_usd } => {
+                assert_eq!(usage.input_tokens, 500);
+                assert_eq!(usage.cache_read_input_tokens, 100);
+                assert_eq!(usage.output_tokens, 50);
+                assert!(cost_usd.is_none());
+            }
+            other => panic!("expected usage event, got {:?}", other),
+        }
+
+        let events =
+            format.parse_line(r#"{"type":"turn.failed","error":{"message":"usage limit"}}"#);
+        assert_eq!(
+            events,
+            vec![AgentEvent::Result {
+                success: false,
+                text: Some("usage limit".to_string()),
+                duration_ms: None,
+                num_turns: None,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_event_summary() {
+        let event = AgentEvent::ShellCommand {
+            command: "cargo build".to_string(),
+            exit_code: Some(0),
+        };
+        assert_eq!(event.summary(), "$ cargo build");
+
+        let event = AgentEvent::FileEdit {
+            path: "src/main.rs".to_string(),
+            tool: "Write".to_string(),
+        };
+        assert_eq!(event.summary(), "edit: src/main.rs");
+    }
+
+    #[test]
+    fn test_token_usage_add() {
+        let mut total = TokenUsage::default();
+        total.add(&TokenUsage {
+            input_tokens: 10,
+            output_tokens: 5,
+            ..Default::default()
+        });
+        total.add(&TokenUsage {
+            input_tokens: 1,
+            cache_read_input_tokens: 2,
+            ..Default::default()
+        });
+        assert_eq!(total.input_tokens, 11);
+        assert_eq!(total.total(), 18);
+    }
+
+    #[test]
+    fn test_event_serialization() {
+        let event = AgentEvent::FileEdit {
+            path: "x.rs".to_string(),
+            tool: "Edit".to_string(),
+        };
+        let json = serde_json::to_string(&event).unwrap();
+        assert!(json.contains("\"type\":\"file_edit\""));
+        let parsed: AgentEvent = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, event);
+    }
+}
@@ -0,0 +1,295 @@
+//! Agent backend traits
+//!
+//! This module defines the core trait for integrating agent CLIs with the
+//! story executor, along with the types passed across that boundary.
+
+#![allow(dead_code)]
+
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use super::report::AgentReport;
+use super::stream::{AgentEvent, StreamFormat};
+use crate::error::{ClassifiedError, ErrorDetector};
+
+/// Everything a backend needs to build an agent command line.
+#[derive(Debug, Clone, Copy)]
+pub struct AgentRequest<'a> {
+    /// The full prompt for the agent
+    pub prompt: &'a str,
+    /// The directory the agent should work in
+    pub project_root: &'a Path,
+    /// ID of the story being implemented (empty if not story-scoped)
+    pub story_id: &'a str,
+    /// Iteration number within the story (1-indexed, 0 if unknown)
+    pub iteration: u32,
+    /// Attempt within the iteration (1-indexed; >1 when retrying a transient failure)
+    pub attempt: u32,
+    /// Model requested for this story (None = the agent's default)
+    pub model: Option<&'a str>,
+}
+
+impl<'a> AgentRequest<'a> {
+    /// Create a request that is not tied to a story iteration.
+    pub fn new(prompt: &'a str, project_root: &'a Path) -> Self {
+        Self {
+            prompt,
+            project_root,
+            story_id: "",
+            iteration: 0,
+            attempt: 1,
+            model: None,
+        }
+    }
+
+    /// Attach the story and iteration being executed.
+    pub fn with_story(mut self, story_id: &'a str, iteration: u32) -> Self {
+        self.story_id = story_id;
+        self.iteration = iteration;
+        self
+    }
+
+    /// Set the attempt number within the iteration.
+    pub fn with_attempt(mut self, attempt: u32) -> Self {
+        self.attempt = attempt;
+        self
+    }
+
+    /// Request a specific model from agents that support model selection.
+    pub fn with_model(mut self, model: Option<&'a str>) -> Self {
+        self.model = model;
+        self
+    }
+}
+
+/// A fully resolved command line for running an agent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AgentInvocation {
+    /// Program to execute
+    pub program: String,
+    /// Arguments passed to the program
+    pub args: Vec<String>,
+    /// Extra environment variables set for the agent process
+    pub env: Vec<(String, String)>,
+}
+
+impl AgentInvocation {
+    /// Create an invocation with no extra environment variables.
+    pub fn new(program: impl Into<String>, args: Vec<String>) -> Self {
+        Self {
+            program: program.into(),
+            args,
+            env: Vec::new(),
+        }
+    }
+
+    /// Add an environment variable to the invocation.
+    pub fn with_env(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.env.push((key.into(), value.into()));
+        self
+    }
+}
+
+/// Features supported by an agent backend.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AgentCapabilities {
+    /// Whether the agent can emit machine-readable (JSON) output
+    pub structured_output: bool,
+    /// Whether the agent accepts a model selection flag
+    pub model_selection: bool,
+    /// Whether the agent runs without interactive permission prompts
+    pub non_interactive: bool,
+}
+
+/// Output parsed from a finished agent run.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AgentOutput {
+    /// The agent's final message, if one could be identified
+    pub final_message: Option<String>,
+    /// Status block the agent ended its final message with, if any
+    pub report: Option<AgentReport>,
+}
+
+impl AgentOutput {
+    /// Output with the given final message, parsing its status block
+    pub fn from_final_message(final_message: Option<String>) -> Self {
+        let report = final_message.as_deref().and_then(AgentReport::parse);
+        Self {
+            final_message,
+            report,
+        }
+    }
+}
+
+/// Trait implemented by every agent CLI that Ralph can drive.
+///
+/// Backends are registered in an [`AgentRegistry`](super::AgentRegistry) by
+/// name and resolved from the configured agent command.
+pub trait AgentBackend: Send + Sync {
+    /// Get the registry name of this backend (e.g., "claude")
+    fn name(&self) -> &str;
+
+    /// Get the program this backend executes
+    fn program(&self) -> &str;
+
+    /// Build the command line for running the agent on a request
+    fn build_invocation(&self, request: &AgentRequest<'_>) -> AgentInvocation;
+
+    /// Get the format of the agent's stdout
+    fn stream_format(&self) -> StreamFormat {
+        StreamFormat::Text
+    }
+
+    /// Parse one line of agent stdout into typed events
+    ///
+    /// The default implementation uses the backend's [`StreamFormat`].
+    fn parse_stream_line(&self, line: &str) -> Vec<AgentEvent> {
+        self.stream_format().parse_line(line)
+    }
+
+    /// Parse the collected stdout of a finished agent run
+    ///
+    /// The default implementation extracts the final message from structured
+    /// output, falling back to the whole trimmed output.
+    fn parse_output(&self, stdout: &str) -> AgentOutput {
+        if let Some(message) = self.stream_format().final_message(stdout) {
+            return AgentOutput::from_final_message(Some(message));
+        }
+
+        let trimmed = stdout.trim();
+        AgentOutput::from_final_message(if trimmed.is_empty() {
+            None
+        } else {
+            Some(trimmed.to_string())
+        })
+    }
+
+    /// Classify a failed run from its output and exit code
+    ///
+    /// The default implementation uses the built-in [`ErrorDetector`] patterns.
+    fn classify_error(&self, output: &str, exit_code: Option<i32>) -> Option<ClassifiedError> {
+        ErrorDetector::new().classify(output, exit_code)
+    }
+
+    /// Report the features this backend supports
+    fn capabilities(&self) -> AgentCapabilities {
+        AgentCapabilities::default()
+    }
+
+    /// Check whether the backend's program can be executed
+    fn is_available(&self) -> bool {
+        is_program_in_path(self.program())
+    }
+}
+
+/// Check if a program exists in PATH (cross-platform)
+///
+/// Paths containing a separator are checked directly on disk.
+pub fn is_program_in_path(program: &str) -> bool {
+    if program.contains(std::path::MAIN_SEPARATOR) || program.contains('/') {
+        return Path::new(program).is_file();
+    }
+
+    #[cfg(target_os = "windows")]
+    let check_cmd = "where";
+    #[cfg(not(target_os = "windows"))]
+    let check_cmd = "which";
+
+    Command::new(check_cmd)
+        .arg(program)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map(|s| s.success())
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct EchoBackend;
+
+    impl AgentBackend for EchoBackend {
+        fn name(&self) -> &str {
+            "echo"
+        }
+
+        fn program(&self) -> &str {
+            "echo"
+        }
+
+        fn build_invocation(&self, request: &AgentRequest<'_>) -> AgentInvocation {
+            AgentInvocation::new("echo", vec![request.prompt.to_string()])
+        }
+    }
+
+    #[test]
+    fn test_invocation_with_env() {
+        let invocation = AgentInvocation::new("agent", vec!["run".to_string()])
+            .with_env("MODE", "ci")
+            .with_env("DEBUG", "1");
+
+        assert_eq!(invocation.program, "agent");
+        assert_eq!(invocation.args, vec!["run"]);
+        assert_eq!(
+            invocation.env,
+            vec![
+                ("MODE".to_string(), "ci".to_string()),
+                ("DEBUG".to_string(), "1".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_agent_request_with_story() {
+        let request = AgentRequest::new("do it", Path::new("/tmp/project"));
+        assert_eq!(request.story_id, "");
+        assert_eq!(request.iteration, 0);
+
+        let request = request.with_story("US-001", 3);
+        assert_eq!(request.story_id, "US-001");
+        assert_eq!(request.iteration, 3);
+        assert_eq!(EchoBackend.build_invocation(&request).args, vec!["do it"]);
+    }
+
+    #[test]
+    fn test_default_parse_output() {
+        let backend = EchoBackend;
+        assert_eq!(
+            backend.parse_output("  done\n").final_message,
+            Some("done".to_string())
+        );
+        assert!(backend.parse_output("\n \n").final_message.is_none());
+    }
+
+    #[test]
+    fn test_parse_output_reads_status_block() {
+        let output =
+            EchoBackend.parse_output("done\n<ralph-status>\nstatus: complete\n</ralph-status>\n");
+        assert!(output.report.unwrap().is_complete());
+        assert!(EchoBackend.parse_output("done\n").report.is_none());
+    }
+
+    #[test]
+    fn test_default_classify_error() {
+        let backend = EchoBackend;
+        let classified = backend
+            .classify_error("Error: rate limit exceeded", Some(1))
+            .expect("rate limit should be classified");
+        assert!(classified.is_usage_limit());
+
+        assert!(backend.classify_error("", Some(1)).is_none());
+    }
+
+    #[test]
+    fn test_default_capabilities() {
+        assert_eq!(EchoBackend.capabilities(), AgentCapabilities::default());
+    }
+
+    #[test]
+    fn test_is_program_in_path_missing() {
+        assert!(!is_program_in_path("ralph-definitely-not-installed-agent"));
+        assert!(!is_program_in_path("/nonexistent/dir/agent.sh"));
+    }
+}
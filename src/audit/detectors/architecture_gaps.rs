@@ -173,11 +173,10 @@ impl ArchitectureGapsDetector {
     /// Convert architecture gaps to AuditFindings
     pub fn to_findings(&self, analysis: &ArchitectureGapsAnalysis) -> Vec<AuditFinding> {
         let mut findings = Vec::new();
-        let mut id_counter = 1;
 
-        for gap in &analysis.gaps {
+        for (index, gap) in analysis.gaps.iter().enumerate() {
             findings.push(AuditFinding {
-                id: format!("ARCH-GAP-{:03}", id_counter),
+                id: format!("ARCH-GAP-{:03}", index + 1),
                 severity: gap.severity,
                 category: "architecture".to_string(),
                 title: self.gap_type_to_title(&gap.gap_type),
@@ -185,7 +184,6 @@ impl ArchitectureGapsDetector {
                 affected_files: gap.files.clone(),
                 recommendation: gap.recommendation.clone(),
             });
-            id_counter += 1;
         }
 
         findings
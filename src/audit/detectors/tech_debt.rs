@@ -164,11 +164,10 @@ impl TechDebtDetector {
     /// Convert tech debt items to AuditFindings
     pub fn to_findings(&self, analysis: &TechDebtAnalysis) -> Vec<AuditFinding> {
         let mut findings = Vec::new();
-        let mut id_counter = 1;
 
-        for item in &analysis.items {
+        for (index, item) in analysis.items.iter().enumerate() {
             findings.push(AuditFinding {
-                id: format!("DEBT-{:03}", id_counter),
+                id: format!("DEBT-{:03}", index + 1),
                 severity: item.severity,
                 category: "tech_debt".to_string(),
                 title: self.debt_type_to_title(&item.debt_type),
@@ -176,7 +175,6 @@ impl TechDebtDetector {
                 affected_files: vec![item.file.clone()],
                 recommendation: item.recommendation.clone(),
             });
-            id_counter += 1;
         }
 
         findings
@@ -217,8 +217,15 @@ impl DocAnalyzer {
         Ok(analysis)
     }
 
+    /// Public items in a Rust source file that lack a doc comment
+    pub fn undocumented_items(&self, content: &str, file: &Path) -> Vec<UndocumentedItem> {
+        let mut analysis = DocumentationAnalysis::default();
+        self.analyze_rust_doc_comments(content, file, &mut analysis);
+        analysis.undocumented_items
+    }
+
     /// Analyze README file
-    fn analyze_readme(&self) -> AuditResult<ReadmeAnalysis> {
+    pub fn analyze_readme(&self) -> AuditResult<ReadmeAnalysis> {
         let mut readme = ReadmeAnalysis::default();
 
         // Look for README files
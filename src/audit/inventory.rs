@@ -504,6 +504,7 @@ impl InventoryScanner {
 }
 
 #[cfg(test)]
+#[allow(clippy::cmp_owned)]
 mod tests {
     use super::*;
     use tempfile::TempDir;
@@ -277,7 +277,7 @@ impl LanguageAnalyzer {
             .collect();
 
         // Sort by LOC descending
-        languages.sort_by(|a, b| b.loc.cmp(&a.loc));
+        languages.sort_by_key(|l| std::cmp::Reverse(l.loc));
 
         // Assign support levels based on percentage
         self.assign_support_levels(&mut languages);
@@ -149,6 +149,25 @@ pub struct AuditFinding {
     pub recommendation: String,
 }
 
+impl std::fmt::Display for AuditFinding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "[{}] {}: {}",
+            self.severity, self.title, self.description
+        )?;
+        if !self.affected_files.is_empty() {
+            let files: Vec<String> = self
+                .affected_files
+                .iter()
+                .map(|file| file.display().to_string())
+                .collect();
+            write!(f, " ({})", files.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
 /// A feature opportunity identified during audit
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FeatureOpportunity {
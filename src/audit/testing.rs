@@ -547,7 +547,7 @@ impl TestAnalyzer {
             .collect();
 
         // Sort by count descending
-        patterns.sort_by(|a, b| b.count.cmp(&a.count));
+        patterns.sort_by_key(|p| std::cmp::Reverse(p.count));
 
         patterns
     }
@@ -10,6 +10,8 @@ pub use manager::{CheckpointError, CheckpointManager, CheckpointResult};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+use crate::metrics::AgentSwitch;
+
 /// Reason why execution was paused and a checkpoint was created.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -26,6 +28,8 @@ pub enum PauseReason {
     Error(String),
     /// Checkpoint saved at iteration boundary (for recovery if interrupted)
     IterationBoundary,
+    /// Run-level token or cost budget was exceeded
+    BudgetExceeded(String),
 }
 
 /// Checkpoint data for a single story's execution state.
@@ -52,6 +56,18 @@ pub struct Checkpoint {
     pub pause_reason: PauseReason,
     /// List of files with uncommitted changes
     pub uncommitted_files: Vec<String>,
+    /// Feature branch the run commits to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branch_name: Option<String>,
+    /// Commit the feature branch was created from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_commit: Option<String>,
+    /// Agent that was active when the checkpoint was saved
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent: Option<String>,
+    /// Switches to fallback agents made during the run, in order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub agent_switches: Vec<AgentSwitch>,
 }
 
 impl Checkpoint {
@@ -70,8 +86,30 @@ impl Checkpoint {
             current_story,
             pause_reason,
             uncommitted_files,
+            branch_name: None,
+            base_commit: None,
+            agent: None,
+            agent_switches: Vec::new(),
         }
     }
+
+    /// Record the feature branch and the commit it was created from.
+    pub fn with_branch(
+        mut self,
+        branch_name: impl Into<String>,
+        base_commit: impl Into<String>,
+    ) -> Self {
+        self.branch_name = Some(branch_name.into());
+        self.base_commit = Some(base_commit.into());
+        self
+    }
+
+    /// Record the active agent and any fallback switches that led to it.
+    pub fn with_agent(mut self, agent: impl Into<String>, switches: Vec<AgentSwitch>) -> Self {
+        self.agent = Some(agent.into());
+        self.agent_switches = switches;
+        self
+    }
 }
 
 impl StoryCheckpoint {
@@ -97,6 +135,7 @@ mod tests {
             PauseReason::UserRequested,
             PauseReason::Timeout,
             PauseReason::Error("Connection failed".to_string()),
+            PauseReason::BudgetExceeded("Cost budget exceeded".to_string()),
         ];
 
         for reason in reasons {
@@ -148,6 +187,55 @@ mod tests {
         assert_eq!(checkpoint.pause_reason, deserialized.pause_reason);
     }
 
+    #[test]
+    fn test_checkpoint_with_branch_roundtrip() {
+        let checkpoint = Checkpoint::new(None, PauseReason::IterationBoundary, vec![])
+            .with_branch("feature/login", "abc123");
+
+        let json = serde_json::to_string(&checkpoint).expect("Failed to serialize Checkpoint");
+        let deserialized: Checkpoint =
+            serde_json::from_str(&json).expect("Failed to deserialize Checkpoint");
+
+        assert_eq!(deserialized.branch_name.as_deref(), Some("feature/login"));
+        assert_eq!(deserialized.base_commit.as_deref(), Some("abc123"));
+    }
+
+    #[test]
+    fn test_checkpoint_without_branch_fields() {
+        // Checkpoints written before branch tracking still load
+        let json = r#"{
+            "version": 1,
+            "created_at": "2024-01-01T00:00:00Z",
+            "current_story": null,
+            "pause_reason": "timeout",
+            "uncommitted_files": []
+        }"#;
+        let checkpoint: Checkpoint = serde_json::from_str(json).unwrap();
+        assert!(checkpoint.branch_name.is_none());
+        assert!(checkpoint.base_commit.is_none());
+        assert!(checkpoint.agent.is_none());
+        assert!(checkpoint.agent_switches.is_empty());
+    }
+
+    #[test]
+    fn test_checkpoint_with_agent_roundtrip() {
+        let switch = AgentSwitch {
+            iteration: 3,
+            from: "claude".to_string(),
+            to: "codex".to_string(),
+            reason: "usage limit".to_string(),
+        };
+        let checkpoint = Checkpoint::new(None, PauseReason::IterationBoundary, vec![])
+            .with_agent("codex", vec![switch.clone()]);
+
+        let json = serde_json::to_string(&checkpoint).expect("Failed to serialize Checkpoint");
+        let deserialized: Checkpoint =
+            serde_json::from_str(&json).expect("Failed to deserialize Checkpoint");
+
+        assert_eq!(deserialized.agent.as_deref(), Some("codex"));
+        assert_eq!(deserialized.agent_switches, vec![switch]);
+    }
+
     #[test]
     fn test_checkpoint_version() {
         let checkpoint = Checkpoint::new(None, PauseReason::Timeout, vec![]);
@@ -168,5 +256,8 @@ mod tests {
 
         let json = serde_json::to_string(&PauseReason::Error("test".to_string())).unwrap();
         assert!(json.contains("error"));
+
+        let json = serde_json::to_string(&PauseReason::BudgetExceeded("over".to_string())).unwrap();
+        assert!(json.contains("budget_exceeded"));
     }
 }
@@ -182,6 +182,7 @@ impl std::fmt::Display for ClassifiedError {
 impl std::error::Error for ClassifiedError {}
 
 #[cfg(test)]
+#[allow(clippy::useless_vec)]
 mod tests {
     use super::*;
 
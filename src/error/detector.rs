@@ -2,9 +2,12 @@
 //!
 //! This module provides regex-based pattern matching to detect and classify errors
 //! from Claude Code agent output. It supports detection of rate limits, usage limits,
-//! authentication errors, and timeout conditions.
+//! authentication errors, and timeout conditions, and extracts when a usage limit
+//! resets so a paused run can resume on its own.
 
+use chrono::{DateTime, Local, NaiveTime, TimeZone, Utc};
 use regex::Regex;
+use std::sync::OnceLock;
 use std::time::Duration;
 
 use super::{
@@ -421,6 +424,93 @@ impl ErrorDetector {
         // Fall back to exit code classification
         exit_code.and_then(|code| self.classify_exit_code(code))
     }
+
+    /// Extracts when a usage limit resets from agent output.
+    ///
+    /// Recognizes Claude's `usage limit reached|<unix seconds>` marker, clock
+    /// times such as `resets 2am` or `reset at 14:30` (the next occurrence in
+    /// local time), and relative delays such as `try again in 45 minutes` or
+    /// `Retry-After: 120`.
+    ///
+    /// # Arguments
+    /// * `text` - The agent output to search
+    ///
+    /// # Returns
+    /// The reset time, if the output names one
+    pub fn extract_reset_time(&self, text: &str) -> Option<DateTime<Utc>> {
+        self.extract_reset_time_at(text, Local::now())
+    }
+
+    /// Extracts a usage limit reset time relative to `now`.
+    ///
+    /// See [`ErrorDetector::extract_reset_time`] for the recognized formats.
+    pub fn extract_reset_time_at(&self, text: &str, now: DateTime<Local>) -> Option<DateTime<Utc>> {
+        static EPOCH: OnceLock<Regex> = OnceLock::new();
+        static CLOCK: OnceLock<Regex> = OnceLock::new();
+        static RELATIVE: OnceLock<Regex> = OnceLock::new();
+        static RETRY_AFTER: OnceLock<Regex> = OnceLock::new();
+
+        let epoch = EPOCH.get_or_init(|| {
+            Regex::new(r"(?i)usage\s*limit\s*reached\s*\|\s*(\d{9,11})").expect("valid regex")
+        });
+        if let Some(caps) = epoch.captures(text) {
+            let seconds: i64 = caps[1].parse().ok()?;
+            return Utc.timestamp_opt(seconds, 0).single();
+        }
+
+        let relative = RELATIVE.get_or_init(|| {
+            Regex::new(
+                r"(?i)(?:try\s+again|retry|resets?)\s+(?:in|after)\s+(\d+)\s*(seconds?|secs?|s|minutes?|mins?|m|hours?|hrs?|h)\b",
+            )
+            .expect("valid regex")
+        });
+        let retry_after = RETRY_AFTER
+            .get_or_init(|| Regex::new(r"(?i)retry-after:\s*(\d+)").expect("valid regex"));
+        if let Some(caps) = relative.captures(text) {
+            let amount: i64 = caps[1].parse().ok()?;
+            let unit = caps[2].to_lowercase();
+            let delay = match unit.chars().next() {
+                Some('h') => chrono::Duration::hours(amount),
+                Some('m') => chrono::Duration::minutes(amount),
+                _ => chrono::Duration::seconds(amount),
+            };
+            return Some((now + delay).with_timezone(&Utc));
+        }
+        if let Some(caps) = retry_after.captures(text) {
+            let seconds: i64 = caps[1].parse().ok()?;
+            return Some((now + chrono::Duration::seconds(seconds)).with_timezone(&Utc));
+        }
+
+        let clock = CLOCK.get_or_init(|| {
+            Regex::new(r"(?i)\bresets?\s+(?:at\s+)?(\d{1,2})(?::(\d{2}))?\s*(am|pm)?\b")
+                .expect("valid regex")
+        });
+        let caps = clock.captures(text)?;
+        let mut hour: u32 = caps[1].parse().ok()?;
+        let minute: u32 = caps.get(2).map_or(Some(0), |m| m.as_str().parse().ok())?;
+        match caps.get(3).map(|m| m.as_str().to_lowercase()).as_deref() {
+            Some("am") if hour <= 12 => hour %= 12,
+            Some("pm") if hour <= 12 => hour = hour % 12 + 12,
+            // A bare number is only a time when it has minutes ("reset at 14:30")
+            None if caps.get(2).is_some() => {}
+            _ => return None,
+        }
+        let time = NaiveTime::from_hms_opt(hour, minute, 0)?;
+
+        // The next occurrence of that clock time, today or tomorrow
+        let mut date = now.date_naive();
+        loop {
+            if let Some(reset) = date.and_time(time).and_local_timezone(Local).earliest() {
+                if reset > now {
+                    return Some(reset.with_timezone(&Utc));
+                }
+            }
+            if date > now.date_naive() {
+                return None;
+            }
+            date = date.succ_opt()?;
+        }
+    }
 }
 
 #[cfg(test)]
@@ -1154,6 +1244,87 @@ mod tests {
         assert!(result.is_none());
     }
 
+    // ==================== Reset Time Tests ====================
+
+    fn local(hour: u32, minute: u32) -> DateTime<Local> {
+        Local
+            .with_ymd_and_hms(2026, 3, 10, hour, minute, 0)
+            .earliest()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_extract_reset_time_from_epoch_marker() {
+        let detector = test_detector();
+        let reset = detector
+            .extract_reset_time_at("Claude AI usage limit reached|1767225600", local(1, 0))
+            .unwrap();
+        assert_eq!(reset.timestamp(), 1767225600);
+    }
+
+    #[test]
+    fn test_extract_reset_time_from_clock_time() {
+        let detector = test_detector();
+        let now = local(1, 15);
+
+        let reset = detector
+            .extract_reset_time_at("5-hour limit reached ∙ resets 2am", now)
+            .unwrap();
+        assert_eq!(reset, local(2, 0).with_timezone(&Utc));
+
+        let reset = detector
+            .extract_reset_time_at("Your limit will reset at 2:30 pm (Europe/London)", now)
+            .unwrap();
+        assert_eq!(reset, local(14, 30).with_timezone(&Utc));
+
+        let reset = detector
+            .extract_reset_time_at("usage limit; reset at 14:30", now)
+            .unwrap();
+        assert_eq!(reset, local(14, 30).with_timezone(&Utc));
+    }
+
+    #[test]
+    fn test_extract_reset_time_rolls_over_to_tomorrow() {
+        let detector = test_detector();
+        let reset = detector
+            .extract_reset_time_at("limit resets 1am", local(23, 0))
+            .unwrap();
+        let expected = Local
+            .with_ymd_and_hms(2026, 3, 11, 1, 0, 0)
+            .earliest()
+            .unwrap();
+        assert_eq!(reset, expected.with_timezone(&Utc));
+    }
+
+    #[test]
+    fn test_extract_reset_time_from_relative_delay() {
+        let detector = test_detector();
+        let now = local(9, 0);
+
+        let reset = detector
+            .extract_reset_time_at("Usage limit reached. Try again in 45 minutes.", now)
+            .unwrap();
+        assert_eq!(reset, local(9, 45).with_timezone(&Utc));
+
+        let reset = detector
+            .extract_reset_time_at("429 Too Many Requests\nRetry-After: 120", now)
+            .unwrap();
+        assert_eq!(reset, local(9, 2).with_timezone(&Utc));
+    }
+
+    #[test]
+    fn test_extract_reset_time_without_reset() {
+        let detector = test_detector();
+        let now = local(9, 0);
+        assert!(detector
+            .extract_reset_time_at("usage limit exceeded", now)
+            .is_none());
+        // A bare number is not a clock time
+        assert!(detector
+            .extract_reset_time_at("resets 3 counters", now)
+            .is_none());
+    }
+
     #[test]
     fn test_pattern_priority() {
         let detector = test_detector();
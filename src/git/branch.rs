@@ -0,0 +1,371 @@
+//! Feature branch preparation
+//!
+//! Before a run starts committing stories, the runner switches to the PRD's
+//! `branchName`: it creates the branch from a configurable base, or resumes
+//! on it if it already exists. It refuses to run on a dirty tree or on a
+//! protected branch such as `main`. Files the run writes itself, such as the
+//! PRD and progress log, do not make the tree dirty.
+
+use std::path::{Path, PathBuf};
+
+use super::{
+    current_branch, git_succeeds, is_git_repository, resolve_commit, run_git, uncommitted_files,
+    GitError,
+};
+
+/// Branches the runner refuses to commit to unless configured otherwise
+pub const DEFAULT_PROTECTED_BRANCHES: &[&str] = &["main", "master"];
+
+/// Configuration for feature branch handling
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BranchConfig {
+    /// Whether to create or check out the PRD branch before running
+    pub enabled: bool,
+    /// Ref new branches are created from (None = the current HEAD)
+    pub base: Option<String>,
+    /// Branches the runner refuses to run on
+    pub protected: Vec<String>,
+    /// Files whose uncommitted changes do not count as a dirty tree
+    pub ignored_files: Vec<PathBuf>,
+}
+
+impl Default for BranchConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            base: None,
+            protected: DEFAULT_PROTECTED_BRANCHES
+                .iter()
+                .map(|b| b.to_string())
+                .collect(),
+            ignored_files: Vec::new(),
+        }
+    }
+}
+
+impl BranchConfig {
+    /// Configuration that leaves the checked-out branch alone
+    pub fn disabled() -> Self {
+        Self {
+            enabled: false,
+            ..Default::default()
+        }
+    }
+
+    /// Set the ref new branches are created from
+    pub fn with_base(mut self, base: impl Into<String>) -> Self {
+        self.base = Some(base.into());
+        self
+    }
+
+    /// Replace the list of protected branches
+    pub fn with_protected(mut self, protected: Vec<String>) -> Self {
+        self.protected = protected;
+        self
+    }
+
+    /// Set the files whose uncommitted changes do not count as a dirty tree
+    pub fn with_ignored_files(mut self, files: Vec<PathBuf>) -> Self {
+        self.ignored_files = files;
+        self
+    }
+
+    /// Whether `branch` is protected
+    pub fn is_protected(&self, branch: &str) -> bool {
+        self.protected.iter().any(|p| p == branch)
+    }
+}
+
+/// The branch a run commits to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BranchSetup {
+    /// Name of the feature branch
+    pub branch: String,
+    /// Commit the branch was created from (or its merge base with the base ref)
+    pub base_commit: String,
+    /// Whether the branch was created by this run
+    pub created: bool,
+}
+
+/// Whether `file` is one of the `ignored` files, however either path is spelled
+fn is_ignored(file: &Path, ignored: &[PathBuf]) -> bool {
+    let Ok(file) = file.canonicalize() else {
+        return false;
+    };
+    ignored
+        .iter()
+        .any(|path| path.canonicalize().is_ok_and(|path| path == file))
+}
+
+/// Create or check out `branch` in the repository at `repo`.
+///
+/// # Arguments
+/// * `repo` - Directory inside the repository
+/// * `branch` - The PRD's `branchName`
+/// * `config` - Base ref and protected branches
+/// * `resume_base` - Base commit recorded in a checkpoint for this branch, if
+///   resuming. A resumed run may continue on a dirty tree when the branch is
+///   already checked out, since an interrupted iteration leaves changes behind.
+///
+/// # Returns
+/// The branch and its base commit
+pub fn prepare_branch(
+    repo: &Path,
+    branch: &str,
+    config: &BranchConfig,
+    resume_base: Option<&str>,
+) -> Result<BranchSetup, GitError> {
+    let branch = branch.trim();
+    if branch.is_empty() || !git_succeeds(repo, &["check-ref-format", "--branch", branch]) {
+        return Err(GitError::InvalidBranchName(branch.to_string()));
+    }
+    if config.is_protected(branch) {
+        return Err(GitError::ProtectedBranch(branch.to_string()));
+    }
+    if !is_git_repository(repo) {
+        return Err(GitError::NotARepository(repo.display().to_string()));
+    }
+
+    let on_branch = current_branch(repo)?.as_deref() == Some(branch);
+    if !(on_branch && resume_base.is_some()) {
+        let mut dirty = uncommitted_files(repo)?;
+        if !dirty.is_empty() && !config.ignored_files.is_empty() {
+            let toplevel = PathBuf::from(run_git(repo, &["rev-parse", "--show-toplevel"])?);
+            dirty.retain(|file| !is_ignored(&toplevel.join(file), &config.ignored_files));
+        }
+        if !dirty.is_empty() {
+            return Err(GitError::DirtyWorkingTree(dirty));
+        }
+    }
+
+    let branch_exists = git_succeeds(
+        repo,
+        &[
+            "rev-parse",
+            "--verify",
+            "--quiet",
+            &format!("refs/heads/{}", branch),
+        ],
+    );
+
+    if on_branch || branch_exists {
+        let base_commit = match resume_base {
+            Some(base) => base.to_string(),
+            None => existing_branch_base(repo, branch, config)?,
+        };
+        if !on_branch {
+            run_git(repo, &["checkout", "-q", branch])?;
+        }
+        return Ok(BranchSetup {
+            branch: branch.to_string(),
+            base_commit,
+            created: false,
+        });
+    }
+
+    let base_commit = resolve_commit(repo, config.base.as_deref().unwrap_or("HEAD"))?;
+    run_git(repo, &["checkout", "-q", "-b", branch, &base_commit])?;
+
+    Ok(BranchSetup {
+        branch: branch.to_string(),
+        base_commit,
+        created: true,
+    })
+}
+
+/// Base commit of a branch that already exists: its merge base with the
+/// configured base, else with the first protected branch that exists, else
+/// with the current HEAD.
+fn existing_branch_base(
+    repo: &Path,
+    branch: &str,
+    config: &BranchConfig,
+) -> Result<String, GitError> {
+    let base_ref = config
+        .base
+        .clone()
+        .or_else(|| {
+            config
+                .protected
+                .iter()
+                .find(|p| resolve_commit(repo, p).is_ok())
+                .cloned()
+        })
+        .unwrap_or_else(|| "HEAD".to_string());
+
+    run_git(repo, &["merge-base", &base_ref, branch])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::test_support::init_repo;
+    use tempfile::TempDir;
+
+    fn repo() -> TempDir {
+        let dir = TempDir::new().unwrap();
+        init_repo(dir.path());
+        dir
+    }
+
+    fn commit_file(dir: &Path, name: &str) -> String {
+        std::fs::write(dir.join(name), name).unwrap();
+        run_git(dir, &["add", "-A"]).unwrap();
+        run_git(dir, &["commit", "-q", "-m", name]).unwrap();
+        resolve_commit(dir, "HEAD").unwrap()
+    }
+
+    #[test]
+    fn test_branch_config_default() {
+        let config = BranchConfig::default();
+        assert!(config.enabled);
+        assert!(config.base.is_none());
+        assert!(config.is_protected("main"));
+        assert!(config.is_protected("master"));
+        assert!(!config.is_protected("feature/x"));
+        assert!(!BranchConfig::disabled().enabled);
+    }
+
+    #[test]
+    fn test_creates_branch_from_head() {
+        let dir = repo();
+        let head = resolve_commit(dir.path(), "HEAD").unwrap();
+
+        let setup =
+            prepare_branch(dir.path(), "feature/new", &BranchConfig::default(), None).unwrap();
+
+        assert!(setup.created);
+        assert_eq!(setup.branch, "feature/new");
+        assert_eq!(setup.base_commit, head);
+        assert_eq!(
+            current_branch(dir.path()).unwrap().as_deref(),
+            Some("feature/new")
+        );
+    }
+
+    #[test]
+    fn test_creates_branch_from_configured_base() {
+        let dir = repo();
+        let base = resolve_commit(dir.path(), "HEAD").unwrap();
+        run_git(dir.path(), &["branch", "develop"]).unwrap();
+        commit_file(dir.path(), "later.txt");
+
+        let config = BranchConfig::default().with_base("develop");
+        let setup = prepare_branch(dir.path(), "feature/new", &config, None).unwrap();
+
+        assert_eq!(setup.base_commit, base);
+        assert!(!dir.path().join("later.txt").exists());
+    }
+
+    #[test]
+    fn test_resumes_existing_branch() {
+        let dir = repo();
+        let base = resolve_commit(dir.path(), "HEAD").unwrap();
+        run_git(dir.path(), &["checkout", "-q", "-b", "feature/old"]).unwrap();
+        commit_file(dir.path(), "work.txt");
+        run_git(dir.path(), &["checkout", "-q", "main"]).unwrap();
+
+        let setup =
+            prepare_branch(dir.path(), "feature/old", &BranchConfig::default(), None).unwrap();
+
+        assert!(!setup.created);
+        assert_eq!(setup.base_commit, base);
+        assert!(dir.path().join("work.txt").exists());
+    }
+
+    #[test]
+    fn test_stays_on_checked_out_branch() {
+        let dir = repo();
+        let base = resolve_commit(dir.path(), "HEAD").unwrap();
+        run_git(dir.path(), &["checkout", "-q", "-b", "feature/here"]).unwrap();
+        commit_file(dir.path(), "work.txt");
+
+        let setup =
+            prepare_branch(dir.path(), "feature/here", &BranchConfig::default(), None).unwrap();
+
+        assert!(!setup.created);
+        assert_eq!(setup.base_commit, base);
+    }
+
+    #[test]
+    fn test_refuses_protected_branch() {
+        let dir = repo();
+        let err = prepare_branch(dir.path(), "main", &BranchConfig::default(), None).unwrap_err();
+        assert!(matches!(err, GitError::ProtectedBranch(ref b) if b == "main"));
+
+        let config = BranchConfig::default().with_protected(vec!["release".to_string()]);
+        assert!(matches!(
+            prepare_branch(dir.path(), "release", &config, None),
+            Err(GitError::ProtectedBranch(_))
+        ));
+    }
+
+    #[test]
+    fn test_refuses_dirty_tree() {
+        let dir = repo();
+        std::fs::write(dir.path().join("README.md"), "dirty\n").unwrap();
+
+        let err =
+            prepare_branch(dir.path(), "feature/new", &BranchConfig::default(), None).unwrap_err();
+
+        assert!(matches!(err, GitError::DirtyWorkingTree(ref files) if files == &["README.md"]));
+        assert_eq!(current_branch(dir.path()).unwrap().as_deref(), Some("main"));
+    }
+
+    #[test]
+    fn test_ignored_files_do_not_make_tree_dirty() {
+        let dir = repo();
+        std::fs::write(dir.path().join("README.md"), "stories updated\n").unwrap();
+        std::fs::write(dir.path().join("progress.txt"), "## US-001\n").unwrap();
+
+        let config = BranchConfig::default().with_ignored_files(vec![
+            dir.path().join("README.md"),
+            dir.path().join("progress.txt"),
+        ]);
+        let setup = prepare_branch(dir.path(), "feature/new", &config, None).unwrap();
+        assert!(setup.created);
+
+        std::fs::write(dir.path().join("other.txt"), "dirty\n").unwrap();
+        let err = prepare_branch(dir.path(), "feature/other", &config, None).unwrap_err();
+        assert!(matches!(err, GitError::DirtyWorkingTree(ref files) if files == &["other.txt"]));
+    }
+
+    #[test]
+    fn test_resume_allows_dirty_tree_on_branch() {
+        let dir = repo();
+        let base = resolve_commit(dir.path(), "HEAD").unwrap();
+        run_git(dir.path(), &["checkout", "-q", "-b", "feature/wip"]).unwrap();
+        std::fs::write(dir.path().join("README.md"), "partial work\n").unwrap();
+
+        let setup = prepare_branch(
+            dir.path(),
+            "feature/wip",
+            &BranchConfig::default(),
+            Some(&base),
+        )
+        .unwrap();
+
+        assert_eq!(setup.base_commit, base);
+        assert!(!setup.created);
+    }
+
+    #[test]
+    fn test_rejects_invalid_branch_name() {
+        let dir = repo();
+        for name in ["", "  ", "bad..name", "bad name"] {
+            assert!(matches!(
+                prepare_branch(dir.path(), name, &BranchConfig::default(), None),
+                Err(GitError::InvalidBranchName(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_requires_repository() {
+        let dir = TempDir::new().unwrap();
+        assert!(matches!(
+            prepare_branch(dir.path(), "feature/x", &BranchConfig::default(), None),
+            Err(GitError::NotARepository(_))
+        ));
+    }
+}
@@ -0,0 +1,415 @@
+//! Commit policy for story commits
+//!
+//! `ralph-commit.toml` in the project root controls how a passing story is
+//! committed: the message template and its conventional-commit type, trailers
+//! recording the story, iteration count and agent behind the commit, DCO
+//! sign-off and GPG signing, which files are staged, and whether failed
+//! iterations leave work-in-progress commits that are squashed once the story
+//! passes. Without the file, every change is committed as
+//! `feat: <id> - <title>`.
+
+use std::path::Path;
+
+use serde::Deserialize;
+use thiserror::Error;
+
+use super::{git_succeeds, run_git, GitError};
+
+/// Commit policy file in the project root
+pub const COMMIT_CONFIG_FILE: &str = "ralph-commit.toml";
+
+/// Message template used when none is configured
+pub const DEFAULT_MESSAGE_TEMPLATE: &str = "{type}: {id} - {title}";
+
+/// Placeholders a message template may use
+const TEMPLATE_PLACEHOLDERS: [&str; 3] = ["{type}", "{id}", "{title}"];
+
+/// Errors that can occur when loading the commit policy
+#[derive(Error, Debug)]
+pub enum CommitConfigError {
+    #[error("Failed to read commit config file: {0}")]
+    ReadError(#[from] std::io::Error),
+    #[error("Failed to parse commit config: {0}")]
+    ParseError(#[from] toml::de::Error),
+    #[error("Invalid commit message template '{0}': {1}")]
+    InvalidTemplate(String, String),
+}
+
+/// Which changes a story commit includes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StageMode {
+    /// Everything in the working tree (`git add -A`)
+    #[default]
+    All,
+    /// Only files the agent changed while working on the story
+    Changed,
+}
+
+/// How Ralph commits a story, as configured in `ralph-commit.toml`
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct CommitPolicy {
+    /// Message template with `{type}`, `{id}` and `{title}` placeholders
+    pub message: String,
+    /// Conventional-commit type for stories that neither set `commitType`
+    /// nor have a title suggesting one
+    pub default_type: String,
+    /// Append `Ralph-Story`, `Ralph-Iterations` and `Ralph-Agent` trailers
+    pub trailers: bool,
+    /// Add a `Signed-off-by` trailer (`git commit --signoff`)
+    pub signoff: bool,
+    /// GPG-sign commits (`git commit -S`)
+    pub gpg_sign: bool,
+    /// Key to sign with, implying `gpg_sign` (None = git's `user.signingkey`)
+    pub gpg_key: Option<String>,
+    /// Which changes are staged
+    pub stage: StageMode,
+    /// Commit each failed iteration's work and squash it when the story passes
+    pub wip_commits: bool,
+}
+
+impl Default for CommitPolicy {
+    fn default() -> Self {
+        Self {
+            message: DEFAULT_MESSAGE_TEMPLATE.to_string(),
+            default_type: "feat".to_string(),
+            trailers: false,
+            signoff: false,
+            gpg_sign: false,
+            gpg_key: None,
+            stage: StageMode::All,
+            wip_commits: false,
+        }
+    }
+}
+
+/// The story a commit is made for
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoryCommit<'a> {
+    /// Story ID (e.g., "US-001")
+    pub story_id: &'a str,
+    /// Story title
+    pub title: &'a str,
+    /// Conventional-commit type set on the story
+    pub commit_type: Option<&'a str>,
+    /// Iterations the story took
+    pub iterations: u32,
+    /// Agent that completed the story
+    pub agent: &'a str,
+}
+
+impl CommitPolicy {
+    /// Parse a commit policy from TOML content
+    pub fn parse(content: &str) -> Result<Self, CommitConfigError> {
+        let policy: CommitPolicy = toml::from_str(content)?;
+        policy.validate()?;
+        Ok(policy)
+    }
+
+    /// Load a commit policy from a file
+    pub fn load(path: &Path) -> Result<Self, CommitConfigError> {
+        let content = std::fs::read_to_string(path)?;
+        Self::parse(&content)
+    }
+
+    /// The project's commit policy, or the default if it has no `ralph-commit.toml`
+    pub fn load_for_project(project_root: &Path) -> Result<Self, CommitConfigError> {
+        let path = project_root.join(COMMIT_CONFIG_FILE);
+        if path.exists() {
+            Self::load(&path)
+        } else {
+            Ok(Self::default())
+        }
+    }
+
+    fn validate(&self) -> Result<(), CommitConfigError> {
+        let invalid = |reason: &str| {
+            Err(CommitConfigError::InvalidTemplate(
+                self.message.clone(),
+                reason.to_string(),
+            ))
+        };
+
+        if self.message.lines().next().unwrap_or("").trim().is_empty() {
+            return invalid("the first line must not be empty");
+        }
+        let mut rest = self.message.as_str();
+        while let Some(start) = rest.find('{') {
+            let Some(end) = rest[start..].find('}') else {
+                return invalid("unclosed '{'");
+            };
+            let placeholder = &rest[start..start + end + 1];
+            if !TEMPLATE_PLACEHOLDERS.contains(&placeholder) {
+                return invalid(&format!(
+                    "unknown placeholder {} (expected {})",
+                    placeholder,
+                    TEMPLATE_PLACEHOLDERS.join(", ")
+                ));
+            }
+            rest = &rest[start + end + 1..];
+        }
+        Ok(())
+    }
+
+    /// Conventional-commit type of a story's commit.
+    ///
+    /// The story's own `commitType` wins, then the type its title suggests,
+    /// then `default_type`.
+    pub fn commit_type(&self, story: &StoryCommit) -> String {
+        story
+            .commit_type
+            .or_else(|| infer_commit_type(story.title))
+            .unwrap_or(&self.default_type)
+            .to_string()
+    }
+
+    /// Commit message for a passing story
+    pub fn message(&self, story: &StoryCommit) -> String {
+        let mut message = self.render(story);
+        if self.trailers {
+            message.push_str(&format!(
+                "\n\nRalph-Story: {}\nRalph-Iterations: {}\nRalph-Agent: {}",
+                story.story_id, story.iterations, story.agent
+            ));
+        }
+        message
+    }
+
+    /// Commit message for the work a failed iteration left behind
+    pub fn wip_message(&self, story: &StoryCommit, iteration: u32) -> String {
+        let rendered = self.render(story);
+        let subject = rendered.lines().next().unwrap_or_default();
+        format!("{} (WIP, iteration {})", subject, iteration)
+    }
+
+    fn render(&self, story: &StoryCommit) -> String {
+        self.message
+            .replace("{type}", &self.commit_type(story))
+            .replace("{id}", story.story_id)
+            .replace("{title}", story.title)
+            .trim_end()
+            .to_string()
+    }
+
+    /// Commit the staged changes in `dir` and return the new commit's hash
+    pub fn commit(&self, dir: &Path, message: &str) -> Result<String, GitError> {
+        let sign_arg = self
+            .gpg_key
+            .as_ref()
+            .map(|key| format!("--gpg-sign={}", key));
+        let mut args = vec!["commit", "-q", "-m", message];
+        if self.signoff {
+            args.push("--signoff");
+        }
+        if let Some(ref arg) = sign_arg {
+            args.push(arg);
+        } else if self.gpg_sign {
+            args.push("--gpg-sign");
+        }
+
+        run_git(dir, &args)?;
+        run_git(dir, &["rev-parse", "HEAD"])
+    }
+}
+
+/// Conventional-commit type suggested by the leading verb of a story title
+pub fn infer_commit_type(title: &str) -> Option<&'static str> {
+    let verb = title
+        .split_whitespace()
+        .next()?
+        .trim_end_matches(':')
+        .to_lowercase();
+    let commit_type = match verb.as_str() {
+        "fix" | "fixes" | "resolve" | "correct" | "repair" => "fix",
+        "refactor" | "restructure" | "simplify" | "extract" | "rename" => "refactor",
+        "document" | "docs" => "docs",
+        "test" | "tests" => "test",
+        "optimize" | "optimise" | "speed" => "perf",
+        "chore" | "bump" | "upgrade" => "chore",
+        _ => return None,
+    };
+    Some(commit_type)
+}
+
+/// Stage `paths` in `dir`, or every change when `paths` is None.
+///
+/// Paths that no longer exist are staged as deletions.
+pub fn stage(dir: &Path, paths: Option<&[String]>) -> Result<(), GitError> {
+    let Some(paths) = paths else {
+        run_git(dir, &["add", "-A"])?;
+        return Ok(());
+    };
+
+    let (present, missing): (Vec<&str>, Vec<&str>) = paths
+        .iter()
+        .map(String::as_str)
+        .partition(|path| dir.join(path).exists());
+    if !present.is_empty() {
+        let mut args = vec!["add", "-A", "--"];
+        args.extend(present);
+        run_git(dir, &args)?;
+    }
+    if !missing.is_empty() {
+        let mut args = vec!["rm", "-q", "--cached", "--ignore-unmatch", "--"];
+        args.extend(missing);
+        run_git(dir, &args)?;
+    }
+    Ok(())
+}
+
+/// Whether the index in `dir` differs from HEAD
+pub fn has_staged_changes(dir: &Path) -> bool {
+    !git_succeeds(dir, &["diff", "--cached", "--quiet"])
+}
+
+/// Fold the commits made since `base` back into the index, ready to be
+/// committed as one
+pub fn squash_since(dir: &Path, base: &str) -> Result<(), GitError> {
+    run_git(dir, &["reset", "--soft", base])?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_support::init_repo;
+    use super::super::uncommitted_files;
+    use super::*;
+    use tempfile::TempDir;
+
+    fn story(title: &str) -> StoryCommit<'_> {
+        StoryCommit {
+            story_id: "US-001",
+            title,
+            commit_type: None,
+            iterations: 2,
+            agent: "claude",
+        }
+    }
+
+    #[test]
+    fn test_default_message() {
+        let policy = CommitPolicy::default();
+        assert_eq!(
+            policy.message(&story("Add greeting")),
+            "feat: US-001 - Add greeting"
+        );
+    }
+
+    #[test]
+    fn test_commit_type_from_story() {
+        let policy = CommitPolicy::default();
+        assert_eq!(policy.commit_type(&story("Fix typo in footer")), "fix");
+        assert_eq!(
+            policy.commit_type(&story("refactor: split parser")),
+            "refactor"
+        );
+        assert_eq!(policy.commit_type(&story("Add login page")), "feat");
+
+        let explicit = StoryCommit {
+            commit_type: Some("build"),
+            ..story("Fix CI caching")
+        };
+        assert_eq!(policy.commit_type(&explicit), "build");
+    }
+
+    #[test]
+    fn test_message_with_trailers() {
+        let policy = CommitPolicy::parse(
+            "message = \"{type}({id}): {title}\"\ndefault_type = \"chore\"\ntrailers = true\n",
+        )
+        .unwrap();
+        assert_eq!(
+            policy.message(&story("Tidy config")),
+            "chore(US-001): Tidy config\n\nRalph-Story: US-001\nRalph-Iterations: 2\nRalph-Agent: claude"
+        );
+        assert_eq!(
+            policy.wip_message(&story("Tidy config"), 3),
+            "chore(US-001): Tidy config (WIP, iteration 3)"
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_bad_templates() {
+        assert!(matches!(
+            CommitPolicy::parse("message = \"{kind}: {title}\""),
+            Err(CommitConfigError::InvalidTemplate(..))
+        ));
+        assert!(matches!(
+            CommitPolicy::parse("message = \"\\nbody only\""),
+            Err(CommitConfigError::InvalidTemplate(..))
+        ));
+        assert!(CommitPolicy::parse("stage = \"some\"").is_err());
+        assert_eq!(
+            CommitPolicy::parse("stage = \"changed\"").unwrap().stage,
+            StageMode::Changed
+        );
+    }
+
+    #[test]
+    fn test_load_for_project_without_file() {
+        let dir = TempDir::new().unwrap();
+        assert_eq!(
+            CommitPolicy::load_for_project(dir.path()).unwrap(),
+            CommitPolicy::default()
+        );
+    }
+
+    #[test]
+    fn test_stage_only_listed_paths() {
+        let dir = TempDir::new().unwrap();
+        init_repo(dir.path());
+        std::fs::write(dir.path().join("mine.txt"), "mine\n").unwrap();
+        std::fs::write(dir.path().join("theirs.txt"), "theirs\n").unwrap();
+        std::fs::remove_file(dir.path().join("README.md")).unwrap();
+
+        let paths = vec![
+            "mine.txt".to_string(),
+            "README.md".to_string(),
+            "gone.txt".to_string(),
+        ];
+        stage(dir.path(), Some(&paths)).unwrap();
+        assert!(has_staged_changes(dir.path()));
+
+        let policy = CommitPolicy {
+            signoff: true,
+            ..CommitPolicy::default()
+        };
+        let hash = policy.commit(dir.path(), "feat: US-001 - Mine").unwrap();
+        assert_eq!(hash, run_git(dir.path(), &["rev-parse", "HEAD"]).unwrap());
+
+        let committed =
+            run_git(dir.path(), &["show", "--name-status", "--format=", "HEAD"]).unwrap();
+        assert_eq!(committed, "D\tREADME.md\nA\tmine.txt");
+        assert_eq!(uncommitted_files(dir.path()).unwrap(), vec!["theirs.txt"]);
+        let body = run_git(dir.path(), &["log", "-1", "--format=%B"]).unwrap();
+        assert!(body.contains("Signed-off-by: "));
+    }
+
+    #[test]
+    fn test_squash_since() {
+        let dir = TempDir::new().unwrap();
+        init_repo(dir.path());
+        let base = run_git(dir.path(), &["rev-parse", "HEAD"]).unwrap();
+        let policy = CommitPolicy::default();
+        for iteration in 1..=2 {
+            std::fs::write(dir.path().join(format!("wip{}.txt", iteration)), "work\n").unwrap();
+            stage(dir.path(), None).unwrap();
+            policy
+                .commit(dir.path(), &format!("wip {}", iteration))
+                .unwrap();
+        }
+
+        squash_since(dir.path(), &base).unwrap();
+        stage(dir.path(), None).unwrap();
+        policy.commit(dir.path(), "feat: US-001 - Done").unwrap();
+
+        let range = format!("{}..HEAD", base);
+        assert_eq!(
+            run_git(dir.path(), &["rev-list", "--count", &range]).unwrap(),
+            "1"
+        );
+        let files = run_git(dir.path(), &["show", "--name-only", "--format=", "HEAD"]).unwrap();
+        assert_eq!(files, "wip1.txt\nwip2.txt");
+    }
+}
@@ -0,0 +1,342 @@
+//! Git module for repository operations
+//!
+//! This module wraps the git CLI operations Ralph performs on the project
+//! repository outside of a single story, such as preparing the PRD's feature
+//! branch before a run, giving parallel stories their own worktrees,
+//! snapshotting the workspace between iterations and committing stories
+//! according to the project's commit policy.
+
+#![allow(dead_code)]
+
+pub mod branch;
+pub mod commit;
+pub mod snapshot;
+pub mod worktree;
+
+pub use branch::{prepare_branch, BranchConfig, BranchSetup, DEFAULT_PROTECTED_BRANCHES};
+pub use commit::{CommitPolicy, StageMode, StoryCommit, COMMIT_CONFIG_FILE};
+pub use snapshot::WorkspaceSnapshot;
+pub use worktree::StoryWorktree;
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+use std::process::Command;
+
+use thiserror::Error;
+
+/// Directory Ralph keeps its own state in; ignored when checking for changes
+pub const RALPH_STATE_DIR: &str = ".ralph";
+
+/// Errors that can occur during git operations
+#[derive(Error, Debug)]
+pub enum GitError {
+    #[error("Failed to run git: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("git {command} failed: {stderr}")]
+    CommandFailed { command: String, stderr: String },
+    #[error("{0} is not inside a git repository")]
+    NotARepository(String),
+    #[error("Invalid branch name '{0}'")]
+    InvalidBranchName(String),
+    #[error("Refusing to run on protected branch '{0}'")]
+    ProtectedBranch(String),
+    #[error("Working tree has uncommitted changes ({}); commit or stash them first", .0.join(", "))]
+    DirtyWorkingTree(Vec<String>),
+}
+
+/// Run a git command in `dir` and return its trimmed stdout
+pub fn run_git(dir: &Path, args: &[&str]) -> Result<String, GitError> {
+    git_output(dir, args).map(|stdout| stdout.trim().to_string())
+}
+
+/// Run a git command in `dir` and return its stdout unmodified
+fn git_output(dir: &Path, args: &[&str]) -> Result<String, GitError> {
+    let output = Command::new("git").args(args).current_dir(dir).output()?;
+
+    if !output.status.success() {
+        return Err(GitError::CommandFailed {
+            command: args.join(" "),
+            stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        });
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Run a git command in `dir` and report whether it succeeded
+pub fn git_succeeds(dir: &Path, args: &[&str]) -> bool {
+    Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .map(|output| output.status.success())
+        .unwrap_or(false)
+}
+
+/// Whether `dir` is inside a git work tree
+pub fn is_git_repository(dir: &Path) -> bool {
+    git_succeeds(dir, &["rev-parse", "--is-inside-work-tree"])
+}
+
+/// The checked-out branch, or None when HEAD is detached
+pub fn current_branch(dir: &Path) -> Result<Option<String>, GitError> {
+    match run_git(dir, &["symbolic-ref", "--quiet", "--short", "HEAD"]) {
+        Ok(branch) => Ok(Some(branch)),
+        Err(GitError::CommandFailed { .. }) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Resolve a revision to a full commit hash
+pub fn resolve_commit(dir: &Path, revision: &str) -> Result<String, GitError> {
+    run_git(
+        dir,
+        &["rev-parse", "--verify", &format!("{}^{{commit}}", revision)],
+    )
+}
+
+/// Files with uncommitted changes, excluding Ralph's own state directory
+pub fn uncommitted_files(dir: &Path) -> Result<Vec<String>, GitError> {
+    let exclude = format!(":(exclude){}", RALPH_STATE_DIR);
+    // Porcelain lines start with a status column, so the output must not be trimmed
+    let stdout = git_output(dir, &["status", "--porcelain", "--", ".", &exclude])?;
+
+    Ok(stdout
+        .lines()
+        .filter(|line| line.len() > 3)
+        .map(|line| line[3..].to_string())
+        .collect())
+}
+
+/// Lines of a file changed in the working tree
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChangedLines {
+    /// A file git does not track yet; every line is new
+    All,
+    /// Line numbers (1-based) added or modified since the base commit
+    Lines(BTreeSet<usize>),
+}
+
+impl ChangedLines {
+    /// Whether the given line (1-based) is new or modified
+    pub fn contains(&self, line: usize) -> bool {
+        match self {
+            ChangedLines::All => true,
+            ChangedLines::Lines(lines) => lines.contains(&line),
+        }
+    }
+}
+
+/// Lines added or modified in the working tree since the `base` commit, by
+/// file.
+///
+/// Changes committed since `base` count too, so a story's WIP commits are
+/// included when `base` is the commit the story started from. Deleted files
+/// and lines are left out, as is Ralph's own state directory. Untracked
+/// files count as entirely new, and so does every file in a repository
+/// without commits yet.
+pub fn changed_lines(dir: &Path, base: &str) -> Result<BTreeMap<String, ChangedLines>, GitError> {
+    let exclude = format!(":(exclude){}", RALPH_STATE_DIR);
+    let mut changes = BTreeMap::new();
+
+    let untracked_args = if resolve_commit(dir, "HEAD").is_ok() {
+        let diff = git_output(
+            dir,
+            &[
+                "diff",
+                base,
+                "--unified=0",
+                "--no-color",
+                "--no-ext-diff",
+                "--",
+                ".",
+                &exclude,
+            ],
+        )?;
+        parse_diff_lines(&diff, &mut changes);
+        vec!["ls-files", "--others", "--exclude-standard"]
+    } else {
+        vec!["ls-files", "--cached", "--others", "--exclude-standard"]
+    };
+
+    let mut args = untracked_args;
+    args.extend(["--", ".", &exclude]);
+    for file in git_output(dir, &args)?.lines() {
+        changes.insert(file.to_string(), ChangedLines::All);
+    }
+    Ok(changes)
+}
+
+/// Collect the new-side line numbers of each hunk in a `--unified=0` diff
+fn parse_diff_lines(diff: &str, changes: &mut BTreeMap<String, ChangedLines>) {
+    let mut file: Option<String> = None;
+    for line in diff.lines() {
+        if let Some(path) = line.strip_prefix("+++ ") {
+            file = path.strip_prefix("b/").map(str::to_string);
+        } else if let (Some(hunk), Some(file)) = (line.strip_prefix("@@ "), &file) {
+            // "@@ -old[,count] +new[,count] @@"
+            let Some(new) = hunk
+                .split_whitespace()
+                .find_map(|part| part.strip_prefix('+'))
+            else {
+                continue;
+            };
+            let mut range = new.splitn(2, ',');
+            let start: usize = range.next().and_then(|n| n.parse().ok()).unwrap_or(0);
+            let count: usize = range.next().and_then(|n| n.parse().ok()).unwrap_or(1);
+            if let ChangedLines::Lines(lines) = changes
+                .entry(file.clone())
+                .or_insert_with(|| ChangedLines::Lines(BTreeSet::new()))
+            {
+                lines.extend(start..start + count);
+            }
+        }
+    }
+}
+
+/// Story ID reduced to characters that are safe in paths and ref names
+pub(crate) fn sanitize_story_id(story_id: &str) -> String {
+    story_id
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+pub(crate) mod test_support {
+    use std::path::Path;
+
+    use super::run_git;
+
+    /// Initialize a repository with one commit on `main`
+    pub fn init_repo(dir: &Path) {
+        run_git(dir, &["init", "-q"]).unwrap();
+        run_git(dir, &["checkout", "-q", "-b", "main"]).unwrap();
+        run_git(dir, &["config", "user.email", "test@example.com"]).unwrap();
+        run_git(dir, &["config", "user.name", "Test"]).unwrap();
+        std::fs::write(dir.join("README.md"), "# Test\n").unwrap();
+        run_git(dir, &["add", "-A"]).unwrap();
+        run_git(dir, &["commit", "-q", "-m", "initial"]).unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::test_support::init_repo;
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_run_git_reports_failure() {
+        let dir = TempDir::new().unwrap();
+        init_repo(dir.path());
+
+        let err = run_git(dir.path(), &["rev-parse", "--verify", "no-such-ref"]).unwrap_err();
+        match err {
+            GitError::CommandFailed { command, .. } => {
+                assert_eq!(command, "rev-parse --verify no-such-ref")
+            }
+            other => panic!("expected CommandFailed, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_is_git_repository() {
+        let dir = TempDir::new().unwrap();
+        assert!(!is_git_repository(dir.path()));
+        init_repo(dir.path());
+        assert!(is_git_repository(dir.path()));
+    }
+
+    #[test]
+    fn test_current_branch() {
+        let dir = TempDir::new().unwrap();
+        init_repo(dir.path());
+        assert_eq!(current_branch(dir.path()).unwrap().as_deref(), Some("main"));
+
+        run_git(dir.path(), &["checkout", "-q", "--detach"]).unwrap();
+        assert_eq!(current_branch(dir.path()).unwrap(), None);
+    }
+
+    #[test]
+    fn test_uncommitted_files_ignores_ralph_dir() {
+        let dir = TempDir::new().unwrap();
+        init_repo(dir.path());
+        assert!(uncommitted_files(dir.path()).unwrap().is_empty());
+
+        std::fs::create_dir(dir.path().join(RALPH_STATE_DIR)).unwrap();
+        std::fs::write(dir.path().join(".ralph/checkpoint.json"), "{}").unwrap();
+        assert!(uncommitted_files(dir.path()).unwrap().is_empty());
+
+        std::fs::write(dir.path().join("README.md"), "changed\n").unwrap();
+        assert_eq!(uncommitted_files(dir.path()).unwrap(), vec!["README.md"]);
+    }
+
+    #[test]
+    fn test_changed_lines() {
+        let dir = TempDir::new().unwrap();
+        init_repo(dir.path());
+        std::fs::write(dir.path().join("lib.rs"), "a\nb\nc\nd\n").unwrap();
+        run_git(dir.path(), &["add", "-A"]).unwrap();
+        run_git(dir.path(), &["commit", "-q", "-m", "lib"]).unwrap();
+        assert!(changed_lines(dir.path(), "HEAD").unwrap().is_empty());
+
+        std::fs::write(dir.path().join("lib.rs"), "a\nB\nc\nd\ne\nf\n").unwrap();
+        std::fs::write(dir.path().join("new.rs"), "x\n").unwrap();
+        std::fs::remove_file(dir.path().join("README.md")).unwrap();
+        std::fs::create_dir(dir.path().join(RALPH_STATE_DIR)).unwrap();
+        std::fs::write(dir.path().join(".ralph/checkpoint.json"), "{}").unwrap();
+
+        let changes = changed_lines(dir.path(), "HEAD").unwrap();
+        assert_eq!(changes.keys().collect::<Vec<_>>(), vec!["lib.rs", "new.rs"]);
+        assert_eq!(
+            changes["lib.rs"],
+            ChangedLines::Lines(BTreeSet::from([2, 5, 6]))
+        );
+        assert!(!changes["lib.rs"].contains(3));
+        assert!(changes["new.rs"].contains(1));
+    }
+
+    #[test]
+    fn test_changed_lines_since_base_include_commits() {
+        let dir = TempDir::new().unwrap();
+        init_repo(dir.path());
+        let base = resolve_commit(dir.path(), "HEAD").unwrap();
+        std::fs::write(dir.path().join("lib.rs"), "a\nb\n").unwrap();
+        run_git(dir.path(), &["add", "-A"]).unwrap();
+        run_git(dir.path(), &["commit", "-q", "-m", "wip"]).unwrap();
+        std::fs::write(dir.path().join("lib.rs"), "a\nb\nc\n").unwrap();
+
+        let since_head = changed_lines(dir.path(), "HEAD").unwrap();
+        assert_eq!(
+            since_head["lib.rs"],
+            ChangedLines::Lines(BTreeSet::from([3]))
+        );
+        let since_base = changed_lines(dir.path(), &base).unwrap();
+        assert_eq!(
+            since_base["lib.rs"],
+            ChangedLines::Lines(BTreeSet::from([1, 2, 3]))
+        );
+    }
+
+    #[test]
+    fn test_resolve_commit() {
+        let dir = TempDir::new().unwrap();
+        init_repo(dir.path());
+        let head = resolve_commit(dir.path(), "HEAD").unwrap();
+        assert_eq!(head.len(), 40);
+        assert_eq!(resolve_commit(dir.path(), "main").unwrap(), head);
+    }
+
+    #[test]
+    fn test_sanitize_story_id() {
+        assert_eq!(sanitize_story_id("US-001"), "US-001");
+        assert_eq!(sanitize_story_id("story 1/a"), "story-1-a");
+    }
+}
@@ -0,0 +1,402 @@
+//! Workspace snapshots between iterations
+//!
+//! Before each iteration the executor records the workspace (tracked and
+//! untracked files, minus ignored files and Ralph's state directory) as a
+//! hidden commit under `refs/ralph/snapshots/<story>/<iteration>`. Taking a
+//! snapshot never touches HEAD, the index or the working tree, so it is safe
+//! to do while the agent's changes are still uncommitted. A snapshot can
+//! later be restored to undo an iteration that left the workspace worse off.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use super::{run_git, sanitize_story_id, GitError, RALPH_STATE_DIR};
+
+/// Namespace the snapshot refs are stored under
+pub const SNAPSHOT_REF_PREFIX: &str = "refs/ralph/snapshots/";
+
+/// Distinguishes the temporary index files of concurrent snapshots
+static INDEX_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// A file that differs between a snapshot and the current workspace
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileDiff {
+    /// Path relative to the directory the diff was taken in
+    pub path: String,
+    /// Lines added plus lines removed (0 for binary files)
+    pub lines: usize,
+}
+
+/// The workspace as it was before an iteration started
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkspaceSnapshot {
+    /// Story the snapshot was taken for
+    pub story_id: String,
+    /// Iteration that started from this workspace
+    pub iteration: u32,
+    /// Hidden commit holding the workspace
+    pub commit: String,
+}
+
+impl WorkspaceSnapshot {
+    /// Snapshot the workspace at `dir` before `iteration` of `story_id`.
+    ///
+    /// Replaces any snapshot already stored for that iteration.
+    pub fn create(dir: &Path, story_id: &str, iteration: u32) -> Result<Self, GitError> {
+        let tree = workspace_tree(dir)?;
+        let message = format!(
+            "ralph snapshot: {} before iteration {}",
+            story_id, iteration
+        );
+        let commit = run_git(dir, &["commit-tree", &tree, "-p", "HEAD", "-m", &message])?;
+        run_git(
+            dir,
+            &["update-ref", &snapshot_ref(story_id, iteration), &commit],
+        )?;
+
+        Ok(Self {
+            story_id: story_id.to_string(),
+            iteration,
+            commit,
+        })
+    }
+
+    /// The snapshot taken before `iteration` of `story_id`, if there is one
+    pub fn find(dir: &Path, story_id: &str, iteration: u32) -> Result<Option<Self>, GitError> {
+        Ok(Self::list(dir, story_id)?
+            .into_iter()
+            .find(|snapshot| snapshot.iteration == iteration))
+    }
+
+    /// All snapshots stored for `story_id`, ordered by iteration
+    pub fn list(dir: &Path, story_id: &str) -> Result<Vec<Self>, GitError> {
+        let prefix = format!("{}{}/", SNAPSHOT_REF_PREFIX, sanitize_story_id(story_id));
+        let refs = run_git(
+            dir,
+            &["for-each-ref", "--format=%(refname) %(objectname)", &prefix],
+        )?;
+
+        let mut snapshots: Vec<Self> = refs
+            .lines()
+            .filter_map(|line| {
+                let (name, commit) = line.split_once(' ')?;
+                let iteration = name.strip_prefix(&prefix)?.parse().ok()?;
+                Some(Self {
+                    story_id: story_id.to_string(),
+                    iteration,
+                    commit: commit.to_string(),
+                })
+            })
+            .collect();
+        snapshots.sort_by_key(|snapshot| snapshot.iteration);
+        Ok(snapshots)
+    }
+
+    /// Put the workspace at `dir` back the way it was in this snapshot.
+    ///
+    /// Files changed since the snapshot are rewritten, files created since
+    /// are deleted, and ignored files and Ralph's state directory are left
+    /// alone. HEAD and the index are not touched. Returns the restored paths.
+    pub fn restore(&self, dir: &Path) -> Result<Vec<String>, GitError> {
+        let changes = self.changes(dir)?;
+
+        let mut rewrite = Vec::new();
+        let mut restored = Vec::new();
+        for line in changes.lines() {
+            let Some((status, path)) = line.split_once('\t') else {
+                continue;
+            };
+            if status == "A" {
+                // Created after the snapshot was taken
+                std::fs::remove_file(dir.join(path))?;
+            } else {
+                rewrite.push(path);
+            }
+            restored.push(path.to_string());
+        }
+
+        if !rewrite.is_empty() {
+            with_temp_index(dir, |index| {
+                run_git_with_index(dir, index, &["read-tree", &self.commit])?;
+                let mut args = vec!["checkout-index", "-f", "--"];
+                args.extend(rewrite.iter().copied());
+                run_git_with_index(dir, index, &args)?;
+                Ok(())
+            })?;
+        }
+
+        Ok(restored)
+    }
+
+    /// Paths that were created, changed or deleted since this snapshot
+    pub fn changed_files(&self, dir: &Path) -> Result<Vec<String>, GitError> {
+        Ok(self
+            .changes(dir)?
+            .lines()
+            .filter_map(|line| line.split_once('\t'))
+            .map(|(_, path)| path.to_string())
+            .collect())
+    }
+
+    /// Files changed since this snapshot, with the number of lines changed in each
+    pub fn diff_stat(&self, dir: &Path) -> Result<Vec<FileDiff>, GitError> {
+        Ok(self
+            .diff(dir, "--numstat")?
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.splitn(3, '\t');
+                let added = fields.next()?;
+                let removed = fields.next()?;
+                let path = fields.next()?;
+                // Binary files report "-" for both counts
+                let lines =
+                    added.parse::<usize>().unwrap_or(0) + removed.parse::<usize>().unwrap_or(0);
+                Some(FileDiff {
+                    path: path.to_string(),
+                    lines,
+                })
+            })
+            .collect())
+    }
+
+    /// `git diff --name-status` from this snapshot to the current workspace
+    fn changes(&self, dir: &Path) -> Result<String, GitError> {
+        self.diff(dir, "--name-status")
+    }
+
+    /// Diff from this snapshot to the current workspace in the given format
+    fn diff(&self, dir: &Path, format: &str) -> Result<String, GitError> {
+        let current = workspace_tree(dir)?;
+        let snapshot_tree = format!("{}^{{tree}}", self.commit);
+        run_git(
+            dir,
+            &[
+                "diff",
+                format,
+                "--no-renames",
+                "--relative",
+                &snapshot_tree,
+                &current,
+            ],
+        )
+    }
+}
+
+/// Ref a story iteration's snapshot is stored under
+pub fn snapshot_ref(story_id: &str, iteration: u32) -> String {
+    format!(
+        "{}{}/{}",
+        SNAPSHOT_REF_PREFIX,
+        sanitize_story_id(story_id),
+        iteration
+    )
+}
+
+/// Write the current workspace to a tree object and return its hash
+fn workspace_tree(dir: &Path) -> Result<String, GitError> {
+    with_temp_index(dir, |index| {
+        // Start from the real index so unchanged files aren't rehashed
+        let real_index = git_path(dir, "index")?;
+        if real_index.exists() {
+            std::fs::copy(&real_index, index)?;
+        } else {
+            run_git_with_index(dir, index, &["read-tree", "HEAD"])?;
+        }
+
+        let exclude = format!(":(exclude){}", RALPH_STATE_DIR);
+        run_git_with_index(dir, index, &["add", "-A", "--", ".", &exclude])?;
+        run_git_with_index(dir, index, &["write-tree"])
+    })
+}
+
+/// Run `f` with the path of a scratch index file, removing it afterwards
+fn with_temp_index<T>(
+    dir: &Path,
+    f: impl FnOnce(&Path) -> Result<T, GitError>,
+) -> Result<T, GitError> {
+    let name = format!(
+        "ralph-snapshot-{}-{}.index",
+        std::process::id(),
+        INDEX_COUNTER.fetch_add(1, Ordering::Relaxed)
+    );
+    let index = git_path(dir, &name)?;
+    let result = f(&index);
+    let _ = std::fs::remove_file(&index);
+    result
+}
+
+/// Absolute path of `name` inside the repository's git directory
+fn git_path(dir: &Path, name: &str) -> Result<PathBuf, GitError> {
+    let path = PathBuf::from(run_git(dir, &["rev-parse", "--git-path", name])?);
+    Ok(if path.is_absolute() {
+        path
+    } else {
+        dir.join(path)
+    })
+}
+
+/// Run a git command against `index` instead of the repository's index
+fn run_git_with_index(dir: &Path, index: &Path, args: &[&str]) -> Result<String, GitError> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .env("GIT_INDEX_FILE", index)
+        .output()?;
+
+    if !output.status.success() {
+        return Err(GitError::CommandFailed {
+            command: args.join(" "),
+            stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        });
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_support::init_repo;
+    use super::super::uncommitted_files;
+    use super::*;
+    use tempfile::TempDir;
+
+    fn repo() -> TempDir {
+        let dir = TempDir::new().unwrap();
+        init_repo(dir.path());
+        dir
+    }
+
+    #[test]
+    fn test_create_leaves_workspace_untouched() {
+        let dir = repo();
+        let head = run_git(dir.path(), &["rev-parse", "HEAD"]).unwrap();
+        std::fs::write(dir.path().join("README.md"), "# Edited\n").unwrap();
+        std::fs::write(dir.path().join("new.txt"), "new\n").unwrap();
+
+        let snapshot = WorkspaceSnapshot::create(dir.path(), "US-001", 2).unwrap();
+
+        assert_eq!(run_git(dir.path(), &["rev-parse", "HEAD"]).unwrap(), head);
+        assert_eq!(
+            uncommitted_files(dir.path()).unwrap(),
+            vec!["README.md", "new.txt"]
+        );
+        // The snapshot holds the uncommitted contents
+        let readme = run_git(
+            dir.path(),
+            &["show", &format!("{}:README.md", snapshot.commit)],
+        )
+        .unwrap();
+        assert_eq!(readme, "# Edited");
+        assert_eq!(
+            run_git(dir.path(), &["rev-parse", "refs/ralph/snapshots/US-001/2"]).unwrap(),
+            snapshot.commit
+        );
+    }
+
+    #[test]
+    fn test_snapshot_excludes_ralph_dir() {
+        let dir = repo();
+        std::fs::create_dir(dir.path().join(RALPH_STATE_DIR)).unwrap();
+        std::fs::write(dir.path().join(".ralph/checkpoint.json"), "{}").unwrap();
+
+        let snapshot = WorkspaceSnapshot::create(dir.path(), "US-001", 1).unwrap();
+        let files = run_git(
+            dir.path(),
+            &["ls-tree", "-r", "--name-only", &snapshot.commit],
+        )
+        .unwrap();
+        assert_eq!(files, "README.md");
+    }
+
+    #[test]
+    fn test_restore_snapshot() {
+        let dir = repo();
+        std::fs::write(dir.path().join("kept.txt"), "good\n").unwrap();
+        let snapshot = WorkspaceSnapshot::create(dir.path(), "US-001", 2).unwrap();
+
+        // A later iteration breaks things
+        std::fs::write(dir.path().join("kept.txt"), "broken\n").unwrap();
+        std::fs::remove_file(dir.path().join("README.md")).unwrap();
+        std::fs::write(dir.path().join("stray.txt"), "stray\n").unwrap();
+
+        let mut restored = snapshot.restore(dir.path()).unwrap();
+        restored.sort();
+        assert_eq!(restored, vec!["README.md", "kept.txt", "stray.txt"]);
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("kept.txt")).unwrap(),
+            "good\n"
+        );
+        assert!(dir.path().join("README.md").exists());
+        assert!(!dir.path().join("stray.txt").exists());
+    }
+
+    #[test]
+    fn test_changed_files_since_snapshot() {
+        let dir = repo();
+        std::fs::write(dir.path().join("before.txt"), "already dirty\n").unwrap();
+        let snapshot = WorkspaceSnapshot::create(dir.path(), "US-001", 1).unwrap();
+        assert!(snapshot.changed_files(dir.path()).unwrap().is_empty());
+
+        std::fs::write(dir.path().join("README.md"), "# Edited\n").unwrap();
+        std::fs::write(dir.path().join("added.txt"), "added\n").unwrap();
+        assert_eq!(
+            snapshot.changed_files(dir.path()).unwrap(),
+            vec!["README.md", "added.txt"]
+        );
+    }
+
+    #[test]
+    fn test_diff_stat_counts_lines() {
+        let dir = repo();
+        let snapshot = WorkspaceSnapshot::create(dir.path(), "US-001", 1).unwrap();
+        std::fs::write(dir.path().join("README.md"), "# Edited\nMore\n").unwrap();
+        std::fs::write(dir.path().join("added.txt"), "a\nb\nc\n").unwrap();
+
+        assert_eq!(
+            snapshot.diff_stat(dir.path()).unwrap(),
+            vec![
+                FileDiff {
+                    path: "README.md".to_string(),
+                    lines: 3,
+                },
+                FileDiff {
+                    path: "added.txt".to_string(),
+                    lines: 3,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_restore_unchanged_workspace() {
+        let dir = repo();
+        let snapshot = WorkspaceSnapshot::create(dir.path(), "US-001", 1).unwrap();
+        assert!(snapshot.restore(dir.path()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_list_and_find_snapshots() {
+        let dir = repo();
+        for iteration in [10, 2, 1] {
+            WorkspaceSnapshot::create(dir.path(), "US-001", iteration).unwrap();
+        }
+        WorkspaceSnapshot::create(dir.path(), "US-002", 1).unwrap();
+
+        let iterations: Vec<u32> = WorkspaceSnapshot::list(dir.path(), "US-001")
+            .unwrap()
+            .iter()
+            .map(|snapshot| snapshot.iteration)
+            .collect();
+        assert_eq!(iterations, vec![1, 2, 10]);
+        assert!(WorkspaceSnapshot::find(dir.path(), "US-001", 2)
+            .unwrap()
+            .is_some());
+        assert!(WorkspaceSnapshot::find(dir.path(), "US-001", 3)
+            .unwrap()
+            .is_none());
+        assert!(WorkspaceSnapshot::list(dir.path(), "US-003")
+            .unwrap()
+            .is_empty());
+    }
+}
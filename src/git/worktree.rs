@@ -0,0 +1,180 @@
+//! Per-story worktrees for parallel execution
+//!
+//! When stories run in parallel, each one gets its own `git worktree` checked
+//! out on a story branch, so agents never see each other's uncommitted edits
+//! and a story's commit only contains its own files. Worktrees live under the
+//! repository's git directory, where the main tree's `git add -A` cannot
+//! pick them up. Once a story completes, its branch is merged back into the
+//! feature branch and the worktree is removed.
+
+use std::path::{Path, PathBuf};
+
+use super::{resolve_commit, run_git, sanitize_story_id, GitError};
+
+/// Prefix of the branches story worktrees are checked out on
+pub const STORY_BRANCH_PREFIX: &str = "ralph/story/";
+
+/// Directory under the git common dir that holds story worktrees
+const WORKTREES_DIR: &str = "ralph-worktrees";
+
+/// A worktree a single story runs in
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoryWorktree {
+    /// Story the worktree belongs to
+    pub story_id: String,
+    /// Checkout directory the agent works in
+    pub path: PathBuf,
+    /// Branch the story commits to
+    pub branch: String,
+    /// Commit the story branch was created from
+    pub base_commit: String,
+}
+
+impl StoryWorktree {
+    /// Create a worktree for `story_id` on a fresh story branch at the
+    /// repository's current HEAD.
+    ///
+    /// A worktree or branch left behind by an interrupted run is replaced.
+    pub fn create(repo: &Path, story_id: &str) -> Result<Self, GitError> {
+        let base_commit = resolve_commit(repo, "HEAD")?;
+        let name = sanitize_story_id(story_id);
+        let path = worktrees_dir(repo)?.join(&name);
+        let branch = format!("{}{}", STORY_BRANCH_PREFIX, name);
+
+        if path.exists() {
+            let _ = run_git(
+                repo,
+                &["worktree", "remove", "--force", &path.to_string_lossy()],
+            );
+            let _ = std::fs::remove_dir_all(&path);
+        }
+        run_git(repo, &["worktree", "prune"])?;
+        run_git(
+            repo,
+            &[
+                "worktree",
+                "add",
+                "-B",
+                &branch,
+                &path.to_string_lossy(),
+                &base_commit,
+            ],
+        )?;
+
+        Ok(Self {
+            story_id: story_id.to_string(),
+            path,
+            branch,
+            base_commit,
+        })
+    }
+
+    /// Whether the story branch has commits beyond its base
+    pub fn has_commits(&self, repo: &Path) -> Result<bool, GitError> {
+        let range = format!("{}..{}", self.base_commit, self.branch);
+        let count = run_git(repo, &["rev-list", "--count", &range])?;
+        Ok(count != "0")
+    }
+
+    /// Remove the worktree and delete its story branch
+    pub fn remove(&self, repo: &Path) -> Result<(), GitError> {
+        run_git(
+            repo,
+            &[
+                "worktree",
+                "remove",
+                "--force",
+                &self.path.to_string_lossy(),
+            ],
+        )?;
+        run_git(repo, &["branch", "-D", &self.branch])?;
+        Ok(())
+    }
+}
+
+/// Directory story worktrees are created in for the repository at `repo`
+pub fn worktrees_dir(repo: &Path) -> Result<PathBuf, GitError> {
+    let common_dir = PathBuf::from(run_git(repo, &["rev-parse", "--git-common-dir"])?);
+    let common_dir = if common_dir.is_absolute() {
+        common_dir
+    } else {
+        repo.join(common_dir)
+    };
+    Ok(common_dir.join(WORKTREES_DIR))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_support::init_repo;
+    use super::super::{current_branch, uncommitted_files};
+    use super::*;
+    use tempfile::TempDir;
+
+    fn repo() -> TempDir {
+        let dir = TempDir::new().unwrap();
+        init_repo(dir.path());
+        dir
+    }
+
+    #[test]
+    fn test_create_worktree() {
+        let dir = repo();
+        let worktree = StoryWorktree::create(dir.path(), "US-001").unwrap();
+
+        assert_eq!(worktree.branch, "ralph/story/US-001");
+        assert_eq!(
+            worktree.base_commit,
+            resolve_commit(dir.path(), "HEAD").unwrap()
+        );
+        assert!(worktree.path.join("README.md").exists());
+        assert_eq!(
+            current_branch(&worktree.path).unwrap(),
+            Some("ralph/story/US-001".to_string())
+        );
+        // The main tree stays on its branch and sees no new files
+        assert_eq!(
+            current_branch(dir.path()).unwrap(),
+            Some("main".to_string())
+        );
+        assert!(uncommitted_files(dir.path()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_worktree_commits_stay_on_story_branch() {
+        let dir = repo();
+        let worktree = StoryWorktree::create(dir.path(), "US-001").unwrap();
+        assert!(!worktree.has_commits(dir.path()).unwrap());
+
+        std::fs::write(worktree.path.join("feature.txt"), "feature\n").unwrap();
+        run_git(&worktree.path, &["add", "-A"]).unwrap();
+        run_git(&worktree.path, &["commit", "-q", "-m", "feature"]).unwrap();
+
+        assert!(worktree.has_commits(dir.path()).unwrap());
+        assert!(!dir.path().join("feature.txt").exists());
+        assert_eq!(
+            resolve_commit(dir.path(), "main").unwrap(),
+            worktree.base_commit
+        );
+    }
+
+    #[test]
+    fn test_remove_worktree() {
+        let dir = repo();
+        let worktree = StoryWorktree::create(dir.path(), "US-001").unwrap();
+        worktree.remove(dir.path()).unwrap();
+
+        assert!(!worktree.path.exists());
+        assert!(resolve_commit(dir.path(), &worktree.branch).is_err());
+    }
+
+    #[test]
+    fn test_create_replaces_stale_worktree() {
+        let dir = repo();
+        let first = StoryWorktree::create(dir.path(), "US-001").unwrap();
+        std::fs::write(first.path.join("scratch.txt"), "left over\n").unwrap();
+
+        let second = StoryWorktree::create(dir.path(), "US-001").unwrap();
+        assert_eq!(second.path, first.path);
+        assert!(!second.path.join("scratch.txt").exists());
+    }
+}
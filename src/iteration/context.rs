@@ -5,7 +5,13 @@
 //! previous failures.
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+
+use super::rollback::WorkspaceRollback;
+use crate::quality::TestFailure;
+
+/// Most failing tests listed for one error in the retry prompt
+const MAX_LISTED_TESTS: usize = 10;
 
 /// Error information from a single iteration.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -20,6 +26,9 @@ pub struct IterationError {
     pub failed_gate: Option<String>,
     /// Files involved in the error (if known)
     pub affected_files: Vec<String>,
+    /// Tests that failed (for test gate failures)
+    #[serde(default)]
+    pub failed_tests: Vec<TestFailure>,
 }
 
 impl IterationError {
@@ -31,6 +40,7 @@ impl IterationError {
             message: message.into(),
             failed_gate: None,
             affected_files: Vec::new(),
+            failed_tests: Vec::new(),
         }
     }
 
@@ -46,6 +56,12 @@ impl IterationError {
         self
     }
 
+    /// Set the failing tests.
+    pub fn with_failed_tests(mut self, tests: Vec<TestFailure>) -> Self {
+        self.failed_tests = tests;
+        self
+    }
+
     /// Get a normalized representation for pattern matching.
     /// This is used to detect oscillating or repeating errors.
     pub fn signature(&self) -> String {
@@ -75,6 +91,8 @@ pub enum ErrorCategory {
     Git,
     /// Environment/configuration issues
     Environment,
+    /// Changes that broke the project's change guard
+    ChangePolicy,
     /// Other/unknown errors
     Other,
 }
@@ -92,6 +110,7 @@ impl ErrorCategory {
             ErrorCategory::AgentExecution => "agent_execution",
             ErrorCategory::Git => "git",
             ErrorCategory::Environment => "environment",
+            ErrorCategory::ChangePolicy => "change_policy",
             ErrorCategory::Other => "other",
         }
     }
@@ -104,7 +123,8 @@ impl ErrorCategory {
                 "coverage" => ErrorCategory::Coverage,
                 "lint" => ErrorCategory::Lint,
                 "format" => ErrorCategory::Format,
-                "security_audit" => ErrorCategory::SecurityAudit,
+                "security_audit" | "cargo_deny" | "sast" => ErrorCategory::SecurityAudit,
+                "tests" => ErrorCategory::Test,
                 _ => ErrorCategory::Other,
             };
         }
@@ -275,6 +295,12 @@ pub struct IterationContext {
     pub story_id: String,
     /// User-provided steering guidance (if any)
     pub steering_guidance: Option<SteeringGuidance>,
+    /// Number of failed quality gates after each iteration that ran them
+    #[serde(default)]
+    pub gate_failures: BTreeMap<u32, usize>,
+    /// Iterations whose changes were rolled back
+    #[serde(default)]
+    pub rollbacks: Vec<WorkspaceRollback>,
 }
 
 impl IterationContext {
@@ -288,6 +314,8 @@ impl IterationContext {
             max_iterations,
             story_id: story_id.into(),
             steering_guidance: None,
+            gate_failures: BTreeMap::new(),
+            rollbacks: Vec::new(),
         }
     }
 
@@ -309,6 +337,24 @@ impl IterationContext {
             .extend(files);
     }
 
+    /// Record how many quality gates failed after an iteration.
+    pub fn record_gate_failures(&mut self, iteration: u32, failed_gates: usize) {
+        self.gate_failures.insert(iteration, failed_gates);
+    }
+
+    /// The most recent iteration with the fewest failed gates, and that count.
+    pub fn best_gate_result(&self) -> Option<(u32, usize)> {
+        self.gate_failures
+            .iter()
+            .min_by_key(|&(&iteration, &failed)| (failed, std::cmp::Reverse(iteration)))
+            .map(|(&iteration, &failed)| (iteration, failed))
+    }
+
+    /// Record that an iteration's changes were rolled back.
+    pub fn record_rollback(&mut self, rollback: WorkspaceRollback) {
+        self.rollbacks.push(rollback);
+    }
+
     /// Add an approach hint.
     pub fn add_hint(&mut self, hint: ApproachHint) {
         self.approach_hints.push(hint);
@@ -354,6 +400,17 @@ impl IterationContext {
     /// This provides the agent with information about previous failures
     /// to help it avoid repeating the same mistakes.
     pub fn build_prompt_context(&self) -> String {
+        self.build_context(true)
+    }
+
+    /// Build the previous-iteration context without the steering guidance.
+    ///
+    /// Used by prompt templates, which place the guidance separately.
+    pub fn build_history_context(&self) -> String {
+        self.build_context(false)
+    }
+
+    fn build_context(&self, include_guidance: bool) -> String {
         if self.error_history.is_empty() {
             return String::new();
         }
@@ -381,6 +438,21 @@ impl IterationContext {
                     .collect::<Vec<_>>();
                 context.push_str(&format!("  - Affected files: {}\n", files.join(", ")));
             }
+            if !error.failed_tests.is_empty() {
+                context.push_str("  - Failed tests:\n");
+                for test in error.failed_tests.iter().take(MAX_LISTED_TESTS) {
+                    context.push_str(&format!("    - `{}`\n", test.name));
+                    for line in test.message.lines() {
+                        context.push_str(&format!("      {}\n", line));
+                    }
+                }
+                if error.failed_tests.len() > MAX_LISTED_TESTS {
+                    context.push_str(&format!(
+                        "    - ... and {} more\n",
+                        error.failed_tests.len() - MAX_LISTED_TESTS
+                    ));
+                }
+            }
         }
 
         // Add hints if available
@@ -409,8 +481,16 @@ impl IterationContext {
             }
         }
 
+        // Tell the agent which attempts were discarded
+        if !self.rollbacks.is_empty() {
+            context.push_str("\n### Rolled Back Iterations\n\n");
+            for rollback in &self.rollbacks {
+                context.push_str(&format!("- {}\n", rollback));
+            }
+        }
+
         // Add steering guidance if provided
-        if let Some(ref guidance) = self.steering_guidance {
+        if let Some(guidance) = self.steering_guidance.as_ref().filter(|_| include_guidance) {
             context.push_str(&guidance.build_prompt_section());
         }
 
@@ -581,6 +661,30 @@ mod tests {
         assert!(prompt.contains("iteration 2 of 10"));
     }
 
+    #[test]
+    fn test_iteration_context_build_prompt_context_with_failed_tests() {
+        let mut ctx = IterationContext::new("US-001", 10);
+        ctx.start_iteration(2);
+        ctx.record_error(
+            IterationError::new(1, ErrorCategory::Test, "Gate 'tests' failed: 1 failed")
+                .with_gate("tests")
+                .with_failed_tests(vec![TestFailure {
+                    name: "auth::tests::test_login".to_string(),
+                    message: "panicked at src/auth.rs:40:9:\nassertion failed: token.is_some()"
+                        .to_string(),
+                }]),
+        );
+
+        let prompt = ctx.build_prompt_context();
+        assert!(prompt.contains(
+            "  - Failed tests:\n    - `auth::tests::test_login`\n      panicked at src/auth.rs:40:9:\n      assertion failed: token.is_some()\n"
+        ));
+        assert_eq!(
+            ErrorCategory::from_error_message("", Some("tests")),
+            ErrorCategory::Test
+        );
+    }
+
     #[test]
     fn test_iteration_context_build_prompt_context_with_hints() {
         let mut ctx = IterationContext::new("US-001", 10);
@@ -597,4 +701,39 @@ mod tests {
         assert!(prompt.contains("Fix imports first"));
         assert!(prompt.contains("80%"));
     }
+
+    #[test]
+    fn test_iteration_context_best_gate_result() {
+        let mut ctx = IterationContext::new("US-001", 10);
+        assert_eq!(ctx.best_gate_result(), None);
+
+        ctx.record_gate_failures(1, 3);
+        ctx.record_gate_failures(2, 1);
+        ctx.record_gate_failures(3, 2);
+        assert_eq!(ctx.best_gate_result(), Some((2, 1)));
+
+        ctx.record_gate_failures(4, 1);
+        assert_eq!(ctx.best_gate_result(), Some((4, 1)));
+    }
+
+    #[test]
+    fn test_iteration_context_build_prompt_context_with_rollbacks() {
+        let mut ctx = IterationContext::new("US-001", 10);
+        ctx.start_iteration(4);
+        ctx.record_error(IterationError::new(
+            3,
+            ErrorCategory::Test,
+            "Gate 'test' failed",
+        ));
+        ctx.record_rollback(WorkspaceRollback {
+            iteration: 3,
+            restored_to: 2,
+            failed_gates: 2,
+            restored_failed_gates: 1,
+        });
+
+        let prompt = ctx.build_prompt_context();
+        assert!(prompt.contains("Rolled Back Iterations"));
+        assert!(prompt.contains("rolled back to the workspace iteration 2 left"));
+    }
 }
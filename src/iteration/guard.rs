@@ -0,0 +1,328 @@
+//! Guarding what an iteration may change.
+//!
+//! `ralph-guard.toml` in the project root declares which paths an agent must
+//! not touch, which paths a story may change (defaulting to the story's
+//! `targetFiles`) and how large its diff may grow. After each iteration the
+//! executor checks the workspace's changes since the story started against
+//! the guard. Depending on severity, a violation is fed back to the agent for
+//! the next iteration or fails the story outright.
+
+use std::path::Path;
+
+use glob::Pattern;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::git::snapshot::FileDiff;
+
+/// Change guard file in the project root
+pub const GUARD_CONFIG_FILE: &str = "ralph-guard.toml";
+
+/// Errors that can occur when loading the change guard
+#[derive(Error, Debug)]
+pub enum GuardConfigError {
+    #[error("Failed to read change guard file: {0}")]
+    ReadError(#[from] std::io::Error),
+    #[error("Failed to parse change guard: {0}")]
+    ParseError(#[from] toml::de::Error),
+    #[error("Invalid path pattern '{0}': {1}")]
+    InvalidPattern(String, String),
+}
+
+/// What happens when an iteration breaks a rule
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GuardSeverity {
+    /// Tell the agent and retry the story in the next iteration
+    Retry,
+    /// Fail the story
+    Fail,
+}
+
+/// Severity of each kind of violation
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct GuardSeverities {
+    /// Changing a forbidden path
+    pub forbidden: GuardSeverity,
+    /// Changing a path outside the allowed ones
+    pub outside_allowed: GuardSeverity,
+    /// Changing more files or lines than allowed
+    pub budget: GuardSeverity,
+}
+
+impl Default for GuardSeverities {
+    fn default() -> Self {
+        Self {
+            forbidden: GuardSeverity::Fail,
+            outside_allowed: GuardSeverity::Retry,
+            budget: GuardSeverity::Retry,
+        }
+    }
+}
+
+/// Limits on the changes a story may make, as configured in `ralph-guard.toml`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct ChangeGuard {
+    /// Paths that must not be changed (globs; a plain path also covers
+    /// everything below it)
+    pub forbidden: Vec<String>,
+    /// Paths that may be changed (empty = the story's `targetFiles`, and
+    /// anything if the story has none)
+    pub allowed: Vec<String>,
+    /// Most files a story may change
+    pub max_files: Option<usize>,
+    /// Most lines (added plus removed) a story may change
+    pub max_lines: Option<usize>,
+    /// Severity of each kind of violation
+    pub severity: GuardSeverities,
+}
+
+/// A rule an iteration's changes broke
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GuardViolation {
+    /// A forbidden path was changed
+    Forbidden { path: String, pattern: String },
+    /// A path outside the allowed ones was changed
+    OutsideAllowed { path: String, allowed: Vec<String> },
+    /// More files changed than allowed
+    TooManyFiles { changed: usize, max: usize },
+    /// More lines changed than allowed
+    TooManyLines { changed: usize, max: usize },
+}
+
+impl std::fmt::Display for GuardViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GuardViolation::Forbidden { path, pattern } => write!(
+                f,
+                "{} is protected by '{}' and must not be changed",
+                path, pattern
+            ),
+            GuardViolation::OutsideAllowed { path, allowed } => write!(
+                f,
+                "{} is outside the files this story may change ({})",
+                path,
+                allowed.join(", ")
+            ),
+            GuardViolation::TooManyFiles { changed, max } => write!(
+                f,
+                "{} files changed, more than the limit of {}",
+                changed, max
+            ),
+            GuardViolation::TooManyLines { changed, max } => write!(
+                f,
+                "{} lines changed, more than the limit of {}",
+                changed, max
+            ),
+        }
+    }
+}
+
+impl ChangeGuard {
+    /// Parse a change guard from TOML content
+    pub fn parse(content: &str) -> Result<Self, GuardConfigError> {
+        let guard: ChangeGuard = toml::from_str(content)?;
+        for pattern in guard.forbidden.iter().chain(&guard.allowed) {
+            Pattern::new(pattern)
+                .map_err(|e| GuardConfigError::InvalidPattern(pattern.clone(), e.to_string()))?;
+        }
+        Ok(guard)
+    }
+
+    /// Load a change guard from a file
+    pub fn load(path: &Path) -> Result<Self, GuardConfigError> {
+        let content = std::fs::read_to_string(path)?;
+        Self::parse(&content)
+    }
+
+    /// The project's change guard, or None if it has no `ralph-guard.toml`
+    pub fn load_for_project(project_root: &Path) -> Result<Option<Self>, GuardConfigError> {
+        let path = project_root.join(GUARD_CONFIG_FILE);
+        if path.exists() {
+            Self::load(&path).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Rules broken by `changes`, the files a story changed so far.
+    ///
+    /// `target_files` are the story's `targetFiles`, used as the allowed
+    /// paths when none are configured.
+    pub fn check(&self, changes: &[FileDiff], target_files: &[String]) -> Vec<GuardViolation> {
+        let allowed = if self.allowed.is_empty() {
+            target_files
+        } else {
+            &self.allowed
+        };
+
+        let mut violations = Vec::new();
+        for change in changes {
+            if let Some(pattern) = self
+                .forbidden
+                .iter()
+                .find(|pattern| path_matches(pattern, &change.path))
+            {
+                violations.push(GuardViolation::Forbidden {
+                    path: change.path.clone(),
+                    pattern: pattern.clone(),
+                });
+            } else if !allowed.is_empty()
+                && !allowed
+                    .iter()
+                    .any(|pattern| path_matches(pattern, &change.path))
+            {
+                violations.push(GuardViolation::OutsideAllowed {
+                    path: change.path.clone(),
+                    allowed: allowed.to_vec(),
+                });
+            }
+        }
+
+        if let Some(max) = self.max_files.filter(|&max| changes.len() > max) {
+            violations.push(GuardViolation::TooManyFiles {
+                changed: changes.len(),
+                max,
+            });
+        }
+        let lines: usize = changes.iter().map(|change| change.lines).sum();
+        if let Some(max) = self.max_lines.filter(|&max| lines > max) {
+            violations.push(GuardViolation::TooManyLines {
+                changed: lines,
+                max,
+            });
+        }
+        violations
+    }
+
+    /// How seriously to take a violation
+    pub fn severity(&self, violation: &GuardViolation) -> GuardSeverity {
+        match violation {
+            GuardViolation::Forbidden { .. } => self.severity.forbidden,
+            GuardViolation::OutsideAllowed { .. } => self.severity.outside_allowed,
+            GuardViolation::TooManyFiles { .. } | GuardViolation::TooManyLines { .. } => {
+                self.severity.budget
+            }
+        }
+    }
+}
+
+/// Whether `path` matches the glob `pattern` or lies below it
+fn path_matches(pattern: &str, path: &str) -> bool {
+    if Pattern::new(pattern).is_ok_and(|glob| glob.matches(path)) {
+        return true;
+    }
+    let dir = pattern.trim_end_matches('/');
+    path == dir
+        || path
+            .strip_prefix(dir)
+            .is_some_and(|rest| rest.starts_with('/'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diff(path: &str, lines: usize) -> FileDiff {
+        FileDiff {
+            path: path.to_string(),
+            lines,
+        }
+    }
+
+    #[test]
+    fn test_forbidden_paths() {
+        let guard =
+            ChangeGuard::parse(r#"forbidden = ["prd.json", ".github", "**/*.lock"]"#).unwrap();
+        let violations = guard.check(
+            &[
+                diff("prd.json", 2),
+                diff(".github/workflows/ci.yml", 1),
+                diff("Cargo.lock", 10),
+                diff("src/lib.rs", 3),
+            ],
+            &[],
+        );
+        let paths: Vec<String> = violations
+            .iter()
+            .map(|violation| match violation {
+                GuardViolation::Forbidden { path, .. } => path.clone(),
+                other => panic!("unexpected violation {:?}", other),
+            })
+            .collect();
+        assert_eq!(
+            paths,
+            vec!["prd.json", ".github/workflows/ci.yml", "Cargo.lock"]
+        );
+        assert_eq!(guard.severity(&violations[0]), GuardSeverity::Fail);
+        assert_eq!(
+            violations[1].to_string(),
+            ".github/workflows/ci.yml is protected by '.github' and must not be changed"
+        );
+    }
+
+    #[test]
+    fn test_allowed_defaults_to_target_files() {
+        let guard = ChangeGuard::default();
+        let changes = [diff("src/auth.rs", 5), diff("src/db.rs", 1)];
+
+        // No targetFiles: anything goes
+        assert!(guard.check(&changes, &[]).is_empty());
+
+        let violations = guard.check(&changes, &["src/auth*".to_string()]);
+        assert_eq!(
+            violations,
+            vec![GuardViolation::OutsideAllowed {
+                path: "src/db.rs".to_string(),
+                allowed: vec!["src/auth*".to_string()],
+            }]
+        );
+        assert_eq!(guard.severity(&violations[0]), GuardSeverity::Retry);
+
+        // Configured allowed paths replace targetFiles
+        let guard = ChangeGuard::parse(r#"allowed = ["src/**"]"#).unwrap();
+        assert!(guard.check(&changes, &["src/auth*".to_string()]).is_empty());
+    }
+
+    #[test]
+    fn test_change_budget() {
+        let guard =
+            ChangeGuard::parse("max_files = 1\nmax_lines = 10\n[severity]\nbudget = \"fail\"\n")
+                .unwrap();
+        assert!(guard.check(&[diff("a.rs", 10)], &[]).is_empty());
+
+        let violations = guard.check(&[diff("a.rs", 8), diff("b.rs", 4)], &[]);
+        assert_eq!(
+            violations,
+            vec![
+                GuardViolation::TooManyFiles { changed: 2, max: 1 },
+                GuardViolation::TooManyLines {
+                    changed: 12,
+                    max: 10
+                },
+            ]
+        );
+        assert_eq!(guard.severity(&violations[1]), GuardSeverity::Fail);
+        assert_eq!(
+            violations[1].to_string(),
+            "12 lines changed, more than the limit of 10"
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid_patterns() {
+        assert!(matches!(
+            ChangeGuard::parse(r#"forbidden = ["src/[lib.rs"]"#),
+            Err(GuardConfigError::InvalidPattern(..))
+        ));
+        assert!(ChangeGuard::parse("[severity]\nforbidden = \"ignore\"").is_err());
+    }
+
+    #[test]
+    fn test_load_for_project_without_file() {
+        let dir = tempfile::TempDir::new().unwrap();
+        assert!(ChangeGuard::load_for_project(dir.path()).unwrap().is_none());
+    }
+}
@@ -1,11 +1,19 @@
 //! Iteration management module for Ralph.
 //!
 //! This module provides infrastructure for managing iteration loops during story execution,
-//! including context passing between iterations and futile retry detection.
+//! including context passing between iterations, futile retry detection,
+//! guarding what an iteration may change, rolling back iterations that made
+//! things worse and splitting stories that proved too large.
 
 pub mod context;
 pub mod futility;
+pub mod guard;
+pub mod rollback;
+pub mod split;
 
 // Re-exports for convenience
 pub use context::{ApproachHint, IterationContext, IterationError};
 pub use futility::{FutileRetryDetector, FutilityVerdict};
+pub use guard::{ChangeGuard, GuardSeverity, GuardViolation, GUARD_CONFIG_FILE};
+pub use rollback::{RollbackPolicy, WorkspaceRollback};
+pub use split::{SplitError, SubStory};
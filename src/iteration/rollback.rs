@@ -0,0 +1,112 @@
+//! Rolling back iterations that made the workspace worse.
+//!
+//! The executor snapshots the workspace before every iteration. When an
+//! iteration's quality gates regress, the rollback policy decides whether to
+//! restore the workspace to the best state seen so far, so the next iteration
+//! does not start from whatever the failed attempt left behind.
+
+use serde::{Deserialize, Serialize};
+
+use super::context::IterationContext;
+
+/// When the executor restores an earlier workspace snapshot.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RollbackPolicy {
+    /// Keep whatever the previous iteration left behind
+    Never,
+    /// Restore the best earlier state when more gates fail than in it
+    #[default]
+    OnRegression,
+}
+
+impl RollbackPolicy {
+    /// The earlier iteration whose resulting workspace should be restored.
+    ///
+    /// `failed_gates` is the number of gates the current iteration failed.
+    /// Returns the most recent iteration with the fewest failed gates if the
+    /// current iteration did worse than it.
+    pub fn rollback_target(&self, context: &IterationContext, failed_gates: usize) -> Option<u32> {
+        match self {
+            RollbackPolicy::Never => None,
+            RollbackPolicy::OnRegression => context
+                .best_gate_result()
+                .filter(|&(_, best)| failed_gates > best)
+                .map(|(iteration, _)| iteration),
+        }
+    }
+}
+
+/// A rollback of the workspace after a regressing iteration.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WorkspaceRollback {
+    /// Iteration whose changes were discarded
+    pub iteration: u32,
+    /// Earlier iteration whose resulting workspace was restored
+    pub restored_to: u32,
+    /// Gates failing after the discarded iteration
+    pub failed_gates: usize,
+    /// Gates failing after the restored iteration
+    pub restored_failed_gates: usize,
+}
+
+impl std::fmt::Display for WorkspaceRollback {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Iteration {} failed {} gate(s), more than iteration {} ({}); its changes were rolled back to the workspace iteration {} left",
+            self.iteration,
+            self.failed_gates,
+            self.restored_to,
+            self.restored_failed_gates,
+            self.restored_to
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_no_target_without_gate_history() {
+        let context = IterationContext::new("US-001", 5);
+        assert_eq!(
+            RollbackPolicy::OnRegression.rollback_target(&context, 3),
+            None
+        );
+    }
+
+    #[test]
+    fn test_target_on_regression() {
+        let mut context = IterationContext::new("US-001", 5);
+        context.record_gate_failures(1, 2);
+        context.record_gate_failures(2, 1);
+        context.record_gate_failures(3, 1);
+
+        // Ties go to the most recent iteration
+        assert_eq!(
+            RollbackPolicy::OnRegression.rollback_target(&context, 2),
+            Some(3)
+        );
+        // Doing as well as the best state is not a regression
+        assert_eq!(
+            RollbackPolicy::OnRegression.rollback_target(&context, 1),
+            None
+        );
+        assert_eq!(RollbackPolicy::Never.rollback_target(&context, 4), None);
+    }
+
+    #[test]
+    fn test_rollback_display() {
+        let rollback = WorkspaceRollback {
+            iteration: 4,
+            restored_to: 2,
+            failed_gates: 3,
+            restored_failed_gates: 1,
+        };
+        assert_eq!(
+            rollback.to_string(),
+            "Iteration 4 failed 3 gate(s), more than iteration 2 (1); its changes were rolled back to the workspace iteration 2 left"
+        );
+    }
+}
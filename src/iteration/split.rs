@@ -0,0 +1,539 @@
+//! Splitting stories that are too large to finish.
+//!
+//! When the futility detector gives up on a story (deferring it or pausing
+//! for guidance) and splitting is enabled, the executor asks the agent to
+//! break the story into smaller sub-stories instead. The agent answers with a
+//! JSON array of stories, which are validated and written to `prd.json` in
+//! place of the original, so the run picks them up like any other story.
+
+use std::collections::HashSet;
+use std::path::Path;
+
+use serde::Deserialize;
+use serde_json::Value;
+use thiserror::Error;
+
+use super::context::IterationContext;
+use super::futility::PatternSummary;
+use crate::mcp::tools::load_prd::{PrdFile, PrdUserStory};
+
+/// Fewest sub-stories a split may produce
+pub const MIN_SUB_STORIES: usize = 2;
+
+/// Most sub-stories a split may produce
+pub const MAX_SUB_STORIES: usize = 8;
+
+/// Errors that can occur when splitting a story
+#[derive(Error, Debug)]
+pub enum SplitError {
+    #[error("Agent did not answer with a JSON array of stories")]
+    MissingStories,
+    #[error("Failed to parse sub-stories: {0}")]
+    ParseError(#[from] serde_json::Error),
+    #[error("Invalid split: {0}")]
+    Invalid(String),
+    #[error("Failed to update PRD: {0}")]
+    PrdError(String),
+}
+
+/// A story the agent proposed to replace part of the original
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubStory {
+    /// Story ID (e.g., "US-003.1")
+    pub id: String,
+    /// Story title
+    pub title: String,
+    /// Story description
+    #[serde(default)]
+    pub description: String,
+    /// Acceptance criteria
+    #[serde(default)]
+    pub acceptance_criteria: Vec<String>,
+    /// Earlier sub-stories or existing stories this one depends on
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+    /// Files this sub-story will modify (empty = the original's)
+    #[serde(default)]
+    pub target_files: Vec<String>,
+}
+
+/// Build the prompt asking the agent to split `story`.
+///
+/// `reason` is why the futility detector stopped retrying; the error history
+/// and pattern summary tell the agent which parts of the story kept failing.
+pub fn split_prompt(
+    story: &PrdUserStory,
+    context: &IterationContext,
+    summary: &PatternSummary,
+    reason: &str,
+) -> String {
+    let mut prompt = format!(
+        "# Split Story {}: {}\n\n\
+         Repeated attempts to implement this story have failed: {}\n\n\
+         Break it into smaller stories that can each be implemented, and pass \
+         the quality gates, on their own. Do not change any files.\n\n\
+         ## Story\n\n{}\n",
+        story.id, story.title, reason, story.description
+    );
+
+    if !story.acceptance_criteria.is_empty() {
+        prompt.push_str("\n### Acceptance Criteria\n\n");
+        for criterion in &story.acceptance_criteria {
+            prompt.push_str(&format!("- {}\n", criterion));
+        }
+    }
+    if !story.target_files.is_empty() {
+        prompt.push_str(&format!(
+            "\n### Target Files\n\n{}\n",
+            story.target_files.join(", ")
+        ));
+    }
+
+    prompt.push_str("\n## Failure Patterns\n\n");
+    prompt.push_str(&format!(
+        "- {} errors in {} iterations\n",
+        summary.total_errors, summary.total_iterations
+    ));
+    let mut categories: Vec<_> = summary.errors_by_category.iter().collect();
+    categories.sort_by(|a, b| b.1.cmp(a.1).then(a.0.as_str().cmp(b.0.as_str())));
+    if !categories.is_empty() {
+        let counts: Vec<String> = categories
+            .iter()
+            .map(|(category, count)| format!("{} x{}", category.as_str(), count))
+            .collect();
+        prompt.push_str(&format!("- By category: {}\n", counts.join(", ")));
+    }
+    if let Some((signature, count)) = &summary.most_frequent_error {
+        prompt.push_str(&format!(
+            "- Most frequent error ({} times): {}\n",
+            count, signature
+        ));
+    }
+    if summary.has_oscillation {
+        prompt.push_str("- Fixing one error kept reintroducing another\n");
+    }
+    if summary.has_stagnation {
+        prompt.push_str("- Iterations stopped making progress\n");
+    }
+    prompt.push_str(&context.build_history_context());
+
+    prompt.push_str(&format!(
+        "\n## Answer\n\n\
+         Reply with only a JSON array of {min} to {max} stories, in the order they \
+         should be implemented. Number the IDs {id}.1, {id}.2 and so on. \
+         `dependsOn` may only name earlier stories in the array or other stories \
+         in the PRD. Together the stories must cover every acceptance criterion \
+         of {id}.\n\n\
+         ```json\n\
+         [\n  \
+           {{\"id\": \"{id}.1\", \"title\": \"...\", \"description\": \"...\", \
+         \"acceptanceCriteria\": [\"...\"], \"dependsOn\": [], \"targetFiles\": [\"...\"]}},\n  \
+           {{\"id\": \"{id}.2\", \"title\": \"...\", \"description\": \"...\", \
+         \"acceptanceCriteria\": [\"...\"], \"dependsOn\": [\"{id}.1\"], \"targetFiles\": [\"...\"]}}\n\
+         ]\n\
+         ```\n",
+        min = MIN_SUB_STORIES,
+        max = MAX_SUB_STORIES,
+        id = story.id
+    ));
+    prompt
+}
+
+/// Parse the sub-stories from the agent's answer.
+///
+/// Takes the first ```` ```json ```` block if there is one, otherwise the
+/// outermost `[...]` of the answer.
+pub fn parse_sub_stories(answer: &str) -> Result<Vec<SubStory>, SplitError> {
+    let fenced = answer.find("```json").and_then(|start| {
+        let body = &answer[start + "```json".len()..];
+        body.find("```").map(|end| &body[..end])
+    });
+    let json = fenced
+        .or_else(|| {
+            let start = answer.find('[')?;
+            let end = answer.rfind(']')?;
+            (start < end).then(|| &answer[start..=end])
+        })
+        .ok_or(SplitError::MissingStories)?;
+    Ok(serde_json::from_str(json)?)
+}
+
+/// Check that `sub_stories` can replace `story` in `prd`.
+///
+/// Sub-story IDs must be new and unique, every sub-story needs a title and
+/// acceptance criteria, and `dependsOn` may only name earlier sub-stories or
+/// other existing stories. Stories that depend on the original, directly or
+/// through others, will depend on the sub-stories once it is split, so a
+/// sub-story may not depend on them; the split cannot introduce a cycle.
+pub fn validate_split(
+    story: &PrdUserStory,
+    prd: &PrdFile,
+    sub_stories: &[SubStory],
+) -> Result<(), SplitError> {
+    if !(MIN_SUB_STORIES..=MAX_SUB_STORIES).contains(&sub_stories.len()) {
+        return Err(SplitError::Invalid(format!(
+            "expected {} to {} sub-stories, got {}",
+            MIN_SUB_STORIES,
+            MAX_SUB_STORIES,
+            sub_stories.len()
+        )));
+    }
+
+    let existing: HashSet<&str> = prd
+        .user_stories
+        .iter()
+        .map(|s| s.id.as_str())
+        .filter(|id| *id != story.id)
+        .collect();
+    let dependents = dependents_of(&story.id, prd);
+    let mut earlier: HashSet<&str> = HashSet::new();
+    for sub in sub_stories {
+        let id = sub.id.trim();
+        if id.is_empty() {
+            return Err(SplitError::Invalid("a sub-story has no ID".to_string()));
+        }
+        if id == story.id || existing.contains(id) || earlier.contains(id) {
+            return Err(SplitError::Invalid(format!(
+                "story ID {} is already used",
+                id
+            )));
+        }
+        if sub.title.trim().is_empty() {
+            return Err(SplitError::Invalid(format!("{} has no title", id)));
+        }
+        if sub.acceptance_criteria.is_empty() {
+            return Err(SplitError::Invalid(format!(
+                "{} has no acceptance criteria",
+                id
+            )));
+        }
+        if let Some(dep) = sub
+            .depends_on
+            .iter()
+            .find(|dep| !earlier.contains(dep.as_str()) && !existing.contains(dep.as_str()))
+        {
+            return Err(SplitError::Invalid(format!(
+                "{} depends on {}, which is neither an earlier sub-story nor another story",
+                id, dep
+            )));
+        }
+        if let Some(dep) = sub
+            .depends_on
+            .iter()
+            .find(|dep| dependents.contains(dep.as_str()))
+        {
+            return Err(SplitError::Invalid(format!(
+                "{} depends on {}, which depends on {}",
+                id, dep, story.id
+            )));
+        }
+        earlier.insert(id);
+    }
+    Ok(())
+}
+
+/// IDs of the stories in `prd` that depend on `story_id`, directly or
+/// through other stories.
+fn dependents_of<'a>(story_id: &str, prd: &'a PrdFile) -> HashSet<&'a str> {
+    let mut dependents: HashSet<&str> = HashSet::new();
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for story in &prd.user_stories {
+            if !dependents.contains(story.id.as_str())
+                && story
+                    .depends_on
+                    .iter()
+                    .any(|dep| dep == story_id || dependents.contains(dep.as_str()))
+            {
+                dependents.insert(story.id.as_str());
+                changed = true;
+            }
+        }
+    }
+    dependents
+}
+
+/// Replace `story_id` in the PRD at `prd_path` with `sub_stories`.
+///
+/// Each sub-story starts as a copy of the original, so it keeps the
+/// original's priority, dependencies and per-story overrides; the original's
+/// `verify` checks move to the last sub-story. Stories that depended on the
+/// original depend on all of its sub-stories instead. The PRD is edited as
+/// raw JSON to preserve fields Ralph does not know about.
+pub fn apply_split(
+    prd_path: &Path,
+    story_id: &str,
+    sub_stories: &[SubStory],
+) -> Result<(), SplitError> {
+    let content = std::fs::read_to_string(prd_path)
+        .map_err(|e| SplitError::PrdError(format!("Failed to read PRD: {}", e)))?;
+    let mut json: Value = serde_json::from_str(&content)
+        .map_err(|e| SplitError::PrdError(format!("Failed to parse PRD: {}", e)))?;
+
+    let stories = json
+        .get_mut("userStories")
+        .and_then(Value::as_array_mut)
+        .ok_or_else(|| SplitError::PrdError("PRD has no userStories".to_string()))?;
+    let index = stories
+        .iter()
+        .position(|s| s.get("id").and_then(Value::as_str) == Some(story_id))
+        .ok_or_else(|| SplitError::PrdError(format!("Story not found: {}", story_id)))?;
+    let original = stories.remove(index);
+    let inherited: Vec<Value> = original
+        .get("dependsOn")
+        .and_then(Value::as_array)
+        .cloned()
+        .unwrap_or_default();
+
+    let replacements: Vec<Value> = sub_stories
+        .iter()
+        .enumerate()
+        .map(|(i, sub)| {
+            let mut value = original.clone();
+            value["id"] = Value::from(sub.id.trim());
+            value["title"] = Value::from(sub.title.as_str());
+            value["description"] = Value::from(sub.description.as_str());
+            value["acceptanceCriteria"] = Value::from(sub.acceptance_criteria.clone());
+            value["passes"] = Value::Bool(false);
+
+            let mut depends_on = inherited.clone();
+            depends_on.extend(sub.depends_on.iter().map(|dep| Value::from(dep.as_str())));
+            value["dependsOn"] = Value::Array(depends_on);
+            if !sub.target_files.is_empty() {
+                value["targetFiles"] = Value::from(sub.target_files.clone());
+            }
+            if i + 1 < sub_stories.len() {
+                if let Some(fields) = value.as_object_mut() {
+                    fields.remove("verify");
+                }
+            }
+            value
+        })
+        .collect();
+    stories.splice(index..index, replacements);
+
+    // Stories that waited for the original now wait for all of its parts
+    let sub_ids: Vec<Value> = sub_stories
+        .iter()
+        .map(|sub| Value::from(sub.id.trim()))
+        .collect();
+    for story in stories.iter_mut() {
+        if let Some(depends_on) = story.get_mut("dependsOn").and_then(Value::as_array_mut) {
+            if let Some(pos) = depends_on
+                .iter()
+                .position(|dep| dep.as_str() == Some(story_id))
+            {
+                depends_on.splice(pos..=pos, sub_ids.iter().cloned());
+            }
+        }
+    }
+
+    let updated = serde_json::to_string_pretty(&json)
+        .map_err(|e| SplitError::PrdError(format!("Failed to serialize PRD: {}", e)))?;
+    std::fs::write(prd_path, updated)
+        .map_err(|e| SplitError::PrdError(format!("Failed to write PRD: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::iteration::context::{ErrorCategory, IterationError};
+
+    const PRD: &str = r#"{
+  "project": "demo",
+  "branchName": "main",
+  "userStories": [
+    {
+      "id": "US-001",
+      "title": "Auth",
+      "description": "Login, sessions and password reset",
+      "acceptanceCriteria": ["Login works", "Reset works"],
+      "priority": 1,
+      "passes": false,
+      "maxIterations": 4,
+      "verify": [{ "fileExists": "src/auth.rs" }],
+      "x-owner": "team-a"
+    },
+    {
+      "id": "US-002",
+      "title": "Profile page",
+      "priority": 2,
+      "passes": false,
+      "dependsOn": ["US-001"]
+    }
+  ]
+}"#;
+
+    fn sub(id: &str, depends_on: &[&str]) -> SubStory {
+        SubStory {
+            id: id.to_string(),
+            title: format!("Part {}", id),
+            description: String::new(),
+            acceptance_criteria: vec!["Works".to_string()],
+            depends_on: depends_on.iter().map(|d| d.to_string()).collect(),
+            target_files: Vec::new(),
+        }
+    }
+
+    fn prd() -> PrdFile {
+        serde_json::from_str(PRD).unwrap()
+    }
+
+    #[test]
+    fn test_parse_sub_stories() {
+        let answer = "Here is the split:\n```json\n[\n  {\"id\": \"US-001.1\", \"title\": \"Login\", \
+             \"acceptanceCriteria\": [\"Login works\"]},\n  {\"id\": \"US-001.2\", \"title\": \"Reset\", \
+             \"acceptanceCriteria\": [\"Reset works\"], \"dependsOn\": [\"US-001.1\"]}\n]\n```\nDone.";
+        let subs = parse_sub_stories(answer).unwrap();
+        assert_eq!(subs.len(), 2);
+        assert_eq!(subs[1].depends_on, vec!["US-001.1"]);
+
+        let bare = r#"[{"id": "A", "title": "a"}, {"id": "B", "title": "b"}]"#;
+        assert_eq!(parse_sub_stories(bare).unwrap()[1].id, "B");
+
+        assert!(matches!(
+            parse_sub_stories("I cannot split this story."),
+            Err(SplitError::MissingStories)
+        ));
+        assert!(matches!(
+            parse_sub_stories("[not json]"),
+            Err(SplitError::ParseError(_))
+        ));
+    }
+
+    #[test]
+    fn test_validate_split() {
+        let prd = prd();
+        let story = &prd.user_stories[0];
+        assert!(validate_split(
+            story,
+            &prd,
+            &[sub("US-001.1", &[]), sub("US-001.2", &["US-001.1"])]
+        )
+        .is_ok());
+
+        let invalid = |subs: &[SubStory]| match validate_split(story, &prd, subs) {
+            Err(SplitError::Invalid(message)) => message,
+            other => panic!("expected an invalid split, got {:?}", other),
+        };
+        assert_eq!(
+            invalid(&[sub("US-001.1", &[])]),
+            "expected 2 to 8 sub-stories, got 1"
+        );
+        assert_eq!(
+            invalid(&[sub("US-001.1", &[]), sub("US-002", &[])]),
+            "story ID US-002 is already used"
+        );
+        assert_eq!(
+            invalid(&[sub("US-001.1", &["US-001.2"]), sub("US-001.2", &[])]),
+            "US-001.1 depends on US-001.2, which is neither an earlier sub-story nor another story"
+        );
+        assert_eq!(
+            invalid(&[sub("US-001.1", &[]), sub("US-001.2", &["US-001"])]),
+            "US-001.2 depends on US-001, which is neither an earlier sub-story nor another story"
+        );
+
+        let mut untested = sub("US-001.2", &[]);
+        untested.acceptance_criteria.clear();
+        assert_eq!(
+            invalid(&[sub("US-001.1", &[]), untested]),
+            "US-001.2 has no acceptance criteria"
+        );
+    }
+
+    #[test]
+    fn test_validate_split_rejects_dependencies_on_dependents() {
+        let mut prd = prd();
+        prd.user_stories.push(
+            serde_json::from_str(
+                r#"{"id": "US-003", "title": "Settings", "priority": 3, "passes": false,
+                    "dependsOn": ["US-002"]}"#,
+            )
+            .unwrap(),
+        );
+        prd.user_stories.push(
+            serde_json::from_str(
+                r#"{"id": "US-004", "title": "Theme", "priority": 4, "passes": false}"#,
+            )
+            .unwrap(),
+        );
+        let story = &prd.user_stories[0];
+        let invalid = |subs: &[SubStory]| match validate_split(story, &prd, subs) {
+            Err(SplitError::Invalid(message)) => message,
+            other => panic!("expected an invalid split, got {:?}", other),
+        };
+
+        // US-002 would depend on the sub-stories, and US-003 on US-002
+        assert_eq!(
+            invalid(&[sub("US-001.1", &[]), sub("US-001.2", &["US-002"])]),
+            "US-001.2 depends on US-002, which depends on US-001"
+        );
+        assert_eq!(
+            invalid(&[sub("US-001.1", &["US-003"]), sub("US-001.2", &[])]),
+            "US-001.1 depends on US-003, which depends on US-001"
+        );
+        assert!(validate_split(
+            story,
+            &prd,
+            &[sub("US-001.1", &["US-004"]), sub("US-001.2", &[])]
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn test_apply_split_replaces_story() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("prd.json");
+        std::fs::write(&path, PRD).unwrap();
+
+        let mut second = sub("US-001.2", &["US-001.1"]);
+        second.target_files = vec!["src/reset.rs".to_string()];
+        apply_split(&path, "US-001", &[sub("US-001.1", &[]), second]).unwrap();
+
+        let prd: PrdFile = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        let ids: Vec<&str> = prd.user_stories.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(ids, vec!["US-001.1", "US-001.2", "US-002"]);
+
+        let (first, second) = (&prd.user_stories[0], &prd.user_stories[1]);
+        assert_eq!(first.priority, 1);
+        assert_eq!(first.max_iterations, Some(4));
+        assert!(!first.passes);
+        assert!(first.verify.is_empty());
+        assert_eq!(second.depends_on, vec!["US-001.1"]);
+        assert_eq!(second.target_files, vec!["src/reset.rs"]);
+        assert_eq!(second.verify.len(), 1);
+        assert_eq!(prd.user_stories[2].depends_on, vec!["US-001.1", "US-001.2"]);
+
+        // Fields Ralph does not model survive the rewrite
+        let raw: Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(raw["userStories"][1]["x-owner"], "team-a");
+    }
+
+    #[test]
+    fn test_split_prompt_includes_failure_patterns() {
+        let prd = prd();
+        let mut context = IterationContext::new("US-001", 4);
+        context.start_iteration(1);
+        context.record_error(IterationError::new(
+            1,
+            ErrorCategory::Test,
+            "reset_token test failed",
+        ));
+        let summary = crate::iteration::FutileRetryDetector::new().summarize_patterns(&context);
+
+        let prompt = split_prompt(
+            &prd.user_stories[0],
+            &context,
+            &summary,
+            "Same error repeated",
+        );
+        assert!(prompt.starts_with("# Split Story US-001: Auth"));
+        assert!(prompt.contains("Same error repeated"));
+        assert!(prompt.contains("- Reset works"));
+        assert!(prompt.contains("- 1 errors in 1 iterations"));
+        assert!(prompt.contains("reset_token test failed"));
+        assert!(prompt.contains("Number the IDs US-001.1, US-001.2"));
+    }
+}
@@ -3,9 +3,11 @@
 //! This library exposes Ralph's internal modules for integration testing
 //! and potential use as a library.
 
+pub mod agent;
 pub mod audit;
 pub mod checkpoint;
 pub mod error;
+pub mod git;
 pub mod integrations;
 pub mod interactive_guidance;
 pub mod iteration;
@@ -15,6 +17,7 @@ pub mod metrics;
 pub mod notification;
 pub mod parallel;
 pub mod pause;
+pub mod prompt;
 pub mod quality;
 pub mod runner;
 pub mod timeout;
@@ -3,10 +3,16 @@ use rmcp::{transport::stdio, ServiceExt};
 use std::path::PathBuf;
 use std::process::ExitCode;
 
+use ralphmacchio::agent::AgentRegistry;
 use ralphmacchio::audit;
 use ralphmacchio::checkpoint::{CheckpointManager, PauseReason};
+use ralphmacchio::git::{BranchConfig, CommitPolicy, WorkspaceSnapshot};
+use ralphmacchio::iteration::{ChangeGuard, RollbackPolicy};
 use ralphmacchio::logging::{init_logging, LoggingConfig};
 use ralphmacchio::mcp::RalphMcpServer;
+use ralphmacchio::metrics::limits::{next_time_of_day, parse_duration, parse_time_of_day};
+use ralphmacchio::metrics::{RunLimits, UsageBudget};
+use ralphmacchio::pause::RetryStrategy;
 use ralphmacchio::runner::{Runner, RunnerConfig};
 use ralphmacchio::ui::{DisplayOptions, HelpRenderer, UiMode};
 
@@ -121,6 +127,50 @@ struct Cli {
     #[arg(long)]
     no_checkpoint: bool,
 
+    /// Pause the run when estimated model spend reaches this many USD
+    #[arg(long, value_name = "USD")]
+    budget_usd: Option<f64>,
+
+    /// Pause the run when total model tokens reach this count
+    #[arg(long, value_name = "TOKENS")]
+    max_tokens: Option<u64>,
+
+    /// Create the PRD branch from this ref instead of the current HEAD
+    #[arg(long, value_name = "REF")]
+    base_branch: Option<String>,
+
+    /// Run on the checked-out branch instead of the PRD's branchName
+    #[arg(long, conflicts_with = "base_branch")]
+    no_branch: bool,
+
+    /// Agent prompt template (default: .ralph/prompt.md or a built-in template)
+    #[arg(long, value_name = "FILE")]
+    prompt: Option<PathBuf>,
+
+    /// Longest total wait for usage limits to reset before stopping (0 = don't wait)
+    #[arg(long, value_name = "MINUTES", default_value = "360")]
+    max_limit_wait: u64,
+
+    /// Keep a failed iteration's changes even when its gates regress
+    #[arg(long)]
+    no_rollback: bool,
+
+    /// Ask the agent to split stories that keep failing into smaller ones
+    #[arg(long)]
+    split_stories: bool,
+
+    /// Stop after this many iterations across all stories (0 = unlimited)
+    #[arg(long, value_name = "N", default_value = "0")]
+    max_total_iterations: u32,
+
+    /// Stop once this much time has passed, e.g. 2h or 1h30m
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration)]
+    deadline: Option<std::time::Duration>,
+
+    /// Stop at this local time, e.g. 07:00
+    #[arg(long, value_name = "HH:MM", value_parser = parse_time_of_day)]
+    stop_at: Option<chrono::NaiveTime>,
+
     #[command(subcommand)]
     command: Option<Commands>,
 }
@@ -170,6 +220,50 @@ enum Commands {
         #[arg(long)]
         no_checkpoint: bool,
 
+        /// Pause the run when estimated model spend reaches this many USD
+        #[arg(long, value_name = "USD")]
+        budget_usd: Option<f64>,
+
+        /// Pause the run when total model tokens reach this count
+        #[arg(long, value_name = "TOKENS")]
+        max_tokens: Option<u64>,
+
+        /// Create the PRD branch from this ref instead of the current HEAD
+        #[arg(long, value_name = "REF")]
+        base_branch: Option<String>,
+
+        /// Run on the checked-out branch instead of the PRD's branchName
+        #[arg(long, conflicts_with = "base_branch")]
+        no_branch: bool,
+
+        /// Agent prompt template (default: .ralph/prompt.md or a built-in template)
+        #[arg(long, value_name = "FILE")]
+        prompt: Option<PathBuf>,
+
+        /// Longest total wait for usage limits to reset before stopping (0 = don't wait)
+        #[arg(long, value_name = "MINUTES", default_value = "360")]
+        max_limit_wait: u64,
+
+        /// Keep a failed iteration's changes even when its gates regress
+        #[arg(long)]
+        no_rollback: bool,
+
+        /// Ask the agent to split stories that keep failing into smaller ones
+        #[arg(long)]
+        split_stories: bool,
+
+        /// Stop after this many iterations across all stories (0 = unlimited)
+        #[arg(long, value_name = "N", default_value = "0")]
+        max_total_iterations: u32,
+
+        /// Stop once this much time has passed, e.g. 2h or 1h30m
+        #[arg(long, value_name = "DURATION", value_parser = parse_duration)]
+        deadline: Option<std::time::Duration>,
+
+        /// Stop at this local time, e.g. 07:00
+        #[arg(long, value_name = "HH:MM", value_parser = parse_time_of_day)]
+        stop_at: Option<chrono::NaiveTime>,
+
         /// Print help information
         #[arg(long, short)]
         help: bool,
@@ -230,6 +324,24 @@ enum Commands {
         #[arg(long, short = 'd')]
         dir: Option<PathBuf>,
 
+        /// Print help information
+        #[arg(long, short)]
+        help: bool,
+    },
+    /// Restore the workspace to the snapshot taken before a story iteration
+    Rollback {
+        /// Story whose snapshots to use
+        #[arg(long, short)]
+        story: Option<String>,
+
+        /// Iteration to roll back to (lists the story's snapshots if omitted)
+        #[arg(long, short)]
+        iteration: Option<u32>,
+
+        /// Working directory
+        #[arg(long, short = 'd')]
+        dir: Option<PathBuf>,
+
         /// Print help information
         #[arg(long, short)]
         help: bool,
@@ -311,6 +423,25 @@ async fn main() -> Result<ExitCode, Box<dyn std::error::Error>> {
             println!("  --no-resume              Skip checkpoint prompt (do not resume)");
             println!("  --timeout <SECONDS>      Agent timeout in seconds (overrides default)");
             println!("  --no-checkpoint          Disable checkpointing");
+            println!("  --budget-usd <USD>       Pause when estimated spend reaches this amount");
+            println!("  --max-tokens <TOKENS>    Pause when total model tokens reach this count");
+            println!("  --base-branch <REF>      Create the PRD branch from this ref");
+            println!("  --no-branch              Run on the checked-out branch");
+            println!(
+                "  --prompt <FILE>          Agent prompt template [default: .ralph/prompt.md]"
+            );
+            println!(
+                "  --max-limit-wait <MIN>   Wait up to this long for usage limits to reset [default: 360]"
+            );
+            println!(
+                "  --no-rollback            Keep a failed iteration's changes when gates regress"
+            );
+            println!(
+                "  --split-stories          Split stories that keep failing into smaller ones"
+            );
+            println!("  --max-total-iterations <N>  Stop after N iterations across all stories");
+            println!("  --deadline <DURATION>    Stop once this much time has passed (e.g. 2h)");
+            println!("  --stop-at <HH:MM>        Stop at this local time (e.g. 07:00)");
             println!("  --agent <CMD>            Agent command (claude, codex, amp, or custom)");
             println!("  -h, --help               Print help information");
             return Ok(ExitCode::SUCCESS);
@@ -326,6 +457,17 @@ async fn main() -> Result<ExitCode, Box<dyn std::error::Error>> {
             no_resume,
             timeout,
             no_checkpoint,
+            budget_usd,
+            max_tokens,
+            ref base_branch,
+            no_branch,
+            ref prompt,
+            max_limit_wait,
+            no_rollback,
+            split_stories,
+            max_total_iterations,
+            deadline,
+            stop_at,
             help: false,
         }) => {
             run_stories(
@@ -340,6 +482,16 @@ async fn main() -> Result<ExitCode, Box<dyn std::error::Error>> {
                 timeout,
                 no_checkpoint,
                 agent.clone(),
+                UsageBudget {
+                    max_cost_usd: budget_usd,
+                    max_tokens,
+                },
+                branch_config(base_branch.clone(), no_branch),
+                prompt.clone(),
+                max_limit_wait,
+                rollback_policy(no_rollback),
+                split_stories,
+                run_limits(max_total_iterations, deadline, stop_at),
             )
             .await?;
         }
@@ -460,6 +612,31 @@ async fn main() -> Result<ExitCode, Box<dyn std::error::Error>> {
         }) => {
             return run_status(dir.clone(), cli.quiet);
         }
+        Some(Commands::Rollback { help: true, .. }) => {
+            println!("Restore the workspace to the snapshot taken before a story iteration");
+            println!();
+            println!("Usage: ralph rollback --story <ID> [--iteration <N>]");
+            println!();
+            println!("Options:");
+            println!("  -s, --story <ID>     Story whose snapshots to use");
+            println!(
+                "  -i, --iteration <N>  Iteration to roll back to (lists snapshots if omitted)"
+            );
+            println!("  -d, --dir <DIR>      Working directory [default: .]");
+            println!("  -h, --help           Print help information");
+            return Ok(ExitCode::SUCCESS);
+        }
+        Some(Commands::Rollback {
+            ref story,
+            iteration,
+            ref dir,
+            help: false,
+        }) => {
+            let Some(story) = story else {
+                return Err("ralph rollback requires --story <ID>".into());
+            };
+            run_rollback(story, iteration, dir.clone(), cli.quiet)?;
+        }
         None => {
             // Default: run stories if prd.json exists, otherwise show help
             // Check multiple locations: prd.json, ralph/prd.json
@@ -477,6 +654,16 @@ async fn main() -> Result<ExitCode, Box<dyn std::error::Error>> {
                     cli.timeout,
                     cli.no_checkpoint,
                     cli.agent.clone(),
+                    UsageBudget {
+                        max_cost_usd: cli.budget_usd,
+                        max_tokens: cli.max_tokens,
+                    },
+                    branch_config(cli.base_branch.clone(), cli.no_branch),
+                    cli.prompt.clone(),
+                    cli.max_limit_wait,
+                    rollback_policy(cli.no_rollback),
+                    cli.split_stories,
+                    run_limits(cli.max_total_iterations, cli.deadline, cli.stop_at),
                 )
                 .await?;
             } else {
@@ -510,6 +697,46 @@ fn find_prd_file(default_path: &std::path::Path) -> Option<PathBuf> {
     None
 }
 
+/// Build the feature branch configuration from the CLI flags
+fn branch_config(base_branch: Option<String>, no_branch: bool) -> BranchConfig {
+    if no_branch {
+        return BranchConfig::disabled();
+    }
+    match base_branch {
+        Some(base) => BranchConfig::default().with_base(base),
+        None => BranchConfig::default(),
+    }
+}
+
+/// Rollback policy from the CLI flags
+fn rollback_policy(no_rollback: bool) -> RollbackPolicy {
+    if no_rollback {
+        RollbackPolicy::Never
+    } else {
+        RollbackPolicy::OnRegression
+    }
+}
+
+/// Run limits from the CLI flags, with deadlines counted from now
+fn run_limits(
+    max_total_iterations: u32,
+    deadline: Option<std::time::Duration>,
+    stop_at: Option<chrono::NaiveTime>,
+) -> RunLimits {
+    let now = chrono::Local::now();
+    let mut limits = RunLimits::unlimited();
+    if max_total_iterations > 0 {
+        limits = limits.with_max_total_iterations(max_total_iterations);
+    }
+    if let Some(at) = deadline.and_then(|d| chrono::Duration::from_std(d).ok()) {
+        limits = limits.with_deadline(now + at);
+    }
+    if let Some(at) = stop_at.and_then(|time| next_time_of_day(time, now)) {
+        limits = limits.with_deadline(at);
+    }
+    limits
+}
+
 /// Run stories from the PRD until all pass
 #[allow(clippy::too_many_arguments)]
 async fn run_stories(
@@ -524,6 +751,13 @@ async fn run_stories(
     timeout: Option<u64>,
     no_checkpoint: bool,
     agent: Option<String>,
+    budget: UsageBudget,
+    branch: BranchConfig,
+    prompt: Option<PathBuf>,
+    max_limit_wait_minutes: u64,
+    rollback_policy: RollbackPolicy,
+    split_stories: bool,
+    run_limits: RunLimits,
 ) -> Result<(), Box<dyn std::error::Error>> {
     use ralphmacchio::parallel::scheduler::ParallelRunnerConfig;
 
@@ -541,6 +775,13 @@ async fn run_stories(
         ..Default::default()
     };
 
+    // Built-in agents plus any defined in ralph-agents.toml
+    let agent_registry = AgentRegistry::load_for_project(&working_dir)?;
+    // Commit message, trailers, signing and staging from ralph-commit.toml
+    let commit_policy = CommitPolicy::load_for_project(&working_dir)?;
+    // Protected paths and change budget from ralph-guard.toml
+    let change_guard = ChangeGuard::load_for_project(&working_dir)?;
+
     let config = RunnerConfig {
         prd_path: if prd.is_absolute() {
             prd
@@ -549,8 +790,9 @@ async fn run_stories(
         },
         working_dir: working_dir.clone(),
         max_iterations_per_story: max_iterations,
-        max_total_iterations: 0, // unlimited
-        agent_command: agent,    // auto-detect if None
+        run_limits,
+        agent_command: agent, // auto-detect if None
+        agent_registry,
         display_options,
         parallel,
         parallel_config: Some(parallel_config),
@@ -558,11 +800,26 @@ async fn run_stories(
         no_resume,
         timeout_seconds: timeout,
         no_checkpoint,
+        budget,
+        branch,
+        prompt_path: prompt,
+        retry_strategy: RetryStrategy::default(),
+        rollback_policy,
+        commit_policy,
+        change_guard,
+        split_stories,
+        max_usage_limit_wait: std::time::Duration::from_secs(max_limit_wait_minutes * 60),
     };
 
     let runner = Runner::new(config);
     let result = runner.run().await;
 
+    // Report model usage when the agent emitted any
+    let (tokens, cost) = runner.metrics().run_usage();
+    if tokens.total() > 0 && !cli.quiet {
+        println!("Model usage: {} tokens (~${:.2})", tokens.total(), cost);
+    }
+
     if result.all_passed {
         Ok(())
     } else {
@@ -803,6 +1060,9 @@ fn run_status(dir: Option<PathBuf>, quiet: bool) -> Result<ExitCode, Box<dyn std
                     println!("Current Story: {}", story.story_id);
                     println!("  Iteration: {}/{}", story.iteration, story.max_iterations);
                 }
+                if let Some(ref branch) = checkpoint.branch_name {
+                    println!("Branch: {}", branch);
+                }
 
                 // Display pause reason
                 let reason_str = match &checkpoint.pause_reason {
@@ -811,6 +1071,7 @@ fn run_status(dir: Option<PathBuf>, quiet: bool) -> Result<ExitCode, Box<dyn std
                     PauseReason::UserRequested => "User requested".to_string(),
                     PauseReason::Timeout => "Timeout".to_string(),
                     PauseReason::IterationBoundary => "Iteration boundary".to_string(),
+                    PauseReason::BudgetExceeded(msg) => format!("Budget exceeded: {}", msg),
                     PauseReason::Error(msg) => format!("Error: {}", msg),
                 };
                 println!("Pause Reason: {}", reason_str);
@@ -853,6 +1114,11 @@ fn run_status(dir: Option<PathBuf>, quiet: bool) -> Result<ExitCode, Box<dyn std
                     PauseReason::IterationBoundary => {
                         println!("  Run 'ralph run' to resume execution.");
                     }
+                    PauseReason::BudgetExceeded(_) => {
+                        println!(
+                            "  Raise --budget-usd or --max-tokens, then run 'ralph run' to resume."
+                        );
+                    }
                     PauseReason::Error(_) => {
                         println!(
                             "  Review the error above, fix any issues, then run 'ralph run' to resume."
@@ -891,6 +1157,59 @@ fn run_status(dir: Option<PathBuf>, quiet: bool) -> Result<ExitCode, Box<dyn std
     }
 }
 
+/// List a story's workspace snapshots, or restore the one before `iteration`
+fn run_rollback(
+    story: &str,
+    iteration: Option<u32>,
+    dir: Option<PathBuf>,
+    quiet: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let working_dir = dir.unwrap_or_else(|| std::env::current_dir().unwrap_or_default());
+
+    let Some(iteration) = iteration else {
+        let snapshots = WorkspaceSnapshot::list(&working_dir, story)?;
+        if snapshots.is_empty() {
+            println!("No snapshots found for story {}.", story);
+        } else {
+            println!("Snapshots for story {}:", story);
+            for snapshot in &snapshots {
+                println!(
+                    "  iteration {:>3}  {}",
+                    snapshot.iteration,
+                    &snapshot.commit[..12.min(snapshot.commit.len())]
+                );
+            }
+        }
+        return Ok(());
+    };
+
+    let snapshot = WorkspaceSnapshot::find(&working_dir, story, iteration)?.ok_or_else(|| {
+        format!(
+            "No snapshot for story {} iteration {}. Run 'ralph rollback --story {}' to list them.",
+            story, iteration, story
+        )
+    })?;
+    let restored = snapshot.restore(&working_dir)?;
+
+    if !quiet {
+        if restored.is_empty() {
+            println!(
+                "Workspace already matches story {} before iteration {}.",
+                story, iteration
+            );
+        } else {
+            println!(
+                "Restored the workspace to story {} before iteration {}:",
+                story, iteration
+            );
+            for path in &restored {
+                println!("  - {}", path);
+            }
+        }
+    }
+    Ok(())
+}
+
 /// Format a duration in a human-readable way
 fn format_duration(duration: chrono::Duration) -> String {
     let total_seconds = duration.num_seconds().unsigned_abs();
@@ -10,7 +10,9 @@
 use std::io::{self, Write};
 use std::time::{Duration, Instant};
 
-use crate::quality::{GateResult, Profile, QualityGateChecker};
+use crate::agent::AgentEvent;
+use crate::notification::Notification;
+use crate::quality::{GateProgressState, GateResult, Profile, QualityGateChecker};
 use crate::ui::{
     ActivityIndicator, GateProgress, GateSummary, IterationPreview, IterationSummary,
     IterationSummaryStack, LiveIterationPanel, Theme,
@@ -75,6 +77,13 @@ pub enum ExecutionEvent {
     },
     /// Gate progress within an iteration
     GateProgress(GateProgressEvent),
+    /// Structured activity reported by the agent (tool calls, edits, usage)
+    AgentActivity { iteration: u32, event: AgentEvent },
+    /// Recovery status, such as a retry countdown after a transient agent failure
+    Notification {
+        iteration: u32,
+        notification: Notification,
+    },
     /// Iteration completed
     IterationCompleted {
         iteration: u32,
@@ -199,6 +208,21 @@ impl IterationDisplay {
         }
     }
 
+    /// Show agent activity in the current iteration.
+    pub fn update_agent(&mut self, event: &AgentEvent) {
+        if let Some(ref mut panel) = self.current_panel {
+            let activity = match event {
+                AgentEvent::FileEdit { path, .. } => ActivityIndicator::new("Editing:", path),
+                AgentEvent::ShellCommand { command, .. } => {
+                    ActivityIndicator::new("Running:", command)
+                }
+                AgentEvent::ToolCall { name, .. } => ActivityIndicator::new("Tool:", name),
+                _ => return,
+            };
+            panel.set_activity(activity);
+        }
+    }
+
     /// Refresh the live display.
     pub fn refresh(&mut self) -> io::Result<()> {
         if let Some(ref mut panel) = self.current_panel {
@@ -291,25 +315,11 @@ impl StoryExecutor {
 
     /// Get the list of gate names that will be run.
     pub fn gate_names(&self) -> Vec<String> {
-        // Return the gates that would be checked based on profile
-        let mut gates = Vec::new();
-
-        if self.config.profile.testing.coverage_threshold > 0 {
-            gates.push("coverage".to_string());
-        }
-        if self.config.profile.ci.lint_check {
-            gates.push("lint".to_string());
-        }
-        if self.config.profile.ci.format_check {
-            gates.push("format".to_string());
-        }
-        if self.config.profile.security.cargo_audit {
-            gates.push("security_audit".to_string());
-        }
+        let gates = self.checker.enabled_gate_names();
 
         // If no gates are enabled, return a default set
         if gates.is_empty() {
-            gates = vec!["build".to_string(), "lint".to_string(), "test".to_string()];
+            return vec!["build".to_string(), "lint".to_string(), "test".to_string()];
         }
 
         gates
@@ -324,67 +334,33 @@ impl StoryExecutor {
 
     /// Run quality gates with progress callbacks.
     ///
-    /// Emits GateProgress events as each gate starts and completes.
+    /// Gates run as the profile's `[execution]` settings allow (see
+    /// [`QualityGateChecker::run_all_gates_with_progress`]). Emits
+    /// GateProgress events as each enabled gate starts and completes, and
+    /// returns the enabled gates' results.
     pub fn run_quality_gates(&mut self) -> Vec<GateResult> {
-        let mut results = Vec::new();
-
-        // Run coverage check
-        if self.config.profile.testing.coverage_threshold > 0 {
-            self.emit(ExecutionEvent::GateProgress(GateProgressEvent::started(
-                "coverage",
-            )));
-            let start = Instant::now();
-            let result = self.checker.check_coverage();
-            let duration = start.elapsed();
-            self.emit(ExecutionEvent::GateProgress(GateProgressEvent::completed(
-                &result, duration,
-            )));
-            results.push(result);
-        }
-
-        // Run lint check
-        if self.config.profile.ci.lint_check {
-            self.emit(ExecutionEvent::GateProgress(GateProgressEvent::started(
-                "lint",
-            )));
-            let start = Instant::now();
-            let result = self.checker.check_lint();
-            let duration = start.elapsed();
-            self.emit(ExecutionEvent::GateProgress(GateProgressEvent::completed(
-                &result, duration,
-            )));
-            results.push(result);
-        }
-
-        // Run format check
-        if self.config.profile.ci.format_check {
-            self.emit(ExecutionEvent::GateProgress(GateProgressEvent::started(
-                "format",
-            )));
-            let start = Instant::now();
-            let result = self.checker.check_format();
-            let duration = start.elapsed();
-            self.emit(ExecutionEvent::GateProgress(GateProgressEvent::completed(
-                &result, duration,
-            )));
-            results.push(result);
-        }
-
-        // Run security audit
-        if self.config.profile.security.cargo_audit {
-            self.emit(ExecutionEvent::GateProgress(GateProgressEvent::started(
-                "security_audit",
-            )));
-            let start = Instant::now();
-            let result = self.checker.check_security_audit();
-            let duration = start.elapsed();
-            self.emit(ExecutionEvent::GateProgress(GateProgressEvent::completed(
-                &result, duration,
-            )));
-            results.push(result);
-        }
+        let enabled = self.checker.enabled_gate_names();
+        let on_event = &mut self.on_event;
+        let results = self.checker.run_all_gates_with_progress(|update| {
+            if !enabled.contains(&update.gate_name) {
+                return;
+            }
+            let event = match (update.state, update.result) {
+                (GateProgressState::Running, _) => GateProgressEvent::started(update.gate_name),
+                (_, Some(ref result)) => {
+                    GateProgressEvent::completed(result, update.duration.unwrap_or_default())
+                }
+                (_, None) => return,
+            };
+            if let Some(ref mut callback) = on_event {
+                callback(ExecutionEvent::GateProgress(event));
+            }
+        });
 
         results
+            .into_iter()
+            .filter(|result| enabled.contains(&result.gate_name))
+            .collect()
     }
 
     /// Run a single iteration of the story execution.
@@ -439,6 +415,19 @@ impl StoryExecutor {
         let total_start = Instant::now();
         let gates = self.gate_names();
 
+        // Findings present before the first iteration are not the story's doing
+        match self.checker.capture_audit_baseline() {
+            Ok(Some(baseline)) => {
+                let checker = QualityGateChecker::new(
+                    self.config.profile.clone(),
+                    self.checker.project_root().clone(),
+                );
+                self.checker = checker.with_audit_baseline(baseline);
+            }
+            Ok(None) => {}
+            Err(e) => eprintln!("Warning: Failed to capture audit baseline: {}", e),
+        }
+
         // Create display for UI feedback
         let mut display = IterationDisplay::with_theme(self.config.theme);
 
@@ -505,7 +494,7 @@ mod tests {
             description: "Test profile".to_string(),
             testing: TestingConfig {
                 coverage_threshold: 0,
-                unit_tests: true,
+                unit_tests: false,
                 integration_tests: false,
             },
             ci: CiConfig {
@@ -642,6 +631,12 @@ mod tests {
                 ExecutionEvent::GateProgress(_) => {
                     events.push("gate_progress".to_string());
                 }
+                ExecutionEvent::AgentActivity { .. } => {
+                    events.push("agent_activity".to_string());
+                }
+                ExecutionEvent::Notification { .. } => {
+                    events.push("notification".to_string());
+                }
                 ExecutionEvent::ExecutionFinished { .. } => {
                     events.push("finished".to_string());
                 }
@@ -701,6 +696,50 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_run_quality_gates_follows_gate_dependencies() {
+        let mut profile = create_minimal_profile();
+        profile.gates = vec![
+            toml::from_str(
+                "name = \"codegen\"\ncommand = \"test -f schema.json\"\ndepends_on = [\"schema\"]\n",
+            )
+            .unwrap(),
+            toml::from_str("name = \"schema\"\ncommand = \"touch schema.json\"\n").unwrap(),
+        ];
+        let dir = tempfile::TempDir::new().unwrap();
+
+        let events: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(Vec::new()));
+        let events_clone = events.clone();
+        let mut executor = StoryExecutor::new(create_config_with_profile(profile), dir.path())
+            .on_gate_progress(Box::new(move |event| {
+                let event = match event {
+                    GateProgressEvent::Started { gate_name } => format!("start {}", gate_name),
+                    GateProgressEvent::Completed {
+                        gate_name, passed, ..
+                    } => format!("{} {}", if passed { "pass" } else { "fail" }, gate_name),
+                    GateProgressEvent::Activity { .. } => return,
+                };
+                events_clone.lock().unwrap().push(event);
+            }));
+
+        assert_eq!(executor.gate_names(), vec!["codegen", "schema"]);
+        let results = executor.run_quality_gates();
+
+        // Disabled built-in gates are neither reported nor returned
+        let names: Vec<&str> = results.iter().map(|r| r.gate_name.as_str()).collect();
+        assert_eq!(names, vec!["codegen", "schema"]);
+        assert!(results.iter().all(|r| r.passed), "{:?}", results);
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![
+                "start schema",
+                "pass schema",
+                "start codegen",
+                "pass codegen"
+            ]
+        );
+    }
+
     #[test]
     fn test_story_executor_run_iteration() {
         let profile = create_minimal_profile();
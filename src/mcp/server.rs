@@ -3,8 +3,11 @@
 
 #![allow(dead_code)]
 
+use crate::agent::AgentRegistry;
 use crate::audit::prd_converter::{PrdConverter, PrdConverterConfig};
 use crate::audit::prd_generator::{PrdGenerator, PrdGeneratorConfig};
+use crate::git::CommitPolicy;
+use crate::iteration::ChangeGuard;
 use crate::mcp::resources::{
     list_ralph_resources, read_prd_resource, read_status_resource, ResourceError, PRD_RESOURCE_URI,
     STATUS_RESOURCE_URI,
@@ -19,7 +22,7 @@ use crate::mcp::tools::audit::{
     GetAuditResultsError, GetAuditResultsRequest, GetAuditStatusError, GetAuditStatusRequest,
     StartAuditError, StartAuditRequest,
 };
-use crate::mcp::tools::executor::{detect_agent, ExecutorConfig, StoryExecutor};
+use crate::mcp::tools::executor::{ExecutorConfig, StoryExecutor};
 use crate::mcp::tools::get_status::{GetStatusRequest, GetStatusResponse};
 use crate::mcp::tools::list_stories::{load_stories, ListStoriesRequest, ListStoriesResponse};
 use crate::mcp::tools::load_prd::{
@@ -665,11 +668,65 @@ impl RalphMcpServer {
             };
         }
 
+        // Get project root from PRD path
+        let project_root = prd_path
+            .parent()
+            .map(|p| p.to_path_buf())
+            .unwrap_or_else(|| std::env::current_dir().unwrap_or_default());
+
+        // Load agent backends, including any defined in ralph-agents.toml
+        let agent_registry = match AgentRegistry::load_for_project(&project_root) {
+            Ok(registry) => registry,
+            Err(e) => {
+                {
+                    let mut state = self.state.write().await;
+                    state.execution_state = ExecutionState::Idle;
+                }
+                let response =
+                    create_run_error_response(&RunStoryError::ExecutionError(e.to_string()));
+                return serde_json::to_string_pretty(&response).unwrap_or_else(|e| {
+                    format!("{{\"error\": \"Failed to serialize response: {}\"}}", e)
+                });
+            }
+        };
+
+        // Commit the story as configured in ralph-commit.toml
+        let commit_policy = match CommitPolicy::load_for_project(&project_root) {
+            Ok(policy) => policy,
+            Err(e) => {
+                {
+                    let mut state = self.state.write().await;
+                    state.execution_state = ExecutionState::Idle;
+                }
+                let response =
+                    create_run_error_response(&RunStoryError::ExecutionError(e.to_string()));
+                return serde_json::to_string_pretty(&response).unwrap_or_else(|e| {
+                    format!("{{\"error\": \"Failed to serialize response: {}\"}}", e)
+                });
+            }
+        };
+
+        // Hold the agent to ralph-guard.toml, if the project has one
+        let change_guard = match ChangeGuard::load_for_project(&project_root) {
+            Ok(guard) => guard,
+            Err(e) => {
+                {
+                    let mut state = self.state.write().await;
+                    state.execution_state = ExecutionState::Idle;
+                }
+                let response =
+                    create_run_error_response(&RunStoryError::ExecutionError(e.to_string()));
+                return serde_json::to_string_pretty(&response).unwrap_or_else(|e| {
+                    format!("{{\"error\": \"Failed to serialize response: {}\"}}", e)
+                });
+            }
+        };
+
         // Detect available agent (use test override if available)
         #[cfg(test)]
-        let detected_agent = self.test_agent_override.clone().or_else(detect_agent);
+        let detected_agent = agent_registry.resolve_or_detect(self.test_agent_override.as_deref());
         #[cfg(not(test))]
-        let detected_agent = detect_agent();
+        let detected_agent = agent_registry.detect();
 
         let agent_command = match detected_agent {
             Some(agent) => agent,
@@ -689,12 +746,6 @@ impl RalphMcpServer {
             }
         };
 
-        // Get project root from PRD path
-        let project_root = prd_path
-            .parent()
-            .map(|p| p.to_path_buf())
-            .unwrap_or_else(|| std::env::current_dir().unwrap_or_default());
-
         // Progress file path (in same directory as PRD)
         let progress_path = project_root.join("progress.txt");
 
@@ -709,9 +760,12 @@ impl RalphMcpServer {
                 .as_ref()
                 .map(|c| c.profiles.get("standard").cloned().unwrap_or_default()),
             agent_command,
+            agent_registry,
             max_iterations,
             git_mutex: None, // MCP server executes single story at a time
             timeout_config: crate::timeout::TimeoutConfig::default(),
+            commit_policy,
+            change_guard,
             ..Default::default()
         };
 
@@ -1,6 +1,6 @@
 // Story execution engine for Ralph
 // This module handles the actual execution of user stories including:
-// - Spawning Claude Code or Amp CLI to implement stories
+// - Spawning the configured agent CLI (via the agent registry) to implement stories
 // - Running quality gates after implementation
 // - Updating PRD files on success
 // - Appending to progress.txt
@@ -8,25 +8,46 @@
 
 #![allow(dead_code)]
 
+use std::io::IsTerminal;
 use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
 use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::io::{AsyncBufReadExt, BufReader};
-use tokio::sync::{watch, Mutex};
+use tokio::sync::{mpsc, watch, Mutex};
 
+use crate::agent::{
+    AgentBackend, AgentEvent, AgentOutput, AgentRegistry, AgentReport, AgentRequest, ReportStatus,
+};
 use crate::checkpoint::{Checkpoint, CheckpointManager, PauseReason, StoryCheckpoint};
-use crate::error::classification::{ErrorCategory, TimeoutReason};
+use crate::error::classification::{ErrorCategory, RecoveryHint, TimeoutReason, UsageLimitReason};
+use crate::git::{
+    commit::{has_staged_changes, squash_since, stage},
+    is_git_repository, resolve_commit, CommitPolicy, GitError, StageMode, StoryCommit,
+    WorkspaceSnapshot,
+};
 use crate::iteration::{
     context::{ErrorCategory as IterErrorCategory, IterationContext, IterationError},
     futility::{FutileRetryDetector, FutilityConfig, FutilityVerdict},
+    guard::{ChangeGuard, GuardSeverity, GuardViolation},
+    rollback::{RollbackPolicy, WorkspaceRollback},
+    split::{apply_split, parse_sub_stories, split_prompt, validate_split},
 };
-use crate::metrics::MetricsCollector;
+use crate::metrics::{AgentSwitch, MetricsCollector, RunLimits, UsageBudget};
+use crate::notification::Notification;
+use crate::pause::RetryStrategy;
+use crate::prompt::{extract_learnings, PromptContext, PromptTemplate, LEARNINGS_MARKER};
 use crate::timeout::{HeartbeatEvent, HeartbeatMonitor, TimeoutConfig};
 
+use crate::mcp::executor::ExecutionEvent;
 use crate::mcp::tools::load_prd::{PrdFile, PrdUserStory};
-use crate::quality::{GateResult, Profile, QualityGateChecker};
+use crate::quality::{
+    AuditBaseline, GateResult, Profile, ProjectLanguage, QualityConfig, QualityGateChecker,
+};
+
+/// How long to keep reading agent output after the process exits
+const OUTPUT_DRAIN_TIMEOUT: Duration = Duration::from_secs(2);
 
 /// Result of story execution
 #[derive(Debug)]
@@ -49,6 +70,19 @@ pub struct ExecutionResult {
     pub iteration_context: Option<IterationContext>,
     /// Whether user guidance is needed to continue
     pub needs_guidance: bool,
+    /// Sub-stories that replaced the story in the PRD, if it was split
+    pub split_into: Vec<String>,
+}
+
+/// Output of a single successful agent run
+#[derive(Debug, Clone)]
+struct AgentRun {
+    /// Files changed in the working tree after the run
+    files_changed: Vec<String>,
+    /// Typed events parsed from the agent's output
+    events: Vec<AgentEvent>,
+    /// Parsed final output of the agent
+    output: AgentOutput,
 }
 
 /// Error types for story execution
@@ -62,14 +96,22 @@ pub enum ExecutorError {
     GitError(String),
     /// Quality gates failed
     QualityGateFailed(String),
-    /// Agent execution failed
-    AgentError(String),
+    /// Agent execution failed, with the agent's exit code if it exited
+    AgentError(String, Option<i32>),
     /// Execution was cancelled
     Cancelled,
     /// IO error
     IoError(String),
     /// Execution timed out
     Timeout(String),
+    /// Run-level token or cost budget was exceeded before the given iteration
+    BudgetExceeded(u32, String),
+    /// Run-level iteration or time limit was reached before the given iteration
+    RunLimitReached(u32, String),
+    /// Prompt template could not be loaded or parsed
+    PromptError(String),
+    /// The agent hit a rate or usage limit it could not wait out
+    UsageLimit(UsageLimitReason, String),
 }
 
 impl std::fmt::Display for ExecutorError {
@@ -79,10 +121,14 @@ impl std::fmt::Display for ExecutorError {
             ExecutorError::PrdError(msg) => write!(f, "PRD error: {}", msg),
             ExecutorError::GitError(msg) => write!(f, "Git error: {}", msg),
             ExecutorError::QualityGateFailed(msg) => write!(f, "Quality gate failed: {}", msg),
-            ExecutorError::AgentError(msg) => write!(f, "Agent execution error: {}", msg),
+            ExecutorError::AgentError(msg, _) => write!(f, "Agent execution error: {}", msg),
             ExecutorError::Cancelled => write!(f, "Execution was cancelled"),
             ExecutorError::IoError(msg) => write!(f, "IO error: {}", msg),
             ExecutorError::Timeout(msg) => write!(f, "Execution timed out: {}", msg),
+            ExecutorError::BudgetExceeded(_, msg) => write!(f, "Budget exceeded: {}", msg),
+            ExecutorError::RunLimitReached(_, msg) => write!(f, "Run limit reached: {}", msg),
+            ExecutorError::PromptError(msg) => write!(f, "Prompt template error: {}", msg),
+            ExecutorError::UsageLimit(_, msg) => write!(f, "Agent usage limit: {}", msg),
         }
     }
 }
@@ -92,7 +138,7 @@ impl std::error::Error for ExecutorError {}
 impl ExecutorError {
     /// Classify this error into an ErrorCategory for recovery decisions.
     pub fn classify(&self) -> ErrorCategory {
-        use crate::error::classification::{FatalReason, TransientReason};
+        use crate::error::classification::{FatalReason, TransientReason, UsageLimitReason};
 
         match self {
             ExecutorError::Timeout(_) => ErrorCategory::Timeout(TimeoutReason::ProcessTimeout),
@@ -101,8 +147,13 @@ impl ExecutorError {
             ExecutorError::PrdError(_) => ErrorCategory::Fatal(FatalReason::ConfigurationError),
             ExecutorError::GitError(_) => ErrorCategory::Transient(TransientReason::ResourceLocked),
             ExecutorError::QualityGateFailed(_) => ErrorCategory::Fatal(FatalReason::InternalError),
-            ExecutorError::AgentError(_) => ErrorCategory::Transient(TransientReason::ServerError),
+            ExecutorError::AgentError(..) => ErrorCategory::Transient(TransientReason::ServerError),
             ExecutorError::IoError(_) => ErrorCategory::Transient(TransientReason::NetworkError),
+            ExecutorError::BudgetExceeded(..) | ExecutorError::RunLimitReached(..) => {
+                ErrorCategory::UsageLimit(UsageLimitReason::QuotaExhausted)
+            }
+            ExecutorError::PromptError(_) => ErrorCategory::Fatal(FatalReason::ConfigurationError),
+            ExecutorError::UsageLimit(reason, _) => ErrorCategory::UsageLimit(reason.clone()),
         }
     }
 }
@@ -120,6 +171,8 @@ pub struct ExecutorConfig {
     pub quality_profile: Option<Profile>,
     /// Agent command to use (e.g., "claude" or "amp")
     pub agent_command: String,
+    /// Model to request from the agent (None = the agent's default)
+    pub model: Option<String>,
     /// Maximum iterations per story
     pub max_iterations: u32,
     /// Optional mutex for serializing git operations across parallel executions
@@ -132,6 +185,26 @@ pub struct ExecutorConfig {
     pub futility_config: FutilityConfig,
     /// Optional metrics collector for tracking execution statistics
     pub metrics_collector: Option<MetricsCollector>,
+    /// Run-level token/cost caps, checked against the metrics collector before each iteration
+    pub budget: UsageBudget,
+    /// Run-level iteration and time limits, checked before each iteration
+    pub run_limits: RunLimits,
+    /// Registry used to resolve `agent_command` to an agent backend
+    pub agent_registry: AgentRegistry,
+    /// Optional channel for streaming execution events (e.g., agent activity) to the UI
+    pub event_sender: Option<mpsc::Sender<ExecutionEvent>>,
+    /// Prompt template file (None = `.ralph/prompt.md` or the built-in template)
+    pub prompt_path: Option<PathBuf>,
+    /// Backoff for retrying transient agent failures within an iteration
+    pub retry_strategy: RetryStrategy,
+    /// When to restore an earlier workspace snapshot after the gates regress
+    pub rollback_policy: RollbackPolicy,
+    /// How passing stories (and failed iterations, in WIP mode) are committed
+    pub commit_policy: CommitPolicy,
+    /// Paths and diff size the agent's changes must stay within (None = unguarded)
+    pub change_guard: Option<ChangeGuard>,
+    /// Ask the agent to split stories the futility detector gives up on
+    pub split_stories: bool,
 }
 
 impl Default for ExecutorConfig {
@@ -142,13 +215,65 @@ impl Default for ExecutorConfig {
             progress_path: PathBuf::from("progress.txt"),
             quality_profile: None,
             agent_command: "claude".to_string(),
+            model: None,
             max_iterations: 10,
             git_mutex: None,
             timeout_config: TimeoutConfig::default(),
             enable_futility_detection: true,
             futility_config: FutilityConfig::default(),
             metrics_collector: None,
+            budget: UsageBudget::unlimited(),
+            run_limits: RunLimits::unlimited(),
+            agent_registry: AgentRegistry::with_builtins(),
+            event_sender: None,
+            prompt_path: None,
+            retry_strategy: RetryStrategy::default(),
+            rollback_policy: RollbackPolicy::default(),
+            commit_policy: CommitPolicy::default(),
+            change_guard: None,
+            split_stories: false,
+        }
+    }
+}
+
+impl ExecutorConfig {
+    /// Apply a story's per-story overrides from the PRD.
+    ///
+    /// The story's `agent`, `model`, `maxIterations`, `timeoutSeconds` and
+    /// `qualityProfile` replace the run-wide settings. Quality profiles are
+    /// looked up in the project's `quality/ralph-quality.toml`, falling back
+    /// to the built-in profiles.
+    pub fn with_story_overrides(mut self, story: &PrdUserStory) -> Result<Self, ExecutorError> {
+        if let Some(ref agent) = story.agent {
+            self.agent_command = agent.clone();
         }
+        if let Some(ref model) = story.model {
+            self.model = Some(model.clone());
+        }
+        if let Some(max_iterations) = story.max_iterations {
+            self.max_iterations = max_iterations;
+        }
+        if let Some(seconds) = story.timeout_seconds {
+            self.timeout_config = self
+                .timeout_config
+                .with_agent_timeout(Duration::from_secs(seconds));
+        }
+        if let Some(ref name) = story.quality_profile {
+            let quality = QualityConfig::load_for_project(&self.project_root)
+                .map_err(|e| ExecutorError::PrdError(e.to_string()))?;
+            let profile = quality.get_profile_by_name(name).ok_or_else(|| {
+                let mut available = quality.profile_names();
+                available.sort();
+                ExecutorError::PrdError(format!(
+                    "Story {} uses unknown quality profile '{}' (available: {})",
+                    story.id,
+                    name,
+                    available.join(", ")
+                ))
+            })?;
+            self.quality_profile = Some(profile.clone());
+        }
+        Ok(self)
     }
 }
 
@@ -156,6 +281,8 @@ impl Default for ExecutorConfig {
 pub struct StoryExecutor {
     config: ExecutorConfig,
     checkpoint_manager: Option<CheckpointManager>,
+    /// Switches to fallback agents made so far, in order
+    agent_switches: std::sync::Mutex<Vec<AgentSwitch>>,
 }
 
 impl StoryExecutor {
@@ -166,6 +293,7 @@ impl StoryExecutor {
         Self {
             config,
             checkpoint_manager,
+            agent_switches: std::sync::Mutex::new(Vec::new()),
         }
     }
 
@@ -177,9 +305,142 @@ impl StoryExecutor {
         Self {
             config,
             checkpoint_manager,
+            agent_switches: std::sync::Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Agent currently running the story.
+    ///
+    /// This is `agent_command` until a usage limit switches to a fallback agent.
+    pub fn active_agent(&self) -> String {
+        self.agent_switches()
+            .last()
+            .map(|switch| switch.to.clone())
+            .unwrap_or_else(|| self.config.agent_command.clone())
+    }
+
+    /// Switches to fallback agents made so far, in order
+    pub fn agent_switches(&self) -> Vec<AgentSwitch> {
+        self.agent_switches
+            .lock()
+            .map(|switches| switches.clone())
+            .unwrap_or_default()
+    }
+
+    /// Next fallback agent that has not been used yet, if any is available
+    fn next_fallback_agent(&self) -> Option<String> {
+        let mut tried = vec![self.config.agent_command.clone()];
+        tried.extend(self.agent_switches().into_iter().map(|switch| switch.to));
+        self.config.agent_registry.next_fallback(&tried)
+    }
+
+    /// Switch the remaining iterations to the next fallback agent.
+    ///
+    /// The switch is recorded in the metrics and in a checkpoint so a resumed
+    /// run knows which agent was active. Returns None when no fallback is left.
+    fn switch_to_fallback(
+        &self,
+        story_id: &str,
+        iteration: u32,
+        reason: &str,
+    ) -> Option<AgentSwitch> {
+        let to = self.next_fallback_agent()?;
+        let switch = AgentSwitch {
+            iteration,
+            from: self.active_agent(),
+            to,
+            reason: reason.to_string(),
+        };
+        if let Ok(mut switches) = self.agent_switches.lock() {
+            switches.push(switch.clone());
+        }
+
+        if let Some(ref collector) = self.config.metrics_collector {
+            collector.record_agent_switch(switch.clone());
+        }
+        self.save_pause_checkpoint(story_id, iteration, PauseReason::IterationBoundary);
+
+        self.notify(
+            iteration,
+            Notification::resuming(format!(
+                "story {} with fallback agent {} ({} {})",
+                story_id, switch.to, switch.from, switch.reason
+            )),
+        );
+
+        Some(switch)
+    }
+
+    /// Publish a notification to the UI, or print it when there is none.
+    fn notify(&self, iteration: u32, notification: Notification) {
+        match self.config.event_sender {
+            Some(ref sender) => {
+                let _ = sender.try_send(ExecutionEvent::Notification {
+                    iteration,
+                    notification,
+                });
+            }
+            None => eprintln!("{}", notification),
         }
     }
 
+    /// Snapshot the workspace before an iteration so it can be rolled back to.
+    fn snapshot_workspace(&self, story_id: &str, iteration: u32) {
+        if let Err(e) = WorkspaceSnapshot::create(&self.config.project_root, story_id, iteration) {
+            eprintln!(
+                "Warning: Failed to snapshot workspace for story '{}': {}",
+                story_id, e
+            );
+        }
+    }
+
+    /// Restore the workspace `restored_to` left, discarding `iteration`'s changes.
+    ///
+    /// The workspace an iteration left is the snapshot the next one started
+    /// from. Returns None if that snapshot is missing or cannot be restored.
+    fn rollback_workspace(
+        &self,
+        story_id: &str,
+        iteration: u32,
+        restored_to: u32,
+        failed_gates: usize,
+        context: &IterationContext,
+    ) -> Option<WorkspaceRollback> {
+        let root = &self.config.project_root;
+        let restored = WorkspaceSnapshot::find(root, story_id, restored_to + 1)
+            .and_then(|snapshot| snapshot.map(|s| s.restore(root)).transpose());
+        match restored {
+            Ok(Some(_)) => {}
+            Ok(None) => return None,
+            Err(e) => {
+                eprintln!(
+                    "Warning: Failed to roll back workspace for story '{}': {}",
+                    story_id, e
+                );
+                return None;
+            }
+        }
+
+        let rollback = WorkspaceRollback {
+            iteration,
+            restored_to,
+            failed_gates,
+            restored_failed_gates: context
+                .gate_failures
+                .get(&restored_to)
+                .copied()
+                .unwrap_or_default(),
+        };
+        self.notify(
+            iteration,
+            Notification::resuming(format!(
+                "story {} from the workspace iteration {} left ({} made more gates fail)",
+                story_id, restored_to, iteration
+            )),
+        );
+        Some(rollback)
+    }
+
     /// Continue execution of a story with user-provided steering guidance.
     ///
     /// This method resumes execution from a previous iteration context,
@@ -266,12 +527,17 @@ impl StoryExecutor {
         // Load the PRD and find the story
         let prd = self.load_prd()?;
         let story = self.find_story(&prd, story_id)?;
+        let template = self.load_prompt_template()?;
 
         // Update iteration context (may already be initialized if resuming)
         if iter_context.max_iterations == 0 {
             iter_context.max_iterations = self.config.max_iterations;
         }
 
+        // Iterations restart at 1, so earlier gate results no longer match the snapshots
+        iter_context.gate_failures.clear();
+        let snapshots_enabled = is_git_repository(&self.config.project_root);
+
         // Initialize futility detector if enabled
         let futility_detector = if self.config.enable_futility_detection {
             Some(FutileRetryDetector::with_config(
@@ -291,9 +557,28 @@ impl StoryExecutor {
         let mut last_error: Option<String> = None;
         let mut files_changed: Vec<String> = Vec::new();
         let mut last_gate_results: Vec<GateResult> = Vec::new();
+        // HEAD before the first WIP commit, squashed back to when the story passes
+        let mut wip_base: Option<String> = None;
+        let audit_baseline = self.capture_audit_baseline(story_id);
 
         // Iteration loop
         for iteration in 1..=self.config.max_iterations {
+            // End the run between iterations once a run limit is reached
+            if let Some(reached) = self.check_run_limits() {
+                self.save_pause_checkpoint(story_id, iteration, PauseReason::IterationBoundary);
+                return Err(ExecutorError::RunLimitReached(iteration, reached));
+            }
+
+            // Stop before spending more if the run budget is used up
+            if let Some(exceeded) = self.check_budget() {
+                self.save_pause_checkpoint(
+                    story_id,
+                    iteration,
+                    PauseReason::BudgetExceeded(exceeded.clone()),
+                );
+                return Err(ExecutorError::BudgetExceeded(iteration, exceeded));
+            }
+
             iterations_used = iteration;
             iter_context.start_iteration(iteration);
             on_iteration(iteration, self.config.max_iterations);
@@ -308,17 +593,47 @@ impl StoryExecutor {
                 return Err(ExecutorError::Cancelled);
             }
 
-            // Build the prompt with iteration context if we have previous errors
-            let prompt = if iter_context.error_history.is_empty() {
-                self.build_agent_prompt(story, &prd)
-            } else {
-                self.build_agent_prompt_with_context(story, &prd, &iter_context)
-            };
+            if self.config.commit_policy.wip_commits && iteration > 1 {
+                self.commit_wip(story, iteration - 1, &mut wip_base).await;
+            }
+            if snapshots_enabled {
+                self.snapshot_workspace(story_id, iteration);
+            }
 
-            // Run the agent
-            match self.run_agent(&prompt, iteration).await {
-                Ok(changed) => {
-                    files_changed = changed;
+            // Build the prompt, including iteration context if we have previous errors
+            let prompt = self.build_agent_prompt(&template, story, &prd, &iter_context);
+
+            // Run the agent, keeping its status report and learnings. A usage
+            // limit or missing agent moves the remaining iterations to the next
+            // fallback agent, retrying this iteration with it.
+            let run = loop {
+                let run = self
+                    .run_agent_with_retry(&prompt, story_id, iteration, &cancel_receiver)
+                    .await;
+                let reason = match run {
+                    Err(ExecutorError::UsageLimit(ref reason, _)) => match reason {
+                        UsageLimitReason::RateLimited => "was rate limited",
+                        UsageLimitReason::QuotaExhausted => "exhausted its quota",
+                        UsageLimitReason::TokenLimitExceeded => "exceeded its token limit",
+                        UsageLimitReason::ConcurrencyLimit => "hit its concurrency limit",
+                    },
+                    Err(ExecutorError::AgentError(..)) if !self.active_backend().is_available() => {
+                        "is not installed"
+                    }
+                    _ => break run,
+                };
+                if self
+                    .switch_to_fallback(story_id, iteration, reason)
+                    .is_none()
+                {
+                    break run;
+                }
+            };
+            let (report, learnings) = match run {
+                Ok(run) => {
+                    files_changed = run.files_changed;
+                    let learnings = Self::agent_learnings(&run.output);
+                    (run.output.report, learnings)
                 }
                 Err(ExecutorError::Timeout(msg)) => {
                     // Record timeout error in context
@@ -337,6 +652,10 @@ impl StoryExecutor {
                     self.save_timeout_checkpoint(story_id, iteration);
                     return Err(ExecutorError::Timeout(msg));
                 }
+                Err(e @ (ExecutorError::UsageLimit(..) | ExecutorError::Cancelled)) => {
+                    // The checkpoint was saved before the wait; pause the run
+                    return Err(e);
+                }
                 Err(e) => {
                     let error_msg = e.to_string();
                     let category = IterErrorCategory::from_error_message(&error_msg, None);
@@ -355,9 +674,19 @@ impl StoryExecutor {
                     if let Some(ref detector) = futility_detector {
                         let verdict = detector.analyze(&iter_context);
                         if !verdict.should_continue() {
+                            let split_into = self
+                                .split_story(
+                                    story,
+                                    &verdict,
+                                    &iter_context,
+                                    iteration + 1,
+                                    &cancel_receiver,
+                                )
+                                .await;
+
                             // Check if this is a pause for guidance scenario
-                            let needs_guidance =
-                                matches!(verdict, FutilityVerdict::PauseForGuidance { .. });
+                            let needs_guidance = split_into.is_empty()
+                                && matches!(verdict, FutilityVerdict::PauseForGuidance { .. });
 
                             // Record metrics completion (only if not pausing for guidance)
                             if !needs_guidance {
@@ -380,14 +709,121 @@ impl StoryExecutor {
                                 futility_verdict: Some(verdict.clone()),
                                 iteration_context: Some(iter_context),
                                 needs_guidance,
+                                split_into,
                             });
                         }
                     }
 
                     continue; // Try next iteration
                 }
+            };
+
+            // Hold the agent's changes to the project's change guard
+            if let Some(ref guard) = self.config.change_guard {
+                let violations = self.check_changes(guard, story);
+                for violation in &violations {
+                    iter_context.record_error(IterationError::new(
+                        iteration,
+                        IterErrorCategory::ChangePolicy,
+                        format!("Change policy violation: {}", violation),
+                    ));
+                    if let Some(ref collector) = self.config.metrics_collector {
+                        collector.record_error(IterErrorCategory::ChangePolicy);
+                    }
+                }
+
+                let fatal: Vec<String> = violations
+                    .iter()
+                    .filter(|violation| guard.severity(violation) == GuardSeverity::Fail)
+                    .map(ToString::to_string)
+                    .collect();
+                if !fatal.is_empty() {
+                    let message = format!("Change policy violated: {}", fatal.join("; "));
+                    if let Some(ref collector) = self.config.metrics_collector {
+                        collector.complete_story(
+                            false,
+                            execution_start.elapsed(),
+                            Some(message.clone()),
+                        );
+                    }
+                    return Ok(ExecutionResult {
+                        success: false,
+                        commit_hash: None,
+                        error: Some(message),
+                        iterations_used,
+                        gate_results: last_gate_results,
+                        files_changed,
+                        futility_verdict: None,
+                        iteration_context: Some(iter_context),
+                        needs_guidance: false,
+                        split_into: Vec::new(),
+                    });
+                }
+                if !violations.is_empty() {
+                    // Changes that break the guard must not be committed, so skip the gates
+                    let summary: Vec<String> = violations.iter().map(ToString::to_string).collect();
+                    last_error = Some(format!("Change policy violated: {}", summary.join("; ")));
+                    continue;
+                }
             }
 
+            // Act on the agent's own status report before running the gates
+            match report.as_ref().and_then(|r| r.status.as_ref()) {
+                Some(ReportStatus::Blocked { reason }) => {
+                    let message = format!("Agent reported a blocker: {}", reason);
+                    iter_context.record_error(IterationError::new(
+                        iteration,
+                        IterErrorCategory::AgentExecution,
+                        &message,
+                    ));
+                    if let Some(ref collector) = self.config.metrics_collector {
+                        collector.record_error(IterErrorCategory::AgentExecution);
+                    }
+
+                    // Pause for guidance with the agent's stated blocker
+                    return Ok(ExecutionResult {
+                        success: false,
+                        commit_hash: None,
+                        error: Some(message.clone()),
+                        iterations_used,
+                        gate_results: last_gate_results,
+                        files_changed,
+                        futility_verdict: Some(FutilityVerdict::PauseForGuidance {
+                            reason: message,
+                            suggestions: vec![
+                                "Resolve the blocker or provide guidance, then resume the story"
+                                    .to_string(),
+                            ],
+                        }),
+                        iteration_context: Some(iter_context),
+                        needs_guidance: true,
+                        split_into: Vec::new(),
+                    });
+                }
+                Some(ReportStatus::Partial { remaining }) => {
+                    // Unfinished work must not be committed, so skip the gates
+                    let message = if remaining.is_empty() {
+                        "Agent reported partial progress".to_string()
+                    } else {
+                        format!(
+                            "Agent reported partial progress; remaining: {}",
+                            remaining.join("; ")
+                        )
+                    };
+                    iter_context.record_error(
+                        IterationError::new(iteration, IterErrorCategory::Other, &message)
+                            .with_files(files_changed.clone()),
+                    );
+                    if let Some(ref collector) = self.config.metrics_collector {
+                        collector.record_error(IterErrorCategory::Other);
+                    }
+                    last_error = Some(message);
+                    continue;
+                }
+                Some(ReportStatus::Complete) | None => {}
+            }
+            let claimed_complete = report.as_ref().is_some_and(AgentReport::is_complete);
+
             // Check for cancellation before quality gates
             if cancel_receiver.has_changed().unwrap_or(false) && *cancel_receiver.borrow() {
                 return Err(ExecutorError::Cancelled);
@@ -395,7 +831,8 @@ impl StoryExecutor {
 
             // Run quality gates with timing
             let gate_start = std::time::Instant::now();
-            let gate_results = self.run_quality_gates();
+            let gate_results =
+                self.run_quality_gates(story, audit_baseline.as_ref(), wip_base.as_deref());
             let gate_duration = gate_start.elapsed();
 
             // Record gate durations in metrics
@@ -413,9 +850,11 @@ impl StoryExecutor {
 
             if all_passed {
                 // Success! Create commit and update PRD
-                let commit_hash = self.create_commit(story).await?;
+                let commit_hash = self
+                    .create_commit(story, iteration, wip_base.as_deref())
+                    .await?;
                 self.update_prd_passes(story_id)?;
-                self.append_progress(story, &files_changed, iteration)?;
+                self.append_progress(story, &files_changed, iteration, &learnings)?;
 
                 // Record successful completion in metrics
                 if let Some(ref collector) = self.config.metrics_collector {
@@ -432,27 +871,34 @@ impl StoryExecutor {
                     futility_verdict: None,
                     iteration_context: Some(iter_context),
                     needs_guidance: false,
+                    split_into: Vec::new(),
                 });
             }
 
             // Quality gates failed, record in iteration context
             let failed_gates: Vec<&str> = gate_results
                 .iter()
-                .filter(|g| !g.passed)
+                .filter(|g| g.blocks())
                 .map(|g| g.gate_name.as_str())
                 .collect();
 
-            // Record each failed gate as an error
-            for gate_name in &failed_gates {
-                let category = IterErrorCategory::from_error_message("", Some(gate_name));
+            // Record each failed gate as an error, flagging a false completion claim
+            let claim_note = if claimed_complete {
+                " although the agent reported the story complete"
+            } else {
+                ""
+            };
+            for gate in gate_results.iter().filter(|g| g.blocks()) {
+                let category = IterErrorCategory::from_error_message("", Some(&gate.gate_name));
                 iter_context.record_error(
                     IterationError::new(
                         iteration,
                         category,
-                        format!("Gate '{}' failed", gate_name),
+                        Self::gate_failure_message(gate, claim_note),
                     )
-                    .with_gate(*gate_name)
-                    .with_files(files_changed.clone()),
+                    .with_gate(&gate.gate_name)
+                    .with_files(files_changed.clone())
+                    .with_failed_tests(gate.failed_tests.clone()),
                 );
 
                 // Record in metrics
@@ -461,15 +907,49 @@ impl StoryExecutor {
                 }
             }
 
-            last_error = Some(format!("Quality gates failed: {}", failed_gates.join(", ")));
+            last_error = Some(format!(
+                "Quality gates failed{}: {}",
+                claim_note,
+                failed_gates.join(", ")
+            ));
+
+            // Don't let the next iteration start from a workspace worse than an earlier one
+            if snapshots_enabled {
+                if let Some(best) = self
+                    .config
+                    .rollback_policy
+                    .rollback_target(&iter_context, failed_gates.len())
+                {
+                    if let Some(rollback) = self.rollback_workspace(
+                        story_id,
+                        iteration,
+                        best,
+                        failed_gates.len(),
+                        &iter_context,
+                    ) {
+                        iter_context.record_rollback(rollback);
+                    }
+                }
+            }
+            iter_context.record_gate_failures(iteration, failed_gates.len());
 
             // Check for futility after gate failures
             if let Some(ref detector) = futility_detector {
                 let verdict = detector.analyze(&iter_context);
                 if !verdict.should_continue() {
+                    let split_into = self
+                        .split_story(
+                            story,
+                            &verdict,
+                            &iter_context,
+                            iteration + 1,
+                            &cancel_receiver,
+                        )
+                        .await;
+
                     // Check if this is a pause for guidance scenario
-                    let needs_guidance =
-                        matches!(verdict, FutilityVerdict::PauseForGuidance { .. });
+                    let needs_guidance = split_into.is_empty()
+                        && matches!(verdict, FutilityVerdict::PauseForGuidance { .. });
 
                     // Record metrics completion (only if not pausing for guidance)
                     if !needs_guidance {
@@ -492,12 +972,17 @@ impl StoryExecutor {
                         futility_verdict: Some(verdict.clone()),
                         iteration_context: Some(iter_context),
                         needs_guidance,
+                        split_into,
                     });
                 }
             }
         }
 
         // Max iterations reached without success
+        if self.config.commit_policy.wip_commits {
+            self.commit_wip(story, iterations_used, &mut wip_base).await;
+        }
+
         // Record metrics completion
         if let Some(ref collector) = self.config.metrics_collector {
             collector.complete_story(false, execution_start.elapsed(), last_error.clone());
@@ -512,7 +997,7 @@ impl StoryExecutor {
             &last_gate_results,
         );
 
-        Err(ExecutorError::AgentError(failure_summary))
+        Err(ExecutorError::AgentError(failure_summary, None))
     }
 
     /// Build a detailed failure summary for a story that failed all iterations.
@@ -551,7 +1036,13 @@ impl StoryExecutor {
         if !gate_results.is_empty() {
             summary.push_str("Quality Gate Results (Last Iteration):\n");
             for gate in gate_results {
-                let status = if gate.passed { "PASS" } else { "FAIL" };
+                let status = if gate.passed {
+                    "PASS"
+                } else if gate.advisory {
+                    "WARN"
+                } else {
+                    "FAIL"
+                };
                 summary.push_str(&format!("  - {}: {}\n", gate.gate_name, status));
                 if !gate.passed {
                     if let Some(ref details) = gate.details {
@@ -640,18 +1131,6 @@ impl StoryExecutor {
         summary
     }
 
-    /// Build an agent prompt that includes iteration context from previous failures.
-    fn build_agent_prompt_with_context(
-        &self,
-        story: &PrdUserStory,
-        prd: &PrdFile,
-        context: &IterationContext,
-    ) -> String {
-        let base_prompt = self.build_agent_prompt(story, prd);
-        let context_section = context.build_prompt_context();
-        format!("{}{}", base_prompt, context_section)
-    }
-
     /// Load the PRD file
     fn load_prd(&self) -> Result<PrdFile, ExecutorError> {
         let content = std::fs::read_to_string(&self.config.prd_path)
@@ -673,37 +1152,182 @@ impl StoryExecutor {
             .ok_or_else(|| ExecutorError::StoryNotFound(story_id.to_string()))
     }
 
-    /// Build the agent prompt for implementing a story
-    fn build_agent_prompt(&self, story: &PrdUserStory, prd: &PrdFile) -> String {
-        let mut prompt = format!("# Implement User Story: {} - {}\n\n", story.id, story.title);
-
-        if !story.description.is_empty() {
-            prompt.push_str(&format!("## Description\n{}\n\n", story.description));
+    /// Learnings to record for a run: the status block's, else a `Learnings:` list
+    fn agent_learnings(output: &AgentOutput) -> Vec<String> {
+        match &output.report {
+            Some(report) if !report.learnings.is_empty() => report.learnings.clone(),
+            _ => output
+                .final_message
+                .as_deref()
+                .map(extract_learnings)
+                .unwrap_or_default(),
         }
+    }
+
+    /// Load the prompt template for this project
+    fn load_prompt_template(&self) -> Result<PromptTemplate, ExecutorError> {
+        crate::prompt::load_template(
+            &self.config.project_root,
+            self.config.prompt_path.as_deref(),
+        )
+        .map(|(template, _)| template)
+        .map_err(|e| ExecutorError::PromptError(e.to_string()))
+    }
+
+    /// Build the agent prompt for implementing a story.
+    ///
+    /// Renders the prompt template with the story, the PRD, the iteration
+    /// context from previous failures and the learnings in progress.txt.
+    fn build_agent_prompt(
+        &self,
+        template: &PromptTemplate,
+        story: &PrdUserStory,
+        prd: &PrdFile,
+        context: &IterationContext,
+    ) -> String {
+        let progress = std::fs::read_to_string(&self.config.progress_path).unwrap_or_default();
+        let prompt_context = PromptContext::for_story(story, prd)
+            .with_iteration(context)
+            .with_progress(&progress, story)
+            .with_quality_profile(self.config.quality_profile.as_ref())
+            .with_language(ProjectLanguage::detect(&self.config.project_root));
+
+        template.render(&prompt_context)
+    }
+
+    /// Run the agent, retrying failures classified as transient or rate-limited.
+    ///
+    /// Failed runs are classified with the backend's error detector from their
+    /// output and exit code. Transient errors are retried with exponential
+    /// backoff from the retry strategy, and retries stay within the same
+    /// iteration. Limits that name a retry delay (rate limits) save a
+    /// `RateLimited` checkpoint and wait it out unless a fallback agent is
+    /// available; other usage limits, or rate limits that outlast the
+    /// retries, return `ExecutorError::UsageLimit` so the caller can switch
+    /// agents or pause. An agent killed by a timeout or signal returns
+    /// `ExecutorError::Timeout`, like one the executor timed out. Unclassified
+    /// and fatal failures are returned unchanged.
+    async fn run_agent_with_retry(
+        &self,
+        prompt: &str,
+        story_id: &str,
+        iteration: u32,
+        cancel_receiver: &watch::Receiver<bool>,
+    ) -> Result<AgentRun, ExecutorError> {
+        let strategy = &self.config.retry_strategy;
+        let backend = self.active_backend();
+
+        let mut attempt = 1;
+        loop {
+            let (message, exit_code) =
+                match self.run_agent(prompt, story_id, iteration, attempt).await {
+                    Err(ExecutorError::AgentError(message, exit_code)) => (message, exit_code),
+                    result => return result,
+                };
+            let Some(classified) = backend.classify_error(&message, exit_code) else {
+                return Err(ExecutorError::AgentError(message, exit_code));
+            };
+
+            let delay = match (&classified.category, &classified.recovery_hint) {
+                (ErrorCategory::Transient(_), _)
+                    if strategy.should_retry(attempt, &classified.category) =>
+                {
+                    strategy.calculate_delay(attempt)
+                }
+                (ErrorCategory::UsageLimit(_), RecoveryHint::RetryAfter(after))
+                    if attempt < strategy.max_attempts && self.next_fallback_agent().is_none() =>
+                {
+                    self.save_pause_checkpoint(story_id, iteration, PauseReason::RateLimited);
+                    (*after).min(strategy.max_delay)
+                }
+                (ErrorCategory::UsageLimit(reason), _) => {
+                    let pause_reason = if *reason == UsageLimitReason::RateLimited {
+                        PauseReason::RateLimited
+                    } else {
+                        PauseReason::UsageLimitExceeded
+                    };
+                    self.save_pause_checkpoint(story_id, iteration, pause_reason);
+                    return Err(ExecutorError::UsageLimit(reason.clone(), message));
+                }
+                (ErrorCategory::Timeout(_), _) => {
+                    return Err(ExecutorError::Timeout(format!(
+                        "{} (iteration {})",
+                        classified.message, iteration
+                    )));
+                }
+                _ => return Err(ExecutorError::AgentError(message, exit_code)),
+            };
 
-        if !story.acceptance_criteria.is_empty() {
-            prompt.push_str("## Acceptance Criteria\n");
-            for (i, criterion) in story.acceptance_criteria.iter().enumerate() {
-                prompt.push_str(&format!("{}. {}\n", i + 1, criterion));
+            attempt += 1;
+            let reason = format!("{} (iteration {})", classified.message, iteration);
+            if !self
+                .wait_for_retry(iteration, attempt, delay, &reason, cancel_receiver)
+                .await
+            {
+                return Err(ExecutorError::Cancelled);
             }
-            prompt.push('\n');
         }
+    }
 
-        prompt.push_str(&format!(
-            "## Project Context\n\
-            - Project: {}\n\
-            - Branch: {}\n\
-            - Story Priority: {}\n\n\
-            ## Instructions\n\
-            1. Implement all acceptance criteria\n\
-            2. Ensure code compiles without errors (cargo check)\n\
-            3. Ensure no clippy warnings (cargo clippy -- -D warnings)\n\
-            4. Ensure proper formatting (cargo fmt)\n\
-            5. Keep changes focused and minimal\n",
-            prd.project, prd.branch_name, story.priority
-        ));
+    /// Wait before retry `attempt`, publishing a `Notification::Retrying` countdown.
+    ///
+    /// The countdown goes to the event channel once per second, or to stderr
+    /// when no channel is configured. Returns false if execution was cancelled
+    /// during the wait.
+    async fn wait_for_retry(
+        &self,
+        iteration: u32,
+        attempt: u32,
+        delay: Duration,
+        reason: &str,
+        cancel_receiver: &watch::Receiver<bool>,
+    ) -> bool {
+        let max_attempts = self.config.retry_strategy.max_attempts;
+        let deadline = tokio::time::Instant::now() + delay;
+        let live_countdown = std::io::stderr().is_terminal();
+        let mut cancel = cancel_receiver.clone();
+        let mut first_tick = true;
 
-        prompt
+        loop {
+            let remaining = deadline.saturating_duration_since(tokio::time::Instant::now());
+            let notification = Notification::retrying(attempt, max_attempts, remaining, reason);
+            match self.config.event_sender {
+                Some(ref sender) => {
+                    let _ = sender.try_send(ExecutionEvent::Notification {
+                        iteration,
+                        notification,
+                    });
+                }
+                None if live_countdown => eprint!("\r\x1b[K{}", notification),
+                None if first_tick => eprintln!("{}", notification),
+                None => {}
+            }
+            first_tick = false;
+
+            if remaining.is_zero() {
+                if live_countdown && self.config.event_sender.is_none() {
+                    eprintln!();
+                }
+                return true;
+            }
+
+            tokio::select! {
+                _ = tokio::time::sleep(remaining.min(Duration::from_secs(1))) => {}
+                Ok(()) = cancel.changed() => {
+                    if *cancel.borrow() {
+                        if live_countdown && self.config.event_sender.is_none() {
+                            eprintln!();
+                        }
+                        return false;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Backend of the active agent
+    fn active_backend(&self) -> Arc<dyn AgentBackend> {
+        self.config.agent_registry.resolve(&self.active_agent())
     }
 
     /// Run the agent (Claude Code or Amp CLI) to implement the story
@@ -711,19 +1335,30 @@ impl StoryExecutor {
     /// This method integrates heartbeat monitoring to detect stalled agents.
     /// The heartbeat is updated whenever the agent produces output, and stall
     /// detection triggers a graceful timeout.
-    async fn run_agent(&self, prompt: &str, iteration: u32) -> Result<Vec<String>, ExecutorError> {
-        let (program, args) = build_agent_invocation(
-            &self.config.agent_command,
-            prompt,
-            self.config.project_root.as_path(),
-        );
+    async fn run_agent(
+        &self,
+        prompt: &str,
+        story_id: &str,
+        iteration: u32,
+        attempt: u32,
+    ) -> Result<AgentRun, ExecutorError> {
+        let backend = self.active_backend();
+        let request = AgentRequest::new(prompt, self.config.project_root.as_path())
+            .with_story(story_id, iteration)
+            .with_attempt(attempt)
+            .with_model(self.config.model.as_deref());
+        let invocation = backend.build_invocation(&request);
+        let program = invocation.program.clone();
 
         // Check if the agent is available (cross-platform)
-        if !is_program_in_path(&program) {
-            return Err(ExecutorError::AgentError(format!(
-                "Agent '{}' not found in PATH. Install Claude Code CLI, Codex CLI, or Amp CLI.",
-                program
-            )));
+        if !backend.is_available() {
+            return Err(ExecutorError::AgentError(
+                format!(
+                    "Agent '{}' not found in PATH. Install Claude Code CLI, Codex CLI, or Amp CLI.",
+                    program
+                ),
+                None,
+            ));
         }
 
         // Create heartbeat monitor for stall detection
@@ -735,13 +1370,16 @@ impl StoryExecutor {
 
         // Spawn the agent process with piped stdout/stderr for streaming
         let mut child = tokio::process::Command::new(&program)
-            .args(&args)
+            .args(&invocation.args)
+            .envs(invocation.env.iter().map(|(k, v)| (k.as_str(), v.as_str())))
             .current_dir(&self.config.project_root)
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
+            // A story aborted by its batch timeout must not leave its agent running
+            .kill_on_drop(true)
             .spawn()
             .map_err(|e| {
-                ExecutorError::AgentError(format!("Failed to spawn {}: {}", program, e))
+                ExecutorError::AgentError(format!("Failed to spawn {}: {}", program, e), None)
             })?;
 
         // Take ownership of stdout and stderr
@@ -756,6 +1394,9 @@ impl StoryExecutor {
         let mut stderr_output = String::new();
         let mut stdout_output = String::new();
 
+        // Typed events parsed from structured agent output
+        let mut agent_events: Vec<AgentEvent> = Vec::new();
+
         // Track if we received a stall detection
         let mut stall_detected = false;
 
@@ -763,6 +1404,9 @@ impl StoryExecutor {
         let timeout_duration = self.config.timeout_config.agent_timeout;
         let timeout_deadline = tokio::time::Instant::now() + timeout_duration;
 
+        // Set once the process exits, bounding how long remaining output is read
+        let mut drain_deadline: Option<tokio::time::Instant> = None;
+
         // Main loop: process output, heartbeat events, and wait for completion
         loop {
             tokio::select! {
@@ -794,17 +1438,23 @@ impl StoryExecutor {
                 }
 
                 // Read stdout line
+                // Disabled after EOF so a finished stream can't spin the loop
                 line = async {
                     if let Some(ref mut reader) = stdout_reader {
                         reader.next_line().await
                     } else {
                         Ok(None)
                     }
-                } => {
+                }, if stdout_reader.is_some() => {
                     match line {
                         Ok(Some(text)) => {
                             // Activity detected - update heartbeat
                             heartbeat_monitor.pulse().await;
+                            // Parse structured output into typed events
+                            for event in backend.parse_stream_line(&text) {
+                                self.publish_agent_event(iteration, &event);
+                                agent_events.push(event);
+                            }
                             // Collect stdout for error diagnostics
                             stdout_output.push_str(&text);
                             stdout_output.push('\n');
@@ -826,7 +1476,7 @@ impl StoryExecutor {
                     } else {
                         Ok(None)
                     }
-                } => {
+                }, if stderr_reader.is_some() => {
                     match line {
                         Ok(Some(text)) => {
                             // Activity detected - update heartbeat
@@ -846,34 +1496,30 @@ impl StoryExecutor {
                 }
 
                 // Check for process completion
-                status = child.wait() => {
+                status = child.wait(), if drain_deadline.is_none() => {
                     match status {
-                        Ok(exit_status) => {
-                            // Stop heartbeat monitoring
-                            heartbeat_monitor.stop().await;
-
-                            if !exit_status.success() {
-                                // Build comprehensive error message from both streams
-                                let error_details = self.build_agent_error_message(
-                                    &stdout_output,
-                                    &stderr_output,
-                                    exit_status.code()
-                                );
-                                return Err(ExecutorError::AgentError(error_details));
-                            }
-                            // Process completed successfully
-                            let files_changed = self.get_changed_files()?;
-                            return Ok(files_changed);
+                        Ok(_) => {
+                            // Keep reading so output written just before exit
+                            // still reaches the error message and event stream
+                            drain_deadline =
+                                Some(tokio::time::Instant::now() + OUTPUT_DRAIN_TIMEOUT);
                         }
                         Err(e) => {
                             heartbeat_monitor.stop().await;
                             return Err(ExecutorError::AgentError(format!(
                                 "Failed to wait for {}: {}", program, e
-                            )));
+                            ), None));
                         }
                     }
                 }
 
+                // Stop waiting on pipes held open by processes the agent left behind
+                _ = tokio::time::sleep_until(drain_deadline.unwrap_or(timeout_deadline)),
+                    if drain_deadline.is_some() => {
+                    stdout_reader = None;
+                    stderr_reader = None;
+                }
+
                 // Overall timeout
                 _ = tokio::time::sleep_until(timeout_deadline) => {
                     heartbeat_monitor.stop().await;
@@ -908,18 +1554,24 @@ impl StoryExecutor {
                                 &stderr_output,
                                 exit_status.code(),
                             );
-                            return Err(ExecutorError::AgentError(error_details));
+                            return Err(ExecutorError::AgentError(
+                                error_details,
+                                exit_status.code(),
+                            ));
                         }
 
-                        let files_changed = self.get_changed_files()?;
-                        return Ok(files_changed);
+                        return self.finish_agent_run(
+                            backend.as_ref(),
+                            &stdout_output,
+                            agent_events,
+                        );
                     }
                     Err(e) => {
                         heartbeat_monitor.stop().await;
-                        return Err(ExecutorError::AgentError(format!(
-                            "Failed to wait for {}: {}",
-                            program, e
-                        )));
+                        return Err(ExecutorError::AgentError(
+                            format!("Failed to wait for {}: {}", program, e),
+                            None,
+                        ));
                     }
                 }
             }
@@ -938,9 +1590,37 @@ impl StoryExecutor {
             )));
         }
 
+        self.finish_agent_run(backend.as_ref(), &stdout_output, agent_events)
+    }
+
+    /// Collect the results of a successful agent run.
+    fn finish_agent_run(
+        &self,
+        backend: &dyn AgentBackend,
+        stdout: &str,
+        events: Vec<AgentEvent>,
+    ) -> Result<AgentRun, ExecutorError> {
         // Get list of changed files from git
         let files_changed = self.get_changed_files()?;
-        Ok(files_changed)
+        Ok(AgentRun {
+            files_changed,
+            events,
+            output: backend.parse_output(stdout),
+        })
+    }
+
+    /// Forward a parsed agent event to metrics and the event channel.
+    fn publish_agent_event(&self, iteration: u32, event: &AgentEvent) {
+        if let Some(ref collector) = self.config.metrics_collector {
+            collector.record_agent_event(event);
+        }
+
+        if let Some(ref sender) = self.config.event_sender {
+            let _ = sender.try_send(ExecutionEvent::AgentActivity {
+                iteration,
+                event: event.clone(),
+            });
+        }
     }
 
     /// Build a comprehensive error message from agent output.
@@ -1055,6 +1735,11 @@ impl StoryExecutor {
     /// This captures the current execution state so the story can be resumed later.
     /// Errors during checkpoint saving are logged but not propagated.
     fn save_timeout_checkpoint(&self, story_id: &str, iteration: u32) {
+        self.save_pause_checkpoint(story_id, iteration, PauseReason::Timeout);
+    }
+
+    /// Save a checkpoint recording why execution paused at this iteration.
+    fn save_pause_checkpoint(&self, story_id: &str, iteration: u32, pause_reason: PauseReason) {
         if let Some(ref manager) = self.checkpoint_manager {
             // Get uncommitted files for checkpoint
             let uncommitted_files = self.get_changed_files().unwrap_or_default();
@@ -1065,33 +1750,117 @@ impl StoryExecutor {
                     iteration,
                     self.config.max_iterations,
                 )),
-                PauseReason::Timeout,
+                pause_reason,
                 uncommitted_files,
-            );
+            )
+            .with_agent(self.active_agent(), self.agent_switches());
 
             // Save checkpoint with error logging (best effort, but warn on failure)
             if let Err(e) = manager.save(&checkpoint) {
                 eprintln!(
-                    "Warning: Failed to save timeout checkpoint for story '{}': {}",
+                    "Warning: Failed to save checkpoint for story '{}': {}",
                     story_id, e
                 );
             }
         }
     }
 
-    /// Run quality gates and return results
-    fn run_quality_gates(&self) -> Vec<GateResult> {
+    /// Check run usage against the configured budget.
+    ///
+    /// Returns a description of the exceeded cap, or None if within budget
+    /// (or no metrics collector is configured).
+    fn check_budget(&self) -> Option<String> {
+        if !self.config.budget.is_limited() {
+            return None;
+        }
+        self.config
+            .metrics_collector
+            .as_ref()
+            .and_then(|collector| collector.check_budget(&self.config.budget))
+    }
+
+    /// Check the run's iterations and the clock against the run limits.
+    ///
+    /// Returns a description of the reached limit, if any.
+    fn check_run_limits(&self) -> Option<String> {
+        match self.config.metrics_collector {
+            Some(ref collector) => collector.check_run_limits(&self.config.run_limits),
+            None => self.config.run_limits.check(0, chrono::Local::now()),
+        }
+    }
+
+    /// Audit the project before the story's first iteration, so the audit
+    /// gate only counts the findings the story introduces.
+    fn capture_audit_baseline(&self, story_id: &str) -> Option<AuditBaseline> {
         let profile = self.config.quality_profile.clone().unwrap_or_default();
-        let checker = QualityGateChecker::new(profile, &self.config.project_root);
-        checker.run_all()
+        QualityGateChecker::new(profile, &self.config.project_root)
+            .capture_audit_baseline()
+            .unwrap_or_else(|e| {
+                eprintln!(
+                    "Warning: Failed to capture audit baseline for story '{}': {}",
+                    story_id, e
+                );
+                None
+            })
     }
 
-    /// Create a git commit with the proper format
+    /// Run the profile's quality gates, then the story's own verification checks.
     ///
+    /// With WIP commits, `wip_base` is the commit the story started from, so
+    /// the gates that check changed code also see the committed work.
+    fn run_quality_gates(
+        &self,
+        story: &PrdUserStory,
+        audit_baseline: Option<&AuditBaseline>,
+        wip_base: Option<&str>,
+    ) -> Vec<GateResult> {
+        let profile = self.config.quality_profile.clone().unwrap_or_default();
+        let mut checker = QualityGateChecker::new(profile, &self.config.project_root);
+        if let Some(baseline) = audit_baseline {
+            checker = checker.with_audit_baseline(baseline.clone());
+        }
+        if let Some(base) = wip_base {
+            checker = checker.with_change_base(base);
+        }
+        let mut results = checker.run_all();
+        results.extend(checker.run_verifications(&story.verify));
+        results
+    }
+
+    /// Error recorded for a failed gate, shown to the agent on the next iteration.
+    ///
+    /// Gates whose result has `actionable_details` include their message and
+    /// details; failing tests are listed from the error's `failed_tests`
+    /// instead.
+    fn gate_failure_message(gate: &GateResult, claim_note: &str) -> String {
+        let mut message = format!("Gate '{}' failed{}", gate.gate_name, claim_note);
+        if gate.actionable_details {
+            message.push_str(&format!(": {}", gate.message));
+            if let Some(details) = gate
+                .details
+                .as_ref()
+                .filter(|_| gate.failed_tests.is_empty())
+            {
+                for line in details.lines() {
+                    message.push_str(&format!("\n    {}", line));
+                }
+            }
+        }
+        message
+    }
+
+    /// Commit the story according to the commit policy and return the hash.
+    ///
+    /// Any WIP commits made since `wip_base` are squashed into this commit.
     /// If a git_mutex is configured, this method will acquire the lock before
     /// performing git operations to prevent concurrent git operations that could
     /// corrupt the repository.
-    async fn create_commit(&self, story: &PrdUserStory) -> Result<String, ExecutorError> {
+    async fn create_commit(
+        &self,
+        story: &PrdUserStory,
+        iterations: u32,
+        wip_base: Option<&str>,
+    ) -> Result<String, ExecutorError> {
         // Acquire git mutex if configured (for parallel execution)
         let _guard = if let Some(ref mutex) = self.config.git_mutex {
             Some(mutex.lock().await)
@@ -1099,46 +1868,177 @@ impl StoryExecutor {
             None
         };
 
-        // Stage all changes
-        let status = Command::new("git")
-            .args(["add", "-A"])
-            .current_dir(&self.config.project_root)
-            .status()
-            .map_err(|e| ExecutorError::GitError(format!("Failed to stage changes: {}", e)))?;
+        let root = &self.config.project_root;
+        let policy = &self.config.commit_policy;
+        let agent = self.active_agent();
+        let message = policy.message(&Self::story_commit(story, iterations, &agent));
 
-        if !status.success() {
-            return Err(ExecutorError::GitError("git add failed".to_string()));
-        }
+        let commit = || -> Result<String, GitError> {
+            if let Some(base) = wip_base {
+                squash_since(root, base)?;
+            }
+            stage(root, self.files_to_stage(&story.id).as_deref())?;
+            policy.commit(root, &message)
+        };
+        commit().map_err(|e| ExecutorError::GitError(e.to_string()))
+        // _guard is dropped here, releasing the mutex lock
+    }
 
-        // Create commit with proper message format: feat: [ID] - [Title]
-        let commit_message = format!("feat: {} - {}", story.id, story.title);
+    /// Commit the work a failed iteration left behind, recording the commit
+    /// the story started from in `wip_base`. Nothing is committed if the
+    /// iteration changed nothing.
+    async fn commit_wip(
+        &self,
+        story: &PrdUserStory,
+        iteration: u32,
+        wip_base: &mut Option<String>,
+    ) {
+        let _guard = if let Some(ref mutex) = self.config.git_mutex {
+            Some(mutex.lock().await)
+        } else {
+            None
+        };
 
-        let status = Command::new("git")
-            .args(["commit", "-m", &commit_message])
-            .current_dir(&self.config.project_root)
-            .status()
-            .map_err(|e| ExecutorError::GitError(format!("Failed to create commit: {}", e)))?;
+        let root = &self.config.project_root;
+        let policy = &self.config.commit_policy;
+        let agent = self.active_agent();
+        let message = policy.wip_message(&Self::story_commit(story, iteration, &agent), iteration);
 
-        if !status.success() {
-            return Err(ExecutorError::GitError("git commit failed".to_string()));
+        let mut commit = || -> Result<(), GitError> {
+            let head = resolve_commit(root, "HEAD")?;
+            stage(root, self.files_to_stage(&story.id).as_deref())?;
+            if !has_staged_changes(root) {
+                return Ok(());
+            }
+            policy.commit(root, &message)?;
+            wip_base.get_or_insert(head);
+            Ok(())
+        };
+        if let Err(e) = commit() {
+            eprintln!(
+                "Warning: Failed to create WIP commit for story '{}': {}",
+                story.id, e
+            );
         }
+    }
 
-        // Get the commit hash
-        let output = Command::new("git")
-            .args(["rev-parse", "HEAD"])
-            .current_dir(&self.config.project_root)
-            .output()
-            .map_err(|e| ExecutorError::GitError(format!("Failed to get commit hash: {}", e)))?;
+    /// Ask the agent to split a story the futility detector gave up on.
+    ///
+    /// Only deferred stories and stories paused for guidance are split, and
+    /// only when splitting is enabled. Returns the IDs of the sub-stories that
+    /// replaced the story in the PRD, or nothing if the agent's answer was not
+    /// a valid split.
+    async fn split_story(
+        &self,
+        story: &PrdUserStory,
+        verdict: &FutilityVerdict,
+        context: &IterationContext,
+        iteration: u32,
+        cancel_receiver: &watch::Receiver<bool>,
+    ) -> Vec<String> {
+        let reason = match verdict {
+            FutilityVerdict::DeferStory { reason }
+            | FutilityVerdict::PauseForGuidance { reason, .. }
+                if self.config.split_stories =>
+            {
+                reason
+            }
+            _ => return Vec::new(),
+        };
 
-        if !output.status.success() {
-            return Err(ExecutorError::GitError(
-                "Failed to get commit hash".to_string(),
-            ));
+        let summary = FutileRetryDetector::with_config(self.config.futility_config.clone())
+            .summarize_patterns(context);
+        let prompt = split_prompt(story, context, &summary, reason);
+        let split = async {
+            let run = self
+                .run_agent_with_retry(&prompt, &story.id, iteration, cancel_receiver)
+                .await
+                .map_err(|e| e.to_string())?;
+            let answer = run.output.final_message.unwrap_or_default();
+            let sub_stories = parse_sub_stories(&answer).map_err(|e| e.to_string())?;
+            let prd = self.load_prd().map_err(|e| e.to_string())?;
+            validate_split(story, &prd, &sub_stories).map_err(|e| e.to_string())?;
+            apply_split(&self.config.prd_path, &story.id, &sub_stories)
+                .map_err(|e| e.to_string())?;
+            Ok::<_, String>(
+                sub_stories
+                    .into_iter()
+                    .map(|sub| sub.id.trim().to_string())
+                    .collect(),
+            )
+        };
+        match split.await {
+            Ok(sub_stories) => sub_stories,
+            Err(e) => {
+                eprintln!("Warning: Failed to split story '{}': {}", story.id, e);
+                Vec::new()
+            }
         }
+    }
 
-        let hash = String::from_utf8_lossy(&output.stdout).trim().to_string();
-        Ok(hash)
-        // _guard is dropped here, releasing the mutex lock
+    /// Rules the story's changes so far break.
+    ///
+    /// Changes are measured against the workspace the story's first iteration
+    /// started from, so leftovers from earlier stories are not counted.
+    fn check_changes(&self, guard: &ChangeGuard, story: &PrdUserStory) -> Vec<GuardViolation> {
+        let root = &self.config.project_root;
+        match WorkspaceSnapshot::find(root, &story.id, 1)
+            .and_then(|snapshot| snapshot.map(|s| s.diff_stat(root)).transpose())
+        {
+            Ok(Some(changes)) => guard.check(&changes, &story.target_files),
+            Ok(None) => Vec::new(),
+            Err(e) => {
+                eprintln!(
+                    "Warning: Failed to check story '{}' against the change guard: {}",
+                    story.id, e
+                );
+                Vec::new()
+            }
+        }
+    }
+
+    /// Files the story's commit should stage (None = every change).
+    ///
+    /// With `stage = "changed"` these are the files that differ from the
+    /// workspace the story's first iteration started from.
+    fn files_to_stage(&self, story_id: &str) -> Option<Vec<String>> {
+        if self.config.commit_policy.stage == StageMode::All {
+            return None;
+        }
+        let root = &self.config.project_root;
+        match WorkspaceSnapshot::find(root, story_id, 1)
+            .and_then(|snapshot| snapshot.map(|s| s.changed_files(root)).transpose())
+        {
+            Ok(Some(files)) => Some(files),
+            Ok(None) => {
+                eprintln!(
+                    "Warning: No workspace snapshot for story '{}'; staging all changes",
+                    story_id
+                );
+                None
+            }
+            Err(e) => {
+                eprintln!(
+                    "Warning: Failed to find the files story '{}' changed ({}); staging all changes",
+                    story_id, e
+                );
+                None
+            }
+        }
+    }
+
+    fn story_commit<'a>(
+        story: &'a PrdUserStory,
+        iterations: u32,
+        agent: &'a str,
+    ) -> StoryCommit<'a> {
+        StoryCommit {
+            story_id: &story.id,
+            title: &story.title,
+            commit_type: story.commit_type.as_deref(),
+            iterations,
+            agent,
+        }
     }
 
     /// Update the PRD file to set passes: true for the story
@@ -1176,6 +2076,7 @@ impl StoryExecutor {
         story: &PrdUserStory,
         files_changed: &[String],
         iterations: u32,
+        learnings: &[String],
     ) -> Result<(), ExecutorError> {
         use std::io::Write;
 
@@ -1198,13 +2099,20 @@ impl StoryExecutor {
             ));
         }
 
-        entry.push_str(&format!(
-            "- **Iterations used**: {}\n\
-            - **Learnings for future iterations:**\n\
-              - Story completed successfully via automated execution\n\
-            ---\n",
-            iterations
-        ));
+        entry.push_str(&format!("- **Iterations used**: {}\n", iterations));
+        entry.push_str(&format!("- **Agent**: {}\n", self.active_agent()));
+        for switch in self.agent_switches() {
+            entry.push_str(&format!("  - Switched {}\n", switch));
+        }
+
+        // Learnings the agent reported in its final message
+        if !learnings.is_empty() {
+            entry.push_str(&format!("- {}\n", LEARNINGS_MARKER));
+            for learning in learnings {
+                entry.push_str(&format!("  - {}\n", learning));
+            }
+        }
+        entry.push_str("---\n");
 
         // Append to progress file
         let mut file = std::fs::OpenOptions::new()
@@ -1220,93 +2128,16 @@ impl StoryExecutor {
     }
 }
 
-/// Check if a program exists in PATH (cross-platform)
-fn is_program_in_path(program: &str) -> bool {
-    #[cfg(target_os = "windows")]
-    let check_cmd = "where";
-    #[cfg(not(target_os = "windows"))]
-    let check_cmd = "which";
-
-    Command::new(check_cmd)
-        .arg(program)
-        .stdout(Stdio::null())
-        .stderr(Stdio::null())
-        .status()
-        .map(|s| s.success())
-        .unwrap_or(false)
-}
-
 /// Check if a specific agent CLI is available
 pub fn is_agent_available(agent: &str) -> bool {
-    is_program_in_path(agent)
+    AgentRegistry::with_builtins().resolve(agent).is_available()
 }
 
 /// Detect the best available agent CLI
+///
+/// Prefers Claude Code, then Codex, then Amp.
 pub fn detect_agent() -> Option<String> {
-    // Prefer Claude Code, then Codex, then Amp
-    if is_agent_available("claude") {
-        Some("claude".to_string())
-    } else if is_agent_available("codex") {
-        Some("codex".to_string())
-    } else if is_agent_available("amp") {
-        Some("amp".to_string())
-    } else {
-        None
-    }
-}
-
-fn build_agent_invocation(
-    agent_command: &str,
-    prompt: &str,
-    project_root: &Path,
-) -> (String, Vec<String>) {
-    if agent_command == "claude" || agent_command.contains("claude") {
-        // Claude Code CLI - use --print for non-interactive mode
-        // and --dangerously-skip-permissions to allow file changes
-        (
-            "claude".to_string(),
-            vec![
-                "--print".to_string(),
-                "--dangerously-skip-permissions".to_string(),
-                prompt.to_string(),
-            ],
-        )
-    } else if agent_command == "codex" || agent_command.contains("codex") {
-        let mut args = vec![
-            "exec".to_string(),
-            "--full-auto".to_string(),
-            prompt.to_string(),
-        ];
-
-        if let Ok(model) = std::env::var("CODEX_OSS_MODEL") {
-            args.push("--model".to_string());
-            args.push(model);
-        } else if let Ok(model) = std::env::var("CODEX_MODEL") {
-            args.push("--model".to_string());
-            args.push(model);
-        }
-
-        let provider = std::env::var("CODEX_OSS_PROVIDER").unwrap_or_else(|_| "ollama".to_string());
-        if std::env::var("CODEX_OSS")
-            .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
-            .unwrap_or(false)
-        {
-            args.push("--oss".to_string());
-            args.push("--local-provider".to_string());
-            args.push(provider);
-        }
-
-        let mut args_with_dir = vec!["-C".to_string(), project_root.display().to_string()];
-        args_with_dir.extend(args);
-        ("codex".to_string(), args_with_dir)
-    } else if agent_command == "amp" || agent_command.contains("amp") {
-        (
-            "amp".to_string(),
-            vec!["--prompt".to_string(), prompt.to_string()],
-        )
-    } else {
-        (agent_command.to_string(), vec![prompt.to_string()])
-    }
+    AgentRegistry::with_builtins().detect()
 }
 
 #[cfg(test)]
@@ -1396,25 +2227,108 @@ mod tests {
         }
     }
 
-    #[test]
-    fn test_build_agent_prompt() {
+    /// Render the prompt for US-001 in a project containing `manifest`
+    fn render_prompt(manifest: &str, context: &IterationContext) -> String {
         let prd_file = create_test_prd();
+        let project = tempfile::TempDir::new().unwrap();
+        std::fs::write(project.path().join(manifest), "").unwrap();
         let config = ExecutorConfig {
             prd_path: prd_file.path().to_path_buf(),
+            project_root: project.path().to_path_buf(),
+            progress_path: project.path().join("progress.txt"),
             ..Default::default()
         };
         let executor = StoryExecutor::new(config);
 
         let prd = executor.load_prd().unwrap();
         let story = executor.find_story(&prd, "US-001").unwrap();
-        let prompt = executor.build_agent_prompt(story, &prd);
+        let template = executor.load_prompt_template().unwrap();
+        executor.build_agent_prompt(&template, story, &prd, context)
+    }
+
+    #[test]
+    fn test_build_agent_prompt() {
+        let prompt = render_prompt("Cargo.toml", &IterationContext::new("US-001", 3));
 
         assert!(prompt.contains("US-001"));
         assert!(prompt.contains("First story"));
-        assert!(prompt.contains("AC1"));
-        assert!(prompt.contains("AC2"));
+        assert!(prompt.contains("1. AC1\n2. AC2"));
         assert!(prompt.contains("TestProject"));
         assert!(prompt.contains("cargo check"));
+        assert!(!prompt.contains("Previous Iteration Context"));
+    }
+
+    #[test]
+    fn test_build_agent_prompt_uses_project_language() {
+        let prompt = render_prompt("go.mod", &IterationContext::new("US-001", 3));
+
+        assert!(prompt.contains("go test ./..."));
+        assert!(!prompt.contains("cargo"));
+    }
+
+    #[test]
+    fn test_build_agent_prompt_includes_iteration_context() {
+        let mut context = IterationContext::new("US-001", 3);
+        context.start_iteration(2);
+        context.record_error(IterationError::new(
+            1,
+            IterErrorCategory::Compilation,
+            "mismatched types",
+        ));
+
+        let prompt = render_prompt("Cargo.toml", &context);
+
+        assert!(prompt.contains("Previous Iteration Context"));
+        assert!(prompt.contains("mismatched types"));
+        assert!(prompt.contains("iteration 2 of 3"));
+    }
+
+    #[test]
+    fn test_invalid_prompt_template_is_fatal() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("prompt.md");
+        std::fs::write(&path, "{{story}}").unwrap();
+        let executor = StoryExecutor::new(ExecutorConfig {
+            prompt_path: Some(path),
+            ..Default::default()
+        });
+
+        let err = executor.load_prompt_template().unwrap_err();
+
+        assert!(err
+            .to_string()
+            .contains("Unknown template variable 'story'"));
+        assert!(matches!(
+            err.classify(),
+            ErrorCategory::Fatal(crate::error::classification::FatalReason::ConfigurationError)
+        ));
+    }
+
+    #[test]
+    fn test_append_progress_writes_agent_learnings() {
+        let prd_file = create_test_prd();
+        let dir = tempfile::TempDir::new().unwrap();
+        let executor = StoryExecutor::new(ExecutorConfig {
+            prd_path: prd_file.path().to_path_buf(),
+            progress_path: dir.path().join("progress.txt"),
+            ..Default::default()
+        });
+        let prd = executor.load_prd().unwrap();
+        let story = executor.find_story(&prd, "US-001").unwrap();
+
+        executor
+            .append_progress(story, &["src/a.rs".to_string()], 2, &["Use X".to_string()])
+            .unwrap();
+        executor.append_progress(story, &[], 1, &[]).unwrap();
+
+        let progress = std::fs::read_to_string(dir.path().join("progress.txt")).unwrap();
+        assert!(progress.contains("- **Learnings for future iterations:**\n  - Use X\n---\n"));
+        assert!(progress.contains("- **Agent**: claude\n"));
+        assert!(!progress.contains("automated execution"));
+        assert_eq!(
+            progress.matches("Learnings for future iterations").count(),
+            1
+        );
     }
 
     #[test]
@@ -1428,6 +2342,51 @@ mod tests {
             .contains("Git error"));
     }
 
+    #[test]
+    fn test_usage_limit_error_classification() {
+        let error = ExecutorError::UsageLimit(
+            UsageLimitReason::RateLimited,
+            "429 Too Many Requests".into(),
+        );
+        assert!(error.to_string().contains("429"));
+        assert_eq!(
+            error.classify(),
+            ErrorCategory::UsageLimit(UsageLimitReason::RateLimited)
+        );
+    }
+
+    #[test]
+    fn test_gate_failure_message_shows_actionable_details() {
+        let lint = GateResult::fail("lint", "Clippy failed", Some("warning: unused".into()));
+        assert_eq!(
+            StoryExecutor::gate_failure_message(&lint, ""),
+            "Gate 'lint' failed"
+        );
+
+        let schema = GateResult::fail(
+            "schema",
+            "Exited with 1, expected 0",
+            Some("bad\nworse".into()),
+        )
+        .with_actionable_details(true);
+        assert_eq!(
+            StoryExecutor::gate_failure_message(&schema, ""),
+            "Gate 'schema' failed: Exited with 1, expected 0\n    bad\n    worse"
+        );
+
+        let failure = crate::quality::TestFailure {
+            name: "tests::test_login".into(),
+            message: "assertion failed".into(),
+        };
+        let tests = GateResult::fail("tests", "1 passed, 1 failed", Some(failure.to_string()))
+            .with_actionable_details(true)
+            .with_failed_tests(vec![failure]);
+        assert_eq!(
+            StoryExecutor::gate_failure_message(&tests, ""),
+            "Gate 'tests' failed: 1 passed, 1 failed"
+        );
+    }
+
     #[test]
     fn test_update_prd_passes() {
         let prd_file = create_test_prd();
@@ -1468,16 +2427,121 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_execute_story_stops_when_budget_exceeded() {
+        let prd_file = create_test_prd();
+        let project = TempDir::new().unwrap();
+
+        let collector = MetricsCollector::new();
+        collector.start_story("US-000", 1);
+        collector.record_agent_event(&AgentEvent::Usage {
+            usage: crate::agent::TokenUsage {
+                input_tokens: 2000,
+                ..Default::default()
+            },
+            cost_usd: Some(1.5),
+        });
+
+        let config = ExecutorConfig {
+            prd_path: prd_file.path().to_path_buf(),
+            project_root: project.path().to_path_buf(),
+            // The agent must never be spawned once the budget is exhausted
+            agent_command: "ralph-definitely-not-installed-agent".to_string(),
+            metrics_collector: Some(collector.clone()),
+            budget: UsageBudget::unlimited().with_max_cost_usd(1.0),
+            ..Default::default()
+        };
+        let executor = StoryExecutor::new(config);
+        let (_tx, rx) = watch::channel(false);
+
+        let mut reported = Vec::new();
+        let result = executor
+            .execute_story("US-001", rx, |current, _| reported.push(current))
+            .await;
+        match result {
+            Err(ExecutorError::BudgetExceeded(1, msg)) => assert!(msg.contains("$1.50")),
+            other => panic!("expected BudgetExceeded, got {:?}", other),
+        }
+        // The iteration never ran, so it is neither reported nor counted
+        assert!(reported.is_empty());
+        assert_eq!(collector.run_iterations(), 0);
+
+        let checkpoint = CheckpointManager::new(project.path())
+            .unwrap()
+            .load()
+            .unwrap()
+            .expect("checkpoint should be saved");
+        assert!(matches!(
+            checkpoint.pause_reason,
+            PauseReason::BudgetExceeded(_)
+        ));
+        assert_eq!(checkpoint.current_story.unwrap().story_id, "US-001");
+    }
+
     #[test]
-    fn test_build_agent_invocation_codex_default() {
-        let (program, args) =
-            build_agent_invocation("codex", "test prompt", Path::new("/tmp/project"));
-
-        assert_eq!(program, "codex");
-        assert_eq!(args[0], "-C");
-        assert_eq!(args[1], "/tmp/project");
-        assert_eq!(args[2], "exec");
-        assert!(args.contains(&"--full-auto".to_string()));
-        assert!(args.contains(&"test prompt".to_string()));
+    fn test_with_story_overrides() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let config = ExecutorConfig {
+            project_root: dir.path().to_path_buf(),
+            ..Default::default()
+        };
+
+        let unchanged = config
+            .clone()
+            .with_story_overrides(&PrdUserStory::default())
+            .unwrap();
+        assert_eq!(unchanged.agent_command, "claude");
+        assert_eq!(unchanged.max_iterations, 10);
+        assert!(unchanged.quality_profile.is_none());
+
+        let story = PrdUserStory {
+            id: "US-001".to_string(),
+            agent: Some("codex".to_string()),
+            model: Some("gpt-5-mini".to_string()),
+            max_iterations: Some(2),
+            timeout_seconds: Some(30),
+            quality_profile: Some("minimal".to_string()),
+            ..Default::default()
+        };
+        let overridden = config.clone().with_story_overrides(&story).unwrap();
+        assert_eq!(overridden.agent_command, "codex");
+        assert_eq!(overridden.model.as_deref(), Some("gpt-5-mini"));
+        assert_eq!(overridden.max_iterations, 2);
+        assert_eq!(
+            overridden.timeout_config.agent_timeout,
+            Duration::from_secs(30)
+        );
+        assert!(overridden.quality_profile.is_some());
+
+        let story = PrdUserStory {
+            id: "US-002".to_string(),
+            quality_profile: Some("strictest".to_string()),
+            ..Default::default()
+        };
+        let error = config.with_story_overrides(&story).unwrap_err();
+        assert!(matches!(error, ExecutorError::PrdError(_)));
+        assert!(error
+            .to_string()
+            .contains("unknown quality profile 'strictest'"));
+    }
+
+    #[test]
+    fn test_run_agent_resolves_through_registry() {
+        let config = ExecutorConfig {
+            agent_command: "codex".to_string(),
+            project_root: PathBuf::from("/tmp/project"),
+            ..Default::default()
+        };
+
+        let backend = config.agent_registry.resolve(&config.agent_command);
+        let invocation =
+            backend.build_invocation(&AgentRequest::new("test prompt", &config.project_root));
+
+        assert_eq!(invocation.program, "codex");
+        assert_eq!(invocation.args[0], "-C");
+        assert_eq!(invocation.args[1], "/tmp/project");
+        assert_eq!(invocation.args[2], "exec");
+        assert!(invocation.args.contains(&"--full-auto".to_string()));
+        assert!(invocation.args.contains(&"test prompt".to_string()));
     }
 }
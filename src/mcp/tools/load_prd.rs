@@ -6,6 +6,8 @@ use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::Path;
 
+use crate::quality::Verification;
+
 /// Request parameters for the load_prd tool.
 #[derive(Debug, Deserialize, Serialize, JsonSchema)]
 pub struct LoadPrdRequest {
@@ -113,7 +115,10 @@ impl Default for ParallelConfig {
 }
 
 /// User story structure for validation.
-#[derive(Debug, Deserialize)]
+///
+/// `agent`, `model`, `maxIterations`, `timeoutSeconds` and `qualityProfile`
+/// are optional per-story overrides of the run-wide settings.
+#[derive(Debug, Default, Deserialize)]
 pub struct PrdUserStory {
     /// Story ID (e.g., "US-001")
     pub id: String,
@@ -135,6 +140,27 @@ pub struct PrdUserStory {
     /// Files that this story will modify (for conflict detection)
     #[serde(rename = "targetFiles", default)]
     pub target_files: Vec<String>,
+    /// Agent to run this story with (overrides the run's agent)
+    #[serde(default)]
+    pub agent: Option<String>,
+    /// Model to ask the agent for (agents without model selection ignore it)
+    #[serde(default)]
+    pub model: Option<String>,
+    /// Maximum iterations for this story (overrides the run's limit)
+    #[serde(rename = "maxIterations", default)]
+    pub max_iterations: Option<u32>,
+    /// Agent timeout in seconds for this story
+    #[serde(rename = "timeoutSeconds", default)]
+    pub timeout_seconds: Option<u64>,
+    /// Quality profile whose gates this story must pass (e.g. "minimal")
+    #[serde(rename = "qualityProfile", default)]
+    pub quality_profile: Option<String>,
+    /// Conventional-commit type for the story's commit (e.g. "fix")
+    #[serde(rename = "commitType", default)]
+    pub commit_type: Option<String>,
+    /// Story-specific checks run as gates after the profile gates
+    #[serde(default)]
+    pub verify: Vec<Verification>,
 }
 
 /// Validation error types for PRD files.
@@ -234,6 +260,18 @@ fn validate_prd_structure(prd: &PrdFile) -> Result<(), PrdValidationError> {
                 story.id
             )));
         }
+        if story.max_iterations == Some(0) {
+            return Err(PrdValidationError::StructureError(format!(
+                "User story {} has maxIterations of 0",
+                story.id
+            )));
+        }
+        if story.timeout_seconds == Some(0) {
+            return Err(PrdValidationError::StructureError(format!(
+                "User story {} has timeoutSeconds of 0",
+                story.id
+            )));
+        }
     }
 
     Ok(())
@@ -442,6 +480,40 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_validate_prd_story_overrides() {
+        let mut file = NamedTempFile::new().unwrap();
+        let content = r#"{
+            "project": "Test",
+            "branchName": "main",
+            "userStories": [{"id": "US-001", "title": "Typo fix", "priority": 1, "passes": false,
+                "agent": "codex", "model": "gpt-5-mini", "maxIterations": 2,
+                "timeoutSeconds": 120, "qualityProfile": "minimal"}]
+        }"#;
+        file.write_all(content.as_bytes()).unwrap();
+
+        let prd = validate_prd(file.path()).unwrap();
+        let story = &prd.user_stories[0];
+        assert_eq!(story.agent.as_deref(), Some("codex"));
+        assert_eq!(story.model.as_deref(), Some("gpt-5-mini"));
+        assert_eq!(story.max_iterations, Some(2));
+        assert_eq!(story.timeout_seconds, Some(120));
+        assert_eq!(story.quality_profile.as_deref(), Some("minimal"));
+
+        let mut file = NamedTempFile::new().unwrap();
+        let content = r#"{
+            "project": "Test",
+            "branchName": "main",
+            "userStories": [{"id": "US-001", "title": "Typo fix", "priority": 1, "passes": false,
+                "maxIterations": 0}]
+        }"#;
+        file.write_all(content.as_bytes()).unwrap();
+        match validate_prd(file.path()).unwrap_err() {
+            PrdValidationError::StructureError(msg) => assert!(msg.contains("maxIterations")),
+            _ => panic!("Expected StructureError error"),
+        }
+    }
+
     #[test]
     fn test_create_success_response() {
         let prd_file = create_valid_prd();
@@ -0,0 +1,237 @@
+//! Run-level iteration and wall-clock limits.
+//!
+//! Unattended runs often have to fit a window: at most so many iterations,
+//! done within two hours, or finished by 07:00. The runners check these
+//! limits between iterations and, once one is reached, stop with a
+//! checkpoint the next run can resume from.
+
+use std::time::Duration;
+
+use chrono::{DateTime, Local, NaiveTime};
+
+/// Run-level caps on iterations and wall-clock time.
+///
+/// A cap of `None` means unlimited.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RunLimits {
+    /// Most iterations across all stories
+    pub max_total_iterations: Option<u32>,
+    /// Time by which the run must stop
+    pub deadline: Option<DateTime<Local>>,
+}
+
+impl RunLimits {
+    /// Create limits that never stop a run.
+    pub fn unlimited() -> Self {
+        Self::default()
+    }
+
+    /// Set the most iterations across all stories.
+    pub fn with_max_total_iterations(mut self, max_total_iterations: u32) -> Self {
+        self.max_total_iterations = Some(max_total_iterations);
+        self
+    }
+
+    /// Set the time by which the run must stop, keeping an earlier deadline.
+    pub fn with_deadline(mut self, deadline: DateTime<Local>) -> Self {
+        self.deadline = Some(match self.deadline {
+            Some(existing) => existing.min(deadline),
+            None => deadline,
+        });
+        self
+    }
+
+    /// Check whether any cap is set.
+    pub fn is_limited(&self) -> bool {
+        self.max_total_iterations.is_some() || self.deadline.is_some()
+    }
+
+    /// Check whether another iteration may start.
+    ///
+    /// `iterations` is the number of iterations the run has started so far.
+    ///
+    /// # Returns
+    /// A description of the reached limit, or None if the run may continue
+    pub fn check(&self, iterations: u32, now: DateTime<Local>) -> Option<String> {
+        if let Some(max) = self.max_total_iterations {
+            if iterations >= max {
+                return Some(format!("Max total iterations ({}) reached", max));
+            }
+        }
+
+        if let Some(deadline) = self.deadline {
+            if now >= deadline {
+                return Some(format!(
+                    "Deadline reached ({})",
+                    deadline.format("%Y-%m-%d %H:%M")
+                ));
+            }
+        }
+
+        None
+    }
+
+    /// Check whether the run may wait `wait` and still start an iteration
+    /// before its deadline.
+    ///
+    /// # Returns
+    /// A description of the deadline the wait would pass, or None if the run may wait
+    pub fn check_wait(&self, wait: Duration, now: DateTime<Local>) -> Option<String> {
+        let deadline = self.deadline?;
+        let resume = chrono::Duration::from_std(wait)
+            .ok()
+            .and_then(|wait| now.checked_add_signed(wait));
+        if resume.is_some_and(|resume| resume < deadline) {
+            return None;
+        }
+        Some(format!(
+            "Waiting {}s would pass the deadline ({})",
+            wait.as_secs(),
+            deadline.format("%Y-%m-%d %H:%M")
+        ))
+    }
+}
+
+/// Parse a duration such as `90s`, `45m`, `2h` or `1h30m`.
+///
+/// A bare number is taken as minutes.
+pub fn parse_duration(text: &str) -> Result<Duration, String> {
+    let text = text.trim();
+    if let Ok(minutes) = text.parse::<u64>() {
+        return Ok(Duration::from_secs(minutes * 60));
+    }
+
+    let invalid = || format!("invalid duration '{}' (expected e.g. 2h or 1h30m)", text);
+    let mut seconds = 0;
+    let mut amount = String::new();
+    for c in text.chars() {
+        if c.is_ascii_digit() {
+            amount.push(c);
+            continue;
+        }
+        let unit = match c {
+            'd' => 24 * 60 * 60,
+            'h' => 60 * 60,
+            'm' => 60,
+            's' => 1,
+            _ => return Err(invalid()),
+        };
+        let value: u64 = amount.parse().map_err(|_| invalid())?;
+        seconds += value * unit;
+        amount.clear();
+    }
+    if !amount.is_empty() || text.is_empty() {
+        return Err(invalid());
+    }
+    Ok(Duration::from_secs(seconds))
+}
+
+/// Parse a 24-hour clock time such as `07:00`.
+pub fn parse_time_of_day(text: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(text.trim(), "%H:%M")
+        .map_err(|_| format!("invalid time '{}' (expected HH:MM, e.g. 07:00)", text))
+}
+
+/// The next occurrence of `time` after `now`, today or tomorrow.
+pub fn next_time_of_day(time: NaiveTime, now: DateTime<Local>) -> Option<DateTime<Local>> {
+    let mut date = now.date_naive();
+    loop {
+        if let Some(at) = date.and_time(time).and_local_timezone(Local).earliest() {
+            if at > now {
+                return Some(at);
+            }
+        }
+        if date > now.date_naive() {
+            return None;
+        }
+        date = date.succ_opt()?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(hour: u32, minute: u32) -> DateTime<Local> {
+        Local
+            .with_ymd_and_hms(2026, 6, 10, hour, minute, 0)
+            .earliest()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_unlimited() {
+        let limits = RunLimits::unlimited();
+        assert!(!limits.is_limited());
+        assert!(limits.check(u32::MAX, at(23, 59)).is_none());
+    }
+
+    #[test]
+    fn test_iteration_limit() {
+        let limits = RunLimits::unlimited().with_max_total_iterations(5);
+        assert!(limits.is_limited());
+        assert!(limits.check(4, at(12, 0)).is_none());
+        assert_eq!(
+            limits.check(5, at(12, 0)).as_deref(),
+            Some("Max total iterations (5) reached")
+        );
+    }
+
+    #[test]
+    fn test_wait_must_end_before_deadline() {
+        assert!(RunLimits::unlimited()
+            .check_wait(Duration::from_secs(86_400), at(12, 0))
+            .is_none());
+
+        let limits = RunLimits::unlimited().with_deadline(at(13, 0));
+        assert!(limits
+            .check_wait(Duration::from_secs(30 * 60), at(12, 0))
+            .is_none());
+        assert_eq!(
+            limits
+                .check_wait(Duration::from_secs(90 * 60), at(12, 0))
+                .as_deref(),
+            Some("Waiting 5400s would pass the deadline (2026-06-10 13:00)")
+        );
+    }
+
+    #[test]
+    fn test_deadline_keeps_the_earliest() {
+        let limits = RunLimits::unlimited()
+            .with_deadline(at(7, 0))
+            .with_deadline(at(9, 0));
+        assert_eq!(limits.deadline, Some(at(7, 0)));
+        assert!(limits.check(0, at(6, 59)).is_none());
+        assert_eq!(
+            limits.check(0, at(7, 0)).as_deref(),
+            Some("Deadline reached (2026-06-10 07:00)")
+        );
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("90s"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_duration("2h"), Ok(Duration::from_secs(7200)));
+        assert_eq!(parse_duration("1h30m"), Ok(Duration::from_secs(5400)));
+        assert_eq!(parse_duration("1d"), Ok(Duration::from_secs(86400)));
+        assert_eq!(parse_duration("45"), Ok(Duration::from_secs(2700)));
+        assert!(parse_duration("").is_err());
+        assert!(parse_duration("2 hours").is_err());
+        assert!(parse_duration("h").is_err());
+        assert!(parse_duration("1h30").is_err());
+    }
+
+    #[test]
+    fn test_next_time_of_day() {
+        let seven = parse_time_of_day("07:00").unwrap();
+        assert_eq!(next_time_of_day(seven, at(6, 0)), Some(at(7, 0)));
+
+        // Past today's 07:00, the run may go on until tomorrow's
+        let tomorrow = next_time_of_day(seven, at(22, 30)).unwrap();
+        assert_eq!(tomorrow - at(7, 0), chrono::Duration::days(1));
+
+        assert!(parse_time_of_day("7am").is_err());
+        assert!(parse_time_of_day("25:00").is_err());
+    }
+}
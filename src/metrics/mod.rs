@@ -3,13 +3,78 @@
 //! This module provides infrastructure for collecting and analyzing
 //! execution metrics across story executions, iterations, and quality gates.
 
+pub mod limits;
+pub mod usage;
+
+pub use limits::RunLimits;
+pub use usage::{IterationUsage, ModelPricing, UsageBudget};
+
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::{Arc, RwLock};
 use std::time::{Duration, Instant};
 
+use crate::agent::{AgentEvent, TokenUsage};
 use crate::iteration::context::ErrorCategory;
 
+/// Counts of the actions an agent took, derived from its structured output.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AgentActivityCounts {
+    /// Tool calls other than file edits and shell commands
+    pub tool_calls: u32,
+    /// Files created or modified
+    pub file_edits: u32,
+    /// Shell commands run
+    pub shell_commands: u32,
+}
+
+impl AgentActivityCounts {
+    /// Count a single agent event.
+    pub fn record(&mut self, event: &AgentEvent) {
+        match event {
+            AgentEvent::ToolCall { .. } => self.tool_calls += 1,
+            AgentEvent::FileEdit { .. } => self.file_edits += 1,
+            AgentEvent::ShellCommand { .. } => self.shell_commands += 1,
+            _ => {}
+        }
+    }
+
+    /// Add another set of counts to this one.
+    pub fn add(&mut self, other: &AgentActivityCounts) {
+        self.tool_calls += other.tool_calls;
+        self.file_edits += other.file_edits;
+        self.shell_commands += other.shell_commands;
+    }
+
+    /// Total number of recorded actions.
+    pub fn total(&self) -> u32 {
+        self.tool_calls + self.file_edits + self.shell_commands
+    }
+}
+
+/// A switch to a fallback agent during a story.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AgentSwitch {
+    /// Iteration in which the switch happened
+    pub iteration: u32,
+    /// Agent that was replaced
+    pub from: String,
+    /// Fallback agent used from then on
+    pub to: String,
+    /// Why the previous agent was replaced
+    pub reason: String,
+}
+
+impl std::fmt::Display for AgentSwitch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} -> {} at iteration {} ({})",
+            self.from, self.to, self.iteration, self.reason
+        )
+    }
+}
+
 /// Metrics for a single story execution.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StoryMetrics {
@@ -33,6 +98,21 @@ pub struct StoryMetrics {
     pub started_at: std::time::SystemTime,
     /// Timestamp when execution completed
     pub completed_at: std::time::SystemTime,
+    /// Actions the agent took across all iterations
+    #[serde(default)]
+    pub agent_activity: AgentActivityCounts,
+    /// Tokens used across all iterations
+    #[serde(default)]
+    pub token_usage: TokenUsage,
+    /// Reported or estimated cost in USD across all iterations
+    #[serde(default)]
+    pub cost_usd: f64,
+    /// Usage broken down by iteration
+    #[serde(default)]
+    pub iteration_usage: Vec<IterationUsage>,
+    /// Fallback agent switches, in order
+    #[serde(default)]
+    pub agent_switches: Vec<AgentSwitch>,
 }
 
 impl StoryMetrics {
@@ -50,6 +130,11 @@ impl StoryMetrics {
             final_error: None,
             started_at: now,
             completed_at: now,
+            agent_activity: AgentActivityCounts::default(),
+            token_usage: TokenUsage::default(),
+            cost_usd: 0.0,
+            iteration_usage: Vec::new(),
+            agent_switches: Vec::new(),
         }
     }
 
@@ -71,6 +156,28 @@ impl StoryMetrics {
         total / self.gate_durations.len() as u32
     }
 
+    /// Record usage for an iteration, merging with any usage already recorded for it.
+    pub fn record_usage(&mut self, iteration: u32, tokens: &TokenUsage, cost_usd: f64) {
+        self.token_usage.add(tokens);
+        self.cost_usd += cost_usd;
+
+        match self
+            .iteration_usage
+            .iter_mut()
+            .find(|u| u.iteration == iteration)
+        {
+            Some(existing) => {
+                existing.tokens.add(tokens);
+                existing.cost_usd += cost_usd;
+            }
+            None => self.iteration_usage.push(IterationUsage {
+                iteration,
+                tokens: *tokens,
+                cost_usd,
+            }),
+        }
+    }
+
     /// Mark the story as completed.
     pub fn complete(&mut self, success: bool, duration: Duration) {
         self.success = success;
@@ -100,6 +207,18 @@ pub struct ExecutionMetrics {
     pub total_execution_time: Duration,
     /// First-time success rate (stories that passed on first iteration)
     pub first_time_success_rate: f64,
+    /// Agent actions aggregated across all stories
+    #[serde(default)]
+    pub agent_activity: AgentActivityCounts,
+    /// Tokens used across all stories
+    #[serde(default)]
+    pub token_usage: TokenUsage,
+    /// Reported or estimated cost in USD across all stories
+    #[serde(default)]
+    pub cost_usd: f64,
+    /// Fallback agent switches across all stories
+    #[serde(default)]
+    pub agent_switches: u32,
 }
 
 impl ExecutionMetrics {
@@ -173,6 +292,14 @@ pub struct MetricsBuilder {
     parallel_wall_time: Duration,
     /// Sum of individual story durations (for parallelism calculation)
     parallel_sum_time: Duration,
+    /// Pricing used when the agent does not report a cost
+    pricing: ModelPricing,
+    /// Tokens used across the whole run
+    run_tokens: TokenUsage,
+    /// Reported or estimated cost across the whole run
+    run_cost_usd: f64,
+    /// Iterations started across the whole run
+    run_iterations: u32,
 }
 
 impl MetricsBuilder {
@@ -184,9 +311,18 @@ impl MetricsBuilder {
             parallel_start: None,
             parallel_wall_time: Duration::ZERO,
             parallel_sum_time: Duration::ZERO,
+            pricing: ModelPricing::default(),
+            run_tokens: TokenUsage::default(),
+            run_cost_usd: 0.0,
+            run_iterations: 0,
         }
     }
 
+    /// Set the pricing used to estimate cost when the agent does not report one.
+    pub fn set_pricing(&mut self, pricing: ModelPricing) {
+        self.pricing = pricing;
+    }
+
     /// Start tracking a new story.
     pub fn start_story(&mut self, story_id: impl Into<String>, max_iterations: u32) {
         self.current_story = Some(StoryMetrics::new(story_id, max_iterations));
@@ -197,6 +333,7 @@ impl MetricsBuilder {
         if let Some(ref mut story) = self.current_story {
             story.iterations_used = iteration;
         }
+        self.run_iterations += 1;
     }
 
     /// Record a gate duration for the current story.
@@ -213,6 +350,44 @@ impl MetricsBuilder {
         }
     }
 
+    /// Record an agent event for the current story.
+    ///
+    /// Usage events count towards the run totals and are attributed to the
+    /// current iteration, using the reported cost or an estimate from the
+    /// configured pricing.
+    pub fn record_agent_event(&mut self, event: &AgentEvent) {
+        if let AgentEvent::Usage { usage, cost_usd } = event {
+            let cost = cost_usd.unwrap_or_else(|| self.pricing.estimate_cost(usage));
+            self.run_tokens.add(usage);
+            self.run_cost_usd += cost;
+
+            if let Some(ref mut story) = self.current_story {
+                story.record_usage(story.iterations_used, usage, cost);
+            }
+        }
+
+        if let Some(ref mut story) = self.current_story {
+            story.agent_activity.record(event);
+        }
+    }
+
+    /// Record a switch to a fallback agent for the current story.
+    pub fn record_agent_switch(&mut self, switch: AgentSwitch) {
+        if let Some(ref mut story) = self.current_story {
+            story.agent_switches.push(switch);
+        }
+    }
+
+    /// Get the tokens used and cost so far across the run.
+    pub fn run_usage(&self) -> (TokenUsage, f64) {
+        (self.run_tokens, self.run_cost_usd)
+    }
+
+    /// Get the number of iterations started so far across the run.
+    pub fn run_iterations(&self) -> u32 {
+        self.run_iterations
+    }
+
     /// Complete the current story.
     pub fn complete_story(&mut self, success: bool, duration: Duration, error: Option<String>) {
         if let Some(mut story) = self.current_story.take() {
@@ -287,6 +462,18 @@ impl MetricsBuilder {
             .map(|s| s.total_duration)
             .sum();
 
+        // Aggregate agent activity and usage
+        let mut agent_activity = AgentActivityCounts::default();
+        for story in &self.completed_stories {
+            agent_activity.add(&story.agent_activity);
+        }
+        let (token_usage, cost_usd) = self.run_usage();
+        let agent_switches = self
+            .completed_stories
+            .iter()
+            .map(|s| s.agent_switches.len() as u32)
+            .sum();
+
         // Calculate parallelism efficiency
         let parallelism_efficiency = if self.parallel_wall_time > Duration::ZERO {
             self.parallel_sum_time.as_secs_f64() / self.parallel_wall_time.as_secs_f64()
@@ -304,6 +491,10 @@ impl MetricsBuilder {
             failed_stories,
             total_execution_time,
             first_time_success_rate,
+            agent_activity,
+            token_usage,
+            cost_usd,
+            agent_switches,
         }
     }
 }
@@ -328,6 +519,15 @@ impl MetricsCollector {
         }
     }
 
+    /// Create a metrics collector that estimates cost with the given pricing.
+    pub fn with_pricing(pricing: ModelPricing) -> Self {
+        let mut builder = MetricsBuilder::new();
+        builder.set_pricing(pricing);
+        Self {
+            inner: Arc::new(RwLock::new(builder)),
+        }
+    }
+
     /// Start tracking a new story (thread-safe).
     pub fn start_story(&self, story_id: impl Into<String>, max_iterations: u32) {
         if let Ok(mut builder) = self.inner.write() {
@@ -356,6 +556,54 @@ impl MetricsCollector {
         }
     }
 
+    /// Record an agent event (thread-safe).
+    pub fn record_agent_event(&self, event: &AgentEvent) {
+        if let Ok(mut builder) = self.inner.write() {
+            builder.record_agent_event(event);
+        }
+    }
+
+    /// Record a switch to a fallback agent (thread-safe).
+    pub fn record_agent_switch(&self, switch: AgentSwitch) {
+        if let Ok(mut builder) = self.inner.write() {
+            builder.record_agent_switch(switch);
+        }
+    }
+
+    /// Get the tokens used and cost so far across the run (thread-safe).
+    pub fn run_usage(&self) -> (TokenUsage, f64) {
+        if let Ok(builder) = self.inner.read() {
+            builder.run_usage()
+        } else {
+            (TokenUsage::default(), 0.0)
+        }
+    }
+
+    /// Check run usage against a budget (thread-safe).
+    ///
+    /// # Returns
+    /// A description of the exceeded cap, or None if within budget
+    pub fn check_budget(&self, budget: &UsageBudget) -> Option<String> {
+        let (tokens, cost) = self.run_usage();
+        budget.check(&tokens, cost)
+    }
+
+    /// Get the number of iterations started so far across the run (thread-safe).
+    pub fn run_iterations(&self) -> u32 {
+        self.inner
+            .read()
+            .map(|builder| builder.run_iterations())
+            .unwrap_or(0)
+    }
+
+    /// Check the run's iterations and the clock against its limits (thread-safe).
+    ///
+    /// # Returns
+    /// A description of the reached limit, or None if the run may continue
+    pub fn check_run_limits(&self, limits: &RunLimits) -> Option<String> {
+        limits.check(self.run_iterations(), chrono::Local::now())
+    }
+
     /// Complete the current story (thread-safe).
     pub fn complete_story(&self, success: bool, duration: Duration, error: Option<String>) {
         if let Ok(mut builder) = self.inner.write() {
@@ -385,6 +633,9 @@ impl MetricsCollector {
             new_builder.completed_stories = builder.completed_stories.clone();
             new_builder.parallel_wall_time = builder.parallel_wall_time;
             new_builder.parallel_sum_time = builder.parallel_sum_time;
+            new_builder.pricing = builder.pricing;
+            new_builder.run_tokens = builder.run_tokens;
+            new_builder.run_cost_usd = builder.run_cost_usd;
             new_builder.build()
         } else {
             ExecutionMetrics::default()
@@ -444,6 +695,36 @@ pub fn format_metrics(metrics: &ExecutionMetrics) -> String {
         }
     }
 
+    // Model usage
+    if metrics.token_usage.total() > 0 {
+        output.push_str("\n### Model Usage\n");
+        output.push_str(&format!(
+            "- **Tokens**: {} input, {} output, {} cache read, {} cache write\n",
+            metrics.token_usage.input_tokens,
+            metrics.token_usage.output_tokens,
+            metrics.token_usage.cache_read_input_tokens,
+            metrics.token_usage.cache_creation_input_tokens
+        ));
+        output.push_str(&format!("- **Estimated Cost**: ${:.2}\n", metrics.cost_usd));
+    }
+
+    // Agent activity
+    if metrics.agent_activity.total() > 0 {
+        output.push_str("\n### Agent Activity\n");
+        output.push_str(&format!(
+            "- **File Edits**: {}\n- **Shell Commands**: {}\n- **Other Tool Calls**: {}\n",
+            metrics.agent_activity.file_edits,
+            metrics.agent_activity.shell_commands,
+            metrics.agent_activity.tool_calls
+        ));
+    }
+
+    // Fallback agents
+    if metrics.agent_switches > 0 {
+        output.push_str("\n### Fallback Agents\n");
+        output.push_str(&format!("- **Switches**: {}\n", metrics.agent_switches));
+    }
+
     // Error frequencies
     if !metrics.error_frequency.is_empty() {
         output.push_str("\n### Error Frequencies\n");
@@ -642,6 +923,137 @@ mod tests {
     }
 
     #[test]
+    fn test_metrics_collector_agent_activity() {
+        let collector = MetricsCollector::new();
+
+        collector.start_story("US-001", 10);
+        collector.record_agent_event(&AgentEvent::FileEdit {
+            path: "src/lib.rs".to_string(),
+            tool: "Edit".to_string(),
+        });
+        collector.record_agent_event(&AgentEvent::ShellCommand {
+            command: "cargo test".to_string(),
+            exit_code: Some(0),
+        });
+        collector.record_agent_event(&AgentEvent::AssistantText {
+            text: "done".to_string(),
+        });
+        collector.complete_story(true, Duration::from_secs(30), None);
+
+        let metrics = collector.build();
+        assert_eq!(metrics.agent_activity.file_edits, 1);
+        assert_eq!(metrics.agent_activity.shell_commands, 1);
+        assert_eq!(metrics.agent_activity.tool_calls, 0);
+        assert!(format_metrics(&metrics).contains("Agent Activity"));
+    }
+
+    #[test]
+    fn test_metrics_collector_usage_per_iteration() {
+        let collector = MetricsCollector::new();
+
+        collector.start_story("US-001", 10);
+        collector.record_iteration(1);
+        collector.record_agent_event(&AgentEvent::Usage {
+            usage: TokenUsage {
+                input_tokens: 1000,
+                output_tokens: 200,
+                ..Default::default()
+            },
+            cost_usd: Some(0.25),
+        });
+        collector.record_iteration(2);
+        collector.record_agent_event(&AgentEvent::Usage {
+            usage: TokenUsage {
+                input_tokens: 1_000_000,
+                ..Default::default()
+            },
+            cost_usd: None,
+        });
+
+        // Usage of the story in progress counts towards the run
+        let (tokens, cost) = collector.run_usage();
+        assert_eq!(tokens.input_tokens, 1_001_000);
+        assert!((cost - 3.25).abs() < 1e-9);
+
+        collector.complete_story(true, Duration::from_secs(30), None);
+        let metrics = collector.build();
+        assert_eq!(metrics.token_usage.output_tokens, 200);
+        assert!((metrics.cost_usd - 3.25).abs() < 1e-9);
+        assert!(format_metrics(&metrics).contains("Estimated Cost**: $3.25"));
+    }
+
+    #[test]
+    fn test_metrics_collector_agent_switches() {
+        let collector = MetricsCollector::new();
+
+        collector.start_story("US-001", 10);
+        collector.record_agent_switch(AgentSwitch {
+            iteration: 2,
+            from: "claude".to_string(),
+            to: "codex".to_string(),
+            reason: "usage limit".to_string(),
+        });
+        collector.complete_story(true, Duration::from_secs(30), None);
+
+        let metrics = collector.build();
+        assert_eq!(metrics.agent_switches, 1);
+        assert!(format_metrics(&metrics).contains("Switches**: 1"));
+    }
+
+    #[test]
+    fn test_story_metrics_record_usage_merges_iteration() {
+        let mut story = StoryMetrics::new("US-001", 5);
+        let usage = TokenUsage {
+            output_tokens: 10,
+            ..Default::default()
+        };
+        story.record_usage(1, &usage, 0.1);
+        story.record_usage(1, &usage, 0.1);
+        story.record_usage(2, &usage, 0.1);
+
+        assert_eq!(story.iteration_usage.len(), 2);
+        assert_eq!(story.iteration_usage[0].tokens.output_tokens, 20);
+        assert_eq!(story.token_usage.output_tokens, 30);
+    }
+
+    #[test]
+    fn test_metrics_collector_check_budget() {
+        let collector = MetricsCollector::new();
+        let budget = UsageBudget::unlimited().with_max_tokens(500);
+
+        collector.start_story("US-001", 10);
+        collector.record_iteration(1);
+        assert!(collector.check_budget(&budget).is_none());
+
+        collector.record_agent_event(&AgentEvent::Usage {
+            usage: TokenUsage {
+                input_tokens: 600,
+                ..Default::default()
+            },
+            cost_usd: None,
+        });
+        assert!(collector.check_budget(&budget).is_some());
+    }
+
+    #[test]
+    fn test_metrics_collector_counts_run_iterations() {
+        let collector = MetricsCollector::new();
+        let limits = RunLimits::unlimited().with_max_total_iterations(3);
+
+        collector.start_story("US-001", 10);
+        collector.record_iteration(1);
+        collector.record_iteration(2);
+        collector.start_story("US-002", 10);
+        collector.record_iteration(1);
+        assert_eq!(collector.run_iterations(), 3);
+        assert_eq!(
+            collector.check_run_limits(&limits).as_deref(),
+            Some("Max total iterations (3) reached")
+        );
+    }
+
+    #[test]
+    #[allow(clippy::field_reassign_with_default)]
     fn test_format_metrics() {
         let mut metrics = ExecutionMetrics::default();
         metrics.total_stories = 10;
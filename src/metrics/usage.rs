@@ -0,0 +1,184 @@
+//! Token usage, cost estimation and budget caps.
+//!
+//! This module provides the pricing used to estimate the cost of agent runs
+//! that do not report one, per-iteration usage records, and the run-level
+//! budget that pauses execution once it is exceeded.
+
+use serde::{Deserialize, Serialize};
+
+use crate::agent::TokenUsage;
+
+/// Per-token pricing for a model, in USD per million tokens.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ModelPricing {
+    /// Price per million input tokens
+    pub input_per_mtok: f64,
+    /// Price per million output tokens
+    pub output_per_mtok: f64,
+    /// Price per million tokens read from the prompt cache
+    pub cache_read_per_mtok: f64,
+    /// Price per million tokens written to the prompt cache
+    pub cache_write_per_mtok: f64,
+}
+
+impl Default for ModelPricing {
+    /// Defaults to Claude Sonnet list pricing.
+    fn default() -> Self {
+        Self {
+            input_per_mtok: 3.0,
+            output_per_mtok: 15.0,
+            cache_read_per_mtok: 0.30,
+            cache_write_per_mtok: 3.75,
+        }
+    }
+}
+
+impl ModelPricing {
+    /// Estimate the cost of the given usage in USD.
+    pub fn estimate_cost(&self, usage: &TokenUsage) -> f64 {
+        let per_token = |tokens: u64, per_mtok: f64| tokens as f64 * per_mtok / 1_000_000.0;
+        per_token(usage.input_tokens, self.input_per_mtok)
+            + per_token(usage.output_tokens, self.output_per_mtok)
+            + per_token(usage.cache_read_input_tokens, self.cache_read_per_mtok)
+            + per_token(usage.cache_creation_input_tokens, self.cache_write_per_mtok)
+    }
+}
+
+/// Usage recorded for a single iteration.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct IterationUsage {
+    /// Iteration number (1-indexed)
+    pub iteration: u32,
+    /// Tokens used during the iteration
+    pub tokens: TokenUsage,
+    /// Reported or estimated cost in USD
+    pub cost_usd: f64,
+}
+
+/// Run-level caps on model usage.
+///
+/// A cap of `None` means unlimited.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct UsageBudget {
+    /// Maximum estimated spend in USD
+    pub max_cost_usd: Option<f64>,
+    /// Maximum total tokens
+    pub max_tokens: Option<u64>,
+}
+
+impl UsageBudget {
+    /// Create an unlimited budget.
+    pub fn unlimited() -> Self {
+        Self::default()
+    }
+
+    /// Set the maximum spend in USD.
+    pub fn with_max_cost_usd(mut self, max_cost_usd: f64) -> Self {
+        self.max_cost_usd = Some(max_cost_usd);
+        self
+    }
+
+    /// Set the maximum total tokens.
+    pub fn with_max_tokens(mut self, max_tokens: u64) -> Self {
+        self.max_tokens = Some(max_tokens);
+        self
+    }
+
+    /// Check whether any cap is set.
+    pub fn is_limited(&self) -> bool {
+        self.max_cost_usd.is_some() || self.max_tokens.is_some()
+    }
+
+    /// Check usage against the budget.
+    ///
+    /// # Returns
+    /// A description of the exceeded cap, or None if within budget
+    pub fn check(&self, tokens: &TokenUsage, cost_usd: f64) -> Option<String> {
+        if let Some(max_cost) = self.max_cost_usd {
+            if cost_usd >= max_cost {
+                return Some(format!(
+                    "Cost budget exceeded: ${:.2} spent of ${:.2}",
+                    cost_usd, max_cost
+                ));
+            }
+        }
+
+        if let Some(max_tokens) = self.max_tokens {
+            if tokens.total() >= max_tokens {
+                return Some(format!(
+                    "Token budget exceeded: {} tokens used of {}",
+                    tokens.total(),
+                    max_tokens
+                ));
+            }
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimate_cost() {
+        let pricing = ModelPricing::default();
+        let usage = TokenUsage {
+            input_tokens: 1_000_000,
+            output_tokens: 100_000,
+            cache_read_input_tokens: 0,
+            cache_creation_input_tokens: 0,
+        };
+        let cost = pricing.estimate_cost(&usage);
+        assert!((cost - 4.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_estimate_cost_zero() {
+        assert_eq!(
+            ModelPricing::default().estimate_cost(&TokenUsage::default()),
+            0.0
+        );
+    }
+
+    #[test]
+    fn test_unlimited_budget() {
+        let budget = UsageBudget::unlimited();
+        assert!(!budget.is_limited());
+        let usage = TokenUsage {
+            input_tokens: u64::MAX / 2,
+            ..Default::default()
+        };
+        assert!(budget.check(&usage, 1_000_000.0).is_none());
+    }
+
+    #[test]
+    fn test_cost_budget() {
+        let budget = UsageBudget::unlimited().with_max_cost_usd(5.0);
+        assert!(budget.is_limited());
+        assert!(budget.check(&TokenUsage::default(), 4.99).is_none());
+
+        let exceeded = budget.check(&TokenUsage::default(), 5.12).unwrap();
+        assert!(exceeded.contains("$5.12"));
+        assert!(exceeded.contains("$5.00"));
+    }
+
+    #[test]
+    fn test_token_budget() {
+        let budget = UsageBudget::unlimited().with_max_tokens(1000);
+        let under = TokenUsage {
+            input_tokens: 600,
+            output_tokens: 300,
+            ..Default::default()
+        };
+        assert!(budget.check(&under, 0.0).is_none());
+
+        let over = TokenUsage {
+            input_tokens: 600,
+            output_tokens: 500,
+            ..Default::default()
+        };
+        assert!(budget.check(&over, 0.0).unwrap().contains("1100 tokens"));
+    }
+}
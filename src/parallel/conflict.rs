@@ -131,6 +131,7 @@ mod tests {
             futility_verdict: None,
             iteration_context: None,
             needs_guidance: false,
+            split_into: Vec::new(),
         }
     }
 
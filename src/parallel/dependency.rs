@@ -304,6 +304,7 @@ mod tests {
             passes: false,
             depends_on: depends_on.into_iter().map(String::from).collect(),
             target_files: vec![],
+            ..Default::default()
         }
     }
 
@@ -552,6 +553,7 @@ mod tests {
             passes,
             depends_on: depends_on.into_iter().map(String::from).collect(),
             target_files: vec![],
+            ..Default::default()
         }
     }
 
@@ -650,6 +652,7 @@ mod tests {
             passes: false,
             depends_on: depends_on.into_iter().map(String::from).collect(),
             target_files: target_files.into_iter().map(String::from).collect(),
+            ..Default::default()
         }
     }
 
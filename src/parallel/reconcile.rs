@@ -2,10 +2,13 @@
 //!
 //! This module handles detection and reporting of issues that may arise from parallel
 //! execution of stories, including git conflicts, type mismatches, and duplicate imports.
+//! It also merges completed story branches back from their worktrees.
 
 use std::path::PathBuf;
 use std::process::Command;
 
+use crate::git::StoryWorktree;
+
 /// Issues that can be detected during reconciliation
 #[allow(dead_code)]
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -24,6 +27,15 @@ pub enum ReconciliationIssue {
     },
     /// Duplicate import detected
     ImportDuplicate,
+    /// A story branch could not be merged back into the feature branch
+    MergeConflict {
+        /// Story whose branch failed to merge
+        story_id: String,
+        /// Story branch that was being merged
+        branch: String,
+        /// Files that conflicted (empty if the merge failed for another reason)
+        affected_files: Vec<String>,
+    },
 }
 
 /// Result of reconciliation analysis
@@ -154,6 +166,56 @@ impl ReconciliationEngine {
         issues
     }
 
+    /// Merges a completed story's branch into the checked-out branch
+    ///
+    /// Runs `git merge --no-ff` for the story branch of `worktree`. If the merge
+    /// fails, the conflicting files are collected and the merge is aborted so the
+    /// project is left as it was.
+    ///
+    /// # Returns
+    /// A vector containing a `ReconciliationIssue::MergeConflict` if the merge failed.
+    /// Returns an empty vector if the branch merged cleanly.
+    pub fn merge_story(&self, worktree: &StoryWorktree) -> Vec<ReconciliationIssue> {
+        let message = format!("Merge story {}", worktree.story_id);
+        let merged = Command::new("git")
+            .args([
+                "merge",
+                "--no-ff",
+                "--no-edit",
+                "-m",
+                &message,
+                &worktree.branch,
+            ])
+            .current_dir(&self.project_root)
+            .output()
+            .map(|output| output.status.success())
+            .unwrap_or(false);
+
+        if merged {
+            return Vec::new();
+        }
+
+        let affected_files = self
+            .check_git_conflicts()
+            .into_iter()
+            .flat_map(|issue| match issue {
+                ReconciliationIssue::GitConflict { affected_files } => affected_files,
+                _ => Vec::new(),
+            })
+            .collect();
+
+        let _ = Command::new("git")
+            .args(["merge", "--abort"])
+            .current_dir(&self.project_root)
+            .output();
+
+        vec![ReconciliationIssue::MergeConflict {
+            story_id: worktree.story_id.clone(),
+            branch: worktree.branch.clone(),
+            affected_files,
+        }]
+    }
+
     /// Runs full reconciliation checking for all known issue types
     ///
     /// This method combines all individual checks (git conflicts, type errors)
@@ -210,7 +272,16 @@ impl ReconciliationEngine {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::git::run_git;
+    use crate::git::test_support::init_repo;
     use std::env;
+    use std::path::Path;
+
+    fn commit_file(dir: &Path, name: &str, contents: &str) {
+        std::fs::write(dir.join(name), contents).unwrap();
+        run_git(dir, &["add", "-A"]).unwrap();
+        run_git(dir, &["commit", "-q", "-m", name]).unwrap();
+    }
 
     #[test]
     fn test_reconciliation_engine_new() {
@@ -318,6 +389,46 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_merge_story_clean() {
+        let dir = tempfile::TempDir::new().unwrap();
+        init_repo(dir.path());
+        let worktree = StoryWorktree::create(dir.path(), "US-001").unwrap();
+        commit_file(&worktree.path, "feature.txt", "feature\n");
+
+        let engine = ReconciliationEngine::new(dir.path().to_path_buf());
+        assert!(engine.merge_story(&worktree).is_empty());
+        assert!(dir.path().join("feature.txt").exists());
+        let subject = run_git(dir.path(), &["log", "-1", "--format=%s"]).unwrap();
+        assert_eq!(subject, "Merge story US-001");
+    }
+
+    #[test]
+    fn test_merge_story_conflict() {
+        let dir = tempfile::TempDir::new().unwrap();
+        init_repo(dir.path());
+        let worktree = StoryWorktree::create(dir.path(), "US-002").unwrap();
+        commit_file(&worktree.path, "README.md", "# Story\n");
+        commit_file(dir.path(), "README.md", "# Main\n");
+
+        let engine = ReconciliationEngine::new(dir.path().to_path_buf());
+        let issues = engine.merge_story(&worktree);
+        assert_eq!(
+            issues,
+            vec![ReconciliationIssue::MergeConflict {
+                story_id: "US-002".to_string(),
+                branch: "ralph/story/US-002".to_string(),
+                affected_files: vec!["README.md".to_string()],
+            }]
+        );
+        // The merge was aborted, leaving the main tree untouched
+        assert!(engine.check_git_conflicts().is_empty());
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("README.md")).unwrap(),
+            "# Main\n"
+        );
+    }
+
     #[test]
     fn test_reconcile_clean_project() {
         // Use current directory which should be a clean project
@@ -2,16 +2,19 @@
 
 use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
 use std::time::{Duration, Instant};
 
 use tokio::sync::{mpsc, watch, Mutex, RwLock, Semaphore};
 
-use crate::mcp::tools::executor::{detect_agent, ExecutorConfig, StoryExecutor};
-use crate::mcp::tools::load_prd::{validate_prd, PrdFile};
+use crate::checkpoint::{Checkpoint, CheckpointManager, PauseReason};
+use crate::git::{is_git_repository, BranchSetup, StoryWorktree};
+use crate::mcp::tools::executor::{ExecutorConfig, ExecutorError, StoryExecutor};
+use crate::mcp::tools::load_prd::{validate_prd, PrdFile, PrdUserStory};
+use crate::metrics::MetricsCollector;
 use crate::parallel::dependency::{DependencyGraph, StoryNode};
 use crate::parallel::reconcile::{ReconciliationEngine, ReconciliationIssue, ReconciliationResult};
-use crate::runner::{RunResult, RunnerConfig};
+use crate::runner::{describe_branch_setup, RunResult, RunnerConfig};
 use crate::timeout::TimeoutConfig;
 use crate::ui::parallel_display::ParallelRunnerDisplay;
 use crate::ui::parallel_events::{ParallelUIEvent, StoryDisplayInfo};
@@ -49,6 +52,10 @@ pub struct ParallelRunnerConfig {
     /// If a batch takes longer than this, remaining tasks are cancelled.
     /// Default: 30 minutes.
     pub batch_timeout: Duration,
+    /// Whether to run each story in its own git worktree on a story branch.
+    /// Completed stories are merged back into the feature branch after each batch.
+    /// Ignored when the project is not a git repository.
+    pub use_worktrees: bool,
 }
 
 impl Default for ParallelRunnerConfig {
@@ -60,6 +67,7 @@ impl Default for ParallelRunnerConfig {
             conflict_strategy: ConflictStrategy::default(),
             timeout_config: TimeoutConfig::default(),
             batch_timeout: Duration::from_secs(1800), // 30 minutes
+            use_worktrees: true,
         }
     }
 }
@@ -219,6 +227,10 @@ pub struct ParallelRunner {
     git_mutex: Arc<Mutex<()>>,
     /// Optional channel sender for UI events during parallel execution.
     ui_tx: Option<mpsc::Sender<ParallelUIEvent>>,
+    /// Run-level metrics shared by all story executors (used for budget checks).
+    metrics: MetricsCollector,
+    /// Feature branch the run commits to, once prepared.
+    branch_setup: OnceLock<BranchSetup>,
 }
 
 #[allow(dead_code)]
@@ -240,6 +252,8 @@ impl ParallelRunner {
             execution_state,
             git_mutex,
             ui_tx: None,
+            metrics: MetricsCollector::new(),
+            branch_setup: OnceLock::new(),
         }
     }
 
@@ -266,7 +280,7 @@ impl ParallelRunner {
             }
         };
 
-        let total_stories = prd.user_stories.len();
+        let mut total_stories = prd.user_stories.len();
 
         // Build dependency graph
         let mut graph = DependencyGraph::from_stories(&prd.user_stories);
@@ -321,7 +335,11 @@ impl ParallelRunner {
         }
 
         // Detect agent (only needed if there are failing stories)
-        let agent = match self.base_config.agent_command.clone().or_else(detect_agent) {
+        let agent = match self
+            .base_config
+            .agent_registry
+            .resolve_or_detect(self.base_config.agent_command.as_deref())
+        {
             Some(a) => a,
             None => {
                 return RunResult {
@@ -337,7 +355,48 @@ impl ParallelRunner {
             }
         };
 
+        // Executor configuration for each pending story, with its PRD overrides applied
+        let mut story_configs = HashMap::new();
+        for story in prd.user_stories.iter().filter(|s| !s.passes) {
+            match self.story_executor_config(&agent, story) {
+                Ok(config) => {
+                    story_configs.insert(story.id.clone(), config);
+                }
+                Err(e) => {
+                    return RunResult {
+                        all_passed: false,
+                        stories_passed: initially_passing.len(),
+                        total_stories,
+                        total_iterations: 0,
+                        error: Some(format!("Fatal error: {}", e)),
+                    };
+                }
+            }
+        }
+
+        // Commit to the PRD's feature branch, never whatever happens to be checked out
+        match self.base_config.prepare_branch(&prd) {
+            Ok(Some(setup)) => {
+                if !self.base_config.display_options.quiet {
+                    println!("{}", describe_branch_setup(&setup));
+                }
+                let _ = self.branch_setup.set(setup);
+            }
+            Ok(None) => {}
+            Err(e) => {
+                return RunResult {
+                    all_passed: false,
+                    stories_passed: initially_passing.len(),
+                    total_stories,
+                    total_iterations: 0,
+                    error: Some(format!("Branch setup failed: {}", e)),
+                };
+            }
+        }
+
         let mut total_iterations: u32 = 0;
+        let use_worktrees =
+            self.config.use_worktrees && is_git_repository(&self.base_config.working_dir);
 
         // Check if UI should be enabled based on display options
         // Skip UI rendering when quiet mode is set or UI mode is disabled
@@ -445,7 +504,7 @@ impl ParallelRunner {
         };
 
         // Build story info lookup for event creation
-        let story_info_map: HashMap<String, StoryDisplayInfo> = prd
+        let mut story_info_map: HashMap<String, StoryDisplayInfo> = prd
             .user_stories
             .iter()
             .map(|s| {
@@ -462,14 +521,16 @@ impl ParallelRunner {
             let state = self.execution_state.read().await;
             let completed = state.completed.clone();
             let in_flight = state.in_flight.clone();
+            let failed: HashSet<String> = state.failed.keys().cloned().collect();
             drop(state);
 
-            // Get stories ready to execute (dependencies satisfied, not completed, not in flight)
+            // Get stories ready to execute (dependencies satisfied, not completed,
+            // not in flight, not already failed)
             // Keep the full StoryNode so we have access to target_files for locking
             let ready_stories: Vec<_> = graph
                 .get_ready_stories(&completed)
                 .into_iter()
-                .filter(|s| !in_flight.contains(&s.id))
+                .filter(|s| !in_flight.contains(&s.id) && !failed.contains(&s.id))
                 .cloned()
                 .collect();
 
@@ -537,6 +598,7 @@ impl ParallelRunner {
 
             // Spawn tasks for ready stories (up to available semaphore permits)
             let mut handles = Vec::new();
+            let mut batch_worktrees = Vec::new();
 
             // Count concurrent stories for event reporting
             let concurrent_count = {
@@ -550,6 +612,9 @@ impl ParallelRunner {
             for story in ready_stories {
                 let story_id = story.id.clone();
                 let target_files = story.target_files.clone();
+                let Some(mut executor_config) = story_configs.get(&story_id).cloned() else {
+                    continue;
+                };
 
                 // Try to acquire semaphore permit
                 let permit = match self.semaphore.clone().try_acquire_owned() {
@@ -569,19 +634,39 @@ impl ParallelRunner {
                     state.in_flight.insert(story_id.clone());
                 }
 
-                // Clone values for the spawned task
-                let executor_config = ExecutorConfig {
-                    prd_path: self.base_config.prd_path.clone(),
-                    project_root: self.base_config.working_dir.clone(),
-                    progress_path: self.base_config.working_dir.join("progress.txt"),
-                    quality_profile: None,
-                    agent_command: agent.clone(),
-                    max_iterations: self.base_config.max_iterations_per_story,
-                    git_mutex: Some(self.git_mutex.clone()),
-                    timeout_config: self.config.timeout_config.clone(),
-                    ..Default::default()
-                };
+                // Give the story its own checkout so agents never share uncommitted edits
+                if use_worktrees {
+                    let created = {
+                        let _guard = self.git_mutex.lock().await;
+                        StoryWorktree::create(&self.base_config.working_dir, &story_id)
+                    };
+                    match created {
+                        Ok(worktree) => {
+                            executor_config.project_root = worktree.path.clone();
+                            batch_worktrees.push(worktree);
+                        }
+                        Err(e) => {
+                            let error = format!("Failed to create worktree: {}", e);
+                            let mut state = self.execution_state.write().await;
+                            state.in_flight.remove(&story_id);
+                            state.release_locks(&story_id);
+                            state.failed.insert(story_id.clone(), error.clone());
+                            if let Some(ref sender) = ui_sender {
+                                let event = ParallelUIEvent::StoryFailed {
+                                    story_id: story_id.clone(),
+                                    error,
+                                    iteration: 1,
+                                };
+                                let _ = sender.try_send(event);
+                            }
+                            continue;
+                        }
+                    }
+                }
+                // Checkpoints always go to the main project, not the worktree
+                let checkpoint_manager = CheckpointManager::new(&self.base_config.working_dir).ok();
 
+                // Clone values for the spawned task
                 let execution_state = self.execution_state.clone();
                 let story_id_clone = story_id.clone();
                 let task_ui_sender = ui_sender.clone();
@@ -605,7 +690,8 @@ impl ParallelRunner {
                         let _ = sender.try_send(event);
                     }
 
-                    let executor = StoryExecutor::new(executor_config);
+                    let executor =
+                        StoryExecutor::with_checkpoint_manager(executor_config, checkpoint_manager);
                     let (_cancel_tx, cancel_rx) = watch::channel(false);
 
                     // Clone for iteration callback closure
@@ -649,12 +735,18 @@ impl ParallelRunner {
                             (story_id_clone, true, exec_result.iterations_used)
                         }
                         Ok(exec_result) => {
-                            let error_msg = exec_result
-                                .error
-                                .unwrap_or_else(|| "Unknown error".to_string());
-                            state
-                                .failed
-                                .insert(story_id_clone.clone(), error_msg.clone());
+                            // A split story is replaced by its sub-stories, not failed
+                            let error_msg = if exec_result.split_into.is_empty() {
+                                let error_msg = exec_result
+                                    .error
+                                    .unwrap_or_else(|| "Unknown error".to_string());
+                                state
+                                    .failed
+                                    .insert(story_id_clone.clone(), error_msg.clone());
+                                error_msg
+                            } else {
+                                format!("Split into {}", exec_result.split_into.join(", "))
+                            };
                             // Send StoryFailed event
                             if let Some(ref sender) = task_ui_sender {
                                 let event = ParallelUIEvent::StoryFailed {
@@ -666,6 +758,10 @@ impl ParallelRunner {
                             }
                             (story_id_clone, false, exec_result.iterations_used)
                         }
+                        Err(ExecutorError::RunLimitReached(iteration, _)) => {
+                            // Stopped rather than failed; the run ends after this batch
+                            (story_id_clone, false, iteration - 1)
+                        }
                         Err(e) => {
                             state.failed.insert(story_id_clone.clone(), e.to_string());
                             // Send StoryFailed event
@@ -693,50 +789,93 @@ impl ParallelRunner {
                     state.in_flight.iter().cloned().collect()
                 };
 
-                // Wait for all handles to complete, with batch timeout
-                let batch_future = futures::future::join_all(handles);
-                let batch_result =
-                    tokio::time::timeout(self.config.batch_timeout, batch_future).await;
-
-                match batch_result {
-                    Ok(results) => {
-                        for (_story_id, _success, iterations) in results.into_iter().flatten() {
-                            total_iterations += iterations;
-                        }
+                // Wait for all handles to complete, with batch timeout. Stories
+                // still running at the timeout are aborted, and their agents
+                // killed, before any worktree is merged or removed.
+                let abort_handles: Vec<_> = handles.iter().map(|h| h.abort_handle()).collect();
+                let mut batch_future = Box::pin(futures::future::join_all(handles));
+                let timed_out = tokio::time::timeout(self.config.batch_timeout, &mut batch_future)
+                    .await
+                    .is_err();
+                if timed_out {
+                    for handle in &abort_handles {
+                        handle.abort();
                     }
-                    Err(_) => {
-                        // Batch timed out - mark all in-flight stories as failed
-                        let mut state = self.execution_state.write().await;
-                        for story_id in &batch_story_ids {
-                            if state.in_flight.contains(story_id) {
-                                state.in_flight.remove(story_id);
-                                state.release_locks(story_id);
-                                state.failed.insert(
-                                    story_id.clone(),
-                                    format!(
+                }
+                let results = batch_future.await;
+                for (_story_id, _success, iterations) in results.into_iter().flatten() {
+                    total_iterations += iterations;
+                }
+
+                if timed_out {
+                    // Batch timed out - mark the aborted stories as failed, so
+                    // their worktrees are removed without being merged
+                    let mut state = self.execution_state.write().await;
+                    for story_id in &batch_story_ids {
+                        if state.in_flight.contains(story_id) {
+                            state.in_flight.remove(story_id);
+                            state.release_locks(story_id);
+                            state.failed.insert(
+                                story_id.clone(),
+                                format!("Batch timed out after {:?}", self.config.batch_timeout),
+                            );
+                            // Send StoryFailed event
+                            if let Some(ref sender) = ui_sender {
+                                let event = ParallelUIEvent::StoryFailed {
+                                    story_id: story_id.clone(),
+                                    error: format!(
                                         "Batch timed out after {:?}",
                                         self.config.batch_timeout
                                     ),
-                                );
-                                // Send StoryFailed event
-                                if let Some(ref sender) = ui_sender {
-                                    let event = ParallelUIEvent::StoryFailed {
-                                        story_id: story_id.clone(),
-                                        error: format!(
-                                            "Batch timed out after {:?}",
-                                            self.config.batch_timeout
-                                        ),
-                                        iteration: 1,
-                                    };
-                                    let _ = sender.try_send(event);
-                                }
+                                    iteration: 1,
+                                };
+                                let _ = sender.try_send(event);
                             }
                         }
-                        drop(state);
+                    }
+                    drop(state);
+                }
 
-                        // Continue to next iteration - some stories may have completed
-                        // before the timeout and their results were handled in the spawned task
-                        continue;
+                // Bring completed stories back into the feature branch
+                let merge_issues = self.merge_worktrees(batch_worktrees, &graph).await;
+
+                if timed_out {
+                    // Continue to next iteration - some stories may have completed
+                    // before the timeout and their results were handled in the spawned task
+                    continue;
+                }
+
+                // Stop scheduling new stories once the run budget is used up
+                if let Some(reason) = self.metrics.check_budget(&self.base_config.budget) {
+                    self.save_run_checkpoint(PauseReason::BudgetExceeded(reason.clone()));
+                    let state = self.execution_state.read().await;
+                    return RunResult {
+                        all_passed: false,
+                        stories_passed: state.completed.len(),
+                        total_stories,
+                        total_iterations,
+                        error: Some(format!(
+                            "{}. Checkpoint saved. Raise the budget and resume with: ralph --resume",
+                            reason
+                        )),
+                    };
+                }
+
+                // Stop between batches once a run limit is reached
+                if let Some(reached) = self.metrics.check_run_limits(&self.base_config.run_limits) {
+                    let state = self.execution_state.read().await;
+                    if state.completed.len() < total_stories {
+                        self.save_run_checkpoint(PauseReason::IterationBoundary);
+                        return RunResult {
+                            all_passed: false,
+                            stories_passed: state.completed.len(),
+                            total_stories,
+                            total_iterations,
+                            error: Some(format!(
+                                "{}. Checkpoint saved. Resume with: ralph --resume",
+                                reached
+                            )),
+                        };
                     }
                 }
 
@@ -744,8 +883,9 @@ impl ParallelRunner {
                 let reconciliation_result = self
                     .run_reconciliation(
                         &batch_story_ids,
+                        merge_issues,
                         &graph,
-                        &agent,
+                        &story_configs,
                         &mut total_iterations,
                         &ui_sender,
                         &story_info_map,
@@ -763,25 +903,192 @@ impl ParallelRunner {
                         error: Some(error),
                     };
                 }
+
+                // Schedule the sub-stories of stories split in this batch
+                match self.pick_up_split_stories(&agent, &mut graph, &mut story_configs) {
+                    Ok(Some(prd)) => {
+                        total_stories = prd.user_stories.len();
+                        story_info_map.extend(prd.user_stories.iter().map(|s| {
+                            (
+                                s.id.clone(),
+                                StoryDisplayInfo::new(&s.id, &s.title, s.priority),
+                            )
+                        }));
+                    }
+                    Ok(None) => {}
+                    Err(error) => {
+                        let state = self.execution_state.read().await;
+                        return RunResult {
+                            all_passed: false,
+                            stories_passed: state.completed.len(),
+                            total_stories,
+                            total_iterations,
+                            error: Some(error),
+                        };
+                    }
+                }
             }
         }
     }
 
+    /// Rebuild the dependency graph if stories were split since it was built.
+    ///
+    /// Returns the reloaded PRD when it gained stories, after adding an
+    /// executor configuration for each new one.
+    fn pick_up_split_stories(
+        &self,
+        agent: &str,
+        graph: &mut DependencyGraph,
+        story_configs: &mut HashMap<String, ExecutorConfig>,
+    ) -> Result<Option<PrdFile>, String> {
+        let prd = self.load_prd()?;
+        if prd
+            .user_stories
+            .iter()
+            .all(|s| graph.get_node_index(&s.id).is_some())
+        {
+            return Ok(None);
+        }
+
+        let mut rebuilt = DependencyGraph::from_stories(&prd.user_stories);
+        if self.config.infer_dependencies {
+            rebuilt.infer_dependencies();
+        }
+        rebuilt
+            .validate()
+            .map_err(|e| format!("Invalid dependency graph: {}", e))?;
+        for story in prd.user_stories.iter().filter(|s| !s.passes) {
+            if !story_configs.contains_key(&story.id) {
+                let config = self
+                    .story_executor_config(agent, story)
+                    .map_err(|e| format!("Fatal error: {}", e))?;
+                story_configs.insert(story.id.clone(), config);
+            }
+        }
+        *graph = rebuilt;
+        Ok(Some(prd))
+    }
+
+    /// Executor configuration for a story, with the story's PRD overrides applied.
+    fn story_executor_config(
+        &self,
+        agent: &str,
+        story: &PrdUserStory,
+    ) -> Result<ExecutorConfig, ExecutorError> {
+        ExecutorConfig {
+            prd_path: self.base_config.prd_path.clone(),
+            project_root: self.base_config.working_dir.clone(),
+            progress_path: self.base_config.working_dir.join("progress.txt"),
+            quality_profile: None,
+            agent_command: agent.to_string(),
+            agent_registry: self.base_config.agent_registry.clone(),
+            max_iterations: self.base_config.max_iterations_per_story,
+            git_mutex: Some(self.git_mutex.clone()),
+            timeout_config: self.config.timeout_config.clone(),
+            metrics_collector: Some(self.metrics.clone()),
+            budget: self.base_config.budget,
+            run_limits: self.base_config.run_limits,
+            prompt_path: self.base_config.prompt_path.clone(),
+            retry_strategy: self.base_config.retry_strategy.clone(),
+            rollback_policy: self.base_config.rollback_policy,
+            commit_policy: self.base_config.commit_policy.clone(),
+            change_guard: self.base_config.change_guard.clone(),
+            split_stories: self.base_config.split_stories,
+            ..Default::default()
+        }
+        .with_story_overrides(story)
+    }
+
+    /// Merges the story branches of completed stories and removes the batch's worktrees.
+    ///
+    /// Branches are merged in dependency order. Stories that did not complete are
+    /// discarded along with their worktree. Returns the merges that failed.
+    async fn merge_worktrees(
+        &self,
+        mut worktrees: Vec<StoryWorktree>,
+        graph: &DependencyGraph,
+    ) -> Vec<ReconciliationIssue> {
+        if worktrees.is_empty() {
+            return Vec::new();
+        }
+
+        let completed = self.execution_state.read().await.completed.clone();
+        let order = graph.topological_order().unwrap_or_default();
+        worktrees.sort_by_key(|worktree| {
+            order
+                .iter()
+                .position(|id| *id == worktree.story_id)
+                .unwrap_or(usize::MAX)
+        });
+
+        let root = &self.base_config.working_dir;
+        let engine = ReconciliationEngine::new(root.clone());
+        let _guard = self.git_mutex.lock().await;
+        let mut issues = Vec::new();
+        for worktree in worktrees {
+            if completed.contains(&worktree.story_id) && worktree.has_commits(root).unwrap_or(false)
+            {
+                issues.extend(engine.merge_story(&worktree));
+            }
+            if let Err(e) = worktree.remove(root) {
+                eprintln!(
+                    "Warning: Failed to remove worktree for {}: {}",
+                    worktree.story_id, e
+                );
+            }
+        }
+        issues
+    }
+
+    /// Saves a run-level checkpoint when the run stops early, e.g. because the
+    /// usage budget is exceeded or a run limit is reached.
+    ///
+    /// Does nothing if checkpointing is disabled.
+    fn save_run_checkpoint(&self, pause_reason: PauseReason) {
+        if self.base_config.no_checkpoint {
+            return;
+        }
+        match CheckpointManager::new(&self.base_config.working_dir) {
+            Ok(manager) => {
+                let mut checkpoint = Checkpoint::new(None, pause_reason, vec![]);
+                if let Some(setup) = self.branch_setup.get() {
+                    checkpoint = checkpoint.with_branch(&setup.branch, &setup.base_commit);
+                }
+                if let Err(e) = manager.save(&checkpoint) {
+                    eprintln!("Warning: Failed to save checkpoint: {}", e);
+                }
+            }
+            Err(e) => eprintln!("Warning: Failed to initialize checkpoint manager: {}", e),
+        }
+    }
+
     /// Runs reconciliation after a batch completes and handles any issues found.
     ///
+    /// `merge_issues` are the story branches that failed to merge back; they are
+    /// reported alongside the issues found in the project.
+    ///
     /// Returns `None` if reconciliation passed or issues were resolved via sequential retry.
     /// Returns `Some(error)` if reconciliation found issues that couldn't be resolved.
+    #[allow(clippy::too_many_arguments)]
     async fn run_reconciliation(
         &self,
         batch_story_ids: &[String],
+        mut merge_issues: Vec<ReconciliationIssue>,
         graph: &DependencyGraph,
-        agent: &str,
+        story_configs: &HashMap<String, ExecutorConfig>,
         total_iterations: &mut u32,
         ui_sender: &Option<mpsc::Sender<ParallelUIEvent>>,
         story_info_map: &HashMap<String, StoryDisplayInfo>,
     ) -> Option<String> {
         let engine = ReconciliationEngine::new(self.base_config.working_dir.clone());
-        let result = engine.reconcile();
+        let result = match engine.reconcile() {
+            result if merge_issues.is_empty() => result,
+            ReconciliationResult::Clean => ReconciliationResult::IssuesFound(merge_issues),
+            ReconciliationResult::IssuesFound(issues) => {
+                merge_issues.extend(issues);
+                ReconciliationResult::IssuesFound(merge_issues)
+            }
+        };
 
         match result {
             ReconciliationResult::Clean => {
@@ -810,6 +1117,22 @@ impl ParallelRunner {
                         ReconciliationIssue::ImportDuplicate => {
                             "duplicate import detected".to_string()
                         }
+                        ReconciliationIssue::MergeConflict {
+                            story_id,
+                            affected_files,
+                            ..
+                        } if affected_files.is_empty() => {
+                            format!("could not merge {}", story_id)
+                        }
+                        ReconciliationIssue::MergeConflict {
+                            story_id,
+                            affected_files,
+                            ..
+                        } => format!(
+                            "merge conflict for {} in: {}",
+                            story_id,
+                            affected_files.join(", ")
+                        ),
                     })
                     .collect();
 
@@ -866,16 +1189,8 @@ impl ParallelRunner {
                                 let _ = sender.try_send(event);
                             }
 
-                            let executor_config = ExecutorConfig {
-                                prd_path: self.base_config.prd_path.clone(),
-                                project_root: self.base_config.working_dir.clone(),
-                                progress_path: self.base_config.working_dir.join("progress.txt"),
-                                quality_profile: None,
-                                agent_command: agent.to_string(),
-                                max_iterations: self.base_config.max_iterations_per_story,
-                                git_mutex: Some(self.git_mutex.clone()),
-                                timeout_config: self.config.timeout_config.clone(),
-                                ..Default::default()
+                            let Some(executor_config) = story_configs.get(story_id).cloned() else {
+                                continue;
                             };
 
                             let executor = StoryExecutor::new(executor_config);
@@ -918,10 +1233,15 @@ impl ParallelRunner {
                                 }
                                 Ok(exec_result) => {
                                     let mut state = self.execution_state.write().await;
-                                    let error_msg = exec_result
-                                        .error
-                                        .unwrap_or_else(|| "Unknown error".to_string());
-                                    state.failed.insert(story_id.clone(), error_msg.clone());
+                                    let error_msg = if exec_result.split_into.is_empty() {
+                                        let error_msg = exec_result
+                                            .error
+                                            .unwrap_or_else(|| "Unknown error".to_string());
+                                        state.failed.insert(story_id.clone(), error_msg.clone());
+                                        error_msg
+                                    } else {
+                                        format!("Split into {}", exec_result.split_into.join(", "))
+                                    };
                                     *total_iterations += exec_result.iterations_used;
                                     // Send StoryFailed event
                                     if let Some(ref sender) = ui_sender {
@@ -933,6 +1253,9 @@ impl ParallelRunner {
                                         let _ = sender.try_send(event);
                                     }
                                 }
+                                Err(ExecutorError::RunLimitReached(..)) => {
+                                    // Stopped rather than failed; the run ends after this batch
+                                }
                                 Err(e) => {
                                     let mut state = self.execution_state.write().await;
                                     state.failed.insert(story_id.clone(), e.to_string());
@@ -1013,6 +1336,8 @@ impl ParallelRunner {
     ) -> Vec<String> {
         // Collect all affected files from issues
         let mut affected_files: HashSet<String> = HashSet::new();
+        // Stories whose branch failed to merge are always affected
+        let mut unmerged_stories: Vec<String> = Vec::new();
 
         for issue in issues {
             match issue {
@@ -1031,12 +1356,19 @@ impl ParallelRunner {
                     // So we mark all batch stories as affected
                     return batch_story_ids.to_vec();
                 }
+                ReconciliationIssue::MergeConflict { story_id, .. } => {
+                    unmerged_stories.push(story_id.clone());
+                }
             }
         }
 
-        // If we couldn't identify specific files, retry all batch stories
+        // If we couldn't identify specific files, retry the unmerged stories or the whole batch
         if affected_files.is_empty() {
-            return batch_story_ids.to_vec();
+            return if unmerged_stories.is_empty() {
+                batch_story_ids.to_vec()
+            } else {
+                unmerged_stories
+            };
         }
 
         // Find stories whose target_files overlap with affected files
@@ -1058,6 +1390,12 @@ impl ParallelRunner {
             }
         }
 
+        for story_id in unmerged_stories {
+            if !affected_story_ids.contains(&story_id) {
+                affected_story_ids.push(story_id);
+            }
+        }
+
         // If we still couldn't match any stories, retry all
         if affected_story_ids.is_empty() {
             batch_story_ids.to_vec()
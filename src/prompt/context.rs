@@ -0,0 +1,233 @@
+//! Variable values for rendering prompt templates
+
+use std::collections::HashMap;
+
+use super::learnings::{codebase_patterns, relevant_learnings};
+use crate::iteration::context::IterationContext;
+use crate::mcp::tools::load_prd::{PrdFile, PrdUserStory};
+use crate::quality::{Profile, ProjectLanguage};
+
+/// Values of the variables available to a prompt template
+#[derive(Debug, Clone, Default)]
+pub struct PromptContext {
+    values: HashMap<String, String>,
+}
+
+impl PromptContext {
+    /// Create an empty context
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Context with the story and PRD variables set
+    pub fn for_story(story: &PrdUserStory, prd: &PrdFile) -> Self {
+        let criteria = story
+            .acceptance_criteria
+            .iter()
+            .enumerate()
+            .map(|(i, criterion)| format!("{}. {}", i + 1, criterion))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        Self::new()
+            .with("story_id", &story.id)
+            .with("story_title", &story.title)
+            .with("story_description", &story.description)
+            .with("story_priority", story.priority.to_string())
+            .with("acceptance_criteria", criteria)
+            .with("project", &prd.project)
+            .with("branch", &prd.branch_name)
+            .with("prd_description", &prd.description)
+    }
+
+    /// Set a variable
+    pub fn with(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.set(name, value);
+        self
+    }
+
+    /// Set a variable in place
+    pub fn set(&mut self, name: impl Into<String>, value: impl Into<String>) {
+        self.values.insert(name.into(), value.into());
+    }
+
+    /// Set the iteration variables from the iteration context
+    pub fn with_iteration(self, context: &IterationContext) -> Self {
+        self.with("iteration", context.current_iteration.to_string())
+            .with("max_iterations", context.max_iterations.to_string())
+            .with("iteration_context", context.build_history_context())
+            .with(
+                "steering_guidance",
+                context
+                    .steering_guidance
+                    .as_ref()
+                    .map(|guidance| guidance.build_prompt_section())
+                    .unwrap_or_default(),
+            )
+    }
+
+    /// Set `codebase_patterns` and `learnings` from the contents of progress.txt
+    pub fn with_progress(self, progress: &str, story: &PrdUserStory) -> Self {
+        self.with("codebase_patterns", codebase_patterns(progress))
+            .with("learnings", relevant_learnings(progress, story))
+    }
+
+    /// Set `quality_profile` from the requirements of a quality profile
+    pub fn with_quality_profile(self, profile: Option<&Profile>) -> Self {
+        self.with(
+            "quality_profile",
+            profile.map(profile_requirements).unwrap_or_default(),
+        )
+    }
+
+    /// Set `language` from the detected project language
+    pub fn with_language(self, language: Option<ProjectLanguage>) -> Self {
+        self.with(
+            "language",
+            language.map(|l| l.name()).unwrap_or_default().to_string(),
+        )
+    }
+
+    /// Value of a variable, if set
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.values.get(name).map(String::as_str)
+    }
+
+    /// Whether a variable is set to a non-blank value
+    pub fn is_set(&self, name: &str) -> bool {
+        self.get(name).is_some_and(|value| !value.trim().is_empty())
+    }
+}
+
+/// Bullet list of the requirements a quality profile enforces
+fn profile_requirements(profile: &Profile) -> String {
+    let mut requirements = Vec::new();
+
+    if profile.ci.lint_check {
+        requirements.push("Code must pass lint checks with no warnings".to_string());
+    }
+    if profile.ci.format_check {
+        requirements.push("Code must be formatted with the project's formatter".to_string());
+    }
+    if profile.testing.unit_tests {
+        requirements.push("Add unit tests for new behavior".to_string());
+    }
+    if profile.testing.integration_tests {
+        requirements.push("Add integration tests where appropriate".to_string());
+    }
+    if profile.testing.coverage_threshold > 0 {
+        requirements.push(format!(
+            "Test coverage must be at least {}%",
+            profile.testing.coverage_threshold
+        ));
+    }
+    if profile.documentation.required {
+        requirements.push("Document public APIs".to_string());
+    }
+    if profile.security.cargo_audit {
+        requirements.push("Dependencies must pass a security audit".to_string());
+    }
+
+    requirements
+        .iter()
+        .map(|r| format!("- {}", r))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::iteration::context::{ErrorCategory, IterationError, SteeringGuidance};
+
+    fn story() -> PrdUserStory {
+        serde_json::from_str(
+            r#"{"id": "US-001", "title": "Add login", "acceptanceCriteria": ["Form", "Submit"],
+                "priority": 2, "passes": false}"#,
+        )
+        .unwrap()
+    }
+
+    fn prd() -> PrdFile {
+        serde_json::from_str(
+            r#"{"project": "Demo", "branchName": "feature/login", "userStories": []}"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_for_story_sets_story_variables() {
+        let context = PromptContext::for_story(&story(), &prd());
+
+        assert_eq!(context.get("story_id"), Some("US-001"));
+        assert_eq!(context.get("story_priority"), Some("2"));
+        assert_eq!(
+            context.get("acceptance_criteria"),
+            Some("1. Form\n2. Submit")
+        );
+        assert_eq!(context.get("branch"), Some("feature/login"));
+        assert!(!context.is_set("story_description"));
+    }
+
+    #[test]
+    fn test_with_iteration_splits_history_and_guidance() {
+        let mut iteration = IterationContext::new("US-001", 5);
+        iteration.start_iteration(2);
+        let context = PromptContext::new().with_iteration(&iteration);
+        assert_eq!(context.get("iteration"), Some("2"));
+        assert!(!context.is_set("iteration_context"));
+        assert!(!context.is_set("steering_guidance"));
+
+        iteration.record_error(IterationError::new(
+            1,
+            ErrorCategory::Compilation,
+            "missing semicolon",
+        ));
+        iteration.set_steering_guidance(SteeringGuidance::new("Check main.rs", 1));
+        let context = PromptContext::new().with_iteration(&iteration);
+
+        let history = context.get("iteration_context").unwrap();
+        assert!(history.contains("missing semicolon"));
+        assert!(!history.contains("Check main.rs"));
+        assert!(context
+            .get("steering_guidance")
+            .unwrap()
+            .contains("Check main.rs"));
+    }
+
+    #[test]
+    fn test_with_progress() {
+        let progress = "## Codebase Patterns\n- Use X\n---\n## 2026-01-01 - US-000\n- **Learnings for future iterations:**\n  - Login forms need CSRF tokens\n---\n";
+        let context = PromptContext::new().with_progress(progress, &story());
+
+        assert_eq!(context.get("codebase_patterns"), Some("- Use X"));
+        assert_eq!(
+            context.get("learnings"),
+            Some("- [US-000] Login forms need CSRF tokens")
+        );
+        assert!(!PromptContext::new()
+            .with_progress("", &story())
+            .is_set("learnings"));
+    }
+
+    #[test]
+    fn test_quality_profile_requirements() {
+        let mut profile = Profile::default();
+        let context = PromptContext::new().with_quality_profile(Some(&profile));
+        assert!(!context.is_set("quality_profile"));
+
+        profile.ci.lint_check = true;
+        profile.testing.coverage_threshold = 80;
+        let context = PromptContext::new().with_quality_profile(Some(&profile));
+        let requirements = context.get("quality_profile").unwrap();
+        assert!(requirements.contains("- Code must pass lint checks"));
+        assert!(requirements.contains("at least 80%"));
+    }
+
+    #[test]
+    fn test_with_language() {
+        let context = PromptContext::new().with_language(Some(ProjectLanguage::Python));
+        assert_eq!(context.get("language"), Some("Python"));
+        assert!(!PromptContext::new().with_language(None).is_set("language"));
+    }
+}
@@ -0,0 +1,402 @@
+//! Learnings carried between stories through progress.txt
+//!
+//! After a story passes, the learnings the agent reports at the end of its
+//! final message are appended to progress.txt. Before each iteration the
+//! prompt gets the `## Codebase Patterns` section and a bounded slice of the
+//! learnings recorded by earlier stories, ranked by how many of the current
+//! story's keywords they mention.
+
+use std::collections::HashSet;
+
+use crate::mcp::tools::load_prd::PrdUserStory;
+
+/// Heading of the progress.txt section holding reusable codebase patterns
+pub const CODEBASE_PATTERNS_HEADING: &str = "## Codebase Patterns";
+
+/// Marker line that starts the learnings list of a progress entry
+pub const LEARNINGS_MARKER: &str = "**Learnings for future iterations:**";
+
+/// Maximum number of prior learnings included in a prompt
+pub const MAX_PROMPT_LEARNINGS: usize = 10;
+
+/// Maximum size of the prior learnings included in a prompt, in bytes
+pub const MAX_PROMPT_LEARNINGS_CHARS: usize = 2000;
+
+/// Maximum size of the codebase patterns section included in a prompt, in bytes
+pub const MAX_PROMPT_PATTERNS_CHARS: usize = 4000;
+
+/// Maximum number of learnings extracted from one agent message
+const MAX_EXTRACTED_LEARNINGS: usize = 10;
+
+/// Placeholder older versions of Ralph wrote instead of real learnings
+const BOILERPLATE_LEARNING: &str = "Story completed successfully via automated execution";
+
+/// Words too common to say anything about relevance
+const STOP_WORDS: &[&str] = &[
+    "about", "after", "also", "before", "been", "does", "each", "from", "have", "into", "make",
+    "must", "only", "should", "some", "than", "that", "the", "them", "then", "there", "these",
+    "they", "this", "when", "where", "which", "will", "with", "without", "would", "your",
+];
+
+/// A learning recorded in progress.txt
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Learning {
+    /// Story the learning was recorded for, if the entry heading names one
+    pub story_id: Option<String>,
+    /// The learning text
+    pub text: String,
+}
+
+/// Extract the body of the `## Codebase Patterns` section from progress.txt.
+///
+/// The section ends at the next `---` separator or `## ` heading.
+pub fn codebase_patterns(progress: &str) -> String {
+    let mut lines = progress.lines();
+    if !lines.any(|line| line.trim() == CODEBASE_PATTERNS_HEADING) {
+        return String::new();
+    }
+
+    let section = lines
+        .take_while(|line| line.trim() != "---" && !line.starts_with("## "))
+        .collect::<Vec<_>>()
+        .join("\n");
+    truncate_lines(section.trim(), MAX_PROMPT_PATTERNS_CHARS)
+}
+
+/// Parse the learnings recorded in progress.txt entries, oldest first.
+///
+/// Entries start with a `## <timestamp> - <story id>` heading; their
+/// learnings are the bullets after the "Learnings for future iterations"
+/// marker. The codebase patterns section and placeholder learnings are skipped.
+pub fn parse_learnings(progress: &str) -> Vec<Learning> {
+    let mut learnings = Vec::new();
+    let mut story_id: Option<String> = None;
+    let mut in_learnings = false;
+
+    for line in progress.lines() {
+        let trimmed = line.trim();
+
+        if let Some(heading) = line.strip_prefix("## ") {
+            story_id = if line.trim() == CODEBASE_PATTERNS_HEADING {
+                None
+            } else {
+                heading
+                    .rsplit_once(" - ")
+                    .map(|(_, id)| id.trim().to_string())
+            };
+            in_learnings = false;
+        } else if trimmed == "---" {
+            in_learnings = false;
+        } else if trimmed.contains(LEARNINGS_MARKER) {
+            in_learnings = true;
+        } else if in_learnings {
+            // Another field of the entry ends the list
+            if trimmed.starts_with("- **") {
+                in_learnings = false;
+                continue;
+            }
+            if let Some(text) = bullet_text(trimmed) {
+                if text != BOILERPLATE_LEARNING {
+                    learnings.push(Learning {
+                        story_id: story_id.clone(),
+                        text: text.to_string(),
+                    });
+                }
+            }
+        }
+    }
+
+    learnings
+}
+
+/// Select the prior learnings most relevant to `story`, formatted as a list.
+///
+/// Learnings are ranked by the number of the story's keywords they mention,
+/// most recent first on ties, and bounded by [`MAX_PROMPT_LEARNINGS`] and
+/// [`MAX_PROMPT_LEARNINGS_CHARS`]. Learnings recorded for the story itself
+/// (from an earlier attempt) are included as well.
+pub fn relevant_learnings(progress: &str, story: &PrdUserStory) -> String {
+    let keywords = story_keywords(story);
+    let learnings = parse_learnings(progress);
+
+    let mut ranked: Vec<(usize, usize, &Learning)> = learnings
+        .iter()
+        .enumerate()
+        .map(|(index, learning)| (relevance(&learning.text, &keywords), index, learning))
+        .collect();
+    ranked.sort_by(|a, b| b.0.cmp(&a.0).then(b.1.cmp(&a.1)));
+
+    let mut seen = HashSet::new();
+    let mut lines: Vec<String> = Vec::new();
+    let mut size = 0;
+    for (_, _, learning) in ranked {
+        if lines.len() >= MAX_PROMPT_LEARNINGS {
+            break;
+        }
+        if !seen.insert(learning.text.to_lowercase()) {
+            continue;
+        }
+        let line = match &learning.story_id {
+            Some(id) => format!("- [{}] {}", id, learning.text),
+            None => format!("- {}", learning.text),
+        };
+        if size + line.len() + 1 > MAX_PROMPT_LEARNINGS_CHARS {
+            continue;
+        }
+        size += line.len() + 1;
+        lines.push(line);
+    }
+
+    lines.join("\n")
+}
+
+/// Extract the learnings an agent reported at the end of its final message.
+///
+/// Looks for a line mentioning "learnings" (a heading, a bold label or a
+/// `Learnings:` prefix) and collects the bullets that follow it, stopping at
+/// the next heading or at the first non-bullet line after the list.
+pub fn extract_learnings(message: &str) -> Vec<String> {
+    let mut lines = message.lines().skip_while(|line| !is_learnings_label(line));
+    let Some(label) = lines.next() else {
+        return Vec::new();
+    };
+
+    let mut learnings = Vec::new();
+
+    // "Learnings: use X" puts the first learning on the label line
+    if let Some((_, inline)) = label.split_once(':') {
+        let inline = inline.trim().trim_start_matches("**").trim();
+        if !inline.is_empty() {
+            learnings.push(inline.to_string());
+        }
+    }
+
+    for line in lines {
+        let trimmed = line.trim();
+        if trimmed.starts_with('#') {
+            break;
+        }
+        match bullet_text(trimmed) {
+            Some(text) => learnings.push(text.to_string()),
+            None if trimmed.is_empty() && learnings.is_empty() => continue,
+            None => break,
+        }
+    }
+
+    learnings.retain(|l| !is_none_marker(l));
+    learnings.truncate(MAX_EXTRACTED_LEARNINGS);
+    learnings
+}
+
+fn is_learnings_label(line: &str) -> bool {
+    let label = line
+        .trim()
+        .trim_start_matches('#')
+        .trim()
+        .trim_start_matches("**")
+        .to_lowercase();
+    label.starts_with("learnings") || label.starts_with("key learnings")
+}
+
+fn is_none_marker(text: &str) -> bool {
+    matches!(
+        text.trim_end_matches('.').to_lowercase().as_str(),
+        "none" | "n/a" | "no new learnings"
+    )
+}
+
+/// Text of a `- ` or `* ` bullet
+fn bullet_text(line: &str) -> Option<&str> {
+    line.strip_prefix("- ")
+        .or_else(|| line.strip_prefix("* "))
+        .map(str::trim)
+        .filter(|text| !text.is_empty())
+}
+
+/// Distinct lowercase words of at least four letters in the story text
+fn story_keywords(story: &PrdUserStory) -> HashSet<String> {
+    let mut text = format!("{} {}", story.title, story.description);
+    for item in story.acceptance_criteria.iter().chain(&story.target_files) {
+        text.push(' ');
+        text.push_str(item);
+    }
+    words(&text)
+}
+
+fn words(text: &str) -> HashSet<String> {
+    text.split(|c: char| !c.is_alphanumeric() && c != '_')
+        .filter(|word| word.len() >= 4)
+        .map(str::to_lowercase)
+        .filter(|word| !STOP_WORDS.contains(&word.as_str()))
+        .collect()
+}
+
+fn relevance(text: &str, keywords: &HashSet<String>) -> usize {
+    words(text).intersection(keywords).count()
+}
+
+/// Cut `text` at a line boundary so it fits in `max` bytes
+fn truncate_lines(text: &str, max: usize) -> String {
+    if text.len() <= max {
+        return text.to_string();
+    }
+    let mut out = String::new();
+    for line in text.lines() {
+        if out.len() + line.len() + 1 > max {
+            break;
+        }
+        out.push_str(line);
+        out.push('\n');
+    }
+    out.push_str("- ... (truncated)");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROGRESS: &str = "\
+# Ralph Progress Log
+---
+
+## Codebase Patterns
+- Errors use thiserror
+- Checkpoints live in .ralph/
+
+---
+
+## 2026-01-18 19:45 - US-001
+- **What was implemented**: Login form
+- **Files changed**:
+  - src/login.rs
+- **Learnings for future iterations:**
+  - The session cookie is set in src/session.rs
+  - Run migrations before the database tests
+---
+
+## 2026-01-18 20:10 - US-002
+- **What was implemented**: Dashboard
+- **Learnings for future iterations:**
+- Story completed successfully via automated execution
+---
+";
+
+    fn story(title: &str, description: &str) -> PrdUserStory {
+        PrdUserStory {
+            id: "US-003".to_string(),
+            title: title.to_string(),
+            description: description.to_string(),
+            acceptance_criteria: Vec::new(),
+            priority: 1,
+            passes: false,
+            depends_on: Vec::new(),
+            target_files: Vec::new(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_codebase_patterns() {
+        assert_eq!(
+            codebase_patterns(PROGRESS),
+            "- Errors use thiserror\n- Checkpoints live in .ralph/"
+        );
+        assert_eq!(
+            codebase_patterns("## Codebase Patterns\n- Only\n## Next\n"),
+            "- Only"
+        );
+        assert_eq!(codebase_patterns("# Log\n## US-001\n"), "");
+    }
+
+    #[test]
+    fn test_codebase_patterns_is_bounded() {
+        let long = format!(
+            "## Codebase Patterns\n{}",
+            "- a pattern worth remembering\n".repeat(500)
+        );
+        let patterns = codebase_patterns(&long);
+        assert!(patterns.len() <= MAX_PROMPT_PATTERNS_CHARS + 20);
+        assert!(patterns.ends_with("(truncated)"));
+    }
+
+    #[test]
+    fn test_parse_learnings_skips_placeholders() {
+        let learnings = parse_learnings(PROGRESS);
+        assert_eq!(
+            learnings,
+            vec![
+                Learning {
+                    story_id: Some("US-001".to_string()),
+                    text: "The session cookie is set in src/session.rs".to_string(),
+                },
+                Learning {
+                    story_id: Some("US-001".to_string()),
+                    text: "Run migrations before the database tests".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_relevant_learnings_ranks_by_keywords() {
+        let learnings = relevant_learnings(PROGRESS, &story("Add database index", ""));
+        let lines: Vec<&str> = learnings.lines().collect();
+        assert_eq!(
+            lines[0],
+            "- [US-001] Run migrations before the database tests"
+        );
+        assert_eq!(lines.len(), 2);
+
+        let learnings = relevant_learnings(PROGRESS, &story("Expire session", "cookie"));
+        assert!(learnings.starts_with("- [US-001] The session cookie"));
+    }
+
+    #[test]
+    fn test_relevant_learnings_is_bounded() {
+        let mut progress = String::new();
+        for i in 0..50 {
+            progress.push_str(&format!(
+                "## 2026-01-01 00:00 - US-{:03}\n- **Learnings for future iterations:**\n  - learning number {}\n---\n",
+                i, i
+            ));
+        }
+        let learnings = relevant_learnings(&progress, &story("Anything", ""));
+        assert_eq!(learnings.lines().count(), MAX_PROMPT_LEARNINGS);
+        // Most recent first when nothing is more relevant
+        assert!(learnings.starts_with("- [US-049] learning number 49"));
+        assert!(learnings.len() <= MAX_PROMPT_LEARNINGS_CHARS);
+    }
+
+    #[test]
+    fn test_relevant_learnings_empty_progress() {
+        assert_eq!(relevant_learnings("", &story("Anything", "")), "");
+    }
+
+    #[test]
+    fn test_extract_learnings_from_list() {
+        let message = "Implemented the login form.\n\n## Learnings\n\n- Forms use the `Form` helper\n* Tests need a seeded user\n\nAll checks pass.";
+        assert_eq!(
+            extract_learnings(message),
+            vec!["Forms use the `Form` helper", "Tests need a seeded user"]
+        );
+    }
+
+    #[test]
+    fn test_extract_learnings_inline_label() {
+        assert_eq!(
+            extract_learnings("Done.\nLearnings: config is loaded lazily\n- and cached"),
+            vec!["config is loaded lazily", "and cached"]
+        );
+        assert_eq!(
+            extract_learnings("**Learnings for future iterations:**\n- Use X"),
+            vec!["Use X"]
+        );
+    }
+
+    #[test]
+    fn test_extract_learnings_none() {
+        assert!(extract_learnings("Implemented the feature.").is_empty());
+        assert!(extract_learnings("Learnings:\n- None").is_empty());
+    }
+}
@@ -0,0 +1,200 @@
+//! Prompt module for agent prompt templates
+//!
+//! The prompt sent to the agent for each story iteration is rendered from a
+//! Markdown template. Ralph uses the first of:
+//!
+//! 1. The file passed with `--prompt`
+//! 2. `.ralph/prompt.md` in the project root
+//! 3. The built-in template for the detected project language
+//!
+//! See [`template`] for the template syntax and [`PROMPT_VARIABLES`] for the
+//! available variables.
+
+#![allow(dead_code)]
+
+pub mod context;
+pub mod learnings;
+pub mod template;
+
+pub use context::PromptContext;
+pub use learnings::{codebase_patterns, extract_learnings, relevant_learnings, LEARNINGS_MARKER};
+pub use template::{PromptError, PromptTemplate, PROMPT_VARIABLES};
+
+use std::path::{Path, PathBuf};
+
+use crate::git::RALPH_STATE_DIR;
+use crate::quality::ProjectLanguage;
+
+/// Per-project prompt override, relative to the project root
+pub const PROJECT_PROMPT_FILE: &str = "prompt.md";
+
+const DEFAULT_TEMPLATE: &str = include_str!("templates/default.md");
+const RUST_TEMPLATE: &str = include_str!("templates/rust.md");
+const GO_TEMPLATE: &str = include_str!("templates/go.md");
+const TYPESCRIPT_TEMPLATE: &str = include_str!("templates/typescript.md");
+const JAVASCRIPT_TEMPLATE: &str = include_str!("templates/javascript.md");
+const PYTHON_TEMPLATE: &str = include_str!("templates/python.md");
+
+/// Where a prompt template was loaded from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PromptSource {
+    /// A template file (`--prompt` or the project override)
+    File(PathBuf),
+    /// The built-in template for a language (None = language-neutral)
+    Builtin(Option<ProjectLanguage>),
+}
+
+impl std::fmt::Display for PromptSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PromptSource::File(path) => write!(f, "{}", path.display()),
+            PromptSource::Builtin(Some(language)) => write!(f, "built-in {} template", language),
+            PromptSource::Builtin(None) => write!(f, "built-in default template"),
+        }
+    }
+}
+
+/// Source text of the built-in template for a language
+pub fn builtin_template(language: Option<ProjectLanguage>) -> &'static str {
+    match language {
+        Some(ProjectLanguage::Rust) => RUST_TEMPLATE,
+        Some(ProjectLanguage::Go) => GO_TEMPLATE,
+        Some(ProjectLanguage::TypeScript) => TYPESCRIPT_TEMPLATE,
+        Some(ProjectLanguage::JavaScript) => JAVASCRIPT_TEMPLATE,
+        Some(ProjectLanguage::Python) => PYTHON_TEMPLATE,
+        None => DEFAULT_TEMPLATE,
+    }
+}
+
+/// Path of the per-project prompt override for `project_root`
+pub fn project_prompt_path(project_root: &Path) -> PathBuf {
+    project_root.join(RALPH_STATE_DIR).join(PROJECT_PROMPT_FILE)
+}
+
+/// Determine which template applies to a project.
+///
+/// An explicit `prompt_path` always wins, even if it does not exist, so a
+/// mistyped `--prompt` fails instead of silently falling back.
+pub fn resolve_source(project_root: &Path, prompt_path: Option<&Path>) -> PromptSource {
+    if let Some(path) = prompt_path {
+        return PromptSource::File(path.to_path_buf());
+    }
+
+    let project_prompt = project_prompt_path(project_root);
+    if project_prompt.is_file() {
+        return PromptSource::File(project_prompt);
+    }
+
+    PromptSource::Builtin(ProjectLanguage::detect(project_root))
+}
+
+/// Load and parse the template that applies to a project.
+///
+/// # Arguments
+/// * `project_root` - Root of the project, used for the override and language detection
+/// * `prompt_path` - Template file given with `--prompt`, if any
+pub fn load_template(
+    project_root: &Path,
+    prompt_path: Option<&Path>,
+) -> Result<(PromptTemplate, PromptSource), PromptError> {
+    let source = resolve_source(project_root, prompt_path);
+
+    let template = match &source {
+        PromptSource::File(path) => {
+            let text = std::fs::read_to_string(path).map_err(|e| PromptError::Io {
+                path: path.display().to_string(),
+                source: e,
+            })?;
+            PromptTemplate::parse(&text)?
+        }
+        PromptSource::Builtin(language) => PromptTemplate::parse(builtin_template(*language))?,
+    };
+
+    Ok((template, source))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_builtin_templates_parse() {
+        let languages = [
+            None,
+            Some(ProjectLanguage::Rust),
+            Some(ProjectLanguage::Go),
+            Some(ProjectLanguage::TypeScript),
+            Some(ProjectLanguage::JavaScript),
+            Some(ProjectLanguage::Python),
+        ];
+        for language in languages {
+            let template = PromptTemplate::parse(builtin_template(language))
+                .unwrap_or_else(|e| panic!("{:?} template is invalid: {}", language, e));
+            assert!(template.variables().contains(&"story_id"));
+            assert!(template.variables().contains(&"iteration_context"));
+        }
+    }
+
+    #[test]
+    fn test_builtin_templates_use_language_tooling() {
+        assert!(builtin_template(Some(ProjectLanguage::Rust)).contains("cargo clippy"));
+        assert!(builtin_template(Some(ProjectLanguage::Go)).contains("go test"));
+        assert!(builtin_template(Some(ProjectLanguage::Python)).contains("pytest"));
+        assert!(builtin_template(Some(ProjectLanguage::TypeScript)).contains("tsc"));
+        assert!(!builtin_template(None).contains("cargo"));
+        assert!(!builtin_template(Some(ProjectLanguage::JavaScript)).contains("cargo"));
+    }
+
+    #[test]
+    fn test_resolve_source_detects_language() {
+        let dir = TempDir::new().unwrap();
+        assert_eq!(
+            resolve_source(dir.path(), None),
+            PromptSource::Builtin(None)
+        );
+
+        std::fs::write(dir.path().join("go.mod"), "module demo\n").unwrap();
+        assert_eq!(
+            resolve_source(dir.path(), None),
+            PromptSource::Builtin(Some(ProjectLanguage::Go))
+        );
+    }
+
+    #[test]
+    fn test_project_override_and_explicit_path() {
+        let dir = TempDir::new().unwrap();
+        std::fs::create_dir(dir.path().join(RALPH_STATE_DIR)).unwrap();
+        std::fs::write(project_prompt_path(dir.path()), "Project: {{story_id}}").unwrap();
+
+        let (template, source) = load_template(dir.path(), None).unwrap();
+        assert_eq!(source, PromptSource::File(project_prompt_path(dir.path())));
+        let context = PromptContext::new().with("story_id", "US-001");
+        assert_eq!(template.render(&context), "Project: US-001");
+
+        let explicit = dir.path().join("custom.md");
+        std::fs::write(&explicit, "Custom: {{story_id}}").unwrap();
+        let (template, _) = load_template(dir.path(), Some(&explicit)).unwrap();
+        assert_eq!(template.render(&context), "Custom: US-001");
+    }
+
+    #[test]
+    fn test_missing_explicit_path_is_an_error() {
+        let dir = TempDir::new().unwrap();
+        let missing = dir.path().join("missing.md");
+        let err = load_template(dir.path(), Some(&missing)).unwrap_err();
+        assert!(matches!(err, PromptError::Io { .. }));
+        assert!(err.to_string().contains("missing.md"));
+    }
+
+    #[test]
+    fn test_invalid_override_is_an_error() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("bad.md");
+        std::fs::write(&path, "{{#if story_id}}unclosed").unwrap();
+        assert!(matches!(
+            load_template(dir.path(), Some(&path)),
+            Err(PromptError::UnclosedBlock { .. })
+        ));
+    }
+}
@@ -0,0 +1,406 @@
+//! Prompt template parsing and rendering
+//!
+//! Templates are Markdown with two kinds of tags:
+//!
+//! - `{{name}}` is replaced with the value of the variable `name`
+//! - `{{#if name}} ... {{/if}}` keeps its body only when `name` is non-empty
+//!
+//! Blocks may be nested. A block tag alone on its line is removed together
+//! with the line, so conditional sections do not leave blank lines behind.
+//! Variable names are checked against [`PROMPT_VARIABLES`] when the template
+//! is parsed, so a typo fails the run instead of silently rendering nothing.
+//! Text between braces that is not a valid tag (such as `{{}}` in a code
+//! sample) is left as-is.
+
+use thiserror::Error;
+
+use super::context::PromptContext;
+
+/// Variables available to prompt templates, with a short description of each
+pub const PROMPT_VARIABLES: &[(&str, &str)] = &[
+    ("story_id", "ID of the story being implemented"),
+    ("story_title", "Title of the story"),
+    ("story_description", "Description of the story"),
+    ("story_priority", "Priority of the story"),
+    (
+        "acceptance_criteria",
+        "Numbered list of acceptance criteria",
+    ),
+    ("project", "Project name from the PRD"),
+    ("branch", "Branch name from the PRD"),
+    ("prd_description", "Description of the PRD"),
+    ("iteration", "Current iteration number"),
+    ("max_iterations", "Maximum iterations for the story"),
+    (
+        "iteration_context",
+        "Errors and hints from previous iterations (empty on the first)",
+    ),
+    ("steering_guidance", "Guidance provided by the user, if any"),
+    (
+        "codebase_patterns",
+        "Codebase Patterns section of progress.txt",
+    ),
+    (
+        "learnings",
+        "Learnings from earlier stories most relevant to this one",
+    ),
+    (
+        "quality_profile",
+        "Requirements of the active quality profile",
+    ),
+    ("language", "Detected project language (empty if unknown)"),
+];
+
+/// Errors that can occur when loading or parsing a prompt template
+#[derive(Error, Debug)]
+pub enum PromptError {
+    #[error("Failed to read prompt template {path}: {source}")]
+    Io {
+        path: String,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("Unknown template variable '{name}' on line {line}")]
+    UnknownVariable { name: String, line: usize },
+    #[error("Unsupported template tag '{{{{{tag}}}}}' on line {line}")]
+    UnsupportedTag { tag: String, line: usize },
+    #[error("Unclosed {{{{#if {name}}}}} block opened on line {line}")]
+    UnclosedBlock { name: String, line: usize },
+    #[error("Unexpected {{{{/if}}}} on line {line}")]
+    UnexpectedClose { line: usize },
+}
+
+/// A node of a parsed template
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Text(String),
+    Variable(String),
+    If { name: String, body: Vec<Node> },
+}
+
+/// A parsed prompt template
+#[derive(Debug, Clone, PartialEq)]
+pub struct PromptTemplate {
+    nodes: Vec<Node>,
+}
+
+/// An open `{{#if}}` block while parsing
+struct OpenBlock {
+    name: String,
+    line: usize,
+    body: Vec<Node>,
+}
+
+impl PromptTemplate {
+    /// Parse a template, validating its tags and variable names.
+    pub fn parse(source: &str) -> Result<Self, PromptError> {
+        let mut stack: Vec<OpenBlock> = Vec::new();
+        let mut nodes: Vec<Node> = Vec::new();
+        let mut text = String::new();
+        let mut rest = source;
+
+        while let Some(start) = rest.find("{{") {
+            let Some(len) = rest[start + 2..].find("}}") else {
+                break;
+            };
+            let tag = rest[start + 2..start + 2 + len].trim();
+            let after = &rest[start + 4 + len..];
+            let line = line_number(source, rest, start);
+
+            let kind = match classify_tag(tag) {
+                Some(kind) => kind,
+                None => {
+                    // Not a tag: keep the braces as literal text
+                    text.push_str(&rest[..start + 2]);
+                    rest = &rest[start + 2..];
+                    continue;
+                }
+            };
+
+            text.push_str(&rest[..start]);
+            rest = after;
+
+            let current = stack.last_mut().map_or(&mut nodes, |block| &mut block.body);
+            match kind {
+                TagKind::Variable(name) => {
+                    check_variable(name, line)?;
+                    flush_text(&mut text, current);
+                    current.push(Node::Variable(name.to_string()));
+                }
+                TagKind::OpenIf(name) => {
+                    check_variable(name, line)?;
+                    rest = strip_standalone(&mut text, rest);
+                    flush_text(&mut text, current);
+                    stack.push(OpenBlock {
+                        name: name.to_string(),
+                        line,
+                        body: Vec::new(),
+                    });
+                }
+                TagKind::CloseIf => {
+                    rest = strip_standalone(&mut text, rest);
+                    flush_text(&mut text, current);
+                    let block = stack.pop().ok_or(PromptError::UnexpectedClose { line })?;
+                    let parent = stack.last_mut().map_or(&mut nodes, |b| &mut b.body);
+                    parent.push(Node::If {
+                        name: block.name,
+                        body: block.body,
+                    });
+                }
+                TagKind::Unsupported => {
+                    return Err(PromptError::UnsupportedTag {
+                        tag: tag.to_string(),
+                        line,
+                    });
+                }
+            }
+        }
+
+        text.push_str(rest);
+        if let Some(block) = stack.pop() {
+            return Err(PromptError::UnclosedBlock {
+                name: block.name,
+                line: block.line,
+            });
+        }
+        flush_text(&mut text, &mut nodes);
+
+        Ok(Self { nodes })
+    }
+
+    /// Render the template with the given variable values.
+    ///
+    /// Variables without a value render as empty strings.
+    pub fn render(&self, context: &PromptContext) -> String {
+        let mut output = String::new();
+        render_nodes(&self.nodes, context, &mut output);
+        output
+    }
+
+    /// Names of the variables referenced by this template
+    pub fn variables(&self) -> Vec<&str> {
+        let mut names = Vec::new();
+        collect_variables(&self.nodes, &mut names);
+        names
+    }
+}
+
+/// The kind of a `{{...}}` tag
+enum TagKind<'a> {
+    Variable(&'a str),
+    OpenIf(&'a str),
+    CloseIf,
+    Unsupported,
+}
+
+/// Classify the trimmed contents of a `{{...}}` tag, or None if it is not a tag
+fn classify_tag(tag: &str) -> Option<TagKind<'_>> {
+    if let Some(block) = tag.strip_prefix('#') {
+        return Some(match block.split_once(char::is_whitespace) {
+            Some(("if", name)) if is_identifier(name.trim()) => TagKind::OpenIf(name.trim()),
+            _ => TagKind::Unsupported,
+        });
+    }
+    if let Some(block) = tag.strip_prefix('/') {
+        return Some(if block.trim() == "if" {
+            TagKind::CloseIf
+        } else {
+            TagKind::Unsupported
+        });
+    }
+    is_identifier(tag).then_some(TagKind::Variable(tag))
+}
+
+fn is_identifier(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn check_variable(name: &str, line: usize) -> Result<(), PromptError> {
+    if PROMPT_VARIABLES.iter().any(|(known, _)| *known == name) {
+        Ok(())
+    } else {
+        Err(PromptError::UnknownVariable {
+            name: name.to_string(),
+            line,
+        })
+    }
+}
+
+/// 1-indexed line of the tag at `offset` within `rest`, a suffix of `source`
+fn line_number(source: &str, rest: &str, offset: usize) -> usize {
+    let consumed = source.len() - rest.len() + offset;
+    source[..consumed].matches('\n').count() + 1
+}
+
+/// Drop a block tag's line when the tag is the only thing on it.
+///
+/// `text` holds the text before the tag and `rest` the source after it; when
+/// both are blank up to the surrounding newlines, the leading indentation is
+/// removed from `text` and the trailing newline skipped in `rest`.
+fn strip_standalone<'a>(text: &mut String, rest: &'a str) -> &'a str {
+    let line_start = text.rfind('\n').map_or(0, |i| i + 1);
+    if !text[line_start..].trim().is_empty() {
+        return rest;
+    }
+    let line_end = rest.find('\n').map_or(rest.len(), |i| i + 1);
+    if !rest[..line_end].trim().is_empty() {
+        return rest;
+    }
+    text.truncate(line_start);
+    &rest[line_end..]
+}
+
+fn flush_text(text: &mut String, nodes: &mut Vec<Node>) {
+    if !text.is_empty() {
+        nodes.push(Node::Text(std::mem::take(text)));
+    }
+}
+
+fn render_nodes(nodes: &[Node], context: &PromptContext, output: &mut String) {
+    for node in nodes {
+        match node {
+            Node::Text(text) => output.push_str(text),
+            Node::Variable(name) => output.push_str(context.get(name).unwrap_or_default()),
+            Node::If { name, body } => {
+                if context.is_set(name) {
+                    render_nodes(body, context, output);
+                }
+            }
+        }
+    }
+}
+
+fn collect_variables<'a>(nodes: &'a [Node], names: &mut Vec<&'a str>) {
+    for node in nodes {
+        match node {
+            Node::Text(_) => {}
+            Node::Variable(name) => {
+                if !names.contains(&name.as_str()) {
+                    names.push(name);
+                }
+            }
+            Node::If { name, body } => {
+                if !names.contains(&name.as_str()) {
+                    names.push(name);
+                }
+                collect_variables(body, names);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(source: &str, context: &PromptContext) -> String {
+        PromptTemplate::parse(source).unwrap().render(context)
+    }
+
+    #[test]
+    fn test_substitutes_variables() {
+        let context = PromptContext::new()
+            .with("story_id", "US-001")
+            .with("story_title", "Add login");
+        assert_eq!(
+            render("# {{story_id}} - {{ story_title }}", &context),
+            "# US-001 - Add login"
+        );
+    }
+
+    #[test]
+    fn test_missing_variable_renders_empty() {
+        assert_eq!(
+            render("[{{learnings}}]", &PromptContext::new()),
+            "[]".to_string()
+        );
+    }
+
+    #[test]
+    fn test_if_block_uses_non_empty_values() {
+        let template = "a\n{{#if learnings}}\nLearnings: {{learnings}}\n{{/if}}\nb\n";
+
+        let with = PromptContext::new().with("learnings", "use X");
+        assert_eq!(render(template, &with), "a\nLearnings: use X\nb\n");
+
+        let blank = PromptContext::new().with("learnings", "  \n");
+        assert_eq!(render(template, &blank), "a\nb\n");
+    }
+
+    #[test]
+    fn test_inline_if_block_keeps_surrounding_text() {
+        let context = PromptContext::new().with("language", "Go");
+        assert_eq!(
+            render("Use {{#if language}}{{language}} {{/if}}tools", &context),
+            "Use Go tools"
+        );
+        assert_eq!(
+            render(
+                "Use {{#if language}}{{language}} {{/if}}tools",
+                &PromptContext::new()
+            ),
+            "Use tools"
+        );
+    }
+
+    #[test]
+    fn test_nested_blocks() {
+        let template = "{{#if project}}\nP\n{{#if branch}}\nB\n{{/if}}\n{{/if}}\n";
+        let context = PromptContext::new().with("project", "x");
+        assert_eq!(render(template, &context), "P\n");
+        let context = context.with("branch", "y");
+        assert_eq!(render(template, &context), "P\nB\n");
+    }
+
+    #[test]
+    fn test_non_tag_braces_are_literal() {
+        let template = "println!(\"{{}}\", x); {{ not a tag }} {{story_id";
+        assert_eq!(render(template, &PromptContext::new()), template);
+    }
+
+    #[test]
+    fn test_unknown_variable_is_rejected() {
+        let err = PromptTemplate::parse("line one\n{{story_idd}}").unwrap_err();
+        match err {
+            PromptError::UnknownVariable { name, line } => {
+                assert_eq!(name, "story_idd");
+                assert_eq!(line, 2);
+            }
+            other => panic!("expected UnknownVariable, got {:?}", other),
+        }
+        assert!(matches!(
+            PromptTemplate::parse("{{#if nope}}x{{/if}}"),
+            Err(PromptError::UnknownVariable { .. })
+        ));
+    }
+
+    #[test]
+    fn test_unbalanced_blocks_are_rejected() {
+        let err = PromptTemplate::parse("\n{{#if branch}}\nx\n").unwrap_err();
+        assert!(
+            matches!(err, PromptError::UnclosedBlock { ref name, line: 2 } if name == "branch")
+        );
+        assert!(err.to_string().contains("{{#if branch}}"));
+
+        assert!(matches!(
+            PromptTemplate::parse("x\n{{/if}}"),
+            Err(PromptError::UnexpectedClose { line: 2 })
+        ));
+    }
+
+    #[test]
+    fn test_unsupported_block_is_rejected() {
+        let err = PromptTemplate::parse("{{#each story_id}}{{/each}}").unwrap_err();
+        assert!(matches!(err, PromptError::UnsupportedTag { .. }));
+        assert!(err.to_string().contains("{{#each story_id}}"));
+    }
+
+    #[test]
+    fn test_variables_lists_references_once() {
+        let template = PromptTemplate::parse(
+            "{{story_id}} {{#if learnings}}{{learnings}}{{/if}} {{story_id}}",
+        )
+        .unwrap();
+        assert_eq!(template.variables(), vec!["story_id", "learnings"]);
+    }
+}
@@ -0,0 +1,265 @@
+//! Audit findings for the story's changes.
+//!
+//! The audit gate runs the profile's enabled audit sections that produce
+//! findings: `patterns` (technical debt markers such as FIXME comments and
+//! debug code), `deps` (outdated dependencies) and `arch` (architecture
+//! gaps). Only findings in files the story changed are considered, and
+//! findings that already existed when the story started, recorded in an
+//! [`AuditBaseline`], are not the story's doing and are left out. The
+//! remaining critical and high findings are held to the profile's
+//! `max_critical_findings` and `max_high_findings`.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use thiserror::Error;
+
+use crate::audit::{
+    ArchitectureAnalyzer, ArchitectureGapsDetector, AuditError, AuditFinding, DependencyParser,
+    Severity, TechDebtDetector, TechDebtType,
+};
+use crate::git::{self, GitError};
+
+use super::{AuditConfig, AuditSections};
+
+/// Errors that can occur while checking audit findings
+#[derive(Error, Debug)]
+pub enum AuditGateError {
+    #[error("Failed to find changed files: {0}")]
+    Git(#[from] GitError),
+
+    #[error("Audit failed: {0}")]
+    Audit(#[from] AuditError),
+}
+
+/// What identifies a finding across audits; ids are numbered per audit
+type FindingKey = (String, String, String, Vec<PathBuf>);
+
+fn finding_key(finding: &AuditFinding) -> FindingKey {
+    (
+        finding.category.clone(),
+        finding.title.clone(),
+        finding.description.clone(),
+        finding.affected_files.clone(),
+    )
+}
+
+/// The findings present when a story started
+#[derive(Debug, Clone, Default)]
+pub struct AuditBaseline {
+    findings: HashSet<FindingKey>,
+}
+
+impl AuditBaseline {
+    /// Audit the project as it is now.
+    pub fn capture(project_root: &Path, sections: &AuditSections) -> Result<Self, AuditGateError> {
+        Ok(Self::from_findings(&collect_findings(
+            project_root,
+            sections,
+        )?))
+    }
+
+    /// A baseline of the given findings
+    pub fn from_findings(findings: &[AuditFinding]) -> Self {
+        Self {
+            findings: findings.iter().map(finding_key).collect(),
+        }
+    }
+
+    /// Whether the finding was already present at the baseline
+    pub fn contains(&self, finding: &AuditFinding) -> bool {
+        self.findings.contains(&finding_key(finding))
+    }
+
+    /// Number of findings in the baseline
+    pub fn len(&self) -> usize {
+        self.findings.len()
+    }
+
+    /// Whether the baseline has no findings
+    pub fn is_empty(&self) -> bool {
+        self.findings.is_empty()
+    }
+}
+
+/// Run the enabled audit sections that produce findings.
+pub fn collect_findings(
+    project_root: &Path,
+    sections: &AuditSections,
+) -> Result<Vec<AuditFinding>, AuditGateError> {
+    let root = project_root.to_path_buf();
+    let mut findings = Vec::new();
+
+    if sections.patterns || sections.deps {
+        let dependencies = if sections.deps {
+            Some(DependencyParser::new(root.clone()).parse()?)
+        } else {
+            None
+        };
+        let detector = TechDebtDetector::new(root.clone());
+        let mut analysis = detector.analyze(dependencies.as_ref())?;
+        if !sections.patterns {
+            analysis
+                .items
+                .retain(|item| item.debt_type == TechDebtType::OutdatedDependency);
+        }
+        findings.extend(detector.to_findings(&analysis));
+    }
+
+    if sections.arch {
+        let architecture = ArchitectureAnalyzer::new(root.clone()).analyze()?;
+        let detector = ArchitectureGapsDetector::new(root.clone());
+        let analysis = detector.analyze(Some(&architecture))?;
+        findings.extend(detector.to_findings(&analysis));
+    }
+
+    // Detectors may report absolute paths; changes are relative to the root
+    for finding in &mut findings {
+        for file in &mut finding.affected_files {
+            if let Ok(relative) = file.strip_prefix(&root) {
+                *file = relative.to_path_buf();
+            }
+        }
+    }
+    Ok(findings)
+}
+
+/// Findings the story introduced: those affecting files changed since the
+/// `base` commit that are not in the `baseline`.
+///
+/// Architecture findings name a module directory rather than a file, so a
+/// finding also affects the changed files inside its directories.
+pub fn new_findings(
+    project_root: &Path,
+    sections: &AuditSections,
+    baseline: Option<&AuditBaseline>,
+    base: &str,
+) -> Result<Vec<AuditFinding>, AuditGateError> {
+    let changes = git::changed_lines(project_root, base)?;
+    Ok(collect_findings(project_root, sections)?
+        .into_iter()
+        .filter(|finding| {
+            finding.affected_files.iter().any(|affected| {
+                changes
+                    .keys()
+                    .any(|changed| Path::new(changed).starts_with(affected))
+            })
+        })
+        .filter(|finding| !baseline.is_some_and(|baseline| baseline.contains(finding)))
+        .collect())
+}
+
+/// The critical and high findings, most severe first, if either count is
+/// over its threshold. Empty if the findings are within the thresholds.
+pub fn offending_findings<'a>(
+    findings: &'a [AuditFinding],
+    config: &AuditConfig,
+) -> Vec<&'a AuditFinding> {
+    let count = |severity| {
+        findings
+            .iter()
+            .filter(|finding| finding.severity == severity)
+            .count()
+    };
+    if count(Severity::Critical) <= config.max_critical_findings as usize
+        && count(Severity::High) <= config.max_high_findings as usize
+    {
+        return Vec::new();
+    }
+
+    let mut offending: Vec<&AuditFinding> = findings
+        .iter()
+        .filter(|finding| finding.severity >= Severity::High)
+        .collect();
+    offending.sort_by_key(|finding| std::cmp::Reverse(finding.severity));
+    offending
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::test_support::init_repo;
+    use tempfile::TempDir;
+
+    fn finding(id: &str, severity: Severity) -> AuditFinding {
+        AuditFinding {
+            id: id.to_string(),
+            severity,
+            category: "architecture".to_string(),
+            title: "Layer Violation".to_string(),
+            description: format!("{} crosses a layer", id),
+            affected_files: vec![PathBuf::from("src/ui/view.rs")],
+            recommendation: "Add an abstraction layer".to_string(),
+        }
+    }
+
+    fn patterns_only() -> AuditSections {
+        AuditSections {
+            structure: false,
+            patterns: true,
+            api: false,
+            deps: false,
+            tests: false,
+            docs: false,
+            arch: false,
+        }
+    }
+
+    #[test]
+    fn test_only_new_findings_in_changed_files_count() {
+        let dir = TempDir::new().unwrap();
+        init_repo(dir.path());
+        std::fs::create_dir(dir.path().join("src")).unwrap();
+        std::fs::write(
+            dir.path().join("src/lib.rs"),
+            "// FIXME: old parser bug\npub fn parse() {}\n",
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("src/other.rs"), "// FIXME: untouched\n").unwrap();
+        git::run_git(dir.path(), &["add", "-A"]).unwrap();
+        git::run_git(dir.path(), &["commit", "-q", "-m", "lib"]).unwrap();
+
+        let baseline = AuditBaseline::capture(dir.path(), &patterns_only()).unwrap();
+        assert_eq!(baseline.len(), 2);
+
+        std::fs::write(
+            dir.path().join("src/lib.rs"),
+            "// FIXME: old parser bug\npub fn parse() {}\n// FIXME: new lexer bug\npub fn lex() {}\n",
+        )
+        .unwrap();
+
+        let descriptions = |baseline: Option<&AuditBaseline>| -> Vec<String> {
+            new_findings(dir.path(), &patterns_only(), baseline, "HEAD")
+                .unwrap()
+                .into_iter()
+                .map(|finding| finding.description)
+                .collect()
+        };
+        assert_eq!(descriptions(Some(&baseline)), vec!["FIXME: new lexer bug"]);
+        // Without a baseline, every finding in a changed file counts
+        assert_eq!(descriptions(None).len(), 2);
+    }
+
+    #[test]
+    fn test_offending_findings_respect_thresholds() {
+        let config = AuditConfig {
+            enabled: true,
+            max_critical_findings: 0,
+            max_high_findings: 1,
+            sections: AuditSections::default(),
+        };
+        let within = vec![finding("A", Severity::High), finding("B", Severity::Medium)];
+        assert!(offending_findings(&within, &config).is_empty());
+
+        let over = vec![
+            finding("A", Severity::High),
+            finding("B", Severity::Medium),
+            finding("C", Severity::Critical),
+        ];
+        let ids: Vec<&str> = offending_findings(&over, &config)
+            .iter()
+            .map(|finding| finding.id.as_str())
+            .collect();
+        assert_eq!(ids, vec!["C", "A"]);
+    }
+}
@@ -0,0 +1,359 @@
+//! User-defined command gates declared in a quality profile.
+//!
+//! Besides the built-in gates, a profile can list project-specific checks
+//! such as schema validation or a codegen-up-to-date check. Each one is a
+//! shell command with its own working directory, environment, timeout and
+//! expected exit code, optionally judged by patterns in its output. A
+//! required gate blocks the story like any built-in gate; an advisory one is
+//! reported but never blocks.
+//!
+//! ```toml
+//! [[profiles.standard.gates]]
+//! name = "schema"
+//! command = "npm run validate-schema"
+//! working_dir = "web"
+//! env = { NODE_ENV = "test" }
+//! timeout_secs = 120
+//! fail_pattern = "(?i)invalid"
+//! required = true
+//! depends_on = ["tests"]
+//! ```
+
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use regex::Regex;
+use serde::Deserialize;
+
+use super::gates::GateResult;
+use super::verification::output_tail;
+
+/// How often a running gate command is checked for completion
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// A shell command that must succeed before a story is committed.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct CommandGate {
+    /// Name the gate reports under
+    pub name: String,
+    /// Shell command, run with `sh -c`
+    pub command: String,
+    /// Directory to run the command in, relative to the project root
+    #[serde(default)]
+    pub working_dir: Option<PathBuf>,
+    /// Extra environment variables for the command
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    /// Seconds after which the command is killed and the gate fails
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+    /// Exit code the command must return
+    #[serde(default)]
+    pub exit_code: i32,
+    /// Regular expression the output must match for the gate to pass
+    #[serde(default)]
+    pub pass_pattern: Option<String>,
+    /// Regular expression that fails the gate if the output matches it
+    #[serde(default)]
+    pub fail_pattern: Option<String>,
+    /// Whether a failure blocks the story (false = advisory)
+    #[serde(default = "default_required")]
+    pub required: bool,
+    /// Gates that must finish before this one starts
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+}
+
+fn default_required() -> bool {
+    true
+}
+
+impl CommandGate {
+    /// Run the gate in `project_root`
+    pub fn run(&self, project_root: &Path) -> GateResult {
+        self.run_with_output(project_root, &mut String::new())
+    }
+
+    /// Run the gate in `project_root`, keeping the command's combined output
+    /// in `output`
+    pub(crate) fn run_with_output(&self, project_root: &Path, output: &mut String) -> GateResult {
+        self.evaluate(project_root, output)
+            .with_advisory(!self.required)
+            .with_actionable_details(true)
+    }
+
+    fn evaluate(&self, project_root: &Path, captured: &mut String) -> GateResult {
+        let name = self.name.as_str();
+        let pass_pattern = match compile(name, self.pass_pattern.as_deref()) {
+            Ok(regex) => regex,
+            Err(result) => return result,
+        };
+        let fail_pattern = match compile(name, self.fail_pattern.as_deref()) {
+            Ok(regex) => regex,
+            Err(result) => return result,
+        };
+
+        let dir = match self.working_dir {
+            Some(ref dir) => project_root.join(dir),
+            None => project_root.to_path_buf(),
+        };
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", &self.command])
+            .current_dir(&dir)
+            .envs(&self.env);
+
+        let timeout = self.timeout_secs.map(Duration::from_secs);
+        let output = match run_with_timeout(cmd, timeout) {
+            Ok(Some(output)) => output,
+            Ok(None) => {
+                return GateResult::fail(
+                    name,
+                    format!("Timed out after {}s", self.timeout_secs.unwrap_or_default()),
+                    None,
+                )
+            }
+            Err(e) => {
+                return GateResult::fail(
+                    name,
+                    "Failed to run gate command",
+                    Some(format!("Error: {}", e)),
+                )
+            }
+        };
+
+        captured.clone_from(&output.text);
+        let details = Some(output_tail(&output.text));
+        if output.code != Some(self.exit_code) {
+            let exited = match output.code {
+                Some(code) => format!("Exited with {}", code),
+                None => "Killed by a signal".to_string(),
+            };
+            return GateResult::fail(
+                name,
+                format!("{}, expected {}", exited, self.exit_code),
+                details,
+            );
+        }
+        if let Some(regex) = fail_pattern {
+            if let Some(line) = output.text.lines().find(|line| regex.is_match(line)) {
+                return GateResult::fail(
+                    name,
+                    format!("Output matched /{}/", regex),
+                    Some(line.trim().to_string()),
+                );
+            }
+        }
+        if let Some(regex) = pass_pattern {
+            if !regex.is_match(&output.text) {
+                return GateResult::fail(
+                    name,
+                    format!("Output did not match /{}/", regex),
+                    details,
+                );
+            }
+        }
+        GateResult::pass(name, format!("Exited with {}", self.exit_code))
+    }
+}
+
+/// Compile an optional gate pattern, or the failing result for an invalid one
+fn compile(name: &str, pattern: Option<&str>) -> Result<Option<Regex>, GateResult> {
+    pattern
+        .map(|pattern| {
+            Regex::new(pattern).map_err(|e| {
+                GateResult::fail(
+                    name,
+                    "Invalid gate pattern",
+                    Some(format!("/{}/: {}", pattern, e)),
+                )
+            })
+        })
+        .transpose()
+}
+
+/// Exit code and combined stdout and stderr of a finished command
+struct CommandOutput {
+    code: Option<i32>,
+    text: String,
+}
+
+/// Run `cmd` to completion, or kill it once `timeout` has passed.
+///
+/// # Returns
+/// The command's output, or None if it timed out
+fn run_with_timeout(
+    mut cmd: Command,
+    timeout: Option<Duration>,
+) -> std::io::Result<Option<CommandOutput>> {
+    // Run the command in its own process group so a timeout also kills
+    // anything it started, such as a server or a test runner's workers
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(&mut cmd, 0);
+
+    let mut child = cmd
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    // Drain both pipes while waiting so a chatty command cannot block on them
+    let stdout = child.stdout.take().map(read_to_end);
+    let stderr = child.stderr.take().map(read_to_end);
+
+    let start = Instant::now();
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if timeout.is_some_and(|timeout| start.elapsed() >= timeout) {
+            kill_process_group(&child);
+            let _ = child.kill();
+            let _ = child.wait();
+            return Ok(None);
+        }
+        thread::sleep(POLL_INTERVAL);
+    };
+
+    let mut text = String::new();
+    for reader in [stdout, stderr].into_iter().flatten() {
+        text.push_str(&reader.join().unwrap_or_default());
+    }
+    Ok(Some(CommandOutput {
+        code: status.code(),
+        text,
+    }))
+}
+
+/// Kill every process in the group the gate command leads
+#[cfg(unix)]
+fn kill_process_group(child: &Child) {
+    let _ = Command::new("kill")
+        .args(["-KILL", "--", &format!("-{}", child.id())])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status();
+}
+
+#[cfg(not(unix))]
+fn kill_process_group(_child: &Child) {}
+
+/// Read a pipe to the end on its own thread
+fn read_to_end(mut pipe: impl Read + Send + 'static) -> thread::JoinHandle<String> {
+    thread::spawn(move || {
+        let mut bytes = Vec::new();
+        let _ = pipe.read_to_end(&mut bytes);
+        String::from_utf8_lossy(&bytes).into_owned()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn gate(toml: &str) -> CommandGate {
+        toml::from_str(toml).unwrap()
+    }
+
+    #[test]
+    fn test_parse_defaults() {
+        let gate = gate("name = \"schema\"\ncommand = \"make schema\"\n");
+        assert_eq!(gate.exit_code, 0);
+        assert!(gate.required);
+        assert!(gate.env.is_empty());
+        assert!(gate.working_dir.is_none());
+        assert!(gate.timeout_secs.is_none());
+    }
+
+    #[test]
+    fn test_exit_code_working_dir_and_env() {
+        let dir = TempDir::new().unwrap();
+        std::fs::create_dir(dir.path().join("web")).unwrap();
+        std::fs::write(dir.path().join("web/marker"), "").unwrap();
+
+        let pass = gate(
+            r#"
+name = "env"
+command = "test -f marker && test \"$MODE\" = strict && exit 3"
+working_dir = "web"
+env = { MODE = "strict" }
+exit_code = 3
+"#,
+        )
+        .run(dir.path());
+        assert!(pass.passed, "{:?}", pass);
+
+        let fail = gate("name = \"broken\"\ncommand = \"echo oops; exit 1\"\n").run(dir.path());
+        assert!(!fail.passed);
+        assert!(!fail.advisory);
+        assert_eq!(fail.message, "Exited with 1, expected 0");
+        assert_eq!(fail.details.as_deref(), Some("oops"));
+    }
+
+    #[test]
+    fn test_output_patterns() {
+        let dir = TempDir::new().unwrap();
+        let toml = |command: &str| {
+            format!(
+                "name = \"codegen\"\ncommand = \"{}\"\npass_pattern = \"up to date\"\nfail_pattern = \"^stale:\"\n",
+                command
+            )
+        };
+
+        assert!(
+            gate(&toml("echo generated code is up to date"))
+                .run(dir.path())
+                .passed
+        );
+
+        let stale = gate(&toml("echo stale: api.rs; echo up to date")).run(dir.path());
+        assert!(!stale.passed);
+        assert_eq!(stale.message, "Output matched /^stale:/");
+        assert_eq!(stale.details.as_deref(), Some("stale: api.rs"));
+
+        let silent = gate(&toml("true")).run(dir.path());
+        assert_eq!(silent.message, "Output did not match /up to date/");
+
+        let invalid = gate("name = \"x\"\ncommand = \"true\"\npass_pattern = \"(\"\n");
+        assert_eq!(invalid.run(dir.path()).message, "Invalid gate pattern");
+    }
+
+    #[test]
+    fn test_timeout() {
+        let dir = TempDir::new().unwrap();
+        let start = Instant::now();
+        let result =
+            gate("name = \"slow\"\ncommand = \"sleep 5\"\ntimeout_secs = 0\n").run(dir.path());
+        assert!(!result.passed);
+        assert_eq!(result.message, "Timed out after 0s");
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_timeout_kills_processes_the_command_started() {
+        let dir = TempDir::new().unwrap();
+        let result = gate(
+            "name = \"server\"\ncommand = \"(sleep 2; touch started) & wait\"\ntimeout_secs = 1\n",
+        )
+        .run(dir.path());
+        assert_eq!(result.message, "Timed out after 1s");
+
+        thread::sleep(Duration::from_secs(2));
+        assert!(!dir.path().join("started").exists());
+    }
+
+    #[test]
+    fn test_advisory_gate_does_not_block() {
+        let dir = TempDir::new().unwrap();
+        let result =
+            gate("name = \"docs\"\ncommand = \"false\"\nrequired = false\n").run(dir.path());
+        assert!(!result.passed);
+        assert!(result.advisory);
+        assert!(!result.blocks());
+    }
+}
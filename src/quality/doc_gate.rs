@@ -0,0 +1,287 @@
+//! Documentation checks for the story's changes.
+//!
+//! The documentation gate enforces a profile's `[documentation]` settings
+//! against what a story changed, using the audit's [`DocAnalyzer`]:
+//!
+//! - `required`: public items the story adds must have doc comments
+//! - `readme`: the project must have a README, and its relative links must
+//!   point to files that still exist
+//! - `inline_comments`: private functions the story adds must have a
+//!   comment too
+//!
+//! Items that were already undocumented before the story are not its
+//! concern, so only changes since the commit the story started from are
+//! checked.
+
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use regex::Regex;
+
+use crate::audit::DocAnalyzer;
+use crate::git::{self, ChangedLines, GitError};
+
+use super::DocumentationConfig;
+
+/// A documentation problem found by the gate
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DocIssue {
+    /// A new public item without a doc comment
+    UndocumentedItem {
+        file: String,
+        line: usize,
+        item_type: String,
+        name: String,
+    },
+    /// A new private function without a comment
+    UncommentedFunction {
+        file: String,
+        line: usize,
+        name: String,
+    },
+    /// The project has no README
+    MissingReadme,
+    /// A relative README link to a file that does not exist
+    BrokenReadmeLink { readme: String, target: String },
+}
+
+impl fmt::Display for DocIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DocIssue::UndocumentedItem {
+                file,
+                line,
+                item_type,
+                name,
+            } => write!(
+                f,
+                "{}:{}: public {} `{}` has no doc comment",
+                file, line, item_type, name
+            ),
+            DocIssue::UncommentedFunction { file, line, name } => {
+                write!(f, "{}:{}: function `{}` has no comment", file, line, name)
+            }
+            DocIssue::MissingReadme => f.write_str("No README file in the project root"),
+            DocIssue::BrokenReadmeLink { readme, target } => {
+                write!(f, "{} links to missing file {}", readme, target)
+            }
+        }
+    }
+}
+
+/// Check the working tree's changes since the `base` commit against the
+/// documentation settings.
+pub fn check_documentation(
+    project_root: &Path,
+    config: &DocumentationConfig,
+    base: &str,
+) -> Result<Vec<DocIssue>, GitError> {
+    let mut issues = Vec::new();
+    if config.required || config.inline_comments {
+        let changes = git::changed_lines(project_root, base)?;
+        if config.required {
+            issues.extend(new_undocumented_items(project_root, &changes, base));
+        }
+        if config.inline_comments {
+            issues.extend(new_uncommented_functions(project_root, &changes));
+        }
+    }
+    if config.readme {
+        issues.extend(readme_issues(project_root));
+    }
+    Ok(issues)
+}
+
+/// Public items that are undocumented now but were not in `base`
+fn new_undocumented_items(
+    project_root: &Path,
+    changes: &BTreeMap<String, ChangedLines>,
+    base: &str,
+) -> Vec<DocIssue> {
+    let analyzer = DocAnalyzer::new(project_root.to_path_buf());
+    let mut issues = Vec::new();
+    for (file, lines) in changes.iter().filter(|(file, _)| file.ends_with(".rs")) {
+        let Ok(content) = fs::read_to_string(project_root.join(file)) else {
+            continue;
+        };
+        let before: HashSet<(String, String)> = match lines {
+            ChangedLines::All => HashSet::new(),
+            ChangedLines::Lines(_) => {
+                let old = git::run_git(project_root, &["show", &format!("{}:{}", base, file)])
+                    .unwrap_or_default();
+                analyzer
+                    .undocumented_items(&old, Path::new(file))
+                    .into_iter()
+                    .map(|item| (item.item_type, item.name))
+                    .collect()
+            }
+        };
+
+        issues.extend(
+            analyzer
+                .undocumented_items(&content, Path::new(file))
+                .into_iter()
+                .filter(|item| !before.contains(&(item.item_type.clone(), item.name.clone())))
+                .map(|item| DocIssue::UndocumentedItem {
+                    file: file.clone(),
+                    line: item.line,
+                    item_type: item.item_type,
+                    name: item.name,
+                }),
+        );
+    }
+    issues
+}
+
+/// Private functions on changed lines without a comment above them
+fn new_uncommented_functions(
+    project_root: &Path,
+    changes: &BTreeMap<String, ChangedLines>,
+) -> Vec<DocIssue> {
+    let private_fn =
+        Regex::new(r"^\s*(?:pub\([^)]*\)\s+)?(?:const\s+)?(?:async\s+)?(?:unsafe\s+)?fn\s+(\w+)")
+            .expect("valid regex");
+    let mut issues = Vec::new();
+    for (file, changed) in changes.iter().filter(|(file, _)| file.ends_with(".rs")) {
+        if file.starts_with("tests/") || file.contains("/tests/") {
+            continue;
+        }
+        let Ok(content) = fs::read_to_string(project_root.join(file)) else {
+            continue;
+        };
+        let lines: Vec<&str> = content.lines().collect();
+        for (index, line) in lines.iter().enumerate() {
+            // Test functions explain themselves through their names
+            if line.trim_start().starts_with("#[cfg(test)]") {
+                break;
+            }
+            let Some(name) = private_fn
+                .captures(line)
+                .map(|captures| captures[1].to_string())
+            else {
+                continue;
+            };
+            if changed.contains(index + 1) && !has_comment_above(&lines, index) {
+                issues.push(DocIssue::UncommentedFunction {
+                    file: file.clone(),
+                    line: index + 1,
+                    name,
+                });
+            }
+        }
+    }
+    issues
+}
+
+/// Whether a comment precedes the line at `index`, skipping attributes
+fn has_comment_above(lines: &[&str], index: usize) -> bool {
+    lines[..index]
+        .iter()
+        .rev()
+        .map(|line| line.trim())
+        .find(|line| !line.starts_with("#["))
+        .is_some_and(|line| line.starts_with("//") || line.ends_with("*/"))
+}
+
+/// Problems with the project's README
+fn readme_issues(project_root: &Path) -> Vec<DocIssue> {
+    let readme = DocAnalyzer::new(project_root.to_path_buf())
+        .analyze_readme()
+        .unwrap_or_default();
+    let Some(path) = readme.path.filter(|_| readme.exists) else {
+        return vec![DocIssue::MissingReadme];
+    };
+    let Ok(content) = fs::read_to_string(project_root.join(&path)) else {
+        return Vec::new();
+    };
+
+    let link = Regex::new(r"\]\(\s*<?([^)\s>#?]+)").expect("valid regex");
+    let readme_dir = project_root.join(&path);
+    let readme_dir = readme_dir.parent().unwrap_or(project_root);
+    link.captures_iter(&content)
+        .map(|captures| captures[1].to_string())
+        .filter(|target| !target.contains("://") && !target.starts_with("mailto:"))
+        .filter(|target| {
+            let target = target.trim_start_matches('/');
+            !readme_dir.join(target).exists() && !project_root.join(target).exists()
+        })
+        .map(|target| DocIssue::BrokenReadmeLink {
+            readme: path.display().to_string(),
+            target,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::test_support::init_repo;
+    use tempfile::TempDir;
+
+    fn config(required: bool, readme: bool, inline_comments: bool) -> DocumentationConfig {
+        DocumentationConfig {
+            required,
+            readme,
+            inline_comments,
+        }
+    }
+
+    #[test]
+    fn test_only_new_undocumented_items_are_reported() {
+        let dir = TempDir::new().unwrap();
+        init_repo(dir.path());
+        std::fs::create_dir(dir.path().join("src")).unwrap();
+        std::fs::write(dir.path().join("src/lib.rs"), "pub fn old() {}\n").unwrap();
+        git::run_git(dir.path(), &["add", "-A"]).unwrap();
+        git::run_git(dir.path(), &["commit", "-q", "-m", "lib"]).unwrap();
+
+        std::fs::write(
+            dir.path().join("src/lib.rs"),
+            "pub fn old() {}\n\n/// Documented\npub fn documented() {}\n\npub struct Config;\n\nfn helper() {}\n",
+        )
+        .unwrap();
+
+        let issues = check_documentation(dir.path(), &config(true, false, false), "HEAD").unwrap();
+        assert_eq!(
+            issues
+                .iter()
+                .map(|issue| issue.to_string())
+                .collect::<Vec<_>>(),
+            vec!["src/lib.rs:6: public struct `Config` has no doc comment"]
+        );
+
+        let issues = check_documentation(dir.path(), &config(false, false, true), "HEAD").unwrap();
+        assert_eq!(
+            issues,
+            vec![DocIssue::UncommentedFunction {
+                file: "src/lib.rs".to_string(),
+                line: 8,
+                name: "helper".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_readme_must_exist_with_working_links() {
+        let dir = TempDir::new().unwrap();
+        assert_eq!(readme_issues(dir.path()), vec![DocIssue::MissingReadme]);
+
+        std::fs::create_dir(dir.path().join("docs")).unwrap();
+        std::fs::write(dir.path().join("docs/guide.md"), "# Guide\n").unwrap();
+        std::fs::write(
+            dir.path().join("README.md"),
+            "# App\n\nSee the [guide](docs/guide.md#setup), [API](docs/api.md), \
+             [site](https://example.com) and [top](#app).\n",
+        )
+        .unwrap();
+        assert_eq!(
+            readme_issues(dir.path()),
+            vec![DocIssue::BrokenReadmeLink {
+                readme: "README.md".to_string(),
+                target: "docs/api.md".to_string(),
+            }]
+        );
+    }
+}
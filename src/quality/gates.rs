@@ -2,15 +2,23 @@
 //!
 //! This module provides the infrastructure for running quality gates
 //! against a codebase, including coverage, linting, formatting, and security checks.
+//! Rust projects are checked with cargo; other ecosystems run the equivalent
+//! commands of their [`Toolchain`].
 
 // Allow dead_code for now - these types will be used in future stories
 #![allow(dead_code)]
 
-use crate::quality::Profile;
+use crate::audit::Severity;
+use crate::quality::audit_gate::{self, AuditBaseline, AuditGateError};
+use crate::quality::scheduler::{self, GateJob};
+use crate::quality::verification::output_tail;
+use crate::quality::{doc_gate, sast};
+use crate::quality::{Profile, TestFailure, TestReport, Toolchain, ToolchainGate, Verification};
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 use std::process::Command;
-use std::time::{Duration, Instant};
+use std::sync::OnceLock;
+use std::time::Duration;
 
 /// Progress state for a quality gate.
 ///
@@ -23,6 +31,9 @@ pub enum GateProgressState {
     Passed,
     /// Gate failed
     Failed,
+    /// Gate did not run because a gate it depends on failed, or a required
+    /// gate failed first with `fail_fast`
+    Skipped,
 }
 
 /// Progress update for a quality gate.
@@ -36,6 +47,8 @@ pub struct GateProgressUpdate {
     pub state: GateProgressState,
     /// Duration of the gate execution (only set for Passed/Failed states)
     pub duration: Option<Duration>,
+    /// The gate's result, once it has one (see [`Self::with_result`])
+    pub result: Option<GateResult>,
 }
 
 impl GateProgressUpdate {
@@ -45,6 +58,7 @@ impl GateProgressUpdate {
             gate_name: gate_name.into(),
             state: GateProgressState::Running,
             duration: None,
+            result: None,
         }
     }
 
@@ -54,6 +68,7 @@ impl GateProgressUpdate {
             gate_name: gate_name.into(),
             state: GateProgressState::Passed,
             duration: Some(duration),
+            result: None,
         }
     }
 
@@ -63,9 +78,26 @@ impl GateProgressUpdate {
             gate_name: gate_name.into(),
             state: GateProgressState::Failed,
             duration: Some(duration),
+            result: None,
         }
     }
 
+    /// Create a new Skipped progress update.
+    pub fn skipped(gate_name: impl Into<String>) -> Self {
+        Self {
+            gate_name: gate_name.into(),
+            state: GateProgressState::Skipped,
+            duration: None,
+            result: None,
+        }
+    }
+
+    /// Attach the result of the gate that completed.
+    pub fn with_result(mut self, result: GateResult) -> Self {
+        self.result = Some(result);
+        self
+    }
+
     /// Check if this is a Running state.
     pub fn is_running(&self) -> bool {
         self.state == GateProgressState::Running
@@ -81,11 +113,16 @@ impl GateProgressUpdate {
         self.state == GateProgressState::Failed
     }
 
-    /// Check if the gate has completed (Passed or Failed).
+    /// Check if this is a Skipped state.
+    pub fn is_skipped(&self) -> bool {
+        self.state == GateProgressState::Skipped
+    }
+
+    /// Check if the gate has completed (Passed, Failed or Skipped).
     pub fn is_completed(&self) -> bool {
         matches!(
             self.state,
-            GateProgressState::Passed | GateProgressState::Failed
+            GateProgressState::Passed | GateProgressState::Failed | GateProgressState::Skipped
         )
     }
 
@@ -116,6 +153,17 @@ pub struct GateResult {
     pub message: String,
     /// Additional details about the gate result (e.g., specific errors, metrics)
     pub details: Option<String>,
+    /// Whether a failure is only reported and does not block the story
+    #[serde(default)]
+    pub advisory: bool,
+    /// Tests that failed, with their assertion messages (tests gate only)
+    #[serde(default)]
+    pub failed_tests: Vec<TestFailure>,
+    /// Whether a failure's message and details name exactly what did not
+    /// hold, such as the failing tests, findings or command output, so they
+    /// are worth showing the agent
+    #[serde(default)]
+    pub actionable_details: bool,
 }
 
 impl GateResult {
@@ -126,6 +174,9 @@ impl GateResult {
             passed: true,
             message: message.into(),
             details: None,
+            advisory: false,
+            failed_tests: Vec::new(),
+            actionable_details: false,
         }
     }
 
@@ -140,6 +191,9 @@ impl GateResult {
             passed: false,
             message: message.into(),
             details,
+            advisory: false,
+            failed_tests: Vec::new(),
+            actionable_details: false,
         }
     }
 
@@ -150,8 +204,34 @@ impl GateResult {
             passed: true, // Skipped gates count as passed
             message: format!("Skipped: {}", reason.into()),
             details: None,
+            advisory: false,
+            failed_tests: Vec::new(),
+            actionable_details: false,
         }
     }
+
+    /// Mark the gate as advisory, so a failure does not block the story.
+    pub fn with_advisory(mut self, advisory: bool) -> Self {
+        self.advisory = advisory;
+        self
+    }
+
+    /// Mark the message and details as telling the agent what to fix.
+    pub fn with_actionable_details(mut self, actionable_details: bool) -> Self {
+        self.actionable_details = actionable_details;
+        self
+    }
+
+    /// Attach the tests that failed.
+    pub fn with_failed_tests(mut self, failed_tests: Vec<TestFailure>) -> Self {
+        self.failed_tests = failed_tests;
+        self
+    }
+
+    /// Check whether this result blocks the story (a failed required gate).
+    pub fn blocks(&self) -> bool {
+        !self.passed && !self.advisory
+    }
 }
 
 /// A checker that runs quality gates based on a profile configuration.
@@ -160,6 +240,32 @@ pub struct QualityGateChecker {
     profile: Profile,
     /// The root directory of the project to check
     project_root: PathBuf,
+    /// The toolchain the gates run with, resolved on first use
+    toolchain: OnceLock<Toolchain>,
+    /// Audit findings present when the story started
+    audit_baseline: Option<AuditBaseline>,
+    /// Commit the story's changes are measured from; HEAD if unset
+    change_base: Option<String>,
+}
+
+/// A built-in gate check
+type GateCheck = fn(&QualityGateChecker) -> GateResult;
+
+/// A built-in gate: its name, its check, and whether the profile enables it.
+/// Disabled gates still run, and report themselves as skipped.
+type BuiltinGate = (&'static str, GateCheck, bool);
+
+/// Built-in gates that wait for another gate: coverage runs the tests
+/// again, so it only starts once the tests gate has passed.
+const BUILTIN_DEPENDENCIES: &[(&str, &str)] = &[("coverage", "tests")];
+
+/// One item per line, for gate details
+fn join_lines(items: &[impl std::fmt::Display]) -> String {
+    items
+        .iter()
+        .map(|item| item.to_string())
+        .collect::<Vec<_>>()
+        .join("\n")
 }
 
 impl QualityGateChecker {
@@ -173,9 +279,45 @@ impl QualityGateChecker {
         Self {
             profile,
             project_root: project_root.into(),
+            toolchain: OnceLock::new(),
+            audit_baseline: None,
+            change_base: None,
         }
     }
 
+    /// Set the audit findings present when the story started.
+    ///
+    /// The audit gate only counts findings that are not in the baseline.
+    pub fn with_audit_baseline(mut self, baseline: AuditBaseline) -> Self {
+        self.audit_baseline = Some(baseline);
+        self
+    }
+
+    /// Set the commit the story started from.
+    ///
+    /// The SAST, documentation and audit gates check the changes made since
+    /// this commit rather than since HEAD, so work the story has already
+    /// committed, such as WIP commits, is still checked.
+    pub fn with_change_base(mut self, base: impl Into<String>) -> Self {
+        self.change_base = Some(base.into());
+        self
+    }
+
+    /// The commit the story's changes are measured from
+    fn change_base(&self) -> &str {
+        self.change_base.as_deref().unwrap_or("HEAD")
+    }
+
+    /// Audit the project for a story-start baseline.
+    ///
+    /// Returns None if the profile's audit is disabled.
+    pub fn capture_audit_baseline(&self) -> Result<Option<AuditBaseline>, AuditGateError> {
+        if !self.profile.audit.enabled {
+            return Ok(None);
+        }
+        AuditBaseline::capture(&self.project_root, &self.profile.audit.sections).map(Some)
+    }
+
     /// Get the profile being used for quality checks.
     pub fn profile(&self) -> &Profile {
         &self.profile
@@ -186,6 +328,36 @@ impl QualityGateChecker {
         &self.project_root
     }
 
+    /// Get the toolchain the gates run with.
+    ///
+    /// This is the profile's `toolchain` if set, else the one detected from
+    /// the project, falling back to cargo.
+    pub fn toolchain(&self) -> Toolchain {
+        *self.toolchain.get_or_init(|| {
+            self.profile
+                .toolchain
+                .or_else(|| Toolchain::detect(&self.project_root))
+                .unwrap_or(Toolchain::Cargo)
+        })
+    }
+
+    /// Run `gate` with the project's toolchain.
+    ///
+    /// Returns None for cargo projects, whose gates are built in.
+    fn run_toolchain_gate(&self, gate: ToolchainGate) -> Option<GateResult> {
+        let toolchain = self.toolchain();
+        if toolchain == Toolchain::Cargo {
+            return None;
+        }
+        Some(match toolchain.gate(gate, &self.project_root) {
+            Some(command) => command.run(&self.project_root),
+            None => GateResult::skipped(
+                gate.name(),
+                format!("No {} gate for this {} project", gate.name(), toolchain),
+            ),
+        })
+    }
+
     /// Check code coverage against the profile threshold.
     ///
     /// This method runs either `cargo llvm-cov` or `cargo tarpaulin` to measure
@@ -203,6 +375,10 @@ impl QualityGateChecker {
             return GateResult::skipped("coverage", "Coverage threshold is 0 - no check required");
         }
 
+        if self.toolchain() != Toolchain::Cargo {
+            return self.run_toolchain_coverage();
+        }
+
         // Try cargo-llvm-cov first (more common in CI environments)
         let llvm_cov_result = self.run_llvm_cov();
         if let Some(result) = llvm_cov_result {
@@ -227,6 +403,31 @@ impl QualityGateChecker {
         )
     }
 
+    /// Run the toolchain's coverage command and parse the coverage percentage.
+    fn run_toolchain_coverage(&self) -> GateResult {
+        let toolchain = self.toolchain();
+        let Some(gate) = toolchain.gate(ToolchainGate::Coverage, &self.project_root) else {
+            return GateResult::skipped(
+                "coverage",
+                format!("No coverage gate for this {} project", toolchain),
+            );
+        };
+
+        let mut output = String::new();
+        let result = gate.run_with_output(&self.project_root, &mut output);
+        if !result.passed {
+            return result;
+        }
+        match Self::parse_coverage_percentage(&output) {
+            Some(coverage) => self.evaluate_coverage(coverage, toolchain.name()),
+            None => GateResult::fail(
+                "coverage",
+                "Failed to parse coverage output",
+                Some(format!("Output: {}", output)),
+            ),
+        }
+    }
+
     /// Run cargo-llvm-cov and parse the coverage percentage.
     fn run_llvm_cov(&self) -> Option<GateResult> {
         // Check if cargo-llvm-cov is installed
@@ -385,8 +586,10 @@ impl QualityGateChecker {
         let re_patterns = [
             // Match "XX.XX% coverage" (tarpaulin format)
             r"(\d+(?:\.\d+)?)\s*%\s*coverage",
-            // Match "TOTAL ... XX.XX%" (llvm-cov format)
+            // Match "TOTAL ... XX.XX%" (llvm-cov and pytest-cov format)
             r"TOTAL\s+.*?(\d+(?:\.\d+)?)\s*%",
+            // Match "Lines : XX.XX%" (jest and vitest text-summary format)
+            r"Lines\s*:\s*(\d+(?:\.\d+)?)\s*%",
             // Match standalone percentage at end of line
             r"(\d+(?:\.\d+)?)\s*%\s*$",
         ];
@@ -440,6 +643,9 @@ impl QualityGateChecker {
         if !self.profile.ci.lint_check {
             return GateResult::skipped("lint", "Lint checking not enabled in profile");
         }
+        if let Some(result) = self.run_toolchain_gate(ToolchainGate::Lint) {
+            return result;
+        }
 
         let output = Command::new("cargo")
             .args(["clippy", "--", "-D", "warnings"])
@@ -499,6 +705,9 @@ impl QualityGateChecker {
         if !self.profile.ci.format_check {
             return GateResult::skipped("format", "Format checking not enabled in profile");
         }
+        if let Some(result) = self.run_toolchain_gate(ToolchainGate::Format) {
+            return result;
+        }
 
         let output = Command::new("cargo")
             .args(["fmt", "--check"])
@@ -576,6 +785,9 @@ impl QualityGateChecker {
         if !self.profile.security.cargo_audit {
             return GateResult::skipped("security_audit", "Security audit not enabled in profile");
         }
+        if let Some(result) = self.run_toolchain_gate(ToolchainGate::SecurityAudit) {
+            return result;
+        }
 
         // Check if cargo-audit is installed
         let check_installed = Command::new("cargo")
@@ -783,6 +995,404 @@ impl QualityGateChecker {
         result
     }
 
+    /// Check dependencies against the project's `deny.toml` with cargo-deny.
+    ///
+    /// Runs `cargo deny check`, which covers advisories, licenses, banned
+    /// crates and sources as configured in `deny.toml`.
+    ///
+    /// # Returns
+    ///
+    /// A `GateResult` listing each error cargo-deny reported.
+    pub fn check_cargo_deny(&self) -> GateResult {
+        if !self.profile.security.cargo_deny {
+            return GateResult::skipped("cargo_deny", "cargo-deny not enabled in profile");
+        }
+        if self.toolchain() != Toolchain::Cargo {
+            return GateResult::skipped("cargo_deny", "cargo-deny only checks cargo projects");
+        }
+        if !self.project_root.join("deny.toml").is_file() {
+            return GateResult::fail(
+                "cargo_deny",
+                "No deny.toml in the project root",
+                Some("Create one with: cargo deny init".to_string()),
+            )
+            .with_actionable_details(true);
+        }
+
+        let installed = Command::new("cargo")
+            .args(["deny", "--version"])
+            .current_dir(&self.project_root)
+            .output()
+            .is_ok_and(|output| output.status.success());
+        if !installed {
+            return GateResult::fail(
+                "cargo_deny",
+                "cargo-deny is not installed",
+                Some("Install cargo-deny: cargo install cargo-deny".to_string()),
+            )
+            .with_actionable_details(true);
+        }
+
+        let output = Command::new("cargo")
+            .args(["deny", "--format", "json", "check"])
+            .current_dir(&self.project_root)
+            .output();
+        match output {
+            Ok(output) if output.status.success() => {
+                GateResult::pass("cargo_deny", "No cargo-deny errors")
+            }
+            Ok(output) => {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                let errors = Self::parse_deny_errors(&stderr);
+                if errors.is_empty() {
+                    GateResult::fail(
+                        "cargo_deny",
+                        "cargo deny check failed",
+                        Some(output_tail(&stderr)),
+                    )
+                    .with_actionable_details(true)
+                } else {
+                    GateResult::fail(
+                        "cargo_deny",
+                        format!(
+                            "cargo-deny found {} error{}",
+                            errors.len(),
+                            if errors.len() == 1 { "" } else { "s" }
+                        ),
+                        Some(errors.join("\n")),
+                    )
+                    .with_actionable_details(true)
+                }
+            }
+            Err(e) => GateResult::fail(
+                "cargo_deny",
+                "Failed to run cargo deny",
+                Some(format!("Error: {}", e)),
+            )
+            .with_actionable_details(true),
+        }
+    }
+
+    /// Extract the error diagnostics from cargo-deny's JSON output, one per line.
+    fn parse_deny_errors(output: &str) -> Vec<String> {
+        output
+            .lines()
+            .filter_map(|line| serde_json::from_str::<serde_json::Value>(line).ok())
+            .filter(|event| event["type"] == "diagnostic" && event["fields"]["severity"] == "error")
+            .map(|event| {
+                let fields = &event["fields"];
+                let message = fields["message"].as_str().unwrap_or_default();
+                match fields["code"].as_str() {
+                    Some(code) => format!("{}: {}", code, message),
+                    None => message.to_string(),
+                }
+            })
+            .collect()
+    }
+
+    /// Check the story's changes with the local SAST pattern rules.
+    ///
+    /// Flags `unsafe` code, `.unwrap()` outside tests, and shell or `eval`
+    /// invocations in lines changed since the change base (see
+    /// [`Self::with_change_base`]). See [`crate::quality::sast`].
+    pub fn check_sast(&self) -> GateResult {
+        if !self.profile.security.sast {
+            return GateResult::skipped("sast", "SAST not enabled in profile");
+        }
+        match sast::scan_changes(&self.project_root, self.change_base()) {
+            Ok(findings) if findings.is_empty() => {
+                GateResult::pass("sast", "No SAST findings in changed lines")
+            }
+            Ok(findings) => GateResult::fail(
+                "sast",
+                format!(
+                    "{} SAST finding{} in changed lines",
+                    findings.len(),
+                    if findings.len() == 1 { "" } else { "s" }
+                ),
+                Some(join_lines(&findings)),
+            )
+            .with_actionable_details(true),
+            Err(e) => GateResult::fail(
+                "sast",
+                "Failed to find changed lines",
+                Some(format!("Error: {}", e)),
+            )
+            .with_actionable_details(true),
+        }
+    }
+
+    /// Check the story's documentation against the profile.
+    ///
+    /// Fails on new undocumented public items, a missing README or one with
+    /// broken relative links, and (with `inline_comments`) new uncommented
+    /// functions. See [`crate::quality::doc_gate`].
+    pub fn check_documentation(&self) -> GateResult {
+        let documentation = &self.profile.documentation;
+        if !documentation.required && !documentation.readme && !documentation.inline_comments {
+            return GateResult::skipped("documentation", "Documentation not required in profile");
+        }
+        match doc_gate::check_documentation(&self.project_root, documentation, self.change_base()) {
+            Ok(issues) if issues.is_empty() => {
+                GateResult::pass("documentation", "Documentation is up to date")
+            }
+            Ok(issues) => GateResult::fail(
+                "documentation",
+                format!(
+                    "{} documentation issue{}",
+                    issues.len(),
+                    if issues.len() == 1 { "" } else { "s" }
+                ),
+                Some(join_lines(&issues)),
+            )
+            .with_actionable_details(true),
+            Err(e) => GateResult::fail(
+                "documentation",
+                "Failed to find changed files",
+                Some(format!("Error: {}", e)),
+            )
+            .with_actionable_details(true),
+        }
+    }
+
+    /// Check the audit findings the story introduced against the profile.
+    ///
+    /// Runs the enabled audit sections on the changed files and fails if the
+    /// new critical or high findings exceed `max_critical_findings` or
+    /// `max_high_findings`. Findings in the story-start baseline (see
+    /// [`Self::with_audit_baseline`]) do not count. See
+    /// [`crate::quality::audit_gate`].
+    pub fn check_audit(&self) -> GateResult {
+        let audit = &self.profile.audit;
+        if !audit.enabled {
+            return GateResult::skipped("audit", "Audit not enabled in profile");
+        }
+        let findings = match audit_gate::new_findings(
+            &self.project_root,
+            &audit.sections,
+            self.audit_baseline.as_ref(),
+            self.change_base(),
+        ) {
+            Ok(findings) => findings,
+            Err(e) => {
+                return GateResult::fail("audit", "Audit failed", Some(format!("Error: {}", e)))
+                    .with_actionable_details(true)
+            }
+        };
+
+        let count = |severity| {
+            findings
+                .iter()
+                .filter(|finding| finding.severity == severity)
+                .count()
+        };
+        let message = format!(
+            "{} critical (max {}) and {} high (max {}) new audit findings",
+            count(Severity::Critical),
+            audit.max_critical_findings,
+            count(Severity::High),
+            audit.max_high_findings
+        );
+        let offending = audit_gate::offending_findings(&findings, audit);
+        if offending.is_empty() {
+            GateResult::pass("audit", message)
+        } else {
+            GateResult::fail("audit", message, Some(join_lines(&offending)))
+                .with_actionable_details(true)
+        }
+    }
+
+    /// Check that the code typechecks with the toolchain's type checker.
+    ///
+    /// Runs with the lint gate. For cargo projects clippy already typechecks,
+    /// so this gate is not part of their built-in gates.
+    pub fn check_typecheck(&self) -> GateResult {
+        if !self.profile.ci.lint_check {
+            return GateResult::skipped("typecheck", "Lint checking not enabled in profile");
+        }
+        self.run_toolchain_gate(ToolchainGate::Typecheck)
+            .unwrap_or_else(|| GateResult::skipped("typecheck", "Typechecked by clippy"))
+    }
+
+    /// Run the project's test suite and report per-test results.
+    ///
+    /// Runs when the profile requires unit or integration tests. Cargo
+    /// projects run `cargo test`, reading libtest's JSON events on nightly
+    /// toolchains or nextest's JUnit report when the project's nextest config
+    /// writes one, and libtest's text output otherwise. Other toolchains run
+    /// their test runner as a command.
+    ///
+    /// # Returns
+    ///
+    /// A `GateResult` with the passed/failed/ignored counts, listing each
+    /// failing test and its assertion message in `failed_tests`.
+    pub fn check_tests(&self) -> GateResult {
+        let testing = &self.profile.testing;
+        if !testing.unit_tests && !testing.integration_tests {
+            return GateResult::skipped("tests", "Tests not required in profile");
+        }
+        if let Some(result) = self.run_toolchain_gate(ToolchainGate::Test) {
+            return result.with_actionable_details(true);
+        }
+
+        let (tool, output, report) = if let Some(junit) = self.nextest_junit_path() {
+            let output = Command::new("cargo")
+                .args(["nextest", "run", "--no-fail-fast"])
+                .current_dir(&self.project_root)
+                .output();
+            let report = std::fs::read_to_string(junit)
+                .ok()
+                .and_then(|xml| TestReport::parse_junit(&xml));
+            ("cargo nextest", output, report)
+        } else if self.is_nightly() {
+            let output = Command::new("cargo")
+                .args(["test", "--no-fail-fast", "--"])
+                .args(["-Z", "unstable-options", "--format", "json"])
+                .current_dir(&self.project_root)
+                .output();
+            let report = output.as_ref().ok().and_then(|output| {
+                TestReport::parse_libtest_json(&String::from_utf8_lossy(&output.stdout))
+            });
+            ("cargo test", output, report)
+        } else {
+            let output = Command::new("cargo")
+                .args(["test", "--no-fail-fast"])
+                .current_dir(&self.project_root)
+                .output();
+            let report = output.as_ref().ok().and_then(|output| {
+                TestReport::parse_libtest(&String::from_utf8_lossy(&output.stdout))
+            });
+            ("cargo test", output, report)
+        };
+
+        let output = match output {
+            Ok(output) => output,
+            Err(e) => {
+                return GateResult::fail(
+                    "tests",
+                    format!("Failed to run {}", tool),
+                    Some(format!("Error: {}", e)),
+                )
+                .with_actionable_details(true)
+            }
+        };
+        let combined = format!(
+            "{}{}",
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        match report {
+            Some(report) if output.status.success() && report.failed == 0 => {
+                GateResult::pass("tests", report.counts())
+            }
+            Some(report) if report.failed > 0 => {
+                let details = report
+                    .failures
+                    .iter()
+                    .map(|failure| failure.to_string())
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                GateResult::fail("tests", report.counts(), Some(details))
+                    .with_actionable_details(true)
+                    .with_failed_tests(report.failures)
+            }
+            // Some test target failed without a failing test, e.g. it did not compile
+            Some(report) => GateResult::fail(
+                "tests",
+                format!("{}, but {} failed", report.counts(), tool),
+                Some(output_tail(&combined)),
+            )
+            .with_actionable_details(true),
+            None => GateResult::fail("tests", "Tests did not run", Some(output_tail(&combined)))
+                .with_actionable_details(true),
+        }
+    }
+
+    /// Where nextest writes its JUnit report, if nextest is installed and the
+    /// project's `.config/nextest.toml` enables one for the default profile.
+    fn nextest_junit_path(&self) -> Option<PathBuf> {
+        let config =
+            std::fs::read_to_string(self.project_root.join(".config/nextest.toml")).ok()?;
+        let config: toml::Value = toml::from_str(&config).ok()?;
+        let path = config
+            .get("profile")?
+            .get("default")?
+            .get("junit")?
+            .get("path")?
+            .as_str()?;
+
+        let installed = Command::new("cargo")
+            .args(["nextest", "--version"])
+            .current_dir(&self.project_root)
+            .output()
+            .is_ok_and(|output| output.status.success());
+        installed.then(|| self.project_root.join("target/nextest/default").join(path))
+    }
+
+    /// Check whether the project builds with a nightly toolchain, which
+    /// supports libtest's JSON output.
+    fn is_nightly(&self) -> bool {
+        Command::new("rustc")
+            .arg("-V")
+            .current_dir(&self.project_root)
+            .output()
+            .is_ok_and(|output| String::from_utf8_lossy(&output.stdout).contains("nightly"))
+    }
+
+    /// The built-in gates for the project's toolchain, in the order they run.
+    fn builtin_gates(&self) -> Vec<BuiltinGate> {
+        let profile = &self.profile;
+        let documentation = &profile.documentation;
+        let mut gates: Vec<BuiltinGate> = vec![
+            (
+                "coverage",
+                Self::check_coverage,
+                profile.testing.coverage_threshold > 0,
+            ),
+            ("lint", Self::check_lint, profile.ci.lint_check),
+            ("format", Self::check_format, profile.ci.format_check),
+            (
+                "tests",
+                Self::check_tests,
+                profile.testing.unit_tests || profile.testing.integration_tests,
+            ),
+            (
+                "security_audit",
+                Self::check_security_audit,
+                profile.security.cargo_audit,
+            ),
+            (
+                "cargo_deny",
+                Self::check_cargo_deny,
+                profile.security.cargo_deny,
+            ),
+            ("sast", Self::check_sast, profile.security.sast),
+            (
+                "documentation",
+                Self::check_documentation,
+                documentation.required || documentation.readme || documentation.inline_comments,
+            ),
+            ("audit", Self::check_audit, profile.audit.enabled),
+        ];
+        // Only load the toolchain if the gate it adds could run
+        if profile.ci.lint_check && self.toolchain() != Toolchain::Cargo {
+            gates.insert(1, ("typecheck", Self::check_typecheck, true));
+        }
+        gates
+    }
+
+    /// Names of the gates the profile enables: the built-in gates in the
+    /// order they are declared, then the profile's command gates.
+    pub fn enabled_gate_names(&self) -> Vec<String> {
+        self.builtin_gates()
+            .into_iter()
+            .filter(|(_, _, enabled)| *enabled)
+            .map(|(name, _, _)| name.to_string())
+            .chain(self.profile.gates.iter().map(|gate| gate.name.clone()))
+            .collect()
+    }
+
     /// Run all quality gates configured in the profile.
     ///
     /// Returns a vector of `GateResult` for each gate that was run.
@@ -792,12 +1402,40 @@ impl QualityGateChecker {
     ///
     /// A `Vec<GateResult>` containing the results of all gates.
     pub fn run_all(&self) -> Vec<GateResult> {
-        vec![
-            self.check_coverage(),
-            self.check_lint(),
-            self.check_format(),
-            self.check_security_audit(),
-        ]
+        self.run_all_gates_with_progress(|_| {})
+    }
+
+    /// Gates that must finish before `gate` starts: its built-in
+    /// dependencies, those the profile's `[execution]` declares for it, and
+    /// a command gate's own `depends_on`.
+    fn gate_dependencies(&self, gate: &str, declared: &[String]) -> Vec<String> {
+        let mut dependencies: Vec<String> = BUILTIN_DEPENDENCIES
+            .iter()
+            .filter(|(dependent, _)| *dependent == gate)
+            .map(|(_, dependency)| dependency.to_string())
+            .collect();
+        if let Some(configured) = self.profile.execution.depends_on.get(gate) {
+            dependencies.extend(configured.iter().cloned());
+        }
+        dependencies.extend(declared.iter().cloned());
+        dependencies
+    }
+
+    /// Run the profile's user-defined command gates, in order.
+    pub fn run_command_gates(&self) -> Vec<GateResult> {
+        self.profile
+            .gates
+            .iter()
+            .map(|gate| gate.run(&self.project_root))
+            .collect()
+    }
+
+    /// Run a story's verification checks, in order, after the profile gates.
+    pub fn run_verifications(&self, verifications: &[Verification]) -> Vec<GateResult> {
+        verifications
+            .iter()
+            .map(|verification| verification.run(&self.project_root))
+            .collect()
     }
 
     /// Run all quality gates with progress callbacks.
@@ -806,7 +1444,15 @@ impl QualityGateChecker {
     /// callback before and after each gate execution:
     ///
     /// - Emits `Running` state before each gate starts
-    /// - Emits `Passed` or `Failed` state after each gate completes, with duration
+    /// - Emits `Passed` or `Failed` state after each gate completes, with
+    ///   duration and result
+    /// - Emits `Skipped` state instead for a gate the scheduler did not run
+    ///
+    /// Gates run as the profile's `[execution]` settings allow: after the
+    /// gates they depend on, up to `max_parallel` at once, and with
+    /// `fail_fast` not at all once a required gate has failed. The callback
+    /// is always called on the calling thread. See
+    /// [`crate::quality::scheduler`].
     ///
     /// # Arguments
     ///
@@ -828,69 +1474,34 @@ impl QualityGateChecker {
     ///         GateProgressState::Running => println!("Starting: {}", update.gate_name),
     ///         GateProgressState::Passed => println!("Passed: {} ({:?})", update.gate_name, update.duration),
     ///         GateProgressState::Failed => println!("Failed: {} ({:?})", update.gate_name, update.duration),
+    ///         GateProgressState::Skipped => println!("Skipped: {}", update.gate_name),
     ///     }
     /// });
     /// ```
-    pub fn run_all_gates_with_progress<F>(&self, mut callback: F) -> Vec<GateResult>
+    pub fn run_all_gates_with_progress<F>(&self, callback: F) -> Vec<GateResult>
     where
         F: FnMut(GateProgressUpdate),
     {
-        let mut results = Vec::new();
-
-        // Run coverage check
-        callback(GateProgressUpdate::running("coverage"));
-        let start = Instant::now();
-        let result = self.check_coverage();
-        let duration = start.elapsed();
-        if result.passed {
-            callback(GateProgressUpdate::passed("coverage", duration));
-        } else {
-            callback(GateProgressUpdate::failed("coverage", duration));
-        }
-        results.push(result);
-
-        // Run lint check
-        callback(GateProgressUpdate::running("lint"));
-        let start = Instant::now();
-        let result = self.check_lint();
-        let duration = start.elapsed();
-        if result.passed {
-            callback(GateProgressUpdate::passed("lint", duration));
-        } else {
-            callback(GateProgressUpdate::failed("lint", duration));
-        }
-        results.push(result);
-
-        // Run format check
-        callback(GateProgressUpdate::running("format"));
-        let start = Instant::now();
-        let result = self.check_format();
-        let duration = start.elapsed();
-        if result.passed {
-            callback(GateProgressUpdate::passed("format", duration));
-        } else {
-            callback(GateProgressUpdate::failed("format", duration));
-        }
-        results.push(result);
-
-        // Run security audit
-        callback(GateProgressUpdate::running("security_audit"));
-        let start = Instant::now();
-        let result = self.check_security_audit();
-        let duration = start.elapsed();
-        if result.passed {
-            callback(GateProgressUpdate::passed("security_audit", duration));
-        } else {
-            callback(GateProgressUpdate::failed("security_audit", duration));
-        }
-        results.push(result);
-
-        results
+        let mut jobs: Vec<GateJob> = self
+            .builtin_gates()
+            .into_iter()
+            .map(|(name, check, _)| {
+                GateJob::new(name, self.gate_dependencies(name, &[]), move || check(self))
+            })
+            .collect();
+        jobs.extend(self.profile.gates.iter().map(|gate| {
+            GateJob::new(
+                &gate.name,
+                self.gate_dependencies(&gate.name, &gate.depends_on),
+                move || gate.run(&self.project_root),
+            )
+        }));
+        scheduler::run_gates(jobs, &self.profile.execution, callback)
     }
 
-    /// Check if all gates passed.
+    /// Check if all gates passed, ignoring failed advisory gates.
     pub fn all_passed(results: &[GateResult]) -> bool {
-        results.iter().all(|r| r.passed)
+        !results.iter().any(GateResult::blocks)
     }
 
     /// Get a summary of gate results.
@@ -899,18 +1510,26 @@ impl QualityGateChecker {
         let total = results.len();
         let failed: Vec<&str> = results
             .iter()
-            .filter(|r| !r.passed)
+            .filter(|r| r.blocks())
+            .map(|r| r.gate_name.as_str())
+            .collect();
+        let advisory: Vec<&str> = results
+            .iter()
+            .filter(|r| !r.passed && r.advisory)
             .map(|r| r.gate_name.as_str())
             .collect();
 
-        if failed.is_empty() {
-            format!("All {total} gates passed")
-        } else {
-            format!(
-                "{passed}/{total} gates passed. Failed: {}",
-                failed.join(", ")
-            )
+        if passed == total {
+            return format!("All {total} gates passed");
         }
+        let mut summary = format!("{passed}/{total} gates passed");
+        if !failed.is_empty() {
+            summary.push_str(&format!(". Failed: {}", failed.join(", ")));
+        }
+        if !advisory.is_empty() {
+            summary.push_str(&format!(". Advisory: {}", advisory.join(", ")));
+        }
+        summary
     }
 }
 
@@ -924,7 +1543,7 @@ mod tests {
             description: "Test profile".to_string(),
             testing: TestingConfig {
                 coverage_threshold: coverage,
-                unit_tests: true,
+                unit_tests: false,
                 integration_tests: false,
             },
             ci: CiConfig {
@@ -977,7 +1596,7 @@ mod tests {
         let checker = QualityGateChecker::new(profile, "/tmp/test");
         let results = checker.run_all();
 
-        assert_eq!(results.len(), 4);
+        assert_eq!(results.len(), 9);
         assert!(QualityGateChecker::all_passed(&results));
     }
 
@@ -987,7 +1606,7 @@ mod tests {
         let checker = QualityGateChecker::new(profile, "/tmp/test");
         let results = checker.run_all();
 
-        assert_eq!(results.len(), 4);
+        assert_eq!(results.len(), 9);
         // Coverage gate may fail if tools not installed, lint/format/security are still skipped
     }
 
@@ -1032,6 +1651,391 @@ mod tests {
         assert!(summary.contains("gate3"));
     }
 
+    #[test]
+    fn test_advisory_failures_do_not_block() {
+        let results = vec![
+            GateResult::pass("gate1", "Passed"),
+            GateResult::fail("docs", "Failed", None).with_advisory(true),
+        ];
+        assert!(QualityGateChecker::all_passed(&results));
+        assert_eq!(
+            QualityGateChecker::summary(&results),
+            "1/2 gates passed. Advisory: docs"
+        );
+
+        let mut results = results;
+        results.push(GateResult::fail("schema", "Failed", None));
+        assert!(!QualityGateChecker::all_passed(&results));
+        assert_eq!(
+            QualityGateChecker::summary(&results),
+            "1/3 gates passed. Failed: schema. Advisory: docs"
+        );
+    }
+
+    #[test]
+    fn test_run_all_includes_command_gates() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut profile = create_test_profile(0, false, false, false);
+        profile.gates = toml::from_str::<crate::quality::QualityConfig>(
+            r#"
+            [[profiles.p.gates]]
+            name = "schema"
+            command = "true"
+
+            [[profiles.p.gates]]
+            name = "codegen"
+            command = "false"
+            "#,
+        )
+        .unwrap()
+        .profiles["p"]
+            .gates
+            .clone();
+        let checker = QualityGateChecker::new(profile, dir.path());
+
+        let results = checker.run_all();
+        let names: Vec<&str> = results.iter().map(|r| r.gate_name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "coverage",
+                "lint",
+                "format",
+                "tests",
+                "security_audit",
+                "cargo_deny",
+                "sast",
+                "documentation",
+                "audit",
+                "schema",
+                "codegen"
+            ]
+        );
+        assert!(!QualityGateChecker::all_passed(&results));
+
+        let mut updates = Vec::new();
+        let results = checker.run_all_gates_with_progress(|update| {
+            updates.push((update.gate_name.clone(), update.state))
+        });
+        assert_eq!(results.len(), 11);
+        assert_eq!(
+            updates[18..],
+            [
+                ("schema".to_string(), GateProgressState::Running),
+                ("schema".to_string(), GateProgressState::Passed),
+                ("codegen".to_string(), GateProgressState::Running),
+                ("codegen".to_string(), GateProgressState::Failed),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_gates_follow_execution_settings() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let config = toml::from_str::<crate::quality::QualityConfig>(
+            r#"
+            [profiles.p.execution]
+            max_parallel = 4
+            fail_fast = true
+            depends_on = { lint = ["build"] }
+
+            [[profiles.p.gates]]
+            name = "build"
+            command = "sleep 0.2; false"
+
+            [[profiles.p.gates]]
+            name = "schema"
+            command = "true"
+            depends_on = ["build"]
+            "#,
+        )
+        .unwrap();
+        let mut profile = create_test_profile(0, true, false, false);
+        profile.gates = config.profiles["p"].gates.clone();
+        profile.execution = config.profiles["p"].execution.clone();
+        assert_eq!(profile.execution.max_parallel, 4);
+        assert!(profile.execution.fail_fast);
+        let checker = QualityGateChecker::new(profile, dir.path());
+
+        let mut started = Vec::new();
+        let results = checker.run_all_gates_with_progress(|update| {
+            if update.is_running() {
+                started.push(update.gate_name);
+            }
+        });
+        let result = |name: &str| results.iter().find(|r| r.gate_name == name).unwrap();
+
+        // Lint and schema wait for build, and are skipped once it fails
+        let position = |gate: &str| started.iter().position(|name| name == gate).unwrap();
+        assert!(position("build") < position("lint"));
+        assert!(position("build") < position("schema"));
+        assert_eq!(result("lint").message, "Skipped: Gate 'build' failed");
+        assert_eq!(result("schema").message, "Skipped: Gate 'build' failed");
+        assert!(!result("build").passed);
+        assert!(!QualityGateChecker::all_passed(&results));
+    }
+
+    // Toolchain tests
+
+    #[test]
+    fn test_toolchain_from_profile_or_project() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(dir.path().join("go.mod"), "module example.com/app\n").unwrap();
+        std::fs::write(dir.path().join("main.go"), "package main\n").unwrap();
+
+        let profile = create_test_profile(0, true, false, false);
+        let checker = QualityGateChecker::new(profile.clone(), dir.path());
+        assert_eq!(checker.toolchain(), Toolchain::Go);
+
+        let profile = Profile {
+            toolchain: Some(Toolchain::Pip),
+            ..profile
+        };
+        let checker = QualityGateChecker::new(profile, dir.path());
+        assert_eq!(checker.toolchain(), Toolchain::Pip);
+
+        // Projects in no known ecosystem keep the cargo gates
+        let empty = tempfile::TempDir::new().unwrap();
+        let checker =
+            QualityGateChecker::new(create_test_profile(0, true, false, false), empty.path());
+        assert_eq!(checker.toolchain(), Toolchain::Cargo);
+    }
+
+    #[test]
+    fn test_builtin_gates_follow_toolchain() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let names = |toolchain| {
+            let profile = Profile {
+                toolchain: Some(toolchain),
+                ..create_test_profile(0, true, true, false)
+            };
+            QualityGateChecker::new(profile, dir.path())
+                .builtin_gates()
+                .iter()
+                .map(|(name, _, _)| *name)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            names(Toolchain::Cargo),
+            vec![
+                "coverage",
+                "lint",
+                "format",
+                "tests",
+                "security_audit",
+                "cargo_deny",
+                "sast",
+                "documentation",
+                "audit"
+            ]
+        );
+        assert_eq!(
+            names(Toolchain::Pip),
+            vec![
+                "coverage",
+                "typecheck",
+                "lint",
+                "format",
+                "tests",
+                "security_audit",
+                "cargo_deny",
+                "sast",
+                "documentation",
+                "audit"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tests_gate_runs_when_any_tests_are_required() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let checker =
+            QualityGateChecker::new(create_test_profile(0, false, false, false), dir.path());
+        assert_eq!(
+            checker.check_tests().message,
+            "Skipped: Tests not required in profile"
+        );
+
+        // A project without tests to run fails rather than passing vacuously
+        let mut profile = create_test_profile(0, false, false, false);
+        profile.testing.integration_tests = true;
+        let result = QualityGateChecker::new(profile, dir.path()).check_tests();
+        assert!(!result.passed);
+        assert_eq!(result.message, "Tests did not run");
+    }
+
+    #[test]
+    fn test_parse_deny_errors() {
+        let output = r#"{"type":"summary","fields":{"advisories":{"errors":1}}}
+{"type":"diagnostic","fields":{"severity":"warning","code":"duplicate","message":"found 2 duplicate entries for crate 'syn'"}}
+{"type":"diagnostic","fields":{"severity":"error","code":"rejected","message":"failed to satisfy license requirements"}}"#;
+        assert_eq!(
+            QualityGateChecker::parse_deny_errors(output),
+            vec!["rejected: failed to satisfy license requirements"]
+        );
+    }
+
+    #[test]
+    fn test_cargo_deny_requires_deny_toml() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut profile = create_test_profile(0, false, false, false);
+        profile.security.cargo_deny = true;
+        profile.toolchain = Some(Toolchain::Cargo);
+        let result = QualityGateChecker::new(profile, dir.path()).check_cargo_deny();
+        assert!(!result.passed);
+        assert_eq!(result.message, "No deny.toml in the project root");
+    }
+
+    #[test]
+    fn test_sast_and_documentation_check_changed_code() {
+        let dir = tempfile::TempDir::new().unwrap();
+        crate::git::test_support::init_repo(dir.path());
+        std::fs::create_dir(dir.path().join("src")).unwrap();
+        std::fs::write(
+            dir.path().join("src/lib.rs"),
+            "//! Lib\n\n/// Parse\npub fn parse(s: &str) -> u32 {\n    s.parse().unwrap()\n}\n",
+        )
+        .unwrap();
+
+        let mut profile = create_test_profile(0, false, false, false);
+        profile.security.sast = true;
+        profile.documentation.required = true;
+        profile.documentation.readme = true;
+        let checker = QualityGateChecker::new(profile, dir.path());
+
+        let sast = checker.check_sast();
+        assert_eq!(sast.message, "1 SAST finding in changed lines");
+        assert_eq!(
+            sast.details.as_deref(),
+            Some("src/lib.rs:5: [unwrap] unwrap() can panic; handle or propagate the error")
+        );
+        assert!(checker.check_documentation().passed);
+
+        std::fs::write(dir.path().join("src/extra.rs"), "pub fn extra() {}\n").unwrap();
+        let documentation = checker.check_documentation();
+        assert!(!documentation.passed);
+        assert_eq!(
+            documentation.details.as_deref(),
+            Some("src/extra.rs:1: public function `extra` has no doc comment")
+        );
+    }
+
+    #[test]
+    fn test_sast_and_documentation_check_changes_since_change_base() {
+        let dir = tempfile::TempDir::new().unwrap();
+        crate::git::test_support::init_repo(dir.path());
+        let base = crate::git::resolve_commit(dir.path(), "HEAD").unwrap();
+        std::fs::create_dir(dir.path().join("src")).unwrap();
+        std::fs::write(
+            dir.path().join("src/lib.rs"),
+            "pub fn parse(s: &str) -> u32 {\n    s.parse().unwrap()\n}\n",
+        )
+        .unwrap();
+        crate::git::run_git(dir.path(), &["add", "-A"]).unwrap();
+        crate::git::run_git(dir.path(), &["commit", "-q", "-m", "WIP"]).unwrap();
+
+        let mut profile = create_test_profile(0, false, false, false);
+        profile.security.sast = true;
+        profile.documentation.required = true;
+        let head = QualityGateChecker::new(profile.clone(), dir.path());
+        assert!(head.check_sast().passed);
+        assert!(head.check_documentation().passed);
+
+        let since_base = QualityGateChecker::new(profile, dir.path()).with_change_base(base);
+        assert_eq!(
+            since_base.check_sast().message,
+            "1 SAST finding in changed lines"
+        );
+        assert_eq!(
+            since_base.check_documentation().details.as_deref(),
+            Some("src/lib.rs:1: public function `parse` has no doc comment")
+        );
+    }
+
+    #[test]
+    fn test_audit_gate_counts_findings_since_baseline() {
+        let dir = tempfile::TempDir::new().unwrap();
+        crate::git::test_support::init_repo(dir.path());
+        std::fs::create_dir(dir.path().join("domain")).unwrap();
+        std::fs::create_dir(dir.path().join("infrastructure")).unwrap();
+        std::fs::write(dir.path().join("domain/user.rs"), "pub struct User {}\n").unwrap();
+        std::fs::write(
+            dir.path().join("infrastructure/database.rs"),
+            "pub fn connect() {}\n",
+        )
+        .unwrap();
+        crate::git::run_git(dir.path(), &["add", "-A"]).unwrap();
+        crate::git::run_git(dir.path(), &["commit", "-q", "-m", "layers"]).unwrap();
+
+        let mut profile = create_test_profile(0, false, false, false);
+        assert!(QualityGateChecker::new(profile.clone(), dir.path())
+            .check_audit()
+            .message
+            .starts_with("Skipped"));
+
+        profile.audit.enabled = true;
+        profile.audit.max_high_findings = 0;
+        let checker = QualityGateChecker::new(profile.clone(), dir.path());
+        let baseline = checker.capture_audit_baseline().unwrap().unwrap();
+        let checker = checker.with_audit_baseline(baseline);
+        let result = checker.check_audit();
+        assert!(result.passed);
+        assert_eq!(
+            result.message,
+            "0 critical (max 0) and 0 high (max 0) new audit findings"
+        );
+
+        std::fs::write(
+            dir.path().join("domain/user.rs"),
+            "use crate::infrastructure::database;\n\npub struct User {}\n",
+        )
+        .unwrap();
+        let result = checker.check_audit();
+        assert!(!result.passed);
+        assert_eq!(
+            result.message,
+            "0 critical (max 0) and 1 high (max 0) new audit findings"
+        );
+        assert!(result
+            .details
+            .unwrap()
+            .starts_with("[high] Layer Violation: "));
+    }
+
+    #[test]
+    fn test_toolchain_gate_without_command_is_skipped() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(dir.path().join("package.json"), "{}").unwrap();
+        let profile = Profile {
+            toolchain: Some(Toolchain::Npm),
+            ..create_test_profile(0, true, false, false)
+        };
+        let checker = QualityGateChecker::new(profile, dir.path());
+
+        // Plain JavaScript has no type checker
+        let result = checker.check_typecheck();
+        assert!(result.passed);
+        assert_eq!(
+            result.message,
+            "Skipped: No typecheck gate for this npm project"
+        );
+    }
+
+    #[test]
+    fn test_parse_coverage_percentage_text_summary_format() {
+        let summary =
+            "=============================== Coverage summary ===============================\n\
+                       Statements   : 81.25% ( 13/16 )\n\
+                       Branches     : 50% ( 1/2 )\n\
+                       Functions    : 100% ( 4/4 )\n\
+                       Lines        : 80.5% ( 12/15 )\n";
+        assert_eq!(
+            QualityGateChecker::parse_coverage_percentage(summary),
+            Some(80.5)
+        );
+    }
+
     // Coverage gate tests
 
     #[test]
@@ -1534,6 +2538,7 @@ error: critical issue
         assert_eq!(GateProgressState::Failed, GateProgressState::Failed);
         assert_ne!(GateProgressState::Running, GateProgressState::Passed);
         assert_ne!(GateProgressState::Passed, GateProgressState::Failed);
+        assert_ne!(GateProgressState::Passed, GateProgressState::Skipped);
     }
 
     // ========================================================================
@@ -1578,6 +2583,17 @@ error: critical issue
         assert!(update.is_completed());
     }
 
+    #[test]
+    fn test_gate_progress_update_skipped() {
+        let update = GateProgressUpdate::skipped("coverage");
+        assert_eq!(update.state, GateProgressState::Skipped);
+        assert!(update.duration.is_none());
+        assert!(!update.is_passed());
+        assert!(!update.is_failed());
+        assert!(update.is_skipped());
+        assert!(update.is_completed());
+    }
+
     #[test]
     fn test_gate_progress_update_format_duration_none() {
         let update = GateProgressUpdate::running("test");
@@ -1613,16 +2629,16 @@ error: critical issue
             updates.push(update);
         });
 
-        // Should have 8 updates (Running + Passed/Failed for each of 4 gates)
-        assert_eq!(updates.len(), 8);
+        // Should have 18 updates (Running + Passed/Failed for each of 9 gates)
+        assert_eq!(updates.len(), 18);
 
-        // First update should be Running for coverage
+        // First update should be Running for lint, as coverage waits for tests
         assert!(updates[0].is_running());
-        assert_eq!(updates[0].gate_name, "coverage");
+        assert_eq!(updates[0].gate_name, "lint");
 
-        // Second update should be completed for coverage
+        // Second update should be completed for lint
         assert!(updates[1].is_completed());
-        assert_eq!(updates[1].gate_name, "coverage");
+        assert_eq!(updates[1].gate_name, "lint");
     }
 
     #[test]
@@ -1637,10 +2653,21 @@ error: critical issue
             }
         });
 
-        // Should run gates in order: coverage, lint, format, security_audit
+        // Should run gates in order: lint, format, tests, then coverage once
+        // the tests are done, then security and docs
         assert_eq!(
             gate_names,
-            vec!["coverage", "lint", "format", "security_audit"]
+            vec![
+                "lint",
+                "format",
+                "tests",
+                "coverage",
+                "security_audit",
+                "cargo_deny",
+                "sast",
+                "documentation",
+                "audit"
+            ]
         );
     }
 
@@ -1680,15 +2707,20 @@ error: critical issue
             callback_count += 1;
         });
 
-        // Should return 4 gate results
-        assert_eq!(results.len(), 4);
+        // Should return 9 gate results
+        assert_eq!(results.len(), 9);
         assert_eq!(results[0].gate_name, "coverage");
         assert_eq!(results[1].gate_name, "lint");
         assert_eq!(results[2].gate_name, "format");
-        assert_eq!(results[3].gate_name, "security_audit");
+        assert_eq!(results[3].gate_name, "tests");
+        assert_eq!(results[4].gate_name, "security_audit");
+        assert_eq!(results[5].gate_name, "cargo_deny");
+        assert_eq!(results[6].gate_name, "sast");
+        assert_eq!(results[7].gate_name, "documentation");
+        assert_eq!(results[8].gate_name, "audit");
 
-        // Callback should be called 8 times (2 per gate)
-        assert_eq!(callback_count, 8);
+        // Callback should be called 18 times (2 per gate)
+        assert_eq!(callback_count, 18);
     }
 
     #[test]
@@ -1711,7 +2743,9 @@ error: critical issue
                 name == gate
                     && matches!(
                         *state,
-                        GateProgressState::Passed | GateProgressState::Failed
+                        GateProgressState::Passed
+                            | GateProgressState::Failed
+                            | GateProgressState::Skipped
                     )
             });
 
@@ -1769,7 +2803,9 @@ error: critical issue
         // Verify that progress state matches result
         for result in results {
             let state = completed_states.get(&result.gate_name).unwrap();
-            if result.passed {
+            if *state == GateProgressState::Skipped {
+                assert!(result.message.starts_with("Skipped"));
+            } else if result.passed {
                 assert_eq!(*state, GateProgressState::Passed);
             } else {
                 assert_eq!(*state, GateProgressState::Failed);
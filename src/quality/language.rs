@@ -0,0 +1,111 @@
+//! Project language detection.
+//!
+//! Detects the primary language of a project from the manifest files in its
+//! root directory, so prompts and checks can use the project's own toolchain
+//! instead of assuming Rust.
+
+use std::fmt;
+use std::path::Path;
+
+/// Primary language of a project, detected from its root manifest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ProjectLanguage {
+    Rust,
+    Go,
+    TypeScript,
+    JavaScript,
+    Python,
+}
+
+/// Root manifests checked in order; the first match wins.
+///
+/// TypeScript is checked before JavaScript because TypeScript projects also
+/// have a `package.json`.
+const MANIFESTS: &[(&str, ProjectLanguage)] = &[
+    ("Cargo.toml", ProjectLanguage::Rust),
+    ("go.mod", ProjectLanguage::Go),
+    ("tsconfig.json", ProjectLanguage::TypeScript),
+    ("package.json", ProjectLanguage::JavaScript),
+    ("pyproject.toml", ProjectLanguage::Python),
+    ("setup.py", ProjectLanguage::Python),
+    ("requirements.txt", ProjectLanguage::Python),
+    ("Pipfile", ProjectLanguage::Python),
+];
+
+impl ProjectLanguage {
+    /// Detect the language of the project rooted at `root`.
+    ///
+    /// Returns `None` when no known manifest is present.
+    pub fn detect(root: &Path) -> Option<Self> {
+        MANIFESTS
+            .iter()
+            .find(|(manifest, _)| root.join(manifest).is_file())
+            .map(|(_, language)| *language)
+    }
+
+    /// Human-readable name of the language
+    pub fn name(&self) -> &'static str {
+        match self {
+            ProjectLanguage::Rust => "Rust",
+            ProjectLanguage::Go => "Go",
+            ProjectLanguage::TypeScript => "TypeScript",
+            ProjectLanguage::JavaScript => "JavaScript",
+            ProjectLanguage::Python => "Python",
+        }
+    }
+}
+
+impl fmt::Display for ProjectLanguage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn project_with(files: &[&str]) -> TempDir {
+        let dir = TempDir::new().unwrap();
+        for file in files {
+            std::fs::write(dir.path().join(file), "").unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn test_detect_from_manifest() {
+        let cases = [
+            ("Cargo.toml", ProjectLanguage::Rust),
+            ("go.mod", ProjectLanguage::Go),
+            ("package.json", ProjectLanguage::JavaScript),
+            ("pyproject.toml", ProjectLanguage::Python),
+            ("requirements.txt", ProjectLanguage::Python),
+        ];
+        for (manifest, expected) in cases {
+            let dir = project_with(&[manifest]);
+            assert_eq!(ProjectLanguage::detect(dir.path()), Some(expected));
+        }
+    }
+
+    #[test]
+    fn test_typescript_takes_precedence_over_javascript() {
+        let dir = project_with(&["package.json", "tsconfig.json"]);
+        assert_eq!(
+            ProjectLanguage::detect(dir.path()),
+            Some(ProjectLanguage::TypeScript)
+        );
+    }
+
+    #[test]
+    fn test_detect_unknown_project() {
+        let dir = project_with(&["README.md"]);
+        assert_eq!(ProjectLanguage::detect(dir.path()), None);
+    }
+
+    #[test]
+    fn test_display_name() {
+        assert_eq!(ProjectLanguage::TypeScript.to_string(), "TypeScript");
+    }
+}
@@ -2,17 +2,43 @@
 //!
 //! This module contains quality profiles and gate checking functionality.
 
+pub mod audit_gate;
 pub mod blog_generator;
+pub mod command_gate;
+pub mod doc_gate;
 pub mod gates;
+pub mod language;
 pub mod profiles;
+pub mod sast;
+pub mod scheduler;
+pub mod test_report;
+pub mod toolchain;
+pub mod verification;
 
 // Re-exports for convenience - will be used by CLI and MCP in future stories
 #[allow(unused_imports)]
+pub use audit_gate::{AuditBaseline, AuditGateError};
+#[allow(unused_imports)]
 pub use blog_generator::{slugify, BlogContext, BlogGenerator, BlogGeneratorError, BlogResult};
 #[allow(unused_imports)]
+pub use command_gate::CommandGate;
+#[allow(unused_imports)]
+pub use doc_gate::DocIssue;
+#[allow(unused_imports)]
 pub use gates::{GateProgressState, GateProgressUpdate, GateResult, QualityGateChecker};
 #[allow(unused_imports)]
+pub use language::ProjectLanguage;
+#[allow(unused_imports)]
 pub use profiles::{
-    AuditConfig, AuditSections, BlogConfig, CiConfig, DocumentationConfig, Profile, ProfileLevel,
-    QualityConfig, QualityConfigError, SecurityConfig, TestingConfig,
+    AuditConfig, AuditSections, BlogConfig, CiConfig, DocumentationConfig, GateExecutionConfig,
+    Profile, ProfileLevel, QualityConfig, QualityConfigError, SecurityConfig, TestingConfig,
+    QUALITY_CONFIG_FILE,
 };
+#[allow(unused_imports)]
+pub use sast::SastFinding;
+#[allow(unused_imports)]
+pub use test_report::{TestFailure, TestReport};
+#[allow(unused_imports)]
+pub use toolchain::{Toolchain, ToolchainGate};
+#[allow(unused_imports)]
+pub use verification::{Verification, VERIFY_GATE_PREFIX};
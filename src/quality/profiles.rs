@@ -6,12 +6,19 @@
 // Allow dead_code for now - these types will be used in future stories (US-009+)
 #![allow(dead_code)]
 
+use crate::quality::{CommandGate, Toolchain};
 use config::{Config, ConfigError, Environment, File};
 use serde::Deserialize;
 use std::collections::HashMap;
 use std::path::Path;
 use thiserror::Error;
 
+/// Quality configuration file, relative to the project root
+pub const QUALITY_CONFIG_FILE: &str = "quality/ralph-quality.toml";
+
+/// Profiles used when a project has no quality configuration file
+const BUILTIN_QUALITY_CONFIG: &str = include_str!("../../quality/ralph-quality.toml");
+
 /// The level of a quality profile.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Default)]
 #[serde(rename_all = "lowercase")]
@@ -164,6 +171,34 @@ impl Default for AuditConfig {
     }
 }
 
+/// How a profile's gates are scheduled.
+#[derive(Debug, Clone, Deserialize)]
+pub struct GateExecutionConfig {
+    /// Most gates to run at once (1 runs them one after another)
+    #[serde(default = "default_max_parallel")]
+    pub max_parallel: usize,
+    /// Whether to cancel the gates not yet started once a required gate fails
+    #[serde(default)]
+    pub fail_fast: bool,
+    /// Gates that must finish before a gate starts, by gate name
+    #[serde(default)]
+    pub depends_on: HashMap<String, Vec<String>>,
+}
+
+fn default_max_parallel() -> usize {
+    1
+}
+
+impl Default for GateExecutionConfig {
+    fn default() -> Self {
+        Self {
+            max_parallel: default_max_parallel(),
+            fail_fast: false,
+            depends_on: HashMap::new(),
+        }
+    }
+}
+
 fn default_max_critical() -> u32 {
     0
 }
@@ -196,6 +231,15 @@ pub struct Profile {
     /// Audit configuration
     #[serde(default)]
     pub audit: AuditConfig,
+    /// Toolchain the built-in gates run with (detected from the project if unset)
+    #[serde(default)]
+    pub toolchain: Option<Toolchain>,
+    /// User-defined command gates, run after the built-in gates
+    #[serde(default)]
+    pub gates: Vec<CommandGate>,
+    /// How the gates are scheduled
+    #[serde(default)]
+    pub execution: GateExecutionConfig,
 }
 
 /// Errors that can occur when loading quality configuration.
@@ -280,6 +324,22 @@ impl QualityConfig {
         Ok(quality_config)
     }
 
+    /// The built-in minimal, standard and comprehensive profiles.
+    pub fn builtin() -> Self {
+        toml::from_str(BUILTIN_QUALITY_CONFIG).expect("built-in quality profiles are valid")
+    }
+
+    /// Load a project's `quality/ralph-quality.toml`, or the built-in
+    /// profiles if the project has none.
+    pub fn load_for_project(project_root: &Path) -> Result<Self, QualityConfigError> {
+        let path = project_root.join(QUALITY_CONFIG_FILE);
+        if path.exists() {
+            Self::load(path)
+        } else {
+            Ok(Self::builtin())
+        }
+    }
+
     /// Get a profile by its level.
     pub fn get_profile(&self, level: ProfileLevel) -> Option<&Profile> {
         let name = match level {
@@ -376,6 +436,63 @@ mod tests {
         assert!(comprehensive.blog.generate);
     }
 
+    #[test]
+    fn test_load_for_project_falls_back_to_builtin() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let config = QualityConfig::load_for_project(dir.path()).unwrap();
+        assert_eq!(config.profiles.len(), 3);
+        assert!(config.get_profile_by_name("minimal").is_some());
+
+        std::fs::create_dir(dir.path().join("quality")).unwrap();
+        std::fs::write(
+            dir.path().join(QUALITY_CONFIG_FILE),
+            "[profiles.strict]\ndescription = \"Strict\"\n",
+        )
+        .unwrap();
+        let config = QualityConfig::load_for_project(dir.path()).unwrap();
+        assert_eq!(config.profile_names(), vec!["strict"]);
+    }
+
+    #[test]
+    fn test_load_command_gates() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("quality.toml");
+        std::fs::write(
+            &path,
+            r#"
+            [[profiles.standard.gates]]
+            name = "schema"
+            command = "make validate-schema"
+            working_dir = "api"
+            env = { SCHEMA_STRICT = "1" }
+            timeout_secs = 60
+
+            [[profiles.standard.gates]]
+            name = "docs"
+            command = "make docs"
+            required = false
+            "#,
+        )
+        .unwrap();
+
+        let config = QualityConfig::load(&path).unwrap();
+        let gates = &config.get_profile(ProfileLevel::Standard).unwrap().gates;
+        assert_eq!(gates.len(), 2);
+        assert_eq!(gates[0].name, "schema");
+        assert_eq!(gates[0].working_dir, Some("api".into()));
+        assert_eq!(
+            gates[0].env.get("SCHEMA_STRICT").map(String::as_str),
+            Some("1")
+        );
+        assert_eq!(gates[0].timeout_secs, Some(60));
+        assert!(gates[0].required);
+        assert!(!gates[1].required);
+
+        // The built-in profiles declare no command gates
+        let builtin = QualityConfig::builtin();
+        assert!(builtin.profiles.values().all(|p| p.gates.is_empty()));
+    }
+
     #[test]
     fn test_error_display() {
         let err = QualityConfigError::FileNotFound("test.toml".to_string());
@@ -532,5 +649,10 @@ mod tests {
         assert!(!profile.audit.enabled);
         assert_eq!(profile.audit.max_critical_findings, 0);
         assert_eq!(profile.audit.max_high_findings, 5);
+
+        // Gates run one at a time, to completion
+        assert_eq!(profile.execution.max_parallel, 1);
+        assert!(!profile.execution.fail_fast);
+        assert!(profile.execution.depends_on.is_empty());
     }
 }
@@ -0,0 +1,229 @@
+//! Static analysis of the story's changes with local pattern rules.
+//!
+//! The SAST gate looks for risky constructs in the lines a story adds:
+//! `unsafe` code, `.unwrap()` outside tests, and commands built through a
+//! shell or `eval`, which invite command injection. Only new and modified
+//! lines are checked, so existing code never fails a story. Test code
+//! (`tests/`, `benches/`, `examples/` and everything after a
+//! `#[cfg(test)]` line) is exempt, and a line can opt out with a
+//! `sast:allow` comment.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use regex::Regex;
+
+use crate::git::{self, ChangedLines, GitError};
+
+/// Comment that exempts a line from every rule
+pub const SAST_ALLOW_MARKER: &str = "sast:allow";
+
+/// A pattern rule and the files it applies to
+struct SastRule {
+    id: &'static str,
+    description: &'static str,
+    /// File extensions the rule checks
+    extensions: &'static [&'static str],
+    pattern: &'static str,
+    /// Whether the rule also applies to test code
+    in_tests: bool,
+}
+
+const RULES: &[SastRule] = &[
+    SastRule {
+        id: "unsafe",
+        description: "unsafe code",
+        extensions: &["rs"],
+        pattern: r"\bunsafe\s*(\{|fn\b|impl\b|trait\b)",
+        in_tests: true,
+    },
+    SastRule {
+        id: "unwrap",
+        description: "unwrap() can panic; handle or propagate the error",
+        extensions: &["rs"],
+        pattern: r"\.unwrap\(\)",
+        in_tests: false,
+    },
+    SastRule {
+        id: "shell-command",
+        description: "command run through a shell; pass arguments directly",
+        extensions: &["rs"],
+        pattern: r#"Command::new\(\s*"(sh|bash|zsh|cmd|cmd\.exe|powershell)"\s*\)"#,
+        in_tests: false,
+    },
+    SastRule {
+        id: "shell-command",
+        description: "command run through a shell; pass arguments directly",
+        extensions: &["py"],
+        pattern: r"\bos\.system\(|\bsubprocess\.\w+\(.*shell\s*=\s*True",
+        in_tests: false,
+    },
+    SastRule {
+        id: "shell-command",
+        description: "command run through a shell; pass arguments directly",
+        extensions: &["js", "jsx", "ts", "tsx", "mjs", "cjs"],
+        pattern: r"(^|[^\w.])exec(Sync)?\(|\bspawn(Sync)?\([^)]*shell\s*:\s*true",
+        in_tests: false,
+    },
+    SastRule {
+        id: "shell-command",
+        description: "command run through a shell; pass arguments directly",
+        extensions: &["go"],
+        pattern: r#"exec\.Command\(\s*"(sh|bash)"\s*,\s*"-c""#,
+        in_tests: false,
+    },
+    SastRule {
+        id: "eval",
+        description: "eval of dynamic code",
+        extensions: &["py", "js", "jsx", "ts", "tsx", "mjs", "cjs"],
+        pattern: r"(^|[^\w.])eval\(|\bnew Function\(",
+        in_tests: false,
+    },
+];
+
+/// A rule match in a changed line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SastFinding {
+    /// Rule that matched
+    pub rule: &'static str,
+    /// File, relative to the project root
+    pub file: String,
+    /// Line number (1-based)
+    pub line: usize,
+    /// Why the rule flags this line
+    pub description: &'static str,
+}
+
+impl fmt::Display for SastFinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}: [{}] {}",
+            self.file, self.line, self.rule, self.description
+        )
+    }
+}
+
+/// Check the lines changed in the working tree since the `base` commit.
+pub fn scan_changes(project_root: &Path, base: &str) -> Result<Vec<SastFinding>, GitError> {
+    let changes: BTreeMap<String, ChangedLines> = git::changed_lines(project_root, base)?;
+    let mut findings = Vec::new();
+    for (file, lines) in &changes {
+        if let Ok(content) = fs::read_to_string(project_root.join(file)) {
+            findings.extend(scan_source(file, &content, lines));
+        }
+    }
+    Ok(findings)
+}
+
+/// Check the `changed` lines of a source file.
+pub fn scan_source(file: &str, content: &str, changed: &ChangedLines) -> Vec<SastFinding> {
+    let extension = Path::new(file)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or_default();
+    let rules: Vec<(&SastRule, Regex)> = RULES
+        .iter()
+        .filter(|rule| rule.extensions.contains(&extension))
+        .map(|rule| (rule, Regex::new(rule.pattern).expect("valid SAST pattern")))
+        .collect();
+    if rules.is_empty() {
+        return Vec::new();
+    }
+
+    let test_file = ["tests/", "benches/", "examples/"]
+        .iter()
+        .any(|dir| file.starts_with(dir) || file.contains(&format!("/{}", dir)));
+    let mut in_tests = test_file;
+    let mut findings = Vec::new();
+    for (index, line) in content.lines().enumerate() {
+        if line.trim_start().starts_with("#[cfg(test)]") {
+            in_tests = true;
+        }
+        let number = index + 1;
+        if !changed.contains(number) || line.contains(SAST_ALLOW_MARKER) {
+            continue;
+        }
+        let code = line.trim_start();
+        if code.starts_with("//") || (code.starts_with('#') && !code.starts_with("#[")) {
+            continue;
+        }
+        for (rule, regex) in &rules {
+            if (rule.in_tests || !in_tests) && regex.is_match(line) {
+                findings.push(SastFinding {
+                    rule: rule.id,
+                    file: file.to_string(),
+                    line: number,
+                    description: rule.description,
+                });
+            }
+        }
+    }
+    findings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+
+    #[test]
+    fn test_rust_rules_skip_tests_and_allowed_lines() {
+        let source = "\
+pub fn read(path: &str) -> String {
+    let text = std::fs::read_to_string(path).unwrap();
+    let n = unsafe { libc::getpid() };
+    let _ = std::process::Command::new(\"sh\").arg(\"-c\").arg(&text);
+    let ok = parse(&text).unwrap(); // sast:allow checked above
+    // comments may say .unwrap() freely
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    fn t() { super::read(\"x\").parse::<u32>().unwrap(); }
+}
+";
+        let findings = scan_source("src/lib.rs", source, &ChangedLines::All);
+        let found: Vec<(&str, usize)> = findings.iter().map(|f| (f.rule, f.line)).collect();
+        assert_eq!(
+            found,
+            vec![("unwrap", 2), ("unsafe", 3), ("shell-command", 4)]
+        );
+        assert_eq!(
+            findings[0].to_string(),
+            "src/lib.rs:2: [unwrap] unwrap() can panic; handle or propagate the error"
+        );
+
+        assert!(scan_source("tests/it.rs", source, &ChangedLines::All)
+            .iter()
+            .all(|f| f.rule == "unsafe"));
+    }
+
+    #[test]
+    fn test_only_changed_lines_are_checked() {
+        let source = "let a = x.unwrap();\nlet b = y.unwrap();\n";
+        let changed = ChangedLines::Lines(BTreeSet::from([2]));
+        let findings = scan_source("src/main.rs", source, &changed);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].line, 2);
+    }
+
+    #[test]
+    fn test_other_languages() {
+        let python = "import os\nos.system('rm ' + name)\nsubprocess.run(cmd, shell=True)\nresult = eval(expr)\nsubprocess.run(['ls'])\n";
+        let rules: Vec<&str> = scan_source("app/run.py", python, &ChangedLines::All)
+            .iter()
+            .map(|f| f.rule)
+            .collect();
+        assert_eq!(rules, vec!["shell-command", "shell-command", "eval"]);
+
+        let js =
+            "const { exec } = require('child_process');\nexec(`git ${args}`);\nobj.evaluate(x);\n";
+        assert_eq!(scan_source("src/run.js", js, &ChangedLines::All).len(), 1);
+
+        assert!(scan_source("README.md", "eval(x)", &ChangedLines::All).is_empty());
+    }
+}
@@ -0,0 +1,312 @@
+//! Scheduling quality gates by dependency, concurrently.
+//!
+//! A gate starts once every gate it depends on has finished, and up to the
+//! profile's `max_parallel` gates run at once, each on its own thread.
+//! Gates that are ready at the same time start in the order they were
+//! declared, so with `max_parallel = 1` and no dependencies the gates run
+//! one after another as listed.
+//!
+//! A gate whose dependency failed is skipped, since its result could not be
+//! trusted. With `fail_fast`, the first required gate to fail cancels the
+//! gates that have not started yet; gates already running finish. Progress
+//! is reported on the calling thread as gates start and finish, and results
+//! are returned in declaration order whatever order the gates ran in.
+
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::gates::{GateProgressUpdate, GateResult};
+use super::profiles::GateExecutionConfig;
+
+/// A gate's check, run on a worker thread
+type GateRun<'a> = Box<dyn FnOnce() -> GateResult + Send + 'a>;
+
+/// A gate to schedule
+pub struct GateJob<'a> {
+    /// Name the gate reports under
+    pub name: String,
+    /// Gates that must finish before this one starts; names of gates that
+    /// are not scheduled are ignored
+    pub depends_on: Vec<String>,
+    run: GateRun<'a>,
+}
+
+impl<'a> GateJob<'a> {
+    /// Create a job that runs `run`.
+    pub fn new(
+        name: impl Into<String>,
+        depends_on: Vec<String>,
+        run: impl FnOnce() -> GateResult + Send + 'a,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            depends_on,
+            run: Box::new(run),
+        }
+    }
+}
+
+/// Run `jobs` as `config` allows, reporting progress to `callback`.
+///
+/// Returns one result per job, in the order of `jobs`.
+pub fn run_gates<F>(
+    jobs: Vec<GateJob<'_>>,
+    config: &GateExecutionConfig,
+    mut callback: F,
+) -> Vec<GateResult>
+where
+    F: FnMut(GateProgressUpdate),
+{
+    let names: Vec<String> = jobs.iter().map(|job| job.name.clone()).collect();
+    let dependencies: Vec<Vec<usize>> = jobs
+        .iter()
+        .map(|job| {
+            job.depends_on
+                .iter()
+                .filter_map(|dependency| names.iter().position(|name| name == dependency))
+                .collect()
+        })
+        .collect();
+    // A job's check is taken when it starts or is skipped
+    let mut pending: Vec<Option<GateRun>> = jobs.into_iter().map(|job| Some(job.run)).collect();
+    let mut results: Vec<Option<GateResult>> = names.iter().map(|_| None).collect();
+    let max_parallel = config.max_parallel.max(1);
+    let mut failed_fast: Option<usize> = None;
+
+    thread::scope(|scope| {
+        let (sender, receiver) = mpsc::channel();
+        let mut running = 0;
+        loop {
+            // Start or skip every gate whose dependencies have finished,
+            // until nothing changes
+            let mut changed = true;
+            while changed {
+                changed = false;
+                for index in 0..names.len() {
+                    if pending[index].is_none()
+                        || dependencies[index]
+                            .iter()
+                            .any(|&dependency| results[dependency].is_none())
+                    {
+                        continue;
+                    }
+
+                    let failed_dependency = dependencies[index].iter().find(|&&dependency| {
+                        results[dependency].as_ref().is_some_and(GateResult::blocks)
+                    });
+                    let skip_reason = match (failed_dependency, failed_fast) {
+                        (Some(&dependency), _) => {
+                            Some(format!("Gate '{}' failed", names[dependency]))
+                        }
+                        (None, Some(failed)) => {
+                            Some(format!("Cancelled after gate '{}' failed", names[failed]))
+                        }
+                        (None, None) => None,
+                    };
+
+                    if let Some(reason) = skip_reason {
+                        pending[index] = None;
+                        let result = GateResult::skipped(&names[index], reason);
+                        callback(GateProgressUpdate::running(&names[index]));
+                        callback(
+                            GateProgressUpdate::skipped(&names[index]).with_result(result.clone()),
+                        );
+                        results[index] = Some(result);
+                        changed = true;
+                    } else if running < max_parallel {
+                        let Some(run) = pending[index].take() else {
+                            continue;
+                        };
+                        callback(GateProgressUpdate::running(&names[index]));
+                        let sender = sender.clone();
+                        let name = names[index].clone();
+                        scope.spawn(move || {
+                            let start = Instant::now();
+                            let result = panic::catch_unwind(AssertUnwindSafe(run))
+                                .unwrap_or_else(|_| GateResult::fail(name, "Gate panicked", None));
+                            let _ = sender.send((index, result, start.elapsed()));
+                        });
+                        running += 1;
+                        changed = true;
+                    }
+                }
+            }
+
+            if running == 0 {
+                break;
+            }
+            let Ok((index, result, duration)) = receiver.recv() else {
+                break;
+            };
+            running -= 1;
+            let update = if result.passed {
+                GateProgressUpdate::passed(&names[index], duration)
+            } else {
+                GateProgressUpdate::failed(&names[index], duration)
+            };
+            callback(update.with_result(result.clone()));
+            if config.fail_fast && failed_fast.is_none() && result.blocks() {
+                failed_fast = Some(index);
+            }
+            results[index] = Some(result);
+        }
+    });
+
+    // Gates still without a result were waiting on each other
+    results
+        .into_iter()
+        .zip(&names)
+        .map(|(result, name)| {
+            result.unwrap_or_else(|| {
+                let result = GateResult::fail(name, "Not run: dependency cycle", None);
+                callback(GateProgressUpdate::running(name));
+                callback(
+                    GateProgressUpdate::failed(name, Duration::ZERO).with_result(result.clone()),
+                );
+                result
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::quality::GateProgressState;
+    use std::collections::HashMap;
+
+    fn config(max_parallel: usize, fail_fast: bool) -> GateExecutionConfig {
+        GateExecutionConfig {
+            max_parallel,
+            fail_fast,
+            depends_on: HashMap::new(),
+        }
+    }
+
+    fn job<'a>(name: &str, depends_on: &[&str], passed: bool, millis: u64) -> GateJob<'a> {
+        let gate = name.to_string();
+        GateJob::new(
+            name,
+            depends_on.iter().map(|d| d.to_string()).collect(),
+            move || {
+                thread::sleep(Duration::from_millis(millis));
+                if passed {
+                    GateResult::pass(gate, "ok")
+                } else {
+                    GateResult::fail(gate, "broken", None)
+                }
+            },
+        )
+    }
+
+    fn run(jobs: Vec<GateJob<'_>>, config: &GateExecutionConfig) -> (Vec<GateResult>, Vec<String>) {
+        let mut events = Vec::new();
+        let results = run_gates(jobs, config, |update| {
+            let state = match update.state {
+                GateProgressState::Running => "start",
+                GateProgressState::Passed => "pass",
+                GateProgressState::Failed => "fail",
+                GateProgressState::Skipped => "skip",
+            };
+            events.push(format!("{} {}", state, update.gate_name));
+        });
+        (results, events)
+    }
+
+    /// A job that passes only if `peer` runs at the same time: each tells
+    /// the other it has started and waits to hear back
+    fn concurrent_job<'a>(
+        name: &str,
+        to_peer: mpsc::Sender<()>,
+        from_peer: mpsc::Receiver<()>,
+    ) -> GateJob<'a> {
+        let gate = name.to_string();
+        GateJob::new(name, Vec::new(), move || {
+            let _ = to_peer.send(());
+            match from_peer.recv_timeout(Duration::from_secs(10)) {
+                Ok(()) => GateResult::pass(gate, "ok"),
+                Err(_) => GateResult::fail(gate, "ran alone", None),
+            }
+        })
+    }
+
+    #[test]
+    fn test_independent_gates_run_concurrently_after_dependencies() {
+        let (to_tests, from_format) = mpsc::channel();
+        let (to_format, from_tests) = mpsc::channel();
+        let jobs = vec![
+            job("coverage", &["tests"], true, 0),
+            concurrent_job("format", to_tests, from_tests),
+            concurrent_job("tests", to_format, from_format),
+        ];
+        let (results, events) = run(jobs, &config(2, false));
+
+        assert!(results.iter().all(|r| r.passed), "{:?}", results);
+        assert_eq!(events[..2], ["start format", "start tests"]);
+        let position = |event: &str| events.iter().position(|e| e == event).unwrap();
+        assert!(position("pass tests") < position("start coverage"));
+        assert_eq!(events.last().unwrap(), "pass coverage");
+        let names: Vec<&str> = results.iter().map(|r| r.gate_name.as_str()).collect();
+        assert_eq!(names, vec!["coverage", "format", "tests"]);
+    }
+
+    #[test]
+    fn test_one_at_a_time_keeps_declared_order() {
+        let jobs = vec![job("lint", &[], true, 0), job("format", &[], false, 0)];
+        let (results, events) = run(jobs, &config(1, false));
+        assert_eq!(
+            events,
+            vec!["start lint", "pass lint", "start format", "fail format"]
+        );
+        assert!(results[0].passed && !results[1].passed);
+    }
+
+    #[test]
+    fn test_failed_dependency_skips_dependents() {
+        let jobs = vec![
+            job("tests", &[], false, 0),
+            job("coverage", &["tests"], true, 0),
+        ];
+        let (results, events) = run(jobs, &config(4, false));
+        assert_eq!(results[1].message, "Skipped: Gate 'tests' failed");
+        assert_eq!(events[2..], ["start coverage", "skip coverage"]);
+    }
+
+    #[test]
+    fn test_fail_fast_cancels_gates_not_started() {
+        let advisory = GateJob::new("docs", Vec::new(), || {
+            GateResult::fail("docs", "missing", None).with_advisory(true)
+        });
+        let jobs = vec![
+            advisory,
+            job("format", &[], false, 0),
+            job("lint", &[], true, 0),
+        ];
+        let (results, _) = run(jobs, &config(1, true));
+        assert!(!results[1].passed);
+        // An advisory failure does not stop the run; a required one does
+        assert_eq!(
+            results[2].message,
+            "Skipped: Cancelled after gate 'format' failed"
+        );
+
+        let jobs = vec![job("format", &[], false, 0), job("lint", &[], true, 0)];
+        let (results, _) = run(jobs, &config(1, false));
+        assert_eq!(results[1].message, "ok");
+    }
+
+    #[test]
+    fn test_dependency_cycle_fails_the_gates_in_it() {
+        let jobs = vec![
+            job("a", &["b"], true, 0),
+            job("b", &["a"], true, 0),
+            job("c", &["missing"], true, 0),
+        ];
+        let (results, _) = run(jobs, &config(2, false));
+        assert_eq!(results[0].message, "Not run: dependency cycle");
+        assert_eq!(results[1].message, "Not run: dependency cycle");
+        assert!(results[2].passed);
+    }
+}
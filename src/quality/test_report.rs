@@ -0,0 +1,314 @@
+//! Parsing test runner output into per-test results.
+//!
+//! The tests gate reports how many tests passed, failed and were ignored,
+//! and names every failing test with its assertion message so the retry
+//! prompt can point the agent at the exact tests to fix. Three formats are
+//! understood: libtest's JSON events (nightly toolchains), nextest's JUnit
+//! report (when the project's nextest config enables one) and libtest's
+//! plain text output, which every `cargo test` prints.
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// Lines of a failing test's output kept as its message
+const MAX_MESSAGE_LINES: usize = 8;
+
+/// A test that failed, with the assertion message it failed with
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TestFailure {
+    /// Full test name, e.g. `auth::tests::test_login`
+    pub name: String,
+    /// Panic or assertion message
+    pub message: String,
+}
+
+impl std::fmt::Display for TestFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.message.lines().next() {
+            Some(first) => write!(f, "{}: {}", self.name, first),
+            None => f.write_str(&self.name),
+        }
+    }
+}
+
+/// Outcome of a test run
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TestReport {
+    pub passed: usize,
+    pub failed: usize,
+    pub ignored: usize,
+    /// Failing tests, in the order they were reported
+    pub failures: Vec<TestFailure>,
+}
+
+impl TestReport {
+    /// Counts for display, e.g. `12 passed, 1 failed, 2 ignored`
+    pub fn counts(&self) -> String {
+        format!(
+            "{} passed, {} failed, {} ignored",
+            self.passed, self.failed, self.ignored
+        )
+    }
+
+    /// Parse libtest's plain text output (`cargo test`).
+    ///
+    /// Counts are summed over the `test result:` lines of every test
+    /// binary. Returns `None` if no test binary reported a result, e.g.
+    /// because the tests did not compile.
+    pub fn parse_libtest(output: &str) -> Option<Self> {
+        let summary =
+            Regex::new(r"(?m)^test result: \w+\. (\d+) passed; (\d+) failed; (\d+) ignored")
+                .expect("valid regex");
+
+        let mut report = TestReport::default();
+        let mut found = false;
+        for captures in summary.captures_iter(output) {
+            found = true;
+            report.passed += captures[1].parse::<usize>().unwrap_or(0);
+            report.failed += captures[2].parse::<usize>().unwrap_or(0);
+            report.ignored += captures[3].parse::<usize>().unwrap_or(0);
+        }
+        if !found {
+            return None;
+        }
+
+        // Each failure's output follows a "---- <name> stdout ----" header
+        let mut current: Option<(String, Vec<&str>)> = None;
+        for line in output.lines() {
+            if let Some(name) = line
+                .strip_prefix("---- ")
+                .and_then(|rest| rest.strip_suffix(" stdout ----"))
+            {
+                report.push_failure(current.take());
+                current = Some((name.to_string(), Vec::new()));
+            } else if line == "failures:" || line.starts_with("test result:") {
+                report.push_failure(current.take());
+            } else if let Some((_, ref mut lines)) = current {
+                lines.push(line);
+            }
+        }
+        report.push_failure(current);
+        Some(report)
+    }
+
+    /// Parse libtest's JSON events (`cargo test -- -Z unstable-options --format json`).
+    ///
+    /// Returns `None` if the output holds no test events.
+    pub fn parse_libtest_json(output: &str) -> Option<Self> {
+        let mut report = TestReport::default();
+        let mut found = false;
+        for line in output.lines() {
+            let Ok(event) = serde_json::from_str::<serde_json::Value>(line) else {
+                continue;
+            };
+            if event["type"] != "test" {
+                continue;
+            }
+            let name = event["name"].as_str().unwrap_or_default();
+            match event["event"].as_str() {
+                Some("ok") => report.passed += 1,
+                Some("ignored") => report.ignored += 1,
+                Some("failed") | Some("timeout") => {
+                    report.failed += 1;
+                    let stdout = event["stdout"].as_str().unwrap_or_default();
+                    report.push_failure(Some((name.to_string(), stdout.lines().collect())));
+                }
+                _ => continue,
+            }
+            found = true;
+        }
+        found.then_some(report)
+    }
+
+    /// Parse a JUnit XML report, as written by nextest.
+    ///
+    /// Returns `None` if the report holds no test cases.
+    pub fn parse_junit(xml: &str) -> Option<Self> {
+        let testcase =
+            Regex::new(r"(?s)<testcase\b([^>]*?)(?:/>|>(.*?)</testcase>)").expect("valid regex");
+        let failure =
+            Regex::new(r#"(?s)<(?:failure|error)\b([^>]*?)(?:/>|>(.*?)</(?:failure|error)>)"#)
+                .expect("valid regex");
+
+        let mut report = TestReport::default();
+        let mut found = false;
+        for case in testcase.captures_iter(xml) {
+            found = true;
+            let attributes = &case[1];
+            let body = case.get(2).map_or("", |body| body.as_str());
+            let name = match (
+                xml_attribute(attributes, "classname"),
+                xml_attribute(attributes, "name"),
+            ) {
+                (Some(class), Some(name)) => format!("{} {}", class, name),
+                (None, Some(name)) => name,
+                (_, None) => continue,
+            };
+
+            if let Some(failed) = failure.captures(body) {
+                report.failed += 1;
+                let text = failed
+                    .get(2)
+                    .map(|text| xml_unescape(text.as_str()))
+                    .filter(|text| !text.trim().is_empty())
+                    .or_else(|| xml_attribute(&failed[1], "message"))
+                    .unwrap_or_default();
+                report.push_failure(Some((name, text.lines().collect())));
+            } else if body.contains("<skipped") {
+                report.ignored += 1;
+            } else {
+                report.passed += 1;
+            }
+        }
+        found.then_some(report)
+    }
+
+    /// Record a failing test from its name and output lines
+    fn push_failure(&mut self, failure: Option<(String, Vec<&str>)>) {
+        if let Some((name, lines)) = failure {
+            self.failures.push(TestFailure {
+                message: failure_message(&name, &lines),
+                name,
+            });
+        }
+    }
+}
+
+/// The useful part of a failing test's output: the panic location and
+/// assertion message, without the backtrace hint.
+fn failure_message(name: &str, lines: &[&str]) -> String {
+    let panic_prefix = format!("thread '{}' ", name);
+    let lines: Vec<&str> = lines
+        .iter()
+        .map(|line| line.strip_prefix(&panic_prefix).unwrap_or(line).trim_end())
+        .filter(|line| !line.is_empty() && !line.starts_with("note: run with `RUST_BACKTRACE"))
+        .collect();
+
+    // Skip output the test printed before it panicked
+    let start = lines
+        .iter()
+        .position(|line| line.starts_with("panicked at"))
+        .unwrap_or(0);
+    lines[start..]
+        .iter()
+        .take(MAX_MESSAGE_LINES)
+        .copied()
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Value of an XML attribute in a tag's attribute list
+fn xml_attribute(attributes: &str, name: &str) -> Option<String> {
+    let pattern = Regex::new(&format!(r#"\b{}="([^"]*)""#, regex::escape(name))).ok()?;
+    pattern
+        .captures(attributes)
+        .map(|captures| xml_unescape(&captures[1]))
+}
+
+/// Replace XML's predefined entities
+fn xml_unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&#10;", "\n")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIBTEST_OUTPUT: &str = "\
+running 3 tests
+test tests::adds ... ok
+test tests::parses ... FAILED
+test tests::slow ... ignored
+
+failures:
+
+---- tests::parses stdout ----
+parsing input
+thread 'tests::parses' panicked at src/lib.rs:10:9:
+assertion `left == right` failed
+  left: 1
+ right: 2
+note: run with `RUST_BACKTRACE=1` environment variable to display a backtrace
+
+
+failures:
+    tests::parses
+
+test result: FAILED. 1 passed; 1 failed; 1 ignored; 0 measured; 0 filtered out; finished in 0.00s
+
+running 1 test
+test it_works ... ok
+
+test result: ok. 1 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.00s
+";
+
+    #[test]
+    fn test_parse_libtest() {
+        let report = TestReport::parse_libtest(LIBTEST_OUTPUT).unwrap();
+        assert_eq!(report.counts(), "2 passed, 1 failed, 1 ignored");
+        assert_eq!(
+            report.failures,
+            vec![TestFailure {
+                name: "tests::parses".to_string(),
+                message: "panicked at src/lib.rs:10:9:\nassertion `left == right` failed\n  left: 1\n right: 2"
+                    .to_string(),
+            }]
+        );
+        assert_eq!(
+            report.failures[0].to_string(),
+            "tests::parses: panicked at src/lib.rs:10:9:"
+        );
+    }
+
+    #[test]
+    fn test_parse_libtest_without_results() {
+        assert!(TestReport::parse_libtest("error[E0425]: cannot find value `x`").is_none());
+    }
+
+    #[test]
+    fn test_parse_libtest_json() {
+        let output = r#"{ "type": "suite", "event": "started", "test_count": 3 }
+{ "type": "test", "event": "started", "name": "tests::adds" }
+{ "type": "test", "name": "tests::adds", "event": "ok" }
+{ "type": "test", "name": "tests::parses", "event": "failed", "stdout": "thread 'tests::parses' panicked at src/lib.rs:10:9:\nexpected a number\n" }
+{ "type": "test", "name": "tests::slow", "event": "ignored" }
+{ "type": "suite", "event": "failed", "passed": 1, "failed": 1, "ignored": 1 }"#;
+        let report = TestReport::parse_libtest_json(output).unwrap();
+        assert_eq!(report.counts(), "1 passed, 1 failed, 1 ignored");
+        assert_eq!(report.failures[0].name, "tests::parses");
+        assert_eq!(
+            report.failures[0].message,
+            "panicked at src/lib.rs:10:9:\nexpected a number"
+        );
+        assert!(TestReport::parse_libtest_json(LIBTEST_OUTPUT).is_none());
+    }
+
+    #[test]
+    fn test_parse_junit() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuites name="nextest-run" tests="3" failures="1" errors="0">
+    <testsuite name="app" tests="3" disabled="0" errors="0" failures="1">
+        <testcase name="tests::adds" classname="app" timestamp="2026-06-10T12:00:00Z" time="0.001"/>
+        <testcase name="tests::parses" classname="app" time="0.002">
+            <failure type="test failure">thread &apos;tests::parses&apos; panicked at src/lib.rs:10:9:
+assertion `left == right` failed</failure>
+        </testcase>
+        <testcase name="tests::slow" classname="app" time="0.000">
+            <skipped/>
+        </testcase>
+    </testsuite>
+</testsuites>"#;
+        let report = TestReport::parse_junit(xml).unwrap();
+        assert_eq!(report.counts(), "1 passed, 1 failed, 1 ignored");
+        assert_eq!(report.failures[0].name, "app tests::parses");
+        assert!(report.failures[0]
+            .message
+            .ends_with("assertion `left == right` failed"));
+        assert!(TestReport::parse_junit("<testsuites/>").is_none());
+    }
+}
@@ -0,0 +1,341 @@
+//! Ecosystem toolchains the built-in quality gates run with.
+//!
+//! The built-in gates were written against cargo, which makes them fail
+//! spuriously or pass vacuously on other projects. A toolchain supplies the
+//! commands for each gate in its ecosystem: clippy and rustfmt for cargo,
+//! `tsc`, eslint, prettier and vitest or jest for npm, ruff, mypy and pytest
+//! for pip, and `go vet`, gofmt and `go test` for Go. The toolchain is
+//! detected from the audit's language and dependency analysis unless the
+//! quality profile names one.
+
+use std::fmt;
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::audit::{DependencyEcosystem, DependencyParser, LanguageAnalyzer};
+
+use super::CommandGate;
+
+/// Toolchain the built-in gates run with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Toolchain {
+    /// Rust: clippy, rustfmt, llvm-cov or tarpaulin, cargo-audit
+    Cargo,
+    /// TypeScript and JavaScript: tsc, eslint, prettier, vitest or jest, npm audit
+    Npm,
+    /// Python: mypy, ruff, pytest, pip-audit
+    Pip,
+    /// Go: go vet, gofmt, go test, govulncheck
+    Go,
+}
+
+/// A built-in gate the toolchain runs as a command
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ToolchainGate {
+    Typecheck,
+    Lint,
+    Format,
+    Test,
+    Coverage,
+    SecurityAudit,
+}
+
+impl ToolchainGate {
+    /// Name the gate reports under
+    pub fn name(&self) -> &'static str {
+        match self {
+            ToolchainGate::Typecheck => "typecheck",
+            ToolchainGate::Lint => "lint",
+            ToolchainGate::Format => "format",
+            ToolchainGate::Test => "tests",
+            ToolchainGate::Coverage => "coverage",
+            ToolchainGate::SecurityAudit => "security_audit",
+        }
+    }
+}
+
+impl Toolchain {
+    /// Detect the toolchain of the project rooted at `root`.
+    ///
+    /// The primary languages from the audit's language analysis decide, in
+    /// order of lines of code. If none of them has a toolchain, the ecosystem
+    /// with the most dependencies does. Returns `None` for projects in
+    /// neither.
+    pub fn detect(root: &Path) -> Option<Self> {
+        let analyzer = LanguageAnalyzer::new(root.to_path_buf());
+        if let Some(toolchain) = analyzer
+            .primary_languages()
+            .unwrap_or_default()
+            .iter()
+            .find_map(|language| Self::for_language(&language.name))
+        {
+            return Some(toolchain);
+        }
+
+        let dependencies = DependencyParser::new(root.to_path_buf())
+            .parse()
+            .unwrap_or_default();
+        dependencies
+            .ecosystem_counts
+            .iter()
+            .filter_map(|(ecosystem, count)| Some((Self::for_ecosystem(ecosystem)?, *count)))
+            .max_by_key(|(_, count)| *count)
+            .map(|(toolchain, _)| toolchain)
+    }
+
+    /// The toolchain for a language name from the audit's language analysis
+    pub fn for_language(name: &str) -> Option<Self> {
+        match name {
+            "Rust" => Some(Toolchain::Cargo),
+            "TypeScript" | "JavaScript" => Some(Toolchain::Npm),
+            "Python" => Some(Toolchain::Pip),
+            "Go" => Some(Toolchain::Go),
+            _ => None,
+        }
+    }
+
+    /// The toolchain for a dependency ecosystem
+    pub fn for_ecosystem(ecosystem: &DependencyEcosystem) -> Option<Self> {
+        match ecosystem {
+            DependencyEcosystem::Cargo => Some(Toolchain::Cargo),
+            DependencyEcosystem::Npm => Some(Toolchain::Npm),
+            DependencyEcosystem::Pip => Some(Toolchain::Pip),
+            DependencyEcosystem::Go => Some(Toolchain::Go),
+            _ => None,
+        }
+    }
+
+    /// Human-readable name of the toolchain
+    pub fn name(&self) -> &'static str {
+        match self {
+            Toolchain::Cargo => "cargo",
+            Toolchain::Npm => "npm",
+            Toolchain::Pip => "pip",
+            Toolchain::Go => "go",
+        }
+    }
+
+    /// The command that runs `gate` in the project rooted at `root`.
+    ///
+    /// Returns `None` for cargo, whose gates are built in, and for gates the
+    /// toolchain has no command for (such as typechecking a JavaScript
+    /// project without a `tsconfig.json`).
+    pub fn gate(&self, gate: ToolchainGate, root: &Path) -> Option<CommandGate> {
+        let command = match (self, gate) {
+            (Toolchain::Cargo, _) => return None,
+
+            (Toolchain::Npm, ToolchainGate::Typecheck) => {
+                if !root.join("tsconfig.json").is_file() {
+                    return None;
+                }
+                "npx --no-install tsc --noEmit".to_string()
+            }
+            (Toolchain::Npm, ToolchainGate::Lint) => "npx --no-install eslint .".to_string(),
+            (Toolchain::Npm, ToolchainGate::Format) => {
+                "npx --no-install prettier --check .".to_string()
+            }
+            (Toolchain::Npm, ToolchainGate::Test) => match NpmTestRunner::detect(root) {
+                NpmTestRunner::Vitest => "npx --no-install vitest run".to_string(),
+                NpmTestRunner::Jest => "npx --no-install jest".to_string(),
+                NpmTestRunner::NpmTest => "npm test".to_string(),
+            },
+            (Toolchain::Npm, ToolchainGate::Coverage) => match NpmTestRunner::detect(root) {
+                NpmTestRunner::Vitest => {
+                    "npx --no-install vitest run --coverage --coverage.reporter=text-summary"
+                        .to_string()
+                }
+                NpmTestRunner::Jest | NpmTestRunner::NpmTest => {
+                    "npx --no-install jest --coverage --coverageReporters=text-summary".to_string()
+                }
+            },
+            (Toolchain::Npm, ToolchainGate::SecurityAudit) => {
+                "npm audit --audit-level=high".to_string()
+            }
+
+            (Toolchain::Pip, ToolchainGate::Typecheck) => "mypy .".to_string(),
+            (Toolchain::Pip, ToolchainGate::Lint) => "ruff check .".to_string(),
+            (Toolchain::Pip, ToolchainGate::Format) => "ruff format --check .".to_string(),
+            (Toolchain::Pip, ToolchainGate::Test) => "pytest".to_string(),
+            (Toolchain::Pip, ToolchainGate::Coverage) => {
+                "pytest --cov --cov-report=term".to_string()
+            }
+            (Toolchain::Pip, ToolchainGate::SecurityAudit) => "pip-audit".to_string(),
+
+            (Toolchain::Go, ToolchainGate::Typecheck) => "go build ./...".to_string(),
+            (Toolchain::Go, ToolchainGate::Lint) => "go vet ./...".to_string(),
+            // gofmt -l lists unformatted files but always exits 0
+            (Toolchain::Go, ToolchainGate::Format) => {
+                r#"files=$(gofmt -l .); [ -z "$files" ] || { echo "$files"; exit 1; }"#.to_string()
+            }
+            (Toolchain::Go, ToolchainGate::Test) => "go test ./...".to_string(),
+            (Toolchain::Go, ToolchainGate::Coverage) => {
+                "go test -coverprofile=.ralph-coverage.out ./... > /dev/null \
+                 && go tool cover -func=.ralph-coverage.out; \
+                 status=$?; rm -f .ralph-coverage.out; exit $status"
+                    .to_string()
+            }
+            (Toolchain::Go, ToolchainGate::SecurityAudit) => "govulncheck ./...".to_string(),
+        };
+
+        Some(CommandGate {
+            name: gate.name().to_string(),
+            command,
+            working_dir: None,
+            env: Default::default(),
+            timeout_secs: None,
+            exit_code: 0,
+            pass_pattern: None,
+            fail_pattern: None,
+            required: true,
+            depends_on: Vec::new(),
+        })
+    }
+}
+
+impl fmt::Display for Toolchain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Test runner of an npm project, from its `package.json` dependencies
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum NpmTestRunner {
+    Vitest,
+    Jest,
+    /// Neither; the package's own `test` script
+    NpmTest,
+}
+
+impl NpmTestRunner {
+    fn detect(root: &Path) -> Self {
+        let manifest: serde_json::Value = std::fs::read_to_string(root.join("package.json"))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        let depends_on = |name: &str| {
+            ["dependencies", "devDependencies"]
+                .iter()
+                .any(|section| manifest[section].get(name).is_some())
+        };
+
+        if depends_on("vitest") {
+            NpmTestRunner::Vitest
+        } else if depends_on("jest") {
+            NpmTestRunner::Jest
+        } else {
+            NpmTestRunner::NpmTest
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn project_with(files: &[(&str, &str)]) -> TempDir {
+        let dir = TempDir::new().unwrap();
+        for (path, content) in files {
+            let path = dir.path().join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn test_detect_from_primary_language() {
+        let dir = project_with(&[
+            ("package.json", "{}"),
+            ("tsconfig.json", "{}"),
+            ("src/index.ts", "export const a = 1;\nexport const b = 2;\n"),
+            ("scripts/build.sh", "#!/bin/sh\n"),
+        ]);
+        assert_eq!(Toolchain::detect(dir.path()), Some(Toolchain::Npm));
+
+        let dir = project_with(&[
+            ("pyproject.toml", "[project]\nname = \"app\"\n"),
+            ("app/main.py", "print('hi')\n"),
+        ]);
+        assert_eq!(Toolchain::detect(dir.path()), Some(Toolchain::Pip));
+
+        let dir = project_with(&[
+            ("go.mod", "module example.com/app\n"),
+            ("main.go", "package main\n"),
+        ]);
+        assert_eq!(Toolchain::detect(dir.path()), Some(Toolchain::Go));
+    }
+
+    #[test]
+    fn test_detect_unknown_project() {
+        let dir = project_with(&[("README.md", "# Notes\n")]);
+        assert_eq!(Toolchain::detect(dir.path()), None);
+    }
+
+    #[test]
+    fn test_cargo_gates_are_built_in() {
+        let dir = project_with(&[]);
+        assert!(Toolchain::Cargo
+            .gate(ToolchainGate::Lint, dir.path())
+            .is_none());
+    }
+
+    #[test]
+    fn test_npm_gates_follow_package_json() {
+        let vitest = project_with(&[
+            (
+                "package.json",
+                r#"{"devDependencies": {"vitest": "^1.0.0"}}"#,
+            ),
+            ("tsconfig.json", "{}"),
+        ]);
+        let test = Toolchain::Npm
+            .gate(ToolchainGate::Test, vitest.path())
+            .unwrap();
+        assert_eq!(test.name, "tests");
+        assert_eq!(test.command, "npx --no-install vitest run");
+        assert!(Toolchain::Npm
+            .gate(ToolchainGate::Typecheck, vitest.path())
+            .is_some());
+
+        let jest = project_with(&[("package.json", r#"{"devDependencies": {"jest": "^29"}}"#)]);
+        assert_eq!(
+            Toolchain::Npm
+                .gate(ToolchainGate::Test, jest.path())
+                .unwrap()
+                .command,
+            "npx --no-install jest"
+        );
+        // Plain JavaScript has nothing to typecheck
+        assert!(Toolchain::Npm
+            .gate(ToolchainGate::Typecheck, jest.path())
+            .is_none());
+    }
+
+    #[test]
+    fn test_go_format_gate_fails_on_unformatted_files() {
+        let dir = project_with(&[]);
+        let gate = Toolchain::Go
+            .gate(ToolchainGate::Format, dir.path())
+            .unwrap();
+        assert_eq!(gate.name, "format");
+        assert!(gate.command.starts_with("files=$(gofmt -l .)"));
+    }
+
+    #[test]
+    fn test_profile_override_parses() {
+        let config: crate::quality::QualityConfig =
+            toml::from_str("[profiles.python]\ntoolchain = \"pip\"\n").unwrap();
+        assert_eq!(
+            config.get_profile_by_name("python").unwrap().toolchain,
+            Some(Toolchain::Pip)
+        );
+        assert!(toml::from_str::<crate::quality::QualityConfig>(
+            "[profiles.java]\ntoolchain = \"maven\"\n"
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,263 @@
+//! Story-scoped verification checks declared in the PRD.
+//!
+//! Besides the quality profile's gates, a story can list `verify` entries that
+//! must hold before it is committed: shell commands with an expected exit
+//! code, `cargo test` filters, and file-existence or grep assertions. Each
+//! entry runs as its own gate after the profile gates, so its result shows up
+//! next to them and a failure is fed back to the agent on the next iteration.
+
+use std::path::Path;
+use std::process::Command;
+
+use regex::Regex;
+use serde::Deserialize;
+
+use super::gates::GateResult;
+
+/// Prefix of the gate names verification checks report under
+pub const VERIFY_GATE_PREFIX: &str = "verify: ";
+
+/// Output lines kept in a failed check's details
+const MAX_OUTPUT_LINES: usize = 30;
+
+/// A check a story must pass, from the story's `verify` list in the PRD.
+///
+/// ```json
+/// "verify": [
+///   { "command": "./scripts/smoke.sh", "exitCode": 0 },
+///   { "cargoTest": "auth::tests" },
+///   { "fileExists": "src/auth.rs" },
+///   { "grep": "pub mod auth", "file": "src/lib.rs" }
+/// ]
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(untagged)]
+pub enum Verification {
+    /// Shell command run from the project root with `sh -c`
+    Command {
+        command: String,
+        /// Exit code the command must return
+        #[serde(rename = "exitCode", default)]
+        exit_code: i32,
+    },
+    /// `cargo test` with a test name filter
+    CargoTest {
+        #[serde(rename = "cargoTest")]
+        cargo_test: String,
+    },
+    /// File or directory that must exist, relative to the project root
+    FileExists {
+        #[serde(rename = "fileExists")]
+        file_exists: String,
+    },
+    /// Regular expression that must match a line of a file
+    Grep { grep: String, file: String },
+}
+
+impl Verification {
+    /// Name of the gate this check reports as
+    pub fn gate_name(&self) -> String {
+        let check = match self {
+            Verification::Command { command, .. } => command.clone(),
+            Verification::CargoTest { cargo_test } => format!("cargo test {}", cargo_test),
+            Verification::FileExists { file_exists } => format!("{} exists", file_exists),
+            Verification::Grep { grep, file } => format!("{} matches /{}/", file, grep),
+        };
+        format!("{}{}", VERIFY_GATE_PREFIX, check)
+    }
+
+    /// Run the check in `project_root`
+    pub fn run(&self, project_root: &Path) -> GateResult {
+        let name = self.gate_name();
+        let result = match self {
+            Verification::Command { command, exit_code } => {
+                let mut cmd = Command::new("sh");
+                cmd.args(["-c", command]);
+                run_command(&name, cmd, project_root, *exit_code)
+            }
+            Verification::CargoTest { cargo_test } => {
+                let mut cmd = Command::new("cargo");
+                cmd.args(["test", cargo_test]);
+                run_command(&name, cmd, project_root, 0)
+            }
+            Verification::FileExists { file_exists } => {
+                if project_root.join(file_exists).exists() {
+                    GateResult::pass(name, format!("{} exists", file_exists))
+                } else {
+                    GateResult::fail(name, format!("{} does not exist", file_exists), None)
+                }
+            }
+            Verification::Grep { grep, file } => check_grep(&name, project_root, grep, file),
+        };
+        result.with_actionable_details(true)
+    }
+}
+
+/// Run `cmd` from `project_root` and compare its exit code with `expected`
+fn run_command(name: &str, mut cmd: Command, project_root: &Path, expected: i32) -> GateResult {
+    let output = match cmd.current_dir(project_root).output() {
+        Ok(output) => output,
+        Err(e) => {
+            return GateResult::fail(
+                name,
+                "Failed to run verification command",
+                Some(format!("Error: {}", e)),
+            )
+        }
+    };
+
+    let code = output.status.code();
+    if code == Some(expected) {
+        return GateResult::pass(name, format!("Exited with {}", expected));
+    }
+
+    let exited = match code {
+        Some(code) => format!("Exited with {}", code),
+        None => "Killed by a signal".to_string(),
+    };
+    let combined = format!(
+        "{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    GateResult::fail(
+        name,
+        format!("{}, expected {}", exited, expected),
+        Some(output_tail(&combined)),
+    )
+}
+
+/// Check that a line of `file` matches the regular expression `pattern`
+fn check_grep(name: &str, project_root: &Path, pattern: &str, file: &str) -> GateResult {
+    let regex = match Regex::new(pattern) {
+        Ok(regex) => regex,
+        Err(e) => {
+            return GateResult::fail(
+                name,
+                "Invalid verification pattern",
+                Some(format!("/{}/: {}", pattern, e)),
+            )
+        }
+    };
+    match std::fs::read_to_string(project_root.join(file)) {
+        Ok(content) if content.lines().any(|line| regex.is_match(line)) => {
+            GateResult::pass(name, format!("{} matches /{}/", file, pattern))
+        }
+        Ok(_) => GateResult::fail(
+            name,
+            format!("No line of {} matches /{}/", file, pattern),
+            None,
+        ),
+        Err(e) => GateResult::fail(name, format!("Cannot read {}: {}", file, e), None),
+    }
+}
+
+/// The last lines of a command's output
+pub(crate) fn output_tail(output: &str) -> String {
+    let lines: Vec<&str> = output.trim_end().lines().collect();
+    let start = lines.len().saturating_sub(MAX_OUTPUT_LINES);
+    lines[start..].join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn parse(json: &str) -> Verification {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn test_parse_verification_kinds() {
+        assert_eq!(
+            parse(r#"{ "command": "make check" }"#),
+            Verification::Command {
+                command: "make check".to_string(),
+                exit_code: 0,
+            }
+        );
+        assert_eq!(
+            parse(r#"{ "command": "false", "exitCode": 1 }"#),
+            Verification::Command {
+                command: "false".to_string(),
+                exit_code: 1,
+            }
+        );
+        assert_eq!(
+            parse(r#"{ "cargoTest": "auth::tests" }"#).gate_name(),
+            "verify: cargo test auth::tests"
+        );
+        assert_eq!(
+            parse(r#"{ "fileExists": "src/auth.rs" }"#).gate_name(),
+            "verify: src/auth.rs exists"
+        );
+        assert_eq!(
+            parse(r#"{ "grep": "pub mod auth", "file": "src/lib.rs" }"#).gate_name(),
+            "verify: src/lib.rs matches /pub mod auth/"
+        );
+        assert!(serde_json::from_str::<Verification>(r#"{ "exists": "x" }"#).is_err());
+    }
+
+    #[test]
+    fn test_command_exit_code() {
+        let dir = TempDir::new().unwrap();
+        let pass = parse(r#"{ "command": "exit 3", "exitCode": 3 }"#).run(dir.path());
+        assert!(pass.passed);
+
+        let fail = parse(r#"{ "command": "echo broken; exit 2" }"#).run(dir.path());
+        assert!(!fail.passed);
+        assert_eq!(fail.message, "Exited with 2, expected 0");
+        assert_eq!(fail.details.as_deref(), Some("broken"));
+    }
+
+    #[test]
+    fn test_command_runs_in_project_root() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("marker"), "").unwrap();
+        assert!(
+            parse(r#"{ "command": "test -f marker" }"#)
+                .run(dir.path())
+                .passed
+        );
+    }
+
+    #[test]
+    fn test_file_exists_and_grep() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("lib.rs"), "mod a;\npub mod auth;\n").unwrap();
+
+        assert!(
+            parse(r#"{ "fileExists": "lib.rs" }"#)
+                .run(dir.path())
+                .passed
+        );
+        assert!(
+            !parse(r#"{ "fileExists": "auth.rs" }"#)
+                .run(dir.path())
+                .passed
+        );
+        assert!(
+            parse(r#"{ "grep": "^pub mod auth;$", "file": "lib.rs" }"#)
+                .run(dir.path())
+                .passed
+        );
+
+        let missing = parse(r#"{ "grep": "mod users", "file": "lib.rs" }"#).run(dir.path());
+        assert!(!missing.passed);
+        assert_eq!(missing.message, "No line of lib.rs matches /mod users/");
+        assert!(
+            !parse(r#"{ "grep": "(", "file": "lib.rs" }"#)
+                .run(dir.path())
+                .passed
+        );
+    }
+
+    #[test]
+    fn test_output_tail() {
+        let output: String = (1..=40).map(|i| format!("line {}\n", i)).collect();
+        let tail = output_tail(&output);
+        assert!(tail.starts_with("line 11\n"));
+        assert!(tail.ends_with("line 40"));
+    }
+}
@@ -1,18 +1,27 @@
 // Terminal runner for Ralph
 // This module implements the default "run all stories until complete" behavior
 
+use std::io::IsTerminal;
 use std::io::{self, Write};
 use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
 use tokio::sync::watch;
 
 use chrono::Utc;
 
+use crate::agent::AgentRegistry;
 use crate::checkpoint::{Checkpoint, CheckpointManager, PauseReason, StoryCheckpoint};
-use crate::error::classification::ErrorCategory;
-use crate::mcp::tools::executor::{detect_agent, ExecutorConfig, StoryExecutor};
+use crate::error::classification::{ErrorCategory, UsageLimitReason};
+use crate::error::ErrorDetector;
+use crate::git::{prepare_branch, BranchConfig, BranchSetup, CommitPolicy, GitError};
+use crate::iteration::{ChangeGuard, RollbackPolicy};
+use crate::mcp::tools::executor::{ExecutorConfig, ExecutorError, StoryExecutor};
 use crate::mcp::tools::load_prd::{PrdFile, PrdUserStory};
+use crate::metrics::{AgentSwitch, MetricsCollector, RunLimits, UsageBudget};
 use crate::notification::Notification;
 use crate::parallel::scheduler::ParallelRunnerConfig;
+use crate::pause::RetryStrategy;
 use crate::ui::{DisplayOptions, TuiRunnerDisplay};
 
 /// User's choice when prompted about an existing checkpoint.
@@ -26,6 +35,9 @@ pub enum ResumeChoice {
     ViewDetails,
 }
 
+/// Default bound on waiting for usage limits to reset (covers a 5-hour window)
+pub const DEFAULT_MAX_USAGE_LIMIT_WAIT: Duration = Duration::from_secs(6 * 60 * 60);
+
 /// Configuration for the runner
 #[derive(Debug, Clone)]
 #[allow(dead_code)] // parallel fields will be used in future stories
@@ -36,10 +48,12 @@ pub struct RunnerConfig {
     pub working_dir: PathBuf,
     /// Maximum iterations per story
     pub max_iterations_per_story: u32,
-    /// Maximum total iterations across all stories (0 = unlimited)
-    pub max_total_iterations: u32,
+    /// Total iteration and wall-clock limits for the run
+    pub run_limits: RunLimits,
     /// Agent command to use (auto-detect if None)
     pub agent_command: Option<String>,
+    /// Registry used to resolve and detect agent backends
+    pub agent_registry: AgentRegistry,
     /// Display options for UI rendering (includes quiet mode, verbosity, etc.)
     pub display_options: DisplayOptions,
     /// Enable parallel execution mode
@@ -54,6 +68,25 @@ pub struct RunnerConfig {
     pub timeout_seconds: Option<u64>,
     /// Disable checkpointing
     pub no_checkpoint: bool,
+    /// Run-level token/cost caps (pause with a checkpoint when exceeded)
+    pub budget: UsageBudget,
+    /// Feature branch handling (check out the PRD's branchName before running)
+    pub branch: BranchConfig,
+    /// Agent prompt template (None = `.ralph/prompt.md` or the built-in template)
+    pub prompt_path: Option<PathBuf>,
+    /// Backoff for retrying transient agent failures
+    pub retry_strategy: RetryStrategy,
+    /// When to roll the workspace back after an iteration's gates regress
+    pub rollback_policy: RollbackPolicy,
+    /// How stories are committed (from `ralph-commit.toml`)
+    pub commit_policy: CommitPolicy,
+    /// Limits on what agents may change (from `ralph-guard.toml`)
+    pub change_guard: Option<ChangeGuard>,
+    /// Split stories the futility detector gives up on into sub-stories
+    pub split_stories: bool,
+    /// Longest total time to wait for usage limits to reset before stopping
+    /// (zero = stop with a checkpoint instead of waiting)
+    pub max_usage_limit_wait: Duration,
 }
 
 impl Default for RunnerConfig {
@@ -62,8 +95,9 @@ impl Default for RunnerConfig {
             prd_path: PathBuf::from("prd.json"),
             working_dir: PathBuf::from("."),
             max_iterations_per_story: 10,
-            max_total_iterations: 0, // unlimited
+            run_limits: RunLimits::unlimited(),
             agent_command: None,
+            agent_registry: AgentRegistry::with_builtins(),
             display_options: DisplayOptions::default(),
             parallel: false,
             parallel_config: None,
@@ -71,7 +105,63 @@ impl Default for RunnerConfig {
             no_resume: false,
             timeout_seconds: None,
             no_checkpoint: false,
+            budget: UsageBudget::unlimited(),
+            branch: BranchConfig::default(),
+            prompt_path: None,
+            retry_strategy: RetryStrategy::default(),
+            rollback_policy: RollbackPolicy::default(),
+            commit_policy: CommitPolicy::default(),
+            change_guard: None,
+            split_stories: false,
+            max_usage_limit_wait: DEFAULT_MAX_USAGE_LIMIT_WAIT,
+        }
+    }
+}
+
+impl RunnerConfig {
+    /// Create or check out the PRD's feature branch, if branch handling is enabled.
+    ///
+    /// A checkpoint recorded on the same branch supplies the base commit, so a
+    /// resumed run keeps the base it started from. The PRD and progress log
+    /// are updated by every run, so their changes do not make the tree dirty.
+    pub fn prepare_branch(&self, prd: &PrdFile) -> Result<Option<BranchSetup>, GitError> {
+        if !self.branch.enabled {
+            return Ok(None);
         }
+
+        let resume_base = if self.no_checkpoint {
+            None
+        } else {
+            CheckpointManager::new(&self.working_dir)
+                .ok()
+                .and_then(|manager| manager.load().ok().flatten())
+                .filter(|checkpoint| {
+                    checkpoint.branch_name.as_deref() == Some(prd.branch_name.trim())
+                })
+                .and_then(|checkpoint| checkpoint.base_commit)
+        };
+
+        let branch = self.branch.clone().with_ignored_files(vec![
+            self.prd_path.clone(),
+            self.working_dir.join("progress.txt"),
+        ]);
+        prepare_branch(
+            &self.working_dir,
+            &prd.branch_name,
+            &branch,
+            resume_base.as_deref(),
+        )
+        .map(Some)
+    }
+}
+
+/// One-line description of the branch a run commits to
+pub(crate) fn describe_branch_setup(setup: &BranchSetup) -> String {
+    let base: String = setup.base_commit.chars().take(7).collect();
+    if setup.created {
+        format!("Branch: {} (created from {})", setup.branch, base)
+    } else {
+        format!("Branch: {} (resumed, base {})", setup.branch, base)
     }
 }
 
@@ -96,6 +186,12 @@ pub struct Runner {
     config: RunnerConfig,
     /// Optional checkpoint manager (None if checkpointing is disabled)
     checkpoint_manager: Option<CheckpointManager>,
+    /// Run-level metrics, including token usage checked against the budget
+    metrics: MetricsCollector,
+    /// Feature branch the run commits to, once prepared
+    branch_setup: OnceLock<BranchSetup>,
+    /// Active agent and the fallback switches made so far, for checkpoints
+    agent_state: Mutex<(Option<String>, Vec<AgentSwitch>)>,
 }
 
 impl Runner {
@@ -118,9 +214,17 @@ impl Runner {
         Self {
             config,
             checkpoint_manager,
+            metrics: MetricsCollector::new(),
+            branch_setup: OnceLock::new(),
+            agent_state: Mutex::new((None, Vec::new())),
         }
     }
 
+    /// Get the metrics collected during the run.
+    pub fn metrics(&self) -> &MetricsCollector {
+        &self.metrics
+    }
+
     /// Run all stories until all pass or an error occurs.
     ///
     /// Routes to parallel or sequential execution based on config.parallel.
@@ -164,7 +268,7 @@ impl Runner {
             }
         };
 
-        let total_stories = prd.user_stories.len();
+        let mut total_stories = prd.user_stories.len();
 
         // Initialize display with story list
         let story_status: Vec<(String, bool)> = prd
@@ -188,7 +292,11 @@ impl Runner {
         }
 
         // Detect agent (only needed if there are failing stories)
-        let agent = match self.config.agent_command.clone().or_else(detect_agent) {
+        let mut agent = match self
+            .config
+            .agent_registry
+            .resolve_or_detect(self.config.agent_command.as_deref())
+        {
             Some(a) => a,
             None => {
                 return RunResult {
@@ -204,6 +312,28 @@ impl Runner {
             }
         };
 
+        self.record_agent(&agent, Vec::new());
+
+        // Commit to the PRD's feature branch, never whatever happens to be checked out
+        match self.config.prepare_branch(&prd) {
+            Ok(Some(setup)) => {
+                if !self.config.display_options.quiet {
+                    println!("{}", describe_branch_setup(&setup));
+                }
+                let _ = self.branch_setup.set(setup);
+            }
+            Ok(None) => {}
+            Err(e) => {
+                return RunResult {
+                    all_passed: false,
+                    stories_passed: passing_count,
+                    total_stories,
+                    total_iterations: 0,
+                    error: Some(format!("Branch setup failed: {}", e)),
+                };
+            }
+        }
+
         // Display startup banner (with resume info if applicable)
         if let Some(ref checkpoint) = resume_from {
             println!(
@@ -221,6 +351,9 @@ impl Runner {
         // Track if we're resuming and need to start from a specific iteration
         let mut resume_state = resume_from;
 
+        // Time spent waiting for usage limits to reset, bounded by max_usage_limit_wait
+        let mut usage_limit_waited = Duration::ZERO;
+
         // Main loop - continue until all stories pass
         loop {
             // Reload PRD each iteration to get updated passes status
@@ -237,6 +370,9 @@ impl Runner {
                 }
             };
 
+            // Splitting a story changes how many there are
+            total_stories = prd.user_stories.len();
+
             // Update display with current story states
             let story_status: Vec<(String, bool)> = prd
                 .user_stories
@@ -280,30 +416,16 @@ impl Runner {
                     };
                 }
                 Some(story) => {
-                    // Check total iteration limit
-                    if self.config.max_total_iterations > 0
-                        && total_iterations >= self.config.max_total_iterations
-                    {
-                        // Save checkpoint on reaching iteration limit
-                        self.save_checkpoint(
+                    // Stop before the next story once a run limit is reached
+                    if let Some(reached) = self.metrics.check_run_limits(&self.config.run_limits) {
+                        return self.stop_at_run_limit(
                             &story.id,
                             start_iteration,
                             self.config.max_iterations_per_story,
-                            PauseReason::Error(format!(
-                                "Max total iterations ({}) reached",
-                                self.config.max_total_iterations
-                            )),
-                        );
-                        return RunResult {
-                            all_passed: false,
-                            stories_passed: self.count_passing_stories().unwrap_or(0),
+                            &reached,
                             total_stories,
                             total_iterations,
-                            error: Some(format!(
-                                "Max total iterations ({}) reached",
-                                self.config.max_total_iterations
-                            )),
-                        };
+                        );
                     }
 
                     // Display story start (indicate if resuming)
@@ -312,23 +434,48 @@ impl Runner {
                     }
                     display.start_story(&story.id, &story.title, story.priority);
 
-                    // Calculate remaining iterations when resuming
-                    let max_iterations = self.config.max_iterations_per_story;
-                    let remaining_iterations = max_iterations.saturating_sub(start_iteration - 1);
-
-                    // Execute the story
+                    // Execute the story, with its PRD overrides applied
                     let executor_config = ExecutorConfig {
                         prd_path: self.config.prd_path.clone(),
                         project_root: self.config.working_dir.clone(),
                         progress_path: self.config.working_dir.join("progress.txt"),
                         quality_profile: None,
                         agent_command: agent.clone(),
-                        max_iterations: remaining_iterations,
+                        agent_registry: self.config.agent_registry.clone(),
+                        max_iterations: self.config.max_iterations_per_story,
                         git_mutex: None, // Sequential execution doesn't need mutex
-                        timeout_config: crate::timeout::TimeoutConfig::default(),
+                        timeout_config: self.timeout_config(),
+                        metrics_collector: Some(self.metrics.clone()),
+                        budget: self.config.budget,
+                        run_limits: self.config.run_limits,
+                        prompt_path: self.config.prompt_path.clone(),
+                        retry_strategy: self.config.retry_strategy.clone(),
+                        rollback_policy: self.config.rollback_policy,
+                        commit_policy: self.config.commit_policy.clone(),
+                        change_guard: self.config.change_guard.clone(),
+                        split_stories: self.config.split_stories,
                         ..Default::default()
                     };
+                    let mut executor_config = match executor_config.with_story_overrides(story) {
+                        Ok(config) => config,
+                        Err(e) => {
+                            display.fail_story(&story.id, &e.to_string());
+                            return RunResult {
+                                all_passed: false,
+                                stories_passed: self.count_passing_stories().unwrap_or(0),
+                                total_stories,
+                                total_iterations,
+                                error: Some(format!("Fatal error: {}", e)),
+                            };
+                        }
+                    };
 
+                    // Calculate remaining iterations when resuming
+                    let max_iterations = executor_config.max_iterations;
+                    executor_config.max_iterations =
+                        max_iterations.saturating_sub(start_iteration - 1);
+
+                    self.record_agent(&executor_config.agent_command, Vec::new());
                     let executor = StoryExecutor::new(executor_config);
                     let (_cancel_tx, cancel_rx) = watch::channel(false);
 
@@ -342,14 +489,25 @@ impl Runner {
                         PauseReason::IterationBoundary,
                     );
 
+                    // The iteration the story reached, counting those before resume
+                    let mut reached_iteration = start_iteration;
                     let result = executor
                         .execute_story(&story_id, cancel_rx, |iter, _max| {
                             // Adjust iteration display to account for resume offset
                             let adjusted_iter = iter + start_iteration - 1;
+                            reached_iteration = adjusted_iter;
                             display.update_iteration(adjusted_iter, max_iterations);
                         })
                         .await;
 
+                    // Later stories keep the fallback agent this one switched to,
+                    // unless the switch was away from the story's own agent
+                    let switches = executor.agent_switches();
+                    if !switches.is_empty() && story.agent.is_none() {
+                        agent = executor.active_agent();
+                    }
+                    self.record_agent(&executor.active_agent(), switches);
+
                     // Calculate total iterations used (including those before resume)
                     let iterations_this_run =
                         result.as_ref().map(|r| r.iterations_used).unwrap_or(1);
@@ -362,6 +520,10 @@ impl Runner {
                                 self.clear_checkpoint();
                                 display
                                     .complete_story(&story_id, exec_result.commit_hash.as_deref());
+                            } else if !exec_result.split_into.is_empty() {
+                                // The sub-stories replaced the story in the PRD and run next
+                                self.clear_checkpoint();
+                                display.split_story(&story_id, &exec_result.split_into);
                             } else {
                                 // Save checkpoint on story failure (quality gates didn't pass)
                                 let final_iteration =
@@ -383,6 +545,40 @@ impl Runner {
                                 );
                             }
                         }
+                        Err(ExecutorError::RunLimitReached(iteration, reached)) => {
+                            display.fail_story(&story_id, &reached);
+                            return self.stop_at_run_limit(
+                                &story_id,
+                                start_iteration + iteration - 1,
+                                max_iterations,
+                                &reached,
+                                total_stories,
+                                total_iterations,
+                            );
+                        }
+                        Err(ExecutorError::BudgetExceeded(iteration, reason)) => {
+                            // Budget caps are a hard ceiling: checkpoint and stop the run
+                            // before the iteration the budget did not allow
+                            let notification = Notification::paused(reason.clone());
+                            println!("{}", notification);
+                            self.save_checkpoint(
+                                &story_id,
+                                start_iteration + iteration - 1,
+                                max_iterations,
+                                PauseReason::BudgetExceeded(reason.clone()),
+                            );
+                            display.fail_story(&story_id, &reason);
+                            return RunResult {
+                                all_passed: false,
+                                stories_passed: self.count_passing_stories().unwrap_or(0),
+                                total_stories,
+                                total_iterations,
+                                error: Some(format!(
+                                    "{}. Checkpoint saved. Raise the budget and resume with: ralph --resume",
+                                    reason
+                                )),
+                            };
+                        }
                         Err(e) => {
                             // Classify the error using ErrorDetector
                             let category = e.classify();
@@ -406,19 +602,65 @@ impl Runner {
                                     display.fail_story(&story_id, &e.to_string());
                                     // Continue to next story, will retry on next run
                                 }
-                                ErrorCategory::UsageLimit(_) => {
+                                ErrorCategory::UsageLimit(reason) => {
                                     // For usage limit errors, save checkpoint and pause
                                     let notification = Notification::paused(format!(
                                         "Usage limit exceeded: {}",
                                         e
                                     ));
                                     println!("{}", notification);
+                                    let pause_reason = if *reason == UsageLimitReason::RateLimited {
+                                        PauseReason::RateLimited
+                                    } else {
+                                        PauseReason::UsageLimitExceeded
+                                    };
                                     self.save_checkpoint(
                                         &story_id,
-                                        start_iteration,
+                                        reached_iteration,
                                         max_iterations,
-                                        PauseReason::UsageLimitExceeded,
+                                        pause_reason,
                                     );
+
+                                    // Wait for the limit to reset, then resume the
+                                    // iteration it interrupted
+                                    if let Some(wait) =
+                                        self.usage_limit_wait(&e.to_string(), usage_limit_waited)
+                                    {
+                                        // A reset after the deadline ends the run instead
+                                        if let Some(reached) = self
+                                            .config
+                                            .run_limits
+                                            .check_wait(wait, chrono::Local::now())
+                                        {
+                                            display.fail_story(&story_id, &reached);
+                                            return self.stop_at_run_limit(
+                                                &story_id,
+                                                reached_iteration,
+                                                max_iterations,
+                                                &reached,
+                                                total_stories,
+                                                total_iterations,
+                                            );
+                                        }
+                                        self.wait_for_usage_reset(wait).await;
+                                        usage_limit_waited += wait;
+                                        if !self.config.display_options.quiet {
+                                            println!(
+                                                "{}",
+                                                Notification::resuming(format!(
+                                                    "story {} from iteration {}",
+                                                    story_id, reached_iteration
+                                                ))
+                                            );
+                                        }
+                                        resume_state = Some(StoryCheckpoint::new(
+                                            &story_id,
+                                            reached_iteration,
+                                            max_iterations,
+                                        ));
+                                        continue;
+                                    }
+
                                     display.fail_story(&story_id, &e.to_string());
                                     // Return immediately - user needs to wait or upgrade
                                     return RunResult {
@@ -458,7 +700,7 @@ impl Runner {
                                     println!("{}", notification);
                                     self.save_checkpoint(
                                         &story_id,
-                                        start_iteration,
+                                        reached_iteration,
                                         max_iterations,
                                         PauseReason::Timeout,
                                     );
@@ -483,6 +725,40 @@ impl Runner {
         }
     }
 
+    /// End the run at an iteration boundary after reaching a run limit.
+    ///
+    /// The checkpoint points at `iteration` of `story_id`, so `--resume`
+    /// continues where the run stopped.
+    fn stop_at_run_limit(
+        &self,
+        story_id: &str,
+        iteration: u32,
+        max_iterations: u32,
+        reached: &str,
+        total_stories: usize,
+        total_iterations: u32,
+    ) -> RunResult {
+        if !self.config.display_options.quiet {
+            println!("{}", Notification::paused(reached));
+        }
+        self.save_checkpoint(
+            story_id,
+            iteration,
+            max_iterations,
+            PauseReason::IterationBoundary,
+        );
+        RunResult {
+            all_passed: false,
+            stories_passed: self.count_passing_stories().unwrap_or(0),
+            total_stories,
+            total_iterations,
+            error: Some(format!(
+                "{}. Checkpoint saved. Resume with: ralph --resume",
+                reached
+            )),
+        }
+    }
+
     /// Load the PRD file
     fn load_prd(&self) -> Result<PrdFile, String> {
         let content = std::fs::read_to_string(&self.config.prd_path)
@@ -517,11 +793,19 @@ impl Runner {
     ) {
         if let Some(ref manager) = self.checkpoint_manager {
             let uncommitted_files = self.get_uncommitted_files().unwrap_or_default();
-            let checkpoint = Checkpoint::new(
+            let mut checkpoint = Checkpoint::new(
                 Some(StoryCheckpoint::new(story_id, iteration, max_iterations)),
                 pause_reason,
                 uncommitted_files,
             );
+            if let Some(setup) = self.branch_setup.get() {
+                checkpoint = checkpoint.with_branch(&setup.branch, &setup.base_commit);
+            }
+            if let Ok(state) = self.agent_state.lock() {
+                if let (Some(agent), switches) = &*state {
+                    checkpoint = checkpoint.with_agent(agent, switches.clone());
+                }
+            }
 
             if let Err(e) = manager.save(&checkpoint) {
                 eprintln!("Warning: Failed to save checkpoint: {}", e);
@@ -529,6 +813,79 @@ impl Runner {
         }
     }
 
+    /// Agent timeouts, with the `--timeout` override applied.
+    fn timeout_config(&self) -> crate::timeout::TimeoutConfig {
+        let config = crate::timeout::TimeoutConfig::default();
+        match self.config.timeout_seconds {
+            Some(seconds) => config.with_agent_timeout(Duration::from_secs(seconds)),
+            None => config,
+        }
+    }
+
+    /// Record the active agent, adding any fallback switches that led to it.
+    fn record_agent(&self, agent: &str, switches: Vec<AgentSwitch>) {
+        if let Ok(mut state) = self.agent_state.lock() {
+            state.0 = Some(agent.to_string());
+            state.1.extend(switches);
+        }
+    }
+
+    /// How long to wait for a usage limit named in `error` to reset.
+    ///
+    /// Returns None when the output names no reset time, the reset time has
+    /// already passed (the limit outlived its advertised window), or waiting
+    /// would take the run past `max_usage_limit_wait` in total.
+    fn usage_limit_wait(&self, error: &str, already_waited: Duration) -> Option<Duration> {
+        let reset = ErrorDetector::new().extract_reset_time(error)?;
+        let wait = (reset - Utc::now())
+            .to_std()
+            .ok()
+            .filter(|w| !w.is_zero())?;
+        let remaining = self
+            .config
+            .max_usage_limit_wait
+            .saturating_sub(already_waited);
+        if wait > remaining {
+            if !self.config.display_options.quiet {
+                println!(
+                    "Usage limit resets in {}s, beyond the {}s wait limit",
+                    wait.as_secs(),
+                    remaining.as_secs()
+                );
+            }
+            return None;
+        }
+        Some(wait)
+    }
+
+    /// Wait for a usage limit to reset, showing a `Notification::rate_limited` countdown.
+    ///
+    /// The countdown updates in place each second on a terminal; otherwise it
+    /// is printed once.
+    async fn wait_for_usage_reset(&self, wait: Duration) {
+        let quiet = self.config.display_options.quiet;
+        let live_countdown = !quiet && io::stdout().is_terminal();
+        let deadline = tokio::time::Instant::now() + wait;
+
+        if !quiet && !live_countdown {
+            println!("\n{}", Notification::rate_limited(wait));
+        }
+        loop {
+            let remaining = deadline.saturating_duration_since(tokio::time::Instant::now());
+            if live_countdown {
+                print!("\r\x1b[K{}", Notification::rate_limited(remaining));
+                io::stdout().flush().ok();
+            }
+            if remaining.is_zero() {
+                break;
+            }
+            tokio::time::sleep(remaining.min(Duration::from_secs(1))).await;
+        }
+        if live_countdown {
+            println!();
+        }
+    }
+
     /// Clear the checkpoint (called on successful completion).
     ///
     /// Does nothing if checkpointing is disabled.
@@ -602,6 +959,7 @@ impl Runner {
             PauseReason::UserRequested => "User requested".to_string(),
             PauseReason::Timeout => "Timeout".to_string(),
             PauseReason::IterationBoundary => "Iteration boundary".to_string(),
+            PauseReason::BudgetExceeded(_) => "Budget exceeded".to_string(),
             PauseReason::Error(msg) => {
                 let truncated = if msg.len() > 40 {
                     format!("{}...", &msg[..37])
@@ -642,6 +1000,18 @@ impl Runner {
         let now = Utc::now();
         let age = now.signed_duration_since(checkpoint.created_at);
         println!("Age:           {}", Self::format_duration(age));
+        if let Some(ref branch) = checkpoint.branch_name {
+            println!("Branch:        {}", branch);
+        }
+        if let Some(ref base) = checkpoint.base_commit {
+            println!("Base Commit:   {}", base);
+        }
+        if let Some(ref agent) = checkpoint.agent {
+            println!("Agent:         {}", agent);
+        }
+        for switch in &checkpoint.agent_switches {
+            println!("  Switched:    {}", switch);
+        }
         println!();
 
         // Story checkpoint details
@@ -682,6 +1052,10 @@ impl Runner {
                 println!("  Type:        Iteration Boundary");
                 println!("  Details:     Checkpoint saved at iteration start for recovery");
             }
+            PauseReason::BudgetExceeded(msg) => {
+                println!("  Type:        Budget Exceeded");
+                println!("  Details:     {}", msg);
+            }
             PauseReason::Error(msg) => {
                 println!("  Type:        Error");
                 println!("  Details:     {}", msg);
@@ -183,8 +183,10 @@ impl HeartbeatMonitor {
     /// Stops the background monitoring task.
     ///
     /// This method signals the background task to stop and waits for it
-    /// to complete. After calling this method, `start_monitoring()` can
-    /// be called again to restart monitoring.
+    /// to complete. The task is aborted rather than left to finish its
+    /// current sleep, so stopping never waits for a full heartbeat interval.
+    /// After calling this method, `start_monitoring()` can be called again
+    /// to restart monitoring.
     pub async fn stop(&self) {
         self.stop_flag.store(true, Ordering::SeqCst);
 
@@ -194,6 +196,7 @@ impl HeartbeatMonitor {
         };
 
         if let Some(handle) = handle {
+            handle.abort();
             let _ = handle.await;
         }
     }
@@ -357,6 +360,19 @@ mod tests {
         assert!(!monitor.is_running().await);
     }
 
+    #[tokio::test]
+    async fn test_stop_does_not_wait_for_interval() {
+        let config = TimeoutConfig::new().with_heartbeat_interval(Duration::from_secs(30));
+        let (monitor, _receiver) = HeartbeatMonitor::new(config);
+
+        monitor.start_monitoring().await;
+        let start = std::time::Instant::now();
+        monitor.stop().await;
+
+        assert!(start.elapsed() < Duration::from_secs(5));
+        assert!(!monitor.is_running().await);
+    }
+
     #[tokio::test]
     async fn test_restart_monitoring() {
         let config = test_config();
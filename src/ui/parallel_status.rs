@@ -334,12 +334,11 @@ impl ParallelStatusRenderer {
     pub fn render_overall_progress(&self, state: &ParallelExecutionState) -> String {
         let completed = state.completed_count();
         let total = state.total_count();
-        let percentage = if total == 0 {
-            100
-        } else {
-            // Use saturating_mul to prevent overflow for large values
-            completed.saturating_mul(100) / total
-        };
+        // Use saturating_mul to prevent overflow for large values
+        let percentage = completed
+            .saturating_mul(100)
+            .checked_div(total)
+            .unwrap_or(100);
 
         // Calculate bar width (leaving room for label and counts)
         let label = "Stories: ";
@@ -349,11 +348,9 @@ impl ParallelStatusRenderer {
             .saturating_sub(label.len() + count_display.len() + 2); // +2 for brackets
 
         // Calculate filled/empty portions
-        let filled_count = if total == 0 {
-            bar_width
-        } else {
-            (completed * bar_width) / total
-        };
+        let filled_count = (completed * bar_width)
+            .checked_div(total)
+            .unwrap_or(bar_width);
         let empty_count = bar_width.saturating_sub(filled_count);
 
         let filled_str = progress_chars::FILLED.repeat(filled_count);
@@ -222,11 +222,7 @@ impl StoryProgressWidget {
             "─".repeat(empty),
             self.current,
             self.total,
-            if self.total > 0 {
-                (self.current * 100) / self.total
-            } else {
-                0
-            }
+            (self.current * 100).checked_div(self.total).unwrap_or(0)
         ));
 
         // Story list
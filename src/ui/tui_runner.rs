@@ -375,6 +375,21 @@ impl TuiRunnerDisplay {
         println!("  Continuing to next story...");
     }
 
+    /// Display a story that was split into smaller stories.
+    pub fn split_story(&mut self, story_id: &str, sub_story_ids: &[String]) {
+        if self.quiet {
+            return;
+        }
+
+        println!(); // New line after iteration display
+        println!(
+            "  {} {} into {}",
+            self.style_header("Split"),
+            story_id,
+            sub_story_ids.join(", ")
+        );
+    }
+
     /// Display all stories complete.
     pub fn display_all_complete(&self, total: usize) {
         if self.quiet {
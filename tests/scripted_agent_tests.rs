@@ -0,0 +1,1630 @@
+//! End-to-end tests for story execution using the scripted agent backend.
+//!
+//! These tests drive `StoryExecutor::execute_story` and the `Runner` with a
+//! `scripted:` agent fixture instead of a real agent CLI, covering the
+//! success, retry, transient failure backoff, rate limit, usage limit
+//! wait-and-resume, fallback agent, stall, branch setup, parallel worktree,
+//! workspace rollback, commit policy, story verification, command gate,
+//! tests gate, change guard, story splitting and run limit paths against a
+//! temporary git repository.
+
+#![cfg(unix)]
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Duration;
+
+use ralphmacchio::checkpoint::{CheckpointManager, PauseReason};
+use ralphmacchio::error::{ErrorCategory, ErrorDetector, UsageLimitReason};
+use ralphmacchio::git::{BranchConfig, CommitPolicy};
+use ralphmacchio::iteration::context::ErrorCategory as IterErrorCategory;
+use ralphmacchio::iteration::{ChangeGuard, RollbackPolicy, WorkspaceRollback};
+use ralphmacchio::mcp::executor::ExecutionEvent;
+use ralphmacchio::mcp::tools::executor::{
+    ExecutionResult, ExecutorConfig, ExecutorError, StoryExecutor,
+};
+use ralphmacchio::metrics::{MetricsCollector, RunLimits, UsageBudget};
+use ralphmacchio::notification::Notification;
+use ralphmacchio::parallel::scheduler::ParallelRunnerConfig;
+use ralphmacchio::pause::RetryStrategy;
+use ralphmacchio::quality::{CiConfig, Profile, QualityConfig};
+use ralphmacchio::runner::{Runner, RunnerConfig};
+use ralphmacchio::timeout::TimeoutConfig;
+use ralphmacchio::ui::{DisplayOptions, UiMode};
+use tempfile::TempDir;
+use tokio::sync::{mpsc, watch};
+
+const PRD: &str = r#"{
+    "project": "ScriptedProject",
+    "branchName": "feature/scripted",
+    "description": "PRD for scripted agent tests",
+    "userStories": [
+        {
+            "id": "US-001",
+            "title": "Add greeting",
+            "description": "Write a greeting file",
+            "acceptanceCriteria": ["greeting.txt exists"],
+            "priority": 1,
+            "passes": false
+        }
+    ]
+}"#;
+
+/// A temporary git repository with a PRD and a scripted agent fixture.
+struct ScriptedProject {
+    repo: TempDir,
+    fixture_dir: TempDir,
+}
+
+impl ScriptedProject {
+    fn new(fixture: &str) -> Self {
+        let repo = TempDir::new().expect("Failed to create repo dir");
+        git(repo.path(), &["init", "-q"]);
+        git(repo.path(), &["config", "user.email", "test@example.com"]);
+        git(repo.path(), &["config", "user.name", "Test"]);
+        std::fs::write(repo.path().join("prd.json"), PRD).unwrap();
+        std::fs::write(repo.path().join("README.md"), "# Scripted\n").unwrap();
+        git(repo.path(), &["add", "-A"]);
+        git(repo.path(), &["commit", "-q", "-m", "initial"]);
+
+        let fixture_dir = TempDir::new().expect("Failed to create fixture dir");
+        std::fs::write(fixture_dir.path().join("agent.toml"), fixture).unwrap();
+
+        Self { repo, fixture_dir }
+    }
+
+    fn root(&self) -> &Path {
+        self.repo.path()
+    }
+
+    fn fixture_path(&self) -> PathBuf {
+        self.fixture_dir.path().join("agent.toml")
+    }
+
+    fn config(&self) -> ExecutorConfig {
+        ExecutorConfig {
+            prd_path: self.root().join("prd.json"),
+            project_root: self.root().to_path_buf(),
+            progress_path: self.root().join("progress.txt"),
+            // Default profile skips every gate
+            quality_profile: Some(Profile::default()),
+            agent_command: format!("scripted:{}", self.fixture_path().display()),
+            max_iterations: 3,
+            retry_strategy: fast_retries(),
+            ..Default::default()
+        }
+    }
+
+    fn runner_config(&self, branch: BranchConfig) -> RunnerConfig {
+        RunnerConfig {
+            prd_path: self.root().join("prd.json"),
+            working_dir: self.root().to_path_buf(),
+            max_iterations_per_story: 2,
+            agent_command: Some(format!("scripted:{}", self.fixture_path().display())),
+            display_options: DisplayOptions::new()
+                .with_quiet(true)
+                .with_ui_mode(UiMode::Disabled),
+            no_resume: true,
+            branch,
+            ..Default::default()
+        }
+    }
+
+    async fn execute(&self, config: ExecutorConfig) -> Result<ExecutionResult, ExecutorError> {
+        let executor = StoryExecutor::new(config);
+        let (_tx, rx) = watch::channel(false);
+        executor.execute_story("US-001", rx, |_, _| {}).await
+    }
+}
+
+fn git(dir: &Path, args: &[&str]) -> String {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .expect("Failed to run git");
+    assert!(output.status.success(), "git {:?} failed", args);
+    String::from_utf8_lossy(&output.stdout).trim().to_string()
+}
+
+/// Retry backoff short enough for tests (three attempts per iteration)
+fn fast_retries() -> RetryStrategy {
+    RetryStrategy::new(Duration::from_millis(10), Duration::from_millis(50), 3, 0)
+}
+
+fn expect_agent_error(result: Result<ExecutionResult, ExecutorError>) -> String {
+    match result {
+        Err(ExecutorError::AgentError(msg, _)) => msg,
+        other => panic!("expected AgentError, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_scripted_agent_success_commits_story() {
+    let project = ScriptedProject::new(
+        r#"
+[[iterations]]
+output = ["Wrote greeting.txt"]
+write_files = { "greeting.txt" = "hello\n" }
+"#,
+    );
+
+    let result = project.execute(project.config()).await.unwrap();
+
+    assert!(result.success);
+    assert_eq!(result.iterations_used, 1);
+    assert!(result.files_changed.contains(&"greeting.txt".to_string()));
+    assert_eq!(
+        result.commit_hash.as_deref(),
+        Some(git(project.root(), &["rev-parse", "HEAD"]).as_str())
+    );
+    assert_eq!(
+        git(project.root(), &["log", "-1", "--format=%s"]),
+        "feat: US-001 - Add greeting"
+    );
+
+    let prd = std::fs::read_to_string(project.root().join("prd.json")).unwrap();
+    assert!(prd.contains("\"passes\": true"));
+}
+
+#[tokio::test]
+async fn test_scripted_agent_applies_patch() {
+    let project = ScriptedProject::new(
+        r#"
+[[iterations]]
+patch = """
+--- a/README.md
++++ b/README.md
+@@ -1 +1,2 @@
+ # Scripted
++Patched by the scripted agent.
+"""
+"#,
+    );
+
+    let result = project.execute(project.config()).await.unwrap();
+
+    assert!(result.success);
+    let readme = std::fs::read_to_string(project.root().join("README.md")).unwrap();
+    assert_eq!(readme, "# Scripted\nPatched by the scripted agent.\n");
+}
+
+#[tokio::test]
+async fn test_scripted_agent_failure_is_retried() {
+    let project = ScriptedProject::new(
+        r#"
+[[iterations]]
+stderr = ["error: the agent crashed"]
+exit_code = 7
+
+[[iterations]]
+write_files = { "greeting.txt" = "hello\n" }
+"#,
+    );
+
+    let result = project.execute(project.config()).await.unwrap();
+
+    assert!(result.success);
+    assert_eq!(result.iterations_used, 2);
+    let context = result.iteration_context.unwrap();
+    assert_eq!(context.error_history.len(), 1);
+    assert!(context.error_history[0].message.contains("Exit code: 7"));
+    assert!(context.error_history[0]
+        .message
+        .contains("error: the agent crashed"));
+}
+
+#[tokio::test]
+async fn test_scripted_agent_exhausts_iterations() {
+    let project = ScriptedProject::new(
+        r#"
+[[iterations]]
+exit_code = 1
+"#,
+    );
+    let config = ExecutorConfig {
+        enable_futility_detection: false,
+        ..project.config()
+    };
+
+    let error = expect_agent_error(project.execute(config).await);
+
+    assert!(error.contains("FAILED after 3 iterations"));
+    assert!(error.contains("Exit code: 1"));
+    assert_eq!(git(project.root(), &["rev-list", "--count", "HEAD"]), "1");
+}
+
+#[tokio::test]
+async fn test_scripted_agent_rate_limit_is_classified() {
+    let project = ScriptedProject::new(
+        r#"
+[[iterations]]
+rate_limit = true
+"#,
+    );
+    let config = ExecutorConfig {
+        max_iterations: 1,
+        ..project.config()
+    };
+
+    let error = match project.execute(config).await {
+        Err(ExecutorError::UsageLimit(UsageLimitReason::RateLimited, msg)) => msg,
+        other => panic!("expected a rate-limit UsageLimit error, got {:?}", other),
+    };
+
+    assert!(error.contains("429"));
+    let classified = ErrorDetector::new()
+        .classify(&error, Some(1))
+        .expect("rate limit text should be classified");
+    assert!(matches!(classified.category, ErrorCategory::UsageLimit(_)));
+
+    // Waiting out the rate limit checkpoints the story so it can be resumed
+    let checkpoint = CheckpointManager::new(project.root())
+        .unwrap()
+        .load()
+        .unwrap()
+        .expect("checkpoint should be saved");
+    assert_eq!(checkpoint.pause_reason, PauseReason::RateLimited);
+    assert_eq!(checkpoint.current_story.unwrap().story_id, "US-001");
+}
+
+#[tokio::test]
+async fn test_killed_agent_is_classified_by_exit_code() {
+    let project = ScriptedProject::new(
+        r#"
+[[iterations]]
+stderr = ["worker stopped"]
+exit_code = 137
+"#,
+    );
+    let config = ExecutorConfig {
+        max_iterations: 2,
+        ..project.config()
+    };
+
+    match project.execute(config).await {
+        Err(ExecutorError::Timeout(msg)) => assert!(msg.contains("SIGKILL"), "{}", msg),
+        other => panic!("expected a Timeout error, got {:?}", other),
+    }
+
+    let checkpoint = CheckpointManager::new(project.root())
+        .unwrap()
+        .load()
+        .unwrap()
+        .expect("checkpoint should be saved");
+    assert_eq!(checkpoint.pause_reason, PauseReason::Timeout);
+}
+
+#[tokio::test]
+async fn test_transient_agent_failure_is_retried_within_iteration() {
+    let project = ScriptedProject::new(
+        r#"
+[[iterations]]
+transient_failures = 2
+write_files = { "greeting.txt" = "hello\n" }
+"#,
+    );
+    let (event_tx, mut event_rx) = mpsc::channel(64);
+    let config = ExecutorConfig {
+        event_sender: Some(event_tx),
+        ..project.config()
+    };
+
+    let result = project
+        .execute(config)
+        .await
+        .expect("retries should recover");
+
+    assert!(result.success);
+    // Retries do not consume story iterations
+    assert_eq!(result.iterations_used, 1);
+
+    let mut retry_attempts = Vec::new();
+    while let Ok(event) = event_rx.try_recv() {
+        if let ExecutionEvent::Notification {
+            iteration,
+            notification:
+                Notification::Retrying {
+                    attempt,
+                    max_attempts,
+                    reason,
+                    ..
+                },
+        } = event
+        {
+            assert_eq!(iteration, 1);
+            assert_eq!(max_attempts, 3);
+            assert!(reason.contains("Connection error"));
+            retry_attempts.push(attempt);
+        }
+    }
+    retry_attempts.dedup();
+    assert_eq!(retry_attempts, vec![2, 3]);
+}
+
+#[tokio::test]
+async fn test_transient_failures_past_retry_limit_use_an_iteration() {
+    let project = ScriptedProject::new(
+        r#"
+[[iterations]]
+transient_failures = 5
+"#,
+    );
+    let config = ExecutorConfig {
+        max_iterations: 1,
+        ..project.config()
+    };
+
+    let error = expect_agent_error(project.execute(config).await);
+
+    assert!(error.contains("connection reset by peer"));
+}
+
+#[tokio::test]
+async fn test_usage_limit_pauses_without_retrying() {
+    let project = ScriptedProject::new(
+        r#"
+[[iterations]]
+stderr = ["Error: usage limit reached for this billing period"]
+exit_code = 1
+"#,
+    );
+    let (event_tx, mut event_rx) = mpsc::channel(64);
+    let config = ExecutorConfig {
+        event_sender: Some(event_tx),
+        ..project.config()
+    };
+
+    match project.execute(config).await {
+        Err(ExecutorError::UsageLimit(UsageLimitReason::QuotaExhausted, msg)) => {
+            assert!(msg.contains("usage limit reached"))
+        }
+        other => panic!("expected a quota UsageLimit error, got {:?}", other),
+    }
+
+    while let Ok(event) = event_rx.try_recv() {
+        assert!(
+            !matches!(event, ExecutionEvent::Notification { .. }),
+            "usage limits should not be retried"
+        );
+    }
+    let checkpoint = CheckpointManager::new(project.root())
+        .unwrap()
+        .load()
+        .unwrap()
+        .expect("checkpoint should be saved");
+    assert_eq!(checkpoint.pause_reason, PauseReason::UsageLimitExceeded);
+}
+
+#[tokio::test]
+async fn test_usage_limit_switches_to_fallback_agent() {
+    let project = ScriptedProject::new(
+        r#"
+[[iterations]]
+stderr = ["Error: usage limit reached for this billing period"]
+exit_code = 1
+"#,
+    );
+    let fallback = project.fixture_dir.path().join("fallback.toml");
+    std::fs::write(
+        &fallback,
+        r#"
+[[iterations]]
+output = ["Wrote greeting.txt"]
+write_files = { "greeting.txt" = "hello\n" }
+"#,
+    )
+    .unwrap();
+    let fallback_agent = format!("scripted:{}", fallback.display());
+
+    let mut config = project.config();
+    config
+        .agent_registry
+        .set_fallback_order(vec![fallback_agent.clone()]);
+    let metrics = MetricsCollector::new();
+    config.metrics_collector = Some(metrics.clone());
+    let primary_agent = config.agent_command.clone();
+
+    let executor = StoryExecutor::new(config);
+    let (_tx, rx) = watch::channel(false);
+    let result = executor
+        .execute_story("US-001", rx, |_, _| {})
+        .await
+        .unwrap();
+
+    // The fallback finishes the story in the same iteration
+    assert!(result.success);
+    assert_eq!(result.iterations_used, 1);
+    assert_eq!(executor.active_agent(), fallback_agent);
+    let switches = executor.agent_switches();
+    assert_eq!(switches.len(), 1);
+    assert_eq!(switches[0].from, primary_agent);
+    assert_eq!(switches[0].reason, "exhausted its quota");
+
+    assert_eq!(metrics.build().agent_switches, 1);
+    let progress = std::fs::read_to_string(project.root().join("progress.txt")).unwrap();
+    assert!(progress.contains(&format!("- **Agent**: {}", fallback_agent)));
+    assert!(progress.contains(&format!("  - Switched {}", switches[0])));
+
+    // The switch checkpoint records the fallback agent
+    let checkpoint = CheckpointManager::new(project.root())
+        .unwrap()
+        .load()
+        .unwrap()
+        .expect("checkpoint should be saved");
+    assert_eq!(checkpoint.agent.as_deref(), Some(fallback_agent.as_str()));
+    assert_eq!(checkpoint.agent_switches, switches);
+}
+
+/// Turn the project into a tiny crate checked by the lint and format gates
+fn make_rust_project(project: &ScriptedProject) -> Profile {
+    std::fs::write(
+        project.root().join("Cargo.toml"),
+        "[package]\nname = \"scripted\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+    )
+    .unwrap();
+    std::fs::create_dir_all(project.root().join("src")).unwrap();
+    std::fs::write(project.root().join("src/lib.rs"), "").unwrap();
+    std::fs::write(project.root().join(".gitignore"), "target/\nCargo.lock\n").unwrap();
+    git(project.root(), &["add", "-A"]);
+    git(project.root(), &["commit", "-q", "-m", "crate"]);
+
+    Profile {
+        ci: CiConfig {
+            lint_check: true,
+            format_check: true,
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+const ROLLBACK_FIXTURE: &str = r#"
+# Compiles but is badly formatted: one gate fails
+[[iterations]]
+write_files = { "src/lib.rs" = "pub fn answer() -> u32 {   42 }\n" }
+
+# Breaks the build: lint and format both fail
+[[iterations]]
+write_files = { "src/lib.rs" = "pub fn answer() -> u32 {\n", "stray.txt" = "debris\n" }
+
+[[iterations]]
+write_files = { "src/lib.rs" = "pub fn answer() -> u32 {\n    42\n}\n" }
+"#;
+
+#[tokio::test]
+async fn test_regressing_iteration_is_rolled_back() {
+    let project = ScriptedProject::new(ROLLBACK_FIXTURE);
+    let profile = make_rust_project(&project);
+    let config = ExecutorConfig {
+        quality_profile: Some(profile),
+        ..project.config()
+    };
+
+    let result = project.execute(config).await.unwrap();
+
+    assert!(result.success, "story failed: {:?}", result.error);
+    assert_eq!(result.iterations_used, 3);
+    assert_eq!(
+        result.iteration_context.unwrap().rollbacks,
+        vec![WorkspaceRollback {
+            iteration: 2,
+            restored_to: 1,
+            failed_gates: 2,
+            restored_failed_gates: 1,
+        }]
+    );
+    // The broken iteration's stray file never reaches the commit
+    assert!(!project.root().join("stray.txt").exists());
+    let files = git(
+        project.root(),
+        &["show", "--name-only", "--format=", "HEAD"],
+    );
+    assert!(!files.contains("stray.txt"), "committed: {}", files);
+    assert!(git(
+        project.root(),
+        &["for-each-ref", "refs/ralph/snapshots/US-001/"]
+    )
+    .contains("refs/ralph/snapshots/US-001/3"));
+}
+
+#[tokio::test]
+async fn test_rollback_policy_never_keeps_broken_workspace() {
+    let project = ScriptedProject::new(ROLLBACK_FIXTURE);
+    let profile = make_rust_project(&project);
+    let config = ExecutorConfig {
+        quality_profile: Some(profile),
+        rollback_policy: RollbackPolicy::Never,
+        ..project.config()
+    };
+
+    let result = project.execute(config).await.unwrap();
+
+    assert!(result.success, "story failed: {:?}", result.error);
+    assert!(result.iteration_context.unwrap().rollbacks.is_empty());
+    assert!(project.root().join("stray.txt").exists());
+}
+
+#[tokio::test]
+async fn test_commit_policy_shapes_story_commit() {
+    let project = ScriptedProject::new(
+        r#"
+[[iterations]]
+write_files = { "greeting.txt" = "hello\n" }
+"#,
+    );
+    // Left over from before the story; not the agent's change
+    std::fs::write(project.root().join("notes.txt"), "scratch\n").unwrap();
+    let config = ExecutorConfig {
+        commit_policy: CommitPolicy::parse(
+            "message = \"{type}({id}): {title}\"\ntrailers = true\nsignoff = true\nstage = \"changed\"\n",
+        )
+        .unwrap(),
+        ..project.config()
+    };
+
+    let result = project.execute(config).await.unwrap();
+
+    assert!(result.success);
+    let body = git(project.root(), &["log", "-1", "--format=%B"]);
+    assert!(
+        body.starts_with("feat(US-001): Add greeting\n\nRalph-Story: US-001\nRalph-Iterations: 1\nRalph-Agent: scripted:"),
+        "message: {}",
+        body
+    );
+    assert!(body.contains("Signed-off-by: Test <test@example.com>"));
+    assert_eq!(
+        git(
+            project.root(),
+            &["show", "--name-only", "--format=", "HEAD"]
+        ),
+        "greeting.txt"
+    );
+    assert!(git(project.root(), &["status", "--porcelain"]).contains("notes.txt"));
+}
+
+#[tokio::test]
+async fn test_wip_commits_are_squashed_when_story_passes() {
+    let project = ScriptedProject::new(ROLLBACK_FIXTURE);
+    let profile = make_rust_project(&project);
+    let base = git(project.root(), &["rev-parse", "HEAD"]);
+    let config = ExecutorConfig {
+        quality_profile: Some(profile),
+        commit_policy: CommitPolicy {
+            wip_commits: true,
+            ..CommitPolicy::default()
+        },
+        ..project.config()
+    };
+
+    let result = project.execute(config).await.unwrap();
+
+    assert!(result.success, "story failed: {:?}", result.error);
+    assert_eq!(result.iterations_used, 3);
+    // Iteration 1 left a WIP commit; iteration 2 was rolled back, leaving nothing new
+    let reflog = git(project.root(), &["reflog", "--format=%gs"]);
+    assert!(reflog.contains("feat: US-001 - Add greeting (WIP, iteration 1)"));
+    assert!(!reflog.contains("(WIP, iteration 2)"));
+    // The story ends up as a single commit
+    assert_eq!(
+        git(
+            project.root(),
+            &["rev-list", "--count", &format!("{}..HEAD", base)]
+        ),
+        "1"
+    );
+    assert_eq!(
+        git(project.root(), &["log", "-1", "--format=%s"]),
+        "feat: US-001 - Add greeting"
+    );
+    assert_eq!(
+        git(project.root(), &["show", "HEAD:src/lib.rs"]),
+        "pub fn answer() -> u32 {\n    42\n}"
+    );
+}
+
+#[tokio::test]
+async fn test_sast_checks_code_already_in_wip_commits() {
+    let project = ScriptedProject::new(
+        r#"
+[[iterations]]
+write_files = { "src/lib.rs" = "pub fn parse(s: &str) -> u32 {\n    s.parse().unwrap()\n}\n" }
+
+# Leaves the finding from the WIP commit alone
+[[iterations]]
+write_files = { "notes.txt" = "parse done\n" }
+
+[[iterations]]
+write_files = { "src/lib.rs" = "pub fn parse(s: &str) -> u32 {\n    s.parse().unwrap_or(0)\n}\n" }
+"#,
+    );
+    let mut profile = Profile::default();
+    profile.security.sast = true;
+    let config = ExecutorConfig {
+        quality_profile: Some(profile),
+        commit_policy: CommitPolicy {
+            wip_commits: true,
+            ..CommitPolicy::default()
+        },
+        ..project.config()
+    };
+
+    let result = project.execute(config).await.unwrap();
+
+    assert!(result.success, "story failed: {:?}", result.error);
+    assert_eq!(result.iterations_used, 3);
+}
+
+#[tokio::test]
+async fn test_story_verification_failures_are_fed_back() {
+    let project = ScriptedProject::new(
+        r#"
+[[iterations]]
+write_files = { "greeting.txt" = "goodbye\n" }
+
+[[iterations]]
+write_files = { "greeting.txt" = "hello\n" }
+"#,
+    );
+    let prd = PRD.replace(
+        r#""passes": false"#,
+        r#""passes": false, "verify": [
+            { "fileExists": "greeting.txt" },
+            { "grep": "^hello$", "file": "greeting.txt" },
+            { "command": "echo checked greeting; grep -q hello greeting.txt" }
+        ]"#,
+    );
+    std::fs::write(project.root().join("prd.json"), prd).unwrap();
+    git(project.root(), &["commit", "-q", "-am", "story checks"]);
+
+    let result = project.execute(project.config()).await.unwrap();
+
+    assert!(result.success);
+    assert_eq!(result.iterations_used, 2);
+    let checks: Vec<&str> = result
+        .gate_results
+        .iter()
+        .filter(|gate| gate.gate_name.starts_with("verify: "))
+        .map(|gate| gate.gate_name.as_str())
+        .collect();
+    assert_eq!(
+        checks,
+        vec![
+            "verify: greeting.txt exists",
+            "verify: greeting.txt matches /^hello$/",
+            "verify: echo checked greeting; grep -q hello greeting.txt",
+        ]
+    );
+
+    // The first iteration's failures reach the retry prompt with their output
+    let context = result.iteration_context.unwrap();
+    let messages: Vec<&str> = context
+        .error_history
+        .iter()
+        .map(|error| error.message.as_str())
+        .collect();
+    assert_eq!(messages.len(), 2);
+    assert_eq!(
+        messages[0],
+        "Gate 'verify: greeting.txt matches /^hello$/' failed: No line of greeting.txt matches /^hello$/"
+    );
+    assert!(messages[1].contains("failed: Exited with 1, expected 0\n    checked greeting"));
+    assert!(context
+        .build_history_context()
+        .contains("No line of greeting.txt matches /^hello$/"));
+}
+
+#[tokio::test]
+async fn test_command_gates_block_unless_advisory() {
+    let project = ScriptedProject::new(
+        r#"
+[[iterations]]
+write_files = { "greeting.txt" = "goodbye\n" }
+
+[[iterations]]
+write_files = { "greeting.txt" = "hello\n" }
+"#,
+    );
+    let quality: QualityConfig = toml::from_str(
+        r#"
+[[profiles.custom.gates]]
+name = "greeting"
+command = "grep hello greeting.txt || { echo greeting is not hello; exit 1; }"
+
+[[profiles.custom.gates]]
+name = "spelling"
+command = "echo 'helo?'; exit 1"
+required = false
+"#,
+    )
+    .unwrap();
+    let config = ExecutorConfig {
+        quality_profile: quality.get_profile_by_name("custom").cloned(),
+        ..project.config()
+    };
+
+    let result = project.execute(config).await.unwrap();
+
+    assert!(result.success, "story failed: {:?}", result.error);
+    assert_eq!(result.iterations_used, 2);
+    let spelling = result
+        .gate_results
+        .iter()
+        .find(|gate| gate.gate_name == "spelling")
+        .unwrap();
+    assert!(!spelling.passed && spelling.advisory);
+
+    // Only the required gate's failure reaches the retry prompt
+    let context = result.iteration_context.unwrap();
+    let messages: Vec<&str> = context
+        .error_history
+        .iter()
+        .map(|error| error.message.as_str())
+        .collect();
+    assert_eq!(
+        messages,
+        vec!["Gate 'greeting' failed: Exited with 1, expected 0\n    greeting is not hello"]
+    );
+}
+
+#[tokio::test]
+async fn test_failing_tests_are_named_in_the_retry_prompt() {
+    let project = ScriptedProject::new(
+        r#"
+[[iterations]]
+write_files = { "src/lib.rs" = """
+pub fn answer() -> u32 {
+    41
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn answer_is_42() {
+        assert_eq!(super::answer(), 42);
+    }
+
+    #[test]
+    fn answer_is_positive() {
+        assert!(super::answer() > 0);
+    }
+}
+""" }
+
+[[iterations]]
+write_files = { "src/lib.rs" = """
+pub fn answer() -> u32 {
+    42
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn answer_is_42() {
+        assert_eq!(super::answer(), 42);
+    }
+}
+""" }
+"#,
+    );
+    let mut profile = make_rust_project(&project);
+    profile.ci = CiConfig::default();
+    profile.testing.unit_tests = true;
+    let config = ExecutorConfig {
+        quality_profile: Some(profile),
+        ..project.config()
+    };
+
+    let result = project.execute(config).await.unwrap();
+
+    assert!(result.success, "story failed: {:?}", result.error);
+    assert_eq!(result.iterations_used, 2);
+    let tests = result
+        .gate_results
+        .iter()
+        .find(|gate| gate.gate_name == "tests")
+        .unwrap();
+    assert!(tests.passed);
+    assert!(
+        tests.message.starts_with("1 passed, 0 failed"),
+        "{:?}",
+        tests
+    );
+
+    let context = result.iteration_context.unwrap();
+    let error = &context.error_history[0];
+    assert_eq!(error.category, IterErrorCategory::Test);
+    assert!(error
+        .message
+        .starts_with("Gate 'tests' failed: 1 passed, 1 failed"));
+    assert_eq!(error.failed_tests.len(), 1);
+    assert_eq!(error.failed_tests[0].name, "tests::answer_is_42");
+    assert!(error.failed_tests[0].message.contains("left: 41"));
+    assert!(context
+        .build_history_context()
+        .contains("    - `tests::answer_is_42`\n"));
+}
+
+#[tokio::test]
+async fn test_change_budget_violation_is_retried() {
+    let project = ScriptedProject::new(
+        r#"
+[[iterations]]
+write_files = { "greeting.txt" = "hello\nhello\nhello\nhello\n" }
+
+[[iterations]]
+write_files = { "greeting.txt" = "hello\n" }
+"#,
+    );
+    let config = ExecutorConfig {
+        change_guard: Some(ChangeGuard::parse("max_lines = 2").unwrap()),
+        ..project.config()
+    };
+
+    let result = project.execute(config).await.unwrap();
+
+    assert!(result.success, "story failed: {:?}", result.error);
+    assert_eq!(result.iterations_used, 2);
+    let context = result.iteration_context.unwrap();
+    assert_eq!(context.error_history.len(), 1);
+    assert_eq!(
+        context.error_history[0].category,
+        IterErrorCategory::ChangePolicy
+    );
+    assert_eq!(
+        context.error_history[0].message,
+        "Change policy violation: 4 lines changed, more than the limit of 2"
+    );
+}
+
+#[tokio::test]
+async fn test_forbidden_path_fails_story() {
+    let project = ScriptedProject::new(
+        r#"
+[[iterations]]
+write_files = { "greeting.txt" = "hello\n", "prd.json" = "{}" }
+"#,
+    );
+    let head = git(project.root(), &["rev-parse", "HEAD"]);
+    let config = ExecutorConfig {
+        change_guard: Some(ChangeGuard::parse(r#"forbidden = ["prd.json"]"#).unwrap()),
+        ..project.config()
+    };
+
+    let result = project.execute(config).await.unwrap();
+
+    assert!(!result.success);
+    assert_eq!(result.iterations_used, 1);
+    assert_eq!(
+        result.error.as_deref(),
+        Some("Change policy violated: prd.json is protected by 'prd.json' and must not be changed")
+    );
+    assert!(result.commit_hash.is_none());
+    assert_eq!(git(project.root(), &["rev-parse", "HEAD"]), head);
+}
+
+const SPLIT_FIXTURE: &str = r#"
+[[stories.US-001.iterations]]
+stderr = ["greeting module is too tangled"]
+exit_code = 1
+
+[[stories.US-001.iterations]]
+stderr = ["greeting module is too tangled"]
+exit_code = 1
+
+[[stories.US-001.iterations]]
+stderr = ["greeting module is too tangled"]
+exit_code = 1
+
+[[stories.US-001.iterations]]
+output = [
+    '```json',
+    '[{"id": "US-001.1", "title": "Create greeting file", "acceptanceCriteria": ["greeting.txt exists"]},',
+    ' {"id": "US-001.2", "title": "Say hello", "acceptanceCriteria": ["greeting.txt says hello"], "dependsOn": ["US-001.1"]}]',
+    '```',
+]
+
+[[stories."US-001.1".iterations]]
+write_files = { "greeting.txt" = "" }
+
+[[stories."US-001.2".iterations]]
+write_files = { "greeting.txt" = "hello\n" }
+"#;
+
+#[tokio::test]
+async fn test_futile_story_is_split_into_sub_stories() {
+    let project = ScriptedProject::new(SPLIT_FIXTURE);
+    let config = RunnerConfig {
+        max_iterations_per_story: 5,
+        split_stories: true,
+        ..project.runner_config(BranchConfig::disabled())
+    };
+
+    let result = Runner::new(config).run().await;
+
+    assert!(result.all_passed, "run failed: {:?}", result.error);
+    assert_eq!(result.total_stories, 2);
+    let prd: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(project.root().join("prd.json")).unwrap())
+            .unwrap();
+    let stories = prd["userStories"].as_array().unwrap();
+    let ids: Vec<&str> = stories.iter().map(|s| s["id"].as_str().unwrap()).collect();
+    assert_eq!(ids, vec!["US-001.1", "US-001.2"]);
+    assert!(stories.iter().all(|s| s["passes"] == true));
+    assert_eq!(stories[1]["dependsOn"], serde_json::json!(["US-001.1"]));
+    assert_eq!(
+        std::fs::read_to_string(project.root().join("greeting.txt")).unwrap(),
+        "hello\n"
+    );
+}
+
+#[tokio::test]
+async fn test_futile_story_is_not_split_by_default() {
+    let project = ScriptedProject::new(SPLIT_FIXTURE);
+    let config = ExecutorConfig {
+        max_iterations: 5,
+        ..project.config()
+    };
+
+    let result = project.execute(config).await.unwrap();
+
+    assert!(!result.success);
+    assert!(result.needs_guidance);
+    assert!(result.split_into.is_empty());
+    assert_eq!(result.iterations_used, 3);
+    assert_eq!(
+        std::fs::read_to_string(project.root().join("prd.json")).unwrap(),
+        PRD
+    );
+}
+
+#[tokio::test]
+async fn test_run_stops_at_max_total_iterations_with_checkpoint() {
+    let project = ScriptedProject::new(
+        r#"
+[[iterations]]
+stderr = ["greeting is wrong"]
+exit_code = 1
+
+[[iterations]]
+write_files = { "greeting.txt" = "hello\n" }
+"#,
+    );
+    let config = RunnerConfig {
+        run_limits: RunLimits::unlimited().with_max_total_iterations(1),
+        ..project.runner_config(BranchConfig::disabled())
+    };
+
+    let result = Runner::new(config).run().await;
+
+    assert!(!result.all_passed);
+    assert_eq!(
+        result.error.as_deref(),
+        Some("Max total iterations (1) reached. Checkpoint saved. Resume with: ralph --resume")
+    );
+    assert!(!project.root().join("greeting.txt").exists());
+
+    // The run stops between iterations, so it resumes with the next one
+    let checkpoint = CheckpointManager::new(project.root())
+        .unwrap()
+        .load()
+        .unwrap()
+        .expect("checkpoint should be saved");
+    assert_eq!(checkpoint.pause_reason, PauseReason::IterationBoundary);
+    let story = checkpoint.current_story.unwrap();
+    assert_eq!(story.story_id, "US-001");
+    assert_eq!(story.iteration, 2);
+}
+
+#[tokio::test]
+async fn test_run_stops_at_budget_with_checkpoint_at_next_iteration() {
+    let project = ScriptedProject::new(
+        r#"
+stream_format = "claude"
+
+[[iterations]]
+output = ['{"type":"result","subtype":"success","result":"Tried","total_cost_usd":0.5,"usage":{"input_tokens":100,"output_tokens":10}}']
+
+[[iterations]]
+write_files = { "greeting.txt" = "hello\n" }
+"#,
+    );
+    let prd = PRD.replace(
+        r#""passes": false"#,
+        r#""passes": false, "verify": [{ "fileExists": "greeting.txt" }]"#,
+    );
+    std::fs::write(project.root().join("prd.json"), prd).unwrap();
+    git(project.root(), &["commit", "-q", "-am", "story checks"]);
+    let config = RunnerConfig {
+        budget: UsageBudget::unlimited().with_max_cost_usd(0.25),
+        ..project.runner_config(BranchConfig::disabled())
+    };
+
+    let result = Runner::new(config).run().await;
+
+    assert!(!result.all_passed);
+    assert!(!project.root().join("greeting.txt").exists());
+
+    // The budget stopped the story before its second iteration, so it
+    // resumes there rather than starting over
+    let checkpoint = CheckpointManager::new(project.root())
+        .unwrap()
+        .load()
+        .unwrap()
+        .expect("checkpoint should be saved");
+    assert!(matches!(
+        checkpoint.pause_reason,
+        PauseReason::BudgetExceeded(_)
+    ));
+    let story = checkpoint.current_story.unwrap();
+    assert_eq!(story.story_id, "US-001");
+    assert_eq!(story.iteration, 2);
+}
+
+#[tokio::test]
+async fn test_passed_deadline_stops_before_the_first_iteration() {
+    let project = ScriptedProject::new(
+        r#"
+[[iterations]]
+write_files = { "greeting.txt" = "hello\n" }
+"#,
+    );
+    let config = ExecutorConfig {
+        run_limits: RunLimits::unlimited()
+            .with_deadline(chrono::Local::now() - chrono::Duration::minutes(1)),
+        ..project.config()
+    };
+
+    match project.execute(config).await {
+        Err(ExecutorError::RunLimitReached(1, reached)) => {
+            assert!(reached.starts_with("Deadline reached"))
+        }
+        other => panic!("expected RunLimitReached, got {:?}", other),
+    }
+    assert!(!project.root().join("greeting.txt").exists());
+}
+
+#[tokio::test]
+async fn test_scripted_agent_stall_times_out_with_checkpoint() {
+    let project = ScriptedProject::new(
+        r#"
+[[iterations]]
+output = ["thinking..."]
+stall_seconds = 5
+"#,
+    );
+    let config = ExecutorConfig {
+        timeout_config: TimeoutConfig::new()
+            .with_heartbeat_interval(Duration::from_millis(100))
+            .with_missed_heartbeats_threshold(2),
+        ..project.config()
+    };
+
+    let result = project.execute(config).await;
+
+    match result {
+        Err(ExecutorError::Timeout(msg)) => assert!(msg.contains("stalled")),
+        other => panic!("expected Timeout, got {:?}", other),
+    }
+
+    let checkpoint = CheckpointManager::new(project.root())
+        .unwrap()
+        .load()
+        .unwrap()
+        .expect("checkpoint should be saved");
+    assert_eq!(checkpoint.pause_reason, PauseReason::Timeout);
+    assert_eq!(checkpoint.current_story.unwrap().story_id, "US-001");
+}
+
+#[tokio::test]
+async fn test_scripted_agent_missing_fixture_fails() {
+    let project = ScriptedProject::new("");
+    let config = ExecutorConfig {
+        agent_command: "scripted:/nonexistent/agent.toml".to_string(),
+        max_iterations: 1,
+        ..project.config()
+    };
+
+    let error = expect_agent_error(project.execute(config).await);
+
+    assert!(error.contains("Exit code: 2"));
+    assert!(error.contains("failed to load /nonexistent/agent.toml"));
+}
+
+#[tokio::test]
+async fn test_runner_commits_on_prd_branch() {
+    let project = ScriptedProject::new(
+        r#"
+[[iterations]]
+write_files = { "greeting.txt" = "hello\n" }
+"#,
+    );
+    let start_branch = git(project.root(), &["rev-parse", "--abbrev-ref", "HEAD"]);
+    let base = git(project.root(), &["rev-parse", "HEAD"]);
+
+    let runner = Runner::new(project.runner_config(BranchConfig::default()));
+    let result = runner.run().await;
+
+    assert!(result.all_passed, "run failed: {:?}", result.error);
+    assert_eq!(
+        git(project.root(), &["rev-parse", "--abbrev-ref", "HEAD"]),
+        "feature/scripted"
+    );
+    assert_eq!(
+        git(project.root(), &["log", "-1", "--format=%s"]),
+        "feat: US-001 - Add greeting"
+    );
+    // The starting branch is left untouched
+    assert_eq!(git(project.root(), &["rev-parse", &start_branch]), base);
+}
+
+#[tokio::test]
+async fn test_runner_refuses_dirty_tree() {
+    let project = ScriptedProject::new(
+        r#"
+[[iterations]]
+write_files = { "greeting.txt" = "hello\n" }
+"#,
+    );
+    std::fs::write(project.root().join("README.md"), "local edits\n").unwrap();
+    let start_branch = git(project.root(), &["rev-parse", "--abbrev-ref", "HEAD"]);
+
+    let runner = Runner::new(project.runner_config(BranchConfig::default()));
+    let result = runner.run().await;
+
+    assert!(!result.all_passed);
+    let error = result.error.unwrap();
+    assert!(error.contains("Branch setup failed"));
+    assert!(error.contains("README.md"));
+    assert_eq!(
+        git(project.root(), &["rev-parse", "--abbrev-ref", "HEAD"]),
+        start_branch
+    );
+    assert!(!project.root().join("greeting.txt").exists());
+}
+
+#[tokio::test]
+async fn test_runner_ignores_its_own_prd_and_progress_changes() {
+    let project = ScriptedProject::new(
+        r#"
+[[stories.US-001.iterations]]
+write_files = { "greeting.txt" = "hello\n" }
+
+[[stories.US-002.iterations]]
+write_files = { "farewell.txt" = "bye\n" }
+"#,
+    );
+    let mut config = project.runner_config(BranchConfig::default());
+    config.no_checkpoint = true;
+
+    // A freshly edited PRD is not a dirty tree
+    let prd = PRD.replace("Write a greeting file", "Write a greeting file, politely");
+    std::fs::write(project.root().join("prd.json"), &prd).unwrap();
+    let result = Runner::new(config.clone()).run().await;
+    assert!(result.all_passed, "first run failed: {:?}", result.error);
+
+    // Nor are the PRD and progress log the first run updated
+    let prd = std::fs::read_to_string(project.root().join("prd.json"))
+        .unwrap()
+        .replace(
+            r#""userStories": ["#,
+            r#""userStories": [
+        {
+            "id": "US-002",
+            "title": "Add farewell",
+            "description": "Write a farewell file",
+            "acceptanceCriteria": ["farewell.txt exists"],
+            "priority": 2,
+            "passes": false
+        },"#,
+        );
+    std::fs::write(project.root().join("prd.json"), prd).unwrap();
+    let result = Runner::new(config).run().await;
+    assert!(result.all_passed, "second run failed: {:?}", result.error);
+    assert!(project.root().join("farewell.txt").exists());
+    assert_eq!(
+        git(project.root(), &["log", "-1", "--format=%s"]),
+        "feat: US-002 - Add farewell"
+    );
+}
+
+#[tokio::test]
+async fn test_runner_honors_story_overrides() {
+    // The run-wide agent always fails; the story names its own agent
+    let project = ScriptedProject::new(
+        r#"
+[[iterations]]
+stderr = ["run-wide agent should not be used"]
+exit_code = 1
+"#,
+    );
+    let story_agent = project.fixture_dir.path().join("story-agent.toml");
+    std::fs::write(
+        &story_agent,
+        r#"
+[[iterations]]
+write_files = { "greeting.txt" = "hello\n" }
+"#,
+    )
+    .unwrap();
+    let prd = PRD.replace(
+        r#""passes": false"#,
+        &format!(
+            r#""passes": false, "agent": "scripted:{}", "maxIterations": 1"#,
+            story_agent.display()
+        ),
+    );
+    std::fs::write(project.root().join("prd.json"), prd).unwrap();
+    git(project.root(), &["commit", "-q", "-am", "story overrides"]);
+
+    let runner = Runner::new(project.runner_config(BranchConfig::default()));
+    let result = runner.run().await;
+
+    assert!(result.all_passed, "run failed: {:?}", result.error);
+    assert_eq!(result.total_iterations, 1);
+    assert_eq!(
+        std::fs::read_to_string(project.root().join("greeting.txt")).unwrap(),
+        "hello\n"
+    );
+}
+
+#[tokio::test]
+async fn test_parallel_runner_merges_story_worktrees() {
+    let project = ScriptedProject::new(
+        r#"
+[[stories.US-001.iterations]]
+write_files = { "greeting.txt" = "hello\n" }
+
+[[stories.US-002.iterations]]
+write_files = { "farewell.txt" = "bye\n" }
+"#,
+    );
+    let prd = PRD.replace(
+        r#""passes": false
+        }"#,
+        r#""passes": false
+        },
+        {
+            "id": "US-002",
+            "title": "Add farewell",
+            "description": "Write a farewell file",
+            "acceptanceCriteria": ["farewell.txt exists"],
+            "priority": 2,
+            "passes": false
+        }"#,
+    );
+    std::fs::write(project.root().join("prd.json"), prd).unwrap();
+    git(project.root(), &["commit", "-q", "-am", "second story"]);
+
+    let mut config = project.runner_config(BranchConfig::default());
+    config.parallel = true;
+    let runner = Runner::new(config);
+    let result = runner.run().await;
+
+    assert!(result.all_passed, "run failed: {:?}", result.error);
+    assert_eq!(
+        git(project.root(), &["rev-parse", "--abbrev-ref", "HEAD"]),
+        "feature/scripted"
+    );
+    assert!(project.root().join("greeting.txt").exists());
+    assert!(project.root().join("farewell.txt").exists());
+
+    // Each story committed only its own file on its branch before merging
+    let log = git(project.root(), &["log", "--format=%s"]);
+    assert!(log.contains("Merge story US-001"), "log: {}", log);
+    assert!(log.contains("Merge story US-002"), "log: {}", log);
+    let files = git(
+        project.root(),
+        &["show", "--name-only", "--format=", ":/feat: US-002"],
+    );
+    assert_eq!(files, "farewell.txt");
+
+    // Worktrees and story branches are cleaned up
+    let worktrees = git(project.root(), &["worktree", "list", "--porcelain"]);
+    assert_eq!(worktrees.matches("worktree ").count(), 1);
+    assert_eq!(
+        git(project.root(), &["branch", "--list", "ralph/story/*"]),
+        ""
+    );
+}
+
+#[tokio::test]
+async fn test_parallel_batch_timeout_aborts_stories_still_running() {
+    let project = ScriptedProject::new(
+        r#"
+[[stories.US-001.iterations]]
+write_files = { "greeting.txt" = "hello\n" }
+
+[[stories.US-002.iterations]]
+write_files = { "farewell.txt" = "bye\n" }
+stall_seconds = 30
+"#,
+    );
+    let prd = PRD.replace(
+        r#""passes": false
+        }"#,
+        r#""passes": false
+        },
+        {
+            "id": "US-002",
+            "title": "Add farewell",
+            "description": "Write a farewell file",
+            "acceptanceCriteria": ["farewell.txt exists"],
+            "priority": 2,
+            "passes": false
+        }"#,
+    );
+    std::fs::write(project.root().join("prd.json"), prd).unwrap();
+    git(project.root(), &["commit", "-q", "-am", "second story"]);
+
+    let mut config = project.runner_config(BranchConfig::default());
+    config.parallel = true;
+    config.parallel_config = Some(ParallelRunnerConfig {
+        batch_timeout: Duration::from_secs(3),
+        ..Default::default()
+    });
+    let started = std::time::Instant::now();
+    let result = Runner::new(config).run().await;
+
+    // The stalled story is aborted at the timeout rather than waited for
+    assert!(started.elapsed() < Duration::from_secs(20));
+    assert!(!result.all_passed);
+    assert_eq!(result.stories_passed, 1);
+
+    // The finished story is merged; the aborted one's worktree is dropped
+    assert!(project.root().join("greeting.txt").exists());
+    assert!(!project.root().join("farewell.txt").exists());
+    let worktrees = git(project.root(), &["worktree", "list", "--porcelain"]);
+    assert_eq!(worktrees.matches("worktree ").count(), 1);
+    assert_eq!(
+        git(project.root(), &["branch", "--list", "ralph/story/*"]),
+        ""
+    );
+}
+
+/// Fixture step that reports Claude's usage limit, resetting `seconds` from now
+fn usage_limit_fixture(seconds: i64) -> String {
+    let reset = chrono::Utc::now().timestamp() + seconds;
+    format!(
+        r#"
+[[iterations]]
+stderr = ["Claude AI usage limit reached|{}"]
+exit_code = 1
+"#,
+        reset
+    )
+}
+
+#[tokio::test]
+async fn test_runner_waits_for_usage_limit_reset_and_resumes() {
+    let project = ScriptedProject::new(&usage_limit_fixture(3));
+    let config = RunnerConfig {
+        max_usage_limit_wait: Duration::from_secs(60),
+        ..project.runner_config(BranchConfig::disabled())
+    };
+
+    // Once the run pauses on the limit, let the agent succeed when it resumes
+    let root = project.root().to_path_buf();
+    let fixture_path = project.fixture_path();
+    let unblock = std::thread::spawn(move || {
+        let manager = CheckpointManager::new(&root).unwrap();
+        for _ in 0..500 {
+            let paused = manager.load().ok().flatten().is_some_and(|checkpoint| {
+                checkpoint.pause_reason == PauseReason::UsageLimitExceeded
+            });
+            if paused {
+                std::fs::write(
+                    &fixture_path,
+                    "[[iterations]]\nwrite_files = { \"greeting.txt\" = \"hello\\n\" }\n",
+                )
+                .unwrap();
+                return true;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        false
+    });
+
+    let started = std::time::Instant::now();
+    let result = Runner::new(config).run().await;
+
+    assert!(
+        unblock.join().unwrap(),
+        "run never paused on the usage limit"
+    );
+    assert!(result.all_passed, "run failed: {:?}", result.error);
+    assert!(started.elapsed() >= Duration::from_secs(1));
+    assert_eq!(
+        git(project.root(), &["log", "-1", "--format=%s"]),
+        "feat: US-001 - Add greeting"
+    );
+}
+
+#[tokio::test]
+async fn test_runner_stops_when_usage_limit_reset_exceeds_max_wait() {
+    let project = ScriptedProject::new(&usage_limit_fixture(3600));
+    let config = RunnerConfig {
+        max_usage_limit_wait: Duration::from_secs(60),
+        ..project.runner_config(BranchConfig::disabled())
+    };
+
+    let result = Runner::new(config).run().await;
+
+    assert!(!result.all_passed);
+    assert!(result.error.unwrap().contains("Usage limit exceeded"));
+    let checkpoint = CheckpointManager::new(project.root())
+        .unwrap()
+        .load()
+        .unwrap()
+        .expect("checkpoint should be saved");
+    assert_eq!(checkpoint.pause_reason, PauseReason::UsageLimitExceeded);
+}
+
+#[tokio::test]
+async fn test_usage_limit_reset_after_deadline_stops_at_reached_iteration() {
+    let reset = chrono::Utc::now().timestamp() + 3600;
+    let project = ScriptedProject::new(&format!(
+        r#"
+[[iterations]]
+stderr = ["error: the agent crashed"]
+exit_code = 7
+
+[[iterations]]
+stderr = ["Claude AI usage limit reached|{}"]
+exit_code = 1
+"#,
+        reset
+    ));
+    let config = RunnerConfig {
+        max_usage_limit_wait: Duration::from_secs(2 * 3600),
+        run_limits: RunLimits::unlimited()
+            .with_deadline(chrono::Local::now() + chrono::Duration::minutes(30)),
+        ..project.runner_config(BranchConfig::disabled())
+    };
+
+    let started = std::time::Instant::now();
+    let result = Runner::new(config).run().await;
+
+    // The run stops rather than waiting past its deadline
+    assert!(started.elapsed() < Duration::from_secs(60));
+    assert!(!result.all_passed);
+    assert!(result.error.unwrap().contains("would pass the deadline"));
+    let checkpoint = CheckpointManager::new(project.root())
+        .unwrap()
+        .load()
+        .unwrap()
+        .expect("checkpoint should be saved");
+    assert_eq!(checkpoint.pause_reason, PauseReason::IterationBoundary);
+    // Resuming retries the interrupted iteration, not the story's first
+    assert_eq!(checkpoint.current_story.unwrap().iteration, 2);
+}
+
+#[tokio::test]
+async fn test_invalid_project_prompt_fails_before_agent_runs() {
+    let project = ScriptedProject::new(
+        r#"
+[[iterations]]
+write_files = { "greeting.txt" = "hello\n" }
+"#,
+    );
+    std::fs::create_dir_all(project.root().join(".ralph")).unwrap();
+    std::fs::write(
+        project.root().join(".ralph/prompt.md"),
+        "Implement {{story_name}}\n",
+    )
+    .unwrap();
+
+    match project.execute(project.config()).await {
+        Err(ExecutorError::PromptError(msg)) => assert!(msg.contains("story_name")),
+        other => panic!("expected PromptError, got {:?}", other),
+    }
+    assert!(!project.root().join("greeting.txt").exists());
+}
+
+#[tokio::test]
+async fn test_learnings_round_trip_through_progress() {
+    let fixture_dir = TempDir::new().unwrap();
+    let prompt_log = fixture_dir.path().join("prompts.log");
+    let project = ScriptedProject::new(&format!(
+        r#"
+prompt_log = "{}"
+
+[[iterations]]
+output = ["Wrote greeting.txt", "", "Learnings:", "- Greetings are stored as plain text files"]
+write_files = {{ "greeting.txt" = "hello\n" }}
+"#,
+        prompt_log.display()
+    ));
+    std::fs::write(
+        project.root().join("progress.txt"),
+        "# Progress\n---\n\n## Codebase Patterns\n- Text files end with a newline\n\n---\n\n\
+         ## 2026-01-01 10:00 - US-000\n- **Learnings for future iterations:**\n  - The greeting must be lowercase\n---\n",
+    )
+    .unwrap();
+
+    let result = project.execute(project.config()).await.unwrap();
+    assert!(result.success);
+
+    let prompt = std::fs::read_to_string(&prompt_log).unwrap();
+    assert!(prompt.contains("## Codebase Patterns\n- Text files end with a newline"));
+    assert!(prompt.contains("- [US-000] The greeting must be lowercase"));
+
+    let progress = std::fs::read_to_string(project.root().join("progress.txt")).unwrap();
+    assert!(progress.contains("  - Greetings are stored as plain text files\n"));
+    assert!(!progress.contains("automated execution"));
+}
+
+#[tokio::test]
+async fn test_agent_blocked_report_pauses_for_guidance() {
+    let project = ScriptedProject::new(
+        r#"
+[[iterations]]
+output = ["<ralph-status>", "status: blocked", "reason: The greeting text is not specified", "</ralph-status>"]
+"#,
+    );
+
+    let result = project.execute(project.config()).await.unwrap();
+
+    assert!(!result.success);
+    assert!(result.needs_guidance);
+    assert_eq!(result.iterations_used, 1);
+    assert_eq!(
+        result.error.as_deref(),
+        Some("Agent reported a blocker: The greeting text is not specified")
+    );
+    assert_eq!(git(project.root(), &["rev-list", "--count", "HEAD"]), "1");
+}
+
+#[tokio::test]
+async fn test_agent_partial_report_skips_gates_and_retries() {
+    let project = ScriptedProject::new(
+        r#"
+[[iterations]]
+output = ["<ralph-status>", "status: partial", "remaining:", "- greeting.txt exists", "</ralph-status>"]
+write_files = { "draft.txt" = "wip\n" }
+
+[[iterations]]
+output = ["<ralph-status>", "status: complete", "learnings:", "- Drafts go in draft.txt", "</ralph-status>"]
+write_files = { "greeting.txt" = "hello\n" }
+"#,
+    );
+
+    let result = project.execute(project.config()).await.unwrap();
+
+    assert!(result.success);
+    assert_eq!(result.iterations_used, 2);
+    let context = result.iteration_context.unwrap();
+    assert_eq!(
+        context.error_history[0].message,
+        "Agent reported partial progress; remaining: greeting.txt exists"
+    );
+    // Only one commit: the partial iteration was not committed
+    assert_eq!(git(project.root(), &["rev-list", "--count", "HEAD"]), "2");
+
+    let progress = std::fs::read_to_string(project.root().join("progress.txt")).unwrap();
+    assert!(progress.contains("  - Drafts go in draft.txt\n"));
+}
+
+#[tokio::test]
+async fn test_false_completion_claim_is_flagged() {
+    let project = ScriptedProject::new(
+        r#"
+[[iterations]]
+output = ["<promise>COMPLETE</promise>"]
+write_files = { "greeting.txt" = "hello\n" }
+"#,
+    );
+    // `cargo fmt --check` fails in a project without a Cargo.toml
+    let mut profile = Profile::default();
+    profile.ci.format_check = true;
+    let config = ExecutorConfig {
+        quality_profile: Some(profile),
+        max_iterations: 1,
+        ..project.config()
+    };
+
+    let error = expect_agent_error(project.execute(config).await);
+
+    assert!(error
+        .contains("Quality gates failed although the agent reported the story complete: format"));
+}